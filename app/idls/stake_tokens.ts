@@ -14,24 +14,23 @@ export type StakeTokens = {
   },
   "instructions": [
     {
-      "name": "fundReward",
+      "name": "acceptAdmin",
       "docs": [
-        "Fund reward instruction",
-        "This instruction is used to fund the reward pool",
+        "Accept admin instruction",
+        "This instruction is used by the pending admin to take over the admin role of the pool",
         "",
         "# Arguments",
-        "* `ctx` - context of the program",
-        "* `amount` - amount to fund the reward pool"
+        "* `ctx` - context of the program"
       ],
       "discriminator": [
-        188,
-        50,
-        249,
-        165,
-        93,
-        151,
-        38,
-        63
+        112,
+        42,
+        45,
+        90,
+        116,
+        181,
+        13,
+        170
       ],
       "accounts": [
         {
@@ -39,52 +38,42 @@ export type StakeTokens = {
           "writable": true
         },
         {
-          "name": "stakingTokenAccount",
-          "writable": true
-        },
-        {
-          "name": "admin",
-          "writable": true,
-          "signer": true
-        },
-        {
-          "name": "adminTokenAccount",
-          "writable": true
-        },
-        {
-          "name": "tokenProgram",
-          "address": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
+          "name": "pendingAdmin",
+          "signer": true,
+          "relations": [
+            "stakingAccount"
+          ]
         }
       ],
-      "args": [
-        {
-          "name": "amount",
-          "type": "u64"
-        }
-      ]
+      "args": []
     },
     {
-      "name": "initialize",
+      "name": "addRewardStream",
       "docs": [
-        "Initialize instruction",
-        "This instruction is used to create a new staking pool",
+        "Add reward stream instruction",
+        "This instruction is used by the admin to layer an extra reward token on top of the rewards of the pool",
+        "(e.g. a partner incentive), `amount` tokens being emitted between `start_time` and `end_time`",
+        "and shared pro rata to the stake weights (tier multipliers included)",
+        "Streams can be added to a running pool, existing positions earn from the start of the stream without any migration",
+        "Stream rewards are paid out together with the rewards of the pool, from the `remaining_accounts`",
+        "The stream takes the first slot freed by `retire_reward_stream`, or the next one",
         "",
         "# Arguments",
         "* `ctx` - context of the program",
-        "* `bump` - unique bump for rach pool",
-        "* `token_mint` - program_id (address) of specific token which is allowed stake",
-        "* `reward_rate` - APY return yearly in percentage (0-100)",
-        "* `min_staking_duration` - minimum staking duration in seconds"
+        "* `index` - slot taken by the stream (the free one), part of the seeds of its vault",
+        "* `amount` - amount of reward tokens to emit",
+        "* `start_time` - time in seconds at which the emission starts (now if in the past)",
+        "* `end_time` - time in seconds at which the emission ends"
       ],
       "discriminator": [
-        175,
-        175,
-        109,
+        126,
+        23,
+        174,
         31,
-        13,
-        152,
-        155,
-        237
+        202,
+        0,
+        137,
+        186
       ],
       "accounts": [
         {
@@ -94,7 +83,8 @@ export type StakeTokens = {
             "seeds": [
               {
                 "kind": "account",
-                "path": "admin"
+                "path": "staking_account.creator",
+                "account": "stakingAccount"
               },
               {
                 "kind": "const",
@@ -115,14 +105,69 @@ export type StakeTokens = {
                   110,
                   116
                 ]
+              },
+              {
+                "kind": "account",
+                "path": "staking_account.pool_id",
+                "account": "stakingAccount"
+              }
+            ]
+          }
+        },
+        {
+          "name": "rewardStreamVault",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  114,
+                  101,
+                  119,
+                  97,
+                  114,
+                  100,
+                  95,
+                  115,
+                  116,
+                  114,
+                  101,
+                  97,
+                  109
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "index"
               }
             ]
           }
         },
+        {
+          "name": "streamMint"
+        },
+        {
+          "name": "mint"
+        },
         {
           "name": "admin",
           "writable": true,
-          "signer": true
+          "signer": true,
+          "relations": [
+            "stakingAccount"
+          ]
+        },
+        {
+          "name": "adminTokenAccount",
+          "writable": true
+        },
+        {
+          "name": "tokenProgram"
         },
         {
           "name": "systemProgram",
@@ -131,42 +176,46 @@ export type StakeTokens = {
       ],
       "args": [
         {
-          "name": "bump",
+          "name": "index",
           "type": "u8"
         },
         {
-          "name": "tokenMint",
-          "type": "pubkey"
+          "name": "amount",
+          "type": "u64"
         },
         {
-          "name": "rewardRate",
-          "type": "u8"
+          "name": "startTime",
+          "type": "i64"
         },
         {
-          "name": "minStakingDuration",
+          "name": "endTime",
           "type": "i64"
         }
       ]
     },
     {
-      "name": "redeem",
+      "name": "applyConfigUpdate",
       "docs": [
-        "Redeem instruction",
-        "This instruction is used to redeem staked tokens",
+        "Apply config update instruction",
+        "This instruction is used by the admin to apply the staged pool parameters once the timelock has elapsed",
+        "The APY rewards accrued so far are checkpointed at the old reward rate, the new reward rate only applies",
+        "to the rewards accrued from now on, the new minimum staking duration only applies to the positions opened afterwards",
+        "and the new unbonding cooldown to the unbonding requests made afterwards",
+        "While a cooldown is set, principal can only leave the pool through the unbonding queue",
+        "(`partial_unstake` and `redeem` are disabled), a zero cooldown enables them again",
         "",
         "# Arguments",
-        "* `ctx` - context of the program",
-        "* `force_redeem` - force redeeming"
+        "* `ctx` - context of the program"
       ],
       "discriminator": [
-        184,
-        12,
-        86,
-        149,
-        70,
-        196,
-        97,
-        225
+        211,
+        233,
+        51,
+        21,
+        33,
+        87,
+        209,
+        147
       ],
       "accounts": [
         {
@@ -174,93 +223,127 @@ export type StakeTokens = {
           "writable": true
         },
         {
-          "name": "stakingTokenAccountOwner",
-          "writable": true,
-          "signer": true
-        },
-        {
-          "name": "userStake",
-          "writable": true,
-          "pda": {
-            "seeds": [
-              {
-                "kind": "account",
-                "path": "user"
-              },
-              {
-                "kind": "const",
-                "value": [
-                  117,
-                  115,
-                  101,
-                  114,
-                  95,
-                  115,
-                  116,
-                  97,
-                  107,
-                  101
-                ]
-              }
-            ]
-          }
-        },
-        {
-          "name": "user",
-          "writable": true,
-          "signer": true
-        },
-        {
-          "name": "userTokenAccount",
-          "writable": true
-        },
+          "name": "admin",
+          "signer": true,
+          "relations": [
+            "stakingAccount"
+          ]
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "beginShutdown",
+      "docs": [
+        "Begin shutdown instruction",
+        "This instruction is used by the admin to start winding down the pool",
+        "New stakes are blocked, rewards (reward streams included) stop accruing now, and users can redeem without lock or cooldown",
+        "until the end of the grace period, after which anyone can return the remaining positions with `return_position`",
+        "",
+        "# Arguments",
+        "* `ctx` - context of the program",
+        "* `grace_period` - time in seconds users have to redeem by themselves"
+      ],
+      "discriminator": [
+        89,
+        30,
+        3,
+        71,
+        55,
+        236,
+        253,
+        127
+      ],
+      "accounts": [
         {
-          "name": "stakingTokenAccount",
+          "name": "stakingAccount",
           "writable": true
         },
         {
-          "name": "tokenProgram",
-          "address": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
+          "name": "admin",
+          "signer": true,
+          "relations": [
+            "stakingAccount"
+          ]
         }
       ],
       "args": [
         {
-          "name": "forceRedeem",
-          "type": "bool"
+          "name": "gracePeriod",
+          "type": "i64"
         }
       ]
     },
     {
-      "name": "stake",
+      "name": "claimRewards",
       "docs": [
-        "Stake instruction",
-        "This instruction is used to stake tokens",
+        "Claim rewards instruction",
+        "This instruction is used to pay out the rewards accrued by a position without unstaking",
+        "The principal and the lock of the position are left untouched",
+        "The rewards of the reward streams are paid out as well, `remaining_accounts` holding the accounts of every stream of the pool (see `pay_stream_rewards`)",
+        "A position kept open by an exit for its stream rewards is closed once they are claimed (or forfeited by the retirement of their stream)",
         "",
         "# Arguments",
-        "* `ctx` - context of the program",
-        "* `amount` - amount to stake",
-        "* `timestamp` - custom timestamp for testing"
+        "* `ctx` - context of the program"
       ],
       "discriminator": [
-        206,
-        176,
-        202,
-        18,
-        200,
-        209,
-        179,
-        108
+        4,
+        144,
+        132,
+        71,
+        116,
+        23,
+        151,
+        80
       ],
       "accounts": [
         {
           "name": "stakingAccount",
-          "writable": true
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "staking_account.creator",
+                "account": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  116,
+                  97,
+                  107,
+                  105,
+                  110,
+                  103,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "staking_account.pool_id",
+                "account": "stakingAccount"
+              }
+            ]
+          }
         },
         {
           "name": "userStake",
           "writable": true,
           "pda": {
             "seeds": [
+              {
+                "kind": "account",
+                "path": "stakingAccount"
+              },
               {
                 "kind": "account",
                 "path": "user"
@@ -279,6 +362,11 @@ export type StakeTokens = {
                   107,
                   101
                 ]
+              },
+              {
+                "kind": "account",
+                "path": "user_stake.position_id",
+                "account": "userStake"
               }
             ]
           }
@@ -286,7 +374,10 @@ export type StakeTokens = {
         {
           "name": "user",
           "writable": true,
-          "signer": true
+          "signer": true,
+          "relations": [
+            "userStake"
+          ]
         },
         {
           "name": "userTokenAccount",
@@ -294,102 +385,5875 @@ export type StakeTokens = {
         },
         {
           "name": "stakingTokenAccount",
-          "writable": true
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  116,
+                  97,
+                  107,
+                  105,
+                  110,
+                  103,
+                  95,
+                  116,
+                  111,
+                  107,
+                  101,
+                  110,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              }
+            ]
+          }
         },
         {
-          "name": "tokenProgram",
-          "address": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
+          "name": "mint"
         },
         {
-          "name": "systemProgram",
-          "address": "11111111111111111111111111111111"
-        }
-      ],
-      "args": [
+          "name": "rewardTokenAccount",
+          "writable": true,
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  114,
+                  101,
+                  119,
+                  97,
+                  114,
+                  100,
+                  95,
+                  116,
+                  111,
+                  107,
+                  101,
+                  110,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              }
+            ]
+          }
+        },
         {
-          "name": "amount",
-          "type": "u64"
+          "name": "rewardMint",
+          "optional": true
         },
         {
-          "name": "timestamp",
-          "type": {
-            "option": "i64"
+          "name": "userRewardTokenAccount",
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "tokenProgram"
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "closePool",
+      "docs": [
+        "Close pool instruction",
+        "This instruction is used by the admin to close a pool shutting down once every position is gone",
+        "The tokens left in the vaults (unallocated rewards and rounding dust) are sent to the admin,",
+        "then the vaults and the staking account are closed and their rent returned to the admin",
+        "`remaining_accounts` holds the `(vault, mint, admin token account)` accounts of every reward stream, in order",
+        "A pool with reward streams can only be closed after their claim period, as emptied positions can still hold their rewards",
+        "",
+        "# Arguments",
+        "* `ctx` - context of the program"
+      ],
+      "discriminator": [
+        140,
+        189,
+        209,
+        23,
+        239,
+        62,
+        239,
+        11
+      ],
+      "accounts": [
+        {
+          "name": "stakingAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "staking_account.creator",
+                "account": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  116,
+                  97,
+                  107,
+                  105,
+                  110,
+                  103,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "staking_account.pool_id",
+                "account": "stakingAccount"
+              }
+            ]
+          }
+        },
+        {
+          "name": "stakingTokenAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  116,
+                  97,
+                  107,
+                  105,
+                  110,
+                  103,
+                  95,
+                  116,
+                  111,
+                  107,
+                  101,
+                  110,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "admin",
+          "writable": true,
+          "signer": true,
+          "relations": [
+            "stakingAccount"
+          ]
+        },
+        {
+          "name": "adminTokenAccount",
+          "writable": true
+        },
+        {
+          "name": "mint"
+        },
+        {
+          "name": "rewardTokenAccount",
+          "writable": true,
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  114,
+                  101,
+                  119,
+                  97,
+                  114,
+                  100,
+                  95,
+                  116,
+                  111,
+                  107,
+                  101,
+                  110,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "rewardMint",
+          "optional": true
+        },
+        {
+          "name": "adminRewardTokenAccount",
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "tokenProgram"
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "compound",
+      "docs": [
+        "Compound instruction",
+        "This instruction is used to restake the rewards accrued by a position",
+        "The rewards already sit in the vault, so they are moved from the reward funds to the principal without any transfer",
+        "The owner can always compound, anyone else (a cranker) only if the position opted in with `set_auto_compound`,",
+        "in which case the cranker receives `compound_tip_bps` of the reward",
+        "",
+        "# Arguments",
+        "* `ctx` - context of the program"
+      ],
+      "discriminator": [
+        165,
+        208,
+        251,
+        78,
+        242,
+        160,
+        141,
+        47
+      ],
+      "accounts": [
+        {
+          "name": "stakingAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "staking_account.creator",
+                "account": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  116,
+                  97,
+                  107,
+                  105,
+                  110,
+                  103,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "staking_account.pool_id",
+                "account": "stakingAccount"
+              }
+            ]
+          }
+        },
+        {
+          "name": "userStake",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "stakingAccount"
+              },
+              {
+                "kind": "account",
+                "path": "user_stake.user",
+                "account": "userStake"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  117,
+                  115,
+                  101,
+                  114,
+                  95,
+                  115,
+                  116,
+                  97,
+                  107,
+                  101
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "user_stake.position_id",
+                "account": "userStake"
+              }
+            ]
+          }
+        },
+        {
+          "name": "authority",
+          "signer": true
+        },
+        {
+          "name": "authorityTokenAccount",
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "stakingTokenAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  116,
+                  97,
+                  107,
+                  105,
+                  110,
+                  103,
+                  95,
+                  116,
+                  111,
+                  107,
+                  101,
+                  110,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "mint"
+        },
+        {
+          "name": "tokenProgram"
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "emergencyWithdraw",
+      "docs": [
+        "Emergency withdraw instruction",
+        "This instruction is used by users to get the principal of a position back once the pool is in emergency,",
+        "without any reward, lock or cooldown check, and without any operator signature",
+        "The position (including its unbonding amount) is closed",
+        "",
+        "# Arguments",
+        "* `ctx` - context of the program"
+      ],
+      "discriminator": [
+        239,
+        45,
+        203,
+        64,
+        150,
+        73,
+        218,
+        92
+      ],
+      "accounts": [
+        {
+          "name": "stakingAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "staking_account.creator",
+                "account": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  116,
+                  97,
+                  107,
+                  105,
+                  110,
+                  103,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "staking_account.pool_id",
+                "account": "stakingAccount"
+              }
+            ]
+          }
+        },
+        {
+          "name": "userStake",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "stakingAccount"
+              },
+              {
+                "kind": "account",
+                "path": "user"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  117,
+                  115,
+                  101,
+                  114,
+                  95,
+                  115,
+                  116,
+                  97,
+                  107,
+                  101
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "user_stake.position_id",
+                "account": "userStake"
+              }
+            ]
+          }
+        },
+        {
+          "name": "user",
+          "writable": true,
+          "signer": true,
+          "relations": [
+            "userStake"
+          ]
+        },
+        {
+          "name": "userTokenAccount",
+          "writable": true
+        },
+        {
+          "name": "stakingTokenAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  116,
+                  97,
+                  107,
+                  105,
+                  110,
+                  103,
+                  95,
+                  116,
+                  111,
+                  107,
+                  101,
+                  110,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "mint"
+        },
+        {
+          "name": "tokenProgram"
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "fundEmission",
+      "docs": [
+        "Fund emission instruction",
+        "This instruction is used to fund an emission mode pool with `amount` tokens distributed over `duration` seconds",
+        "Rewards are shared between stakers pro rata to their stake, rewards not yet emitted",
+        "from a previous funding are rolled into the new emission",
+        "",
+        "# Arguments",
+        "* `ctx` - context of the program",
+        "* `amount` - amount of reward tokens to emit",
+        "* `duration` - emission period in seconds"
+      ],
+      "discriminator": [
+        255,
+        9,
+        105,
+        62,
+        254,
+        211,
+        53,
+        1
+      ],
+      "accounts": [
+        {
+          "name": "stakingAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "staking_account.creator",
+                "account": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  116,
+                  97,
+                  107,
+                  105,
+                  110,
+                  103,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "staking_account.pool_id",
+                "account": "stakingAccount"
+              }
+            ]
+          }
+        },
+        {
+          "name": "stakingTokenAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  116,
+                  97,
+                  107,
+                  105,
+                  110,
+                  103,
+                  95,
+                  116,
+                  111,
+                  107,
+                  101,
+                  110,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "admin",
+          "writable": true,
+          "signer": true,
+          "relations": [
+            "stakingAccount"
+          ]
+        },
+        {
+          "name": "adminTokenAccount",
+          "writable": true
+        },
+        {
+          "name": "mint"
+        },
+        {
+          "name": "rewardTokenAccount",
+          "writable": true,
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  114,
+                  101,
+                  119,
+                  97,
+                  114,
+                  100,
+                  95,
+                  116,
+                  111,
+                  107,
+                  101,
+                  110,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "rewardMint",
+          "optional": true
+        },
+        {
+          "name": "tokenProgram"
+        }
+      ],
+      "args": [
+        {
+          "name": "amount",
+          "type": "u64"
+        },
+        {
+          "name": "duration",
+          "type": "i64"
+        }
+      ]
+    },
+    {
+      "name": "fundReward",
+      "docs": [
+        "Fund reward instruction",
+        "This instruction is used to fund the reward pool of an APY mode pool",
+        "",
+        "# Arguments",
+        "* `ctx` - context of the program",
+        "* `amount` - amount to fund the reward pool"
+      ],
+      "discriminator": [
+        188,
+        50,
+        249,
+        165,
+        93,
+        151,
+        38,
+        63
+      ],
+      "accounts": [
+        {
+          "name": "stakingAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "staking_account.creator",
+                "account": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  116,
+                  97,
+                  107,
+                  105,
+                  110,
+                  103,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "staking_account.pool_id",
+                "account": "stakingAccount"
+              }
+            ]
+          }
+        },
+        {
+          "name": "stakingTokenAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  116,
+                  97,
+                  107,
+                  105,
+                  110,
+                  103,
+                  95,
+                  116,
+                  111,
+                  107,
+                  101,
+                  110,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "admin",
+          "writable": true,
+          "signer": true,
+          "relations": [
+            "stakingAccount"
+          ]
+        },
+        {
+          "name": "adminTokenAccount",
+          "writable": true
+        },
+        {
+          "name": "mint"
+        },
+        {
+          "name": "rewardTokenAccount",
+          "writable": true,
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  114,
+                  101,
+                  119,
+                  97,
+                  114,
+                  100,
+                  95,
+                  116,
+                  111,
+                  107,
+                  101,
+                  110,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "rewardMint",
+          "optional": true
+        },
+        {
+          "name": "tokenProgram"
+        }
+      ],
+      "args": [
+        {
+          "name": "amount",
+          "type": "u64"
+        }
+      ]
+    },
+    {
+      "name": "increaseStake",
+      "docs": [
+        "Increase stake instruction",
+        "This instruction is used to add tokens to an existing position",
+        "Rewards accrued so far are settled at the old balance, the start time (lock) of the position is kept",
+        "",
+        "# Arguments",
+        "* `ctx` - context of the program",
+        "* `amount` - amount to add to the position"
+      ],
+      "discriminator": [
+        239,
+        74,
+        179,
+        156,
+        119,
+        147,
+        39,
+        212
+      ],
+      "accounts": [
+        {
+          "name": "stakingAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "staking_account.creator",
+                "account": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  116,
+                  97,
+                  107,
+                  105,
+                  110,
+                  103,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "staking_account.pool_id",
+                "account": "stakingAccount"
+              }
+            ]
+          }
+        },
+        {
+          "name": "userStake",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "stakingAccount"
+              },
+              {
+                "kind": "account",
+                "path": "user"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  117,
+                  115,
+                  101,
+                  114,
+                  95,
+                  115,
+                  116,
+                  97,
+                  107,
+                  101
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "user_stake.position_id",
+                "account": "userStake"
+              }
+            ]
+          }
+        },
+        {
+          "name": "user",
+          "writable": true,
+          "signer": true,
+          "relations": [
+            "userStake"
+          ]
+        },
+        {
+          "name": "userTokenAccount",
+          "writable": true
+        },
+        {
+          "name": "stakingTokenAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  116,
+                  97,
+                  107,
+                  105,
+                  110,
+                  103,
+                  95,
+                  116,
+                  111,
+                  107,
+                  101,
+                  110,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "mint"
+        },
+        {
+          "name": "tokenProgram"
+        }
+      ],
+      "args": [
+        {
+          "name": "amount",
+          "type": "u64"
+        }
+      ]
+    },
+    {
+      "name": "initialize",
+      "docs": [
+        "Initialize instruction",
+        "This instruction is used to create a new staking pool and its token vault",
+        "The vault is a PDA token account owned by the staking account, so tokens can only leave it through this program",
+        "",
+        "# Arguments",
+        "* `ctx` - context of the program",
+        "* `pool_id` - unique id of the pool among the pools created by the same admin",
+        "* `token_mint` - program_id (address) of specific token which is allowed stake",
+        "* `reward_rate` - APY return yearly in percentage (0-100), unused in emission mode",
+        "* `min_staking_duration` - minimum staking duration in seconds",
+        "* `reward_mode` - fixed APY per user, or a funded emission shared by all stakers"
+      ],
+      "discriminator": [
+        175,
+        175,
+        109,
+        31,
+        13,
+        152,
+        155,
+        237
+      ],
+      "accounts": [
+        {
+          "name": "stakingAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "admin"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  116,
+                  97,
+                  107,
+                  105,
+                  110,
+                  103,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "poolId"
+              }
+            ]
+          }
+        },
+        {
+          "name": "stakingTokenAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  116,
+                  97,
+                  107,
+                  105,
+                  110,
+                  103,
+                  95,
+                  116,
+                  111,
+                  107,
+                  101,
+                  110,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "mint"
+        },
+        {
+          "name": "admin",
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "tokenProgram"
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "poolId",
+          "type": "u64"
+        },
+        {
+          "name": "tokenMint",
+          "type": "pubkey"
+        },
+        {
+          "name": "rewardRate",
+          "type": "u8"
+        },
+        {
+          "name": "minStakingDuration",
+          "type": "i64"
+        },
+        {
+          "name": "rewardMode",
+          "type": {
+            "defined": {
+              "name": "rewardMode"
+            }
+          }
+        }
+      ]
+    },
+    {
+      "name": "migratePool",
+      "docs": [
+        "Migrate pool instruction",
+        "This instruction is used by the admin to move a pool of the baseline layout (version 0, see `StakingAccountV0`)",
+        "to a new pool of the current layout, the legacy pool can not be upgraded in place as its PDA has no pool id",
+        "The new pool keeps the token mint, reward rate and minimum staking duration of the legacy pool and pays an APY,",
+        "the reward funds of the legacy pool (held by the admin token account in the baseline) are moved to its vault",
+        "The legacy pool is closed, its positions are then moved one by one with `migrate_user_stake`",
+        "",
+        "# Arguments",
+        "* `ctx` - context of the program",
+        "* `pool_id` - id of the new pool among the pools created by the admin"
+      ],
+      "discriminator": [
+        55,
+        170,
+        171,
+        123,
+        210,
+        69,
+        39,
+        172
+      ],
+      "accounts": [
+        {
+          "name": "legacyStakingAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "admin"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  116,
+                  97,
+                  107,
+                  105,
+                  110,
+                  103,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "stakingAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "admin"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  116,
+                  97,
+                  107,
+                  105,
+                  110,
+                  103,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              },
+              {
+                "kind": "arg",
+                "path": "poolId"
+              }
+            ]
+          }
+        },
+        {
+          "name": "stakingTokenAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  116,
+                  97,
+                  107,
+                  105,
+                  110,
+                  103,
+                  95,
+                  116,
+                  111,
+                  107,
+                  101,
+                  110,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "mint"
+        },
+        {
+          "name": "admin",
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "adminTokenAccount",
+          "writable": true
+        },
+        {
+          "name": "tokenProgram"
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "poolId",
+          "type": "u64"
+        }
+      ]
+    },
+    {
+      "name": "migrateUserStake",
+      "docs": [
+        "Migrate user stake instruction",
+        "This instruction is used by the user, with the admin co-signing, to move a position of the baseline layout",
+        "(version 0, see `UserStakeV0`) to a new position of the user in a migrated pool, the legacy position is not linked",
+        "to any pool and its PDA is derived from the user only, so it can not be upgraded in place",
+        "Nothing on chain tells which pool the legacy position belongs to, so the user picks it by signing",
+        "(the admin alone could move it to any pool it runs)",
+        "The principal (held by the admin token account in the baseline) is moved to the vault of the pool,",
+        "the start time of the position is kept (converted to seconds) so its lock runs from the original stake,",
+        "and the APY accrued since then at the rate of the pool is settled into the rewards owed to the position",
+        "The legacy position is closed, its rent goes back to the user",
+        "",
+        "# Arguments",
+        "* `ctx` - context of the program"
+      ],
+      "discriminator": [
+        29,
+        38,
+        213,
+        47,
+        115,
+        141,
+        135,
+        106
+      ],
+      "accounts": [
+        {
+          "name": "stakingAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "staking_account.creator",
+                "account": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  116,
+                  97,
+                  107,
+                  105,
+                  110,
+                  103,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "staking_account.pool_id",
+                "account": "stakingAccount"
+              }
+            ]
+          }
+        },
+        {
+          "name": "legacyUserStake",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "user"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  117,
+                  115,
+                  101,
+                  114,
+                  95,
+                  115,
+                  116,
+                  97,
+                  107,
+                  101
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "user",
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "userStakeCounter",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "stakingAccount"
+              },
+              {
+                "kind": "account",
+                "path": "user"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  117,
+                  115,
+                  101,
+                  114,
+                  95,
+                  115,
+                  116,
+                  97,
+                  107,
+                  101,
+                  95,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116,
+                  101,
+                  114
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "userStake",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "stakingAccount"
+              },
+              {
+                "kind": "account",
+                "path": "user"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  117,
+                  115,
+                  101,
+                  114,
+                  95,
+                  115,
+                  116,
+                  97,
+                  107,
+                  101
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "user_stake_counter.next_position_id",
+                "account": "userStakeCounter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "stakingTokenAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  116,
+                  97,
+                  107,
+                  105,
+                  110,
+                  103,
+                  95,
+                  116,
+                  111,
+                  107,
+                  101,
+                  110,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "mint"
+        },
+        {
+          "name": "admin",
+          "writable": true,
+          "signer": true,
+          "relations": [
+            "stakingAccount"
+          ]
+        },
+        {
+          "name": "adminTokenAccount",
+          "writable": true
+        },
+        {
+          "name": "tokenProgram"
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "partialUnstake",
+      "docs": [
+        "Partial unstake instruction",
+        "This instruction is used to withdraw part of the principal of a position",
+        "All rewards accrued so far are paid out and the position stays open with the remaining amount",
+        "The rewards of the reward streams are paid out as well from the stream accounts in `remaining_accounts`,",
+        "a stream whose destination is frozen or closed does not block the exit and its rewards stay pending (see `pay_stream_rewards`)",
+        "",
+        "# Arguments",
+        "* `ctx` - context of the program",
+        "* `amount` - amount of principal to withdraw (must be lower than the staked amount, use `redeem` to withdraw everything)"
+      ],
+      "discriminator": [
+        55,
+        241,
+        205,
+        221,
+        45,
+        114,
+        205,
+        163
+      ],
+      "accounts": [
+        {
+          "name": "stakingAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "staking_account.creator",
+                "account": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  116,
+                  97,
+                  107,
+                  105,
+                  110,
+                  103,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "staking_account.pool_id",
+                "account": "stakingAccount"
+              }
+            ]
+          }
+        },
+        {
+          "name": "userStake",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "stakingAccount"
+              },
+              {
+                "kind": "account",
+                "path": "user"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  117,
+                  115,
+                  101,
+                  114,
+                  95,
+                  115,
+                  116,
+                  97,
+                  107,
+                  101
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "user_stake.position_id",
+                "account": "userStake"
+              }
+            ]
+          }
+        },
+        {
+          "name": "user",
+          "writable": true,
+          "signer": true,
+          "relations": [
+            "userStake"
+          ]
+        },
+        {
+          "name": "userTokenAccount",
+          "writable": true
+        },
+        {
+          "name": "stakingTokenAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  116,
+                  97,
+                  107,
+                  105,
+                  110,
+                  103,
+                  95,
+                  116,
+                  111,
+                  107,
+                  101,
+                  110,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "mint"
+        },
+        {
+          "name": "rewardTokenAccount",
+          "writable": true,
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  114,
+                  101,
+                  119,
+                  97,
+                  114,
+                  100,
+                  95,
+                  116,
+                  111,
+                  107,
+                  101,
+                  110,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "rewardMint",
+          "optional": true
+        },
+        {
+          "name": "userRewardTokenAccount",
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "tokenProgram"
+        }
+      ],
+      "args": [
+        {
+          "name": "amount",
+          "type": "u64"
+        }
+      ]
+    },
+    {
+      "name": "previewReward",
+      "docs": [
+        "Preview reward instruction",
+        "This read-only instruction is used to compute what a position would receive at the current time,",
+        "settling copies of the accounts the same way `claim_rewards` and `redeem` do (no account is modified)",
+        "The preview is returned as return data, clients read it by simulating the transaction",
+        "",
+        "# Arguments",
+        "* `ctx` - context of the program"
+      ],
+      "discriminator": [
+        171,
+        148,
+        121,
+        186,
+        32,
+        140,
+        128,
+        51
+      ],
+      "accounts": [
+        {
+          "name": "stakingAccount"
+        },
+        {
+          "name": "userStake"
+        }
+      ],
+      "args": [],
+      "returns": {
+        "defined": {
+          "name": "rewardPreview"
+        }
+      }
+    },
+    {
+      "name": "proposeAdmin",
+      "docs": [
+        "Propose admin instruction",
+        "This instruction is used by the admin to start transferring the pool to a new admin",
+        "The transfer only happens once the new admin signs `accept_admin`, proposing again replaces",
+        "the pending admin and proposing the default pubkey cancels the transfer",
+        "",
+        "# Arguments",
+        "* `ctx` - context of the program",
+        "* `new_admin` - account allowed to accept the admin role"
+      ],
+      "discriminator": [
+        121,
+        214,
+        199,
+        212,
+        87,
+        39,
+        117,
+        234
+      ],
+      "accounts": [
+        {
+          "name": "stakingAccount",
+          "writable": true
+        },
+        {
+          "name": "admin",
+          "signer": true,
+          "relations": [
+            "stakingAccount"
+          ]
+        }
+      ],
+      "args": [
+        {
+          "name": "newAdmin",
+          "type": "pubkey"
+        }
+      ]
+    },
+    {
+      "name": "proposeConfigUpdate",
+      "docs": [
+        "Propose config update instruction",
+        "This instruction is used by the admin to stage new pool parameters, applied by `apply_config_update`",
+        "once the current `config_timelock` has elapsed, so stakers get advance notice of the change",
+        "A new proposal replaces the staged one and restarts the timelock",
+        "",
+        "# Arguments",
+        "* `ctx` - context of the program",
+        "* `config` - new reward rate, minimum staking duration, timelock and unbonding cooldown of the pool"
+      ],
+      "discriminator": [
+        134,
+        33,
+        64,
+        84,
+        30,
+        156,
+        236,
+        79
+      ],
+      "accounts": [
+        {
+          "name": "stakingAccount",
+          "writable": true
+        },
+        {
+          "name": "admin",
+          "signer": true,
+          "relations": [
+            "stakingAccount"
+          ]
+        }
+      ],
+      "args": [
+        {
+          "name": "config",
+          "type": {
+            "defined": {
+              "name": "poolConfig"
+            }
+          }
+        }
+      ]
+    },
+    {
+      "name": "redeem",
+      "docs": [
+        "Redeem instruction",
+        "This instruction is used to redeem the staked tokens of a single position",
+        "Force redeeming before the lock ends applies the penalty policy of the pool",
+        "The rewards of the reward streams are paid out as well from the stream accounts in `remaining_accounts`,",
+        "a stream whose destination is frozen or closed does not block the exit, its rewards stay pending in the position",
+        "which is kept open (with nothing staked) until they are claimed with `claim_rewards` (see `pay_stream_rewards`)",
+        "",
+        "# Arguments",
+        "* `ctx` - context of the program",
+        "* `force_redeem` - force redeeming (before the end of the lock)"
+      ],
+      "discriminator": [
+        184,
+        12,
+        86,
+        149,
+        70,
+        196,
+        97,
+        225
+      ],
+      "accounts": [
+        {
+          "name": "stakingAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "staking_account.creator",
+                "account": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  116,
+                  97,
+                  107,
+                  105,
+                  110,
+                  103,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "staking_account.pool_id",
+                "account": "stakingAccount"
+              }
+            ]
+          }
+        },
+        {
+          "name": "userStake",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "stakingAccount"
+              },
+              {
+                "kind": "account",
+                "path": "user"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  117,
+                  115,
+                  101,
+                  114,
+                  95,
+                  115,
+                  116,
+                  97,
+                  107,
+                  101
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "user_stake.position_id",
+                "account": "userStake"
+              }
+            ]
+          }
+        },
+        {
+          "name": "user",
+          "writable": true,
+          "signer": true,
+          "relations": [
+            "userStake"
+          ]
+        },
+        {
+          "name": "userTokenAccount",
+          "writable": true
+        },
+        {
+          "name": "stakingTokenAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  116,
+                  97,
+                  107,
+                  105,
+                  110,
+                  103,
+                  95,
+                  116,
+                  111,
+                  107,
+                  101,
+                  110,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "treasuryTokenAccount",
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "mint",
+          "writable": true
+        },
+        {
+          "name": "rewardTokenAccount",
+          "writable": true,
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  114,
+                  101,
+                  119,
+                  97,
+                  114,
+                  100,
+                  95,
+                  116,
+                  111,
+                  107,
+                  101,
+                  110,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "rewardMint",
+          "optional": true
+        },
+        {
+          "name": "userRewardTokenAccount",
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "tokenProgram"
+        }
+      ],
+      "args": [
+        {
+          "name": "forceRedeem",
+          "type": "bool"
+        }
+      ]
+    },
+    {
+      "name": "renounceAdmin",
+      "docs": [
+        "Renounce admin instruction",
+        "This instruction is used by the admin to give up the admin role for good",
+        "No one can sign as the default pubkey, so the admin instructions of the pool are disabled afterwards",
+        "",
+        "# Arguments",
+        "* `ctx` - context of the program"
+      ],
+      "discriminator": [
+        223,
+        213,
+        55,
+        194,
+        0,
+        108,
+        225,
+        137
+      ],
+      "accounts": [
+        {
+          "name": "stakingAccount",
+          "writable": true
+        },
+        {
+          "name": "admin",
+          "signer": true,
+          "relations": [
+            "stakingAccount"
+          ]
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "requestUnstake",
+      "docs": [
+        "Request unstake instruction",
+        "This instruction is used to start unbonding part (or all) of the principal of a position",
+        "The rewards accrued so far are paid out and the unbonding amount stops earning rewards,",
+        "it can be withdrawn with `withdraw_unbonded` once the cooldown has elapsed",
+        "The cooldown of the pool is captured by the first pending request of the position, so a later config update",
+        "does not change it, a new request restarts the captured cooldown of the whole unbonding amount of the position",
+        "The rewards of the reward streams are paid out as well from the stream accounts in `remaining_accounts`,",
+        "a stream whose destination is frozen or closed does not block the exit and its rewards stay pending (see `pay_stream_rewards`)",
+        "",
+        "# Arguments",
+        "* `ctx` - context of the program",
+        "* `amount` - amount of principal to unbond"
+      ],
+      "discriminator": [
+        44,
+        154,
+        110,
+        253,
+        160,
+        202,
+        54,
+        34
+      ],
+      "accounts": [
+        {
+          "name": "stakingAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "staking_account.creator",
+                "account": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  116,
+                  97,
+                  107,
+                  105,
+                  110,
+                  103,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "staking_account.pool_id",
+                "account": "stakingAccount"
+              }
+            ]
+          }
+        },
+        {
+          "name": "userStake",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "stakingAccount"
+              },
+              {
+                "kind": "account",
+                "path": "user"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  117,
+                  115,
+                  101,
+                  114,
+                  95,
+                  115,
+                  116,
+                  97,
+                  107,
+                  101
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "user_stake.position_id",
+                "account": "userStake"
+              }
+            ]
+          }
+        },
+        {
+          "name": "user",
+          "writable": true,
+          "signer": true,
+          "relations": [
+            "userStake"
+          ]
+        },
+        {
+          "name": "userTokenAccount",
+          "writable": true
+        },
+        {
+          "name": "stakingTokenAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  116,
+                  97,
+                  107,
+                  105,
+                  110,
+                  103,
+                  95,
+                  116,
+                  111,
+                  107,
+                  101,
+                  110,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "mint"
+        },
+        {
+          "name": "rewardTokenAccount",
+          "writable": true,
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  114,
+                  101,
+                  119,
+                  97,
+                  114,
+                  100,
+                  95,
+                  116,
+                  111,
+                  107,
+                  101,
+                  110,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "rewardMint",
+          "optional": true
+        },
+        {
+          "name": "userRewardTokenAccount",
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "tokenProgram"
+        }
+      ],
+      "args": [
+        {
+          "name": "amount",
+          "type": "u64"
+        }
+      ]
+    },
+    {
+      "name": "retireRewardStream",
+      "docs": [
+        "Retire reward stream instruction",
+        "This instruction is used by the admin to free the slot of a finished reward stream",
+        "Stakers have `STREAM_CLAIM_PERIOD` after the end of the stream to claim its rewards, the rewards left",
+        "in its vault (unclaimed or undistributed) are then sent to the admin, the vault is closed and its slot",
+        "can be taken by a new stream (rewards of the retired stream still pending in positions are forfeited)",
+        "",
+        "# Arguments",
+        "* `ctx` - context of the program",
+        "* `index` - slot of the reward stream to retire"
+      ],
+      "discriminator": [
+        240,
+        71,
+        38,
+        248,
+        160,
+        195,
+        134,
+        142
+      ],
+      "accounts": [
+        {
+          "name": "stakingAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "staking_account.creator",
+                "account": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  116,
+                  97,
+                  107,
+                  105,
+                  110,
+                  103,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "staking_account.pool_id",
+                "account": "stakingAccount"
+              }
+            ]
+          }
+        },
+        {
+          "name": "rewardStreamVault",
+          "writable": true
+        },
+        {
+          "name": "streamMint"
+        },
+        {
+          "name": "admin",
+          "writable": true,
+          "signer": true,
+          "relations": [
+            "stakingAccount"
+          ]
+        },
+        {
+          "name": "adminTokenAccount",
+          "writable": true
+        },
+        {
+          "name": "tokenProgram"
+        }
+      ],
+      "args": [
+        {
+          "name": "index",
+          "type": "u8"
+        }
+      ]
+    },
+    {
+      "name": "returnPosition",
+      "docs": [
+        "Return position instruction",
+        "This instruction is used by anyone (a cranker) to return a position of a pool shutting down after the grace period",
+        "The principal, the unbonding amount and the rewards accrued until the shutdown are sent to the owner",
+        "(rewards are capped by the reward funds) and the position is closed, its rent going back to the owner",
+        "The rewards of the reward streams are paid out as well from the stream accounts in `remaining_accounts`,",
+        "a stream whose destination is frozen or closed does not block the return, its rewards stay pending in the position",
+        "which is kept open (with nothing staked) until they are claimed with `claim_rewards` (see `pay_stream_rewards`)",
+        "",
+        "# Arguments",
+        "* `ctx` - context of the program"
+      ],
+      "discriminator": [
+        203,
+        165,
+        110,
+        247,
+        253,
+        181,
+        21,
+        121
+      ],
+      "accounts": [
+        {
+          "name": "stakingAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "staking_account.creator",
+                "account": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  116,
+                  97,
+                  107,
+                  105,
+                  110,
+                  103,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "staking_account.pool_id",
+                "account": "stakingAccount"
+              }
+            ]
+          }
+        },
+        {
+          "name": "userStake",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "stakingAccount"
+              },
+              {
+                "kind": "account",
+                "path": "user"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  117,
+                  115,
+                  101,
+                  114,
+                  95,
+                  115,
+                  116,
+                  97,
+                  107,
+                  101
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "user_stake.position_id",
+                "account": "userStake"
+              }
+            ]
+          }
+        },
+        {
+          "name": "user",
+          "writable": true,
+          "relations": [
+            "userStake"
+          ]
+        },
+        {
+          "name": "userTokenAccount",
+          "writable": true
+        },
+        {
+          "name": "stakingTokenAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  116,
+                  97,
+                  107,
+                  105,
+                  110,
+                  103,
+                  95,
+                  116,
+                  111,
+                  107,
+                  101,
+                  110,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "authority",
+          "signer": true
+        },
+        {
+          "name": "mint"
+        },
+        {
+          "name": "rewardTokenAccount",
+          "writable": true,
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  114,
+                  101,
+                  119,
+                  97,
+                  114,
+                  100,
+                  95,
+                  116,
+                  111,
+                  107,
+                  101,
+                  110,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "rewardMint",
+          "optional": true
+        },
+        {
+          "name": "userRewardTokenAccount",
+          "writable": true,
+          "optional": true
+        },
+        {
+          "name": "tokenProgram"
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "setAutoCompound",
+      "docs": [
+        "Set auto compound instruction",
+        "This instruction is used by the owner of a position to allow (or forbid) anyone to compound it",
+        "",
+        "# Arguments",
+        "* `ctx` - context of the program",
+        "* `enabled` - whether crankers may compound the position"
+      ],
+      "discriminator": [
+        101,
+        22,
+        46,
+        161,
+        189,
+        86,
+        250,
+        244
+      ],
+      "accounts": [
+        {
+          "name": "stakingAccount"
+        },
+        {
+          "name": "userStake",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "stakingAccount"
+              },
+              {
+                "kind": "account",
+                "path": "user"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  117,
+                  115,
+                  101,
+                  114,
+                  95,
+                  115,
+                  116,
+                  97,
+                  107,
+                  101
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "user_stake.position_id",
+                "account": "userStake"
+              }
+            ]
+          }
+        },
+        {
+          "name": "user",
+          "signer": true,
+          "relations": [
+            "userStake"
+          ]
+        }
+      ],
+      "args": [
+        {
+          "name": "enabled",
+          "type": "bool"
+        }
+      ]
+    },
+    {
+      "name": "setCompoundTip",
+      "docs": [
+        "Set compound tip instruction",
+        "This instruction is used by the admin to set the share of the reward paid to crankers",
+        "",
+        "# Arguments",
+        "* `ctx` - context of the program",
+        "* `compound_tip_bps` - tip in basis points of the compounded reward (at most `MAX_COMPOUND_TIP_BPS`)"
+      ],
+      "discriminator": [
+        88,
+        167,
+        143,
+        2,
+        236,
+        1,
+        94,
+        148
+      ],
+      "accounts": [
+        {
+          "name": "stakingAccount",
+          "writable": true
+        },
+        {
+          "name": "admin",
+          "signer": true,
+          "relations": [
+            "stakingAccount"
+          ]
+        }
+      ],
+      "args": [
+        {
+          "name": "compoundTipBps",
+          "type": "u16"
+        }
+      ]
+    },
+    {
+      "name": "setGuardian",
+      "docs": [
+        "Set guardian instruction",
+        "This instruction is used by the admin to appoint the guardian allowed to pause the pool",
+        "",
+        "# Arguments",
+        "* `ctx` - context of the program",
+        "* `guardian` - guardian of the pool (default pubkey to remove it)"
+      ],
+      "discriminator": [
+        147,
+        243,
+        50,
+        121,
+        154,
+        164,
+        50,
+        30
+      ],
+      "accounts": [
+        {
+          "name": "stakingAccount",
+          "writable": true
+        },
+        {
+          "name": "admin",
+          "signer": true,
+          "relations": [
+            "stakingAccount"
+          ]
+        }
+      ],
+      "args": [
+        {
+          "name": "guardian",
+          "type": "pubkey"
+        }
+      ]
+    },
+    {
+      "name": "setLockupTiers",
+      "docs": [
+        "Set lockup tiers instruction",
+        "This instruction is used by the admin to configure the lockup tiers users can pick when staking",
+        "Existing positions keep the lock and multiplier of the tier they were opened with",
+        "",
+        "# Arguments",
+        "* `ctx` - context of the program",
+        "* `tiers` - lockup tiers (1 to `MAX_LOCKUP_TIERS`), e.g. flexible / 30 / 90 / 180 / 365 days"
+      ],
+      "discriminator": [
+        26,
+        183,
+        115,
+        199,
+        204,
+        230,
+        172,
+        168
+      ],
+      "accounts": [
+        {
+          "name": "stakingAccount",
+          "writable": true
+        },
+        {
+          "name": "admin",
+          "signer": true,
+          "relations": [
+            "stakingAccount"
+          ]
+        }
+      ],
+      "args": [
+        {
+          "name": "tiers",
+          "type": {
+            "vec": {
+              "defined": {
+                "name": "lockupTier"
+              }
+            }
+          }
+        }
+      ]
+    },
+    {
+      "name": "setPenaltyPolicy",
+      "docs": [
+        "Set penalty policy instruction",
+        "This instruction is used by the admin to configure the penalty of force redeeming before the lock ends",
+        "",
+        "# Arguments",
+        "* `ctx` - context of the program",
+        "* `penalty_policy` - how the penalty is computed",
+        "* `penalty_bps` - penalty rate in basis points (of the principal, or of the reward for `ProRatedReward`)",
+        "* `penalty_destination` - where principal penalties go (the treasury being the `treasury_token_account` of the context)"
+      ],
+      "discriminator": [
+        32,
+        238,
+        3,
+        157,
+        42,
+        140,
+        117,
+        1
+      ],
+      "accounts": [
+        {
+          "name": "stakingAccount",
+          "writable": true
+        },
+        {
+          "name": "admin",
+          "signer": true,
+          "relations": [
+            "stakingAccount"
+          ]
+        },
+        {
+          "name": "treasuryTokenAccount",
+          "optional": true
+        }
+      ],
+      "args": [
+        {
+          "name": "penaltyPolicy",
+          "type": {
+            "defined": {
+              "name": "penaltyPolicy"
+            }
+          }
+        },
+        {
+          "name": "penaltyBps",
+          "type": "u16"
+        },
+        {
+          "name": "penaltyDestination",
+          "type": {
+            "defined": {
+              "name": "penaltyDestination"
+            }
+          }
+        }
+      ]
+    },
+    {
+      "name": "setPoolState",
+      "docs": [
+        "Set pool state instruction",
+        "This instruction is used as a kill-switch for incident response",
+        "The admin can move the pool to any state but `ShuttingDown` (see `begin_shutdown`), the guardian can only pause it or trigger the emergency",
+        "",
+        "# Arguments",
+        "* `ctx` - context of the program",
+        "* `pool_state` - new state of the pool"
+      ],
+      "discriminator": [
+        20,
+        104,
+        205,
+        0,
+        74,
+        96,
+        94,
+        19
+      ],
+      "accounts": [
+        {
+          "name": "stakingAccount",
+          "writable": true
+        },
+        {
+          "name": "authority",
+          "signer": true
+        }
+      ],
+      "args": [
+        {
+          "name": "poolState",
+          "type": {
+            "defined": {
+              "name": "poolState"
+            }
+          }
+        }
+      ]
+    },
+    {
+      "name": "setRewardMint",
+      "docs": [
+        "Set reward mint instruction",
+        "This instruction is used by the admin to pay the rewards of the pool in another token than the staked one",
+        "(e.g. for partner co-incentive campaigns), it creates the reward vault which holds the reward funds",
+        "It can only be called once, before the pool is funded or anything is staked",
+        "Rewards in another token can not be compounded, and principal penalties routed to the reward pool",
+        "stay in the staking vault until the pool is closed",
+        "",
+        "# Arguments",
+        "* `ctx` - context of the program"
+      ],
+      "discriminator": [
+        163,
+        98,
+        152,
+        110,
+        111,
+        9,
+        56,
+        94
+      ],
+      "accounts": [
+        {
+          "name": "stakingAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "staking_account.creator",
+                "account": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  116,
+                  97,
+                  107,
+                  105,
+                  110,
+                  103,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "staking_account.pool_id",
+                "account": "stakingAccount"
+              }
+            ]
+          }
+        },
+        {
+          "name": "rewardTokenAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  114,
+                  101,
+                  119,
+                  97,
+                  114,
+                  100,
+                  95,
+                  116,
+                  111,
+                  107,
+                  101,
+                  110,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "rewardMint"
+        },
+        {
+          "name": "mint"
+        },
+        {
+          "name": "admin",
+          "writable": true,
+          "signer": true,
+          "relations": [
+            "stakingAccount"
+          ]
+        },
+        {
+          "name": "tokenProgram"
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "stake",
+      "docs": [
+        "Stake instruction",
+        "This instruction is used to stake tokens",
+        "Every call opens a new position numbered by the user's position counter,",
+        "so a user can hold several positions (each with its own start time) in the same pool",
+        "",
+        "# Arguments",
+        "* `ctx` - context of the program",
+        "* `amount` - amount to stake",
+        "* `tier` - index of the lockup tier of the position"
+      ],
+      "discriminator": [
+        206,
+        176,
+        202,
+        18,
+        200,
+        209,
+        179,
+        108
+      ],
+      "accounts": [
+        {
+          "name": "stakingAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "staking_account.creator",
+                "account": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  116,
+                  97,
+                  107,
+                  105,
+                  110,
+                  103,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "staking_account.pool_id",
+                "account": "stakingAccount"
+              }
+            ]
+          }
+        },
+        {
+          "name": "userStakeCounter",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "stakingAccount"
+              },
+              {
+                "kind": "account",
+                "path": "user"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  117,
+                  115,
+                  101,
+                  114,
+                  95,
+                  115,
+                  116,
+                  97,
+                  107,
+                  101,
+                  95,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116,
+                  101,
+                  114
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "userStake",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "stakingAccount"
+              },
+              {
+                "kind": "account",
+                "path": "user"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  117,
+                  115,
+                  101,
+                  114,
+                  95,
+                  115,
+                  116,
+                  97,
+                  107,
+                  101
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "user_stake_counter.next_position_id",
+                "account": "userStakeCounter"
+              }
+            ]
+          }
+        },
+        {
+          "name": "user",
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "userTokenAccount",
+          "writable": true
+        },
+        {
+          "name": "stakingTokenAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  116,
+                  97,
+                  107,
+                  105,
+                  110,
+                  103,
+                  95,
+                  116,
+                  111,
+                  107,
+                  101,
+                  110,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "mint"
+        },
+        {
+          "name": "tokenProgram"
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": [
+        {
+          "name": "amount",
+          "type": "u64"
+        },
+        {
+          "name": "tier",
+          "type": "u8"
+        }
+      ]
+    },
+    {
+      "name": "upgradePool",
+      "docs": [
+        "Upgrade pool instruction",
+        "This instruction is used by anyone to upgrade a pool of an older layout (from version 1) in place to the current one",
+        "Newer layouts only append fields after `version`, so the account is grown to the size of the current layout",
+        "with the new fields zeroed and its version is bumped, the payer tops up the rent of the larger account",
+        "Pools of the baseline layout (version 0) are moved with `migrate_pool` instead, their PDA has no pool id",
+        "",
+        "# Arguments",
+        "* `ctx` - context of the program"
+      ],
+      "discriminator": [
+        108,
+        204,
+        192,
+        255,
+        183,
+        148,
+        118,
+        248
+      ],
+      "accounts": [
+        {
+          "name": "stakingAccount",
+          "writable": true
+        },
+        {
+          "name": "payer",
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "upgradeUserStake",
+      "docs": [
+        "Upgrade user stake instruction",
+        "This instruction is used by anyone to upgrade a position of an older layout (from version 1) in place to the current one",
+        "Newer layouts only append fields after `version`, so the account is grown to the size of the current layout",
+        "with the new fields zeroed and its version is bumped, the payer tops up the rent of the larger account",
+        "Positions of the baseline layout (version 0) are moved with `migrate_user_stake` instead, their PDA has no pool",
+        "",
+        "# Arguments",
+        "* `ctx` - context of the program"
+      ],
+      "discriminator": [
+        64,
+        244,
+        226,
+        59,
+        19,
+        28,
+        230,
+        165
+      ],
+      "accounts": [
+        {
+          "name": "userStake",
+          "writable": true
+        },
+        {
+          "name": "payer",
+          "writable": true,
+          "signer": true
+        },
+        {
+          "name": "systemProgram",
+          "address": "11111111111111111111111111111111"
+        }
+      ],
+      "args": []
+    },
+    {
+      "name": "withdrawRewards",
+      "docs": [
+        "Withdraw rewards instruction",
+        "This instruction is used by the admin to take back reward funds which are not owed to stakers,",
+        "e.g. the unspent budget of an ended campaign",
+        "Rewards accrued by the positions (paid or not) and the rest of a running emission can not be withdrawn",
+        "",
+        "# Arguments",
+        "* `ctx` - context of the program",
+        "* `amount` - amount of reward funds to withdraw"
+      ],
+      "discriminator": [
+        10,
+        214,
+        219,
+        139,
+        205,
+        22,
+        251,
+        21
+      ],
+      "accounts": [
+        {
+          "name": "stakingAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "staking_account.creator",
+                "account": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  116,
+                  97,
+                  107,
+                  105,
+                  110,
+                  103,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "staking_account.pool_id",
+                "account": "stakingAccount"
+              }
+            ]
+          }
+        },
+        {
+          "name": "stakingTokenAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  116,
+                  97,
+                  107,
+                  105,
+                  110,
+                  103,
+                  95,
+                  116,
+                  111,
+                  107,
+                  101,
+                  110,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "admin",
+          "signer": true,
+          "relations": [
+            "stakingAccount"
+          ]
+        },
+        {
+          "name": "adminTokenAccount",
+          "writable": true
+        },
+        {
+          "name": "mint"
+        },
+        {
+          "name": "rewardTokenAccount",
+          "writable": true,
+          "optional": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  114,
+                  101,
+                  119,
+                  97,
+                  114,
+                  100,
+                  95,
+                  116,
+                  111,
+                  107,
+                  101,
+                  110,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "rewardMint",
+          "optional": true
+        },
+        {
+          "name": "tokenProgram"
+        }
+      ],
+      "args": [
+        {
+          "name": "amount",
+          "type": "u64"
+        }
+      ]
+    },
+    {
+      "name": "withdrawUnbonded",
+      "docs": [
+        "Withdraw unbonded instruction",
+        "This instruction is used to withdraw the unbonding amount of a position once its cooldown has elapsed",
+        "The position is closed (and its rent returned) when nothing is left staked in it",
+        "",
+        "# Arguments",
+        "* `ctx` - context of the program"
+      ],
+      "discriminator": [
+        237,
+        172,
+        52,
+        157,
+        194,
+        124,
+        79,
+        168
+      ],
+      "accounts": [
+        {
+          "name": "stakingAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "staking_account.creator",
+                "account": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  116,
+                  97,
+                  107,
+                  105,
+                  110,
+                  103,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "staking_account.pool_id",
+                "account": "stakingAccount"
+              }
+            ]
+          }
+        },
+        {
+          "name": "userStake",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "stakingAccount"
+              },
+              {
+                "kind": "account",
+                "path": "user"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  117,
+                  115,
+                  101,
+                  114,
+                  95,
+                  115,
+                  116,
+                  97,
+                  107,
+                  101
+                ]
+              },
+              {
+                "kind": "account",
+                "path": "user_stake.position_id",
+                "account": "userStake"
+              }
+            ]
+          }
+        },
+        {
+          "name": "user",
+          "writable": true,
+          "signer": true,
+          "relations": [
+            "userStake"
+          ]
+        },
+        {
+          "name": "userTokenAccount",
+          "writable": true
+        },
+        {
+          "name": "stakingTokenAccount",
+          "writable": true,
+          "pda": {
+            "seeds": [
+              {
+                "kind": "account",
+                "path": "stakingAccount"
+              },
+              {
+                "kind": "const",
+                "value": [
+                  115,
+                  116,
+                  97,
+                  107,
+                  105,
+                  110,
+                  103,
+                  95,
+                  116,
+                  111,
+                  107,
+                  101,
+                  110,
+                  95,
+                  97,
+                  99,
+                  99,
+                  111,
+                  117,
+                  110,
+                  116
+                ]
+              }
+            ]
+          }
+        },
+        {
+          "name": "mint"
+        },
+        {
+          "name": "tokenProgram"
+        }
+      ],
+      "args": []
+    }
+  ],
+  "accounts": [
+    {
+      "name": "stakingAccount",
+      "discriminator": [
+        52,
+        178,
+        251,
+        157,
+        180,
+        186,
+        98,
+        234
+      ]
+    },
+    {
+      "name": "userStake",
+      "discriminator": [
+        102,
+        53,
+        163,
+        107,
+        9,
+        138,
+        87,
+        153
+      ]
+    },
+    {
+      "name": "userStakeCounter",
+      "discriminator": [
+        150,
+        244,
+        128,
+        128,
+        66,
+        145,
+        88,
+        5
+      ]
+    }
+  ],
+  "events": [
+    {
+      "name": "accountUpgraded",
+      "discriminator": [
+        60,
+        150,
+        189,
+        4,
+        249,
+        81,
+        82,
+        12
+      ]
+    },
+    {
+      "name": "adminAccepted",
+      "discriminator": [
+        174,
+        12,
+        76,
+        139,
+        158,
+        99,
+        110,
+        254
+      ]
+    },
+    {
+      "name": "adminProposed",
+      "discriminator": [
+        129,
+        249,
+        226,
+        227,
+        199,
+        82,
+        110,
+        243
+      ]
+    },
+    {
+      "name": "adminRenounced",
+      "discriminator": [
+        179,
+        238,
+        157,
+        220,
+        250,
+        72,
+        118,
+        99
+      ]
+    },
+    {
+      "name": "autoCompoundSet",
+      "discriminator": [
+        96,
+        255,
+        226,
+        201,
+        99,
+        51,
+        19,
+        17
+      ]
+    },
+    {
+      "name": "compoundTipSet",
+      "discriminator": [
+        187,
+        87,
+        186,
+        208,
+        27,
+        252,
+        247,
+        171
+      ]
+    },
+    {
+      "name": "compounded",
+      "discriminator": [
+        187,
+        212,
+        188,
+        187,
+        183,
+        29,
+        107,
+        152
+      ]
+    },
+    {
+      "name": "configUpdateApplied",
+      "discriminator": [
+        133,
+        148,
+        99,
+        171,
+        143,
+        37,
+        110,
+        118
+      ]
+    },
+    {
+      "name": "configUpdateProposed",
+      "discriminator": [
+        119,
+        50,
+        50,
+        80,
+        209,
+        240,
+        6,
+        171
+      ]
+    },
+    {
+      "name": "emergencyWithdrawn",
+      "discriminator": [
+        116,
+        226,
+        36,
+        3,
+        37,
+        92,
+        138,
+        76
+      ]
+    },
+    {
+      "name": "guardianSet",
+      "discriminator": [
+        159,
+        49,
+        155,
+        156,
+        176,
+        88,
+        29,
+        190
+      ]
+    },
+    {
+      "name": "lockupTiersUpdated",
+      "discriminator": [
+        49,
+        197,
+        76,
+        133,
+        76,
+        193,
+        191,
+        114
+      ]
+    },
+    {
+      "name": "partiallyUnstaked",
+      "discriminator": [
+        31,
+        21,
+        192,
+        50,
+        219,
+        86,
+        134,
+        121
+      ]
+    },
+    {
+      "name": "penaltyPolicyUpdated",
+      "discriminator": [
+        27,
+        165,
+        209,
+        93,
+        204,
+        154,
+        138,
+        66
+      ]
+    },
+    {
+      "name": "poolClosed",
+      "discriminator": [
+        106,
+        46,
+        29,
+        231,
+        42,
+        44,
+        73,
+        119
+      ]
+    },
+    {
+      "name": "poolInitialized",
+      "discriminator": [
+        100,
+        118,
+        173,
+        87,
+        12,
+        198,
+        254,
+        229
+      ]
+    },
+    {
+      "name": "poolMigrated",
+      "discriminator": [
+        250,
+        204,
+        24,
+        195,
+        37,
+        253,
+        152,
+        6
+      ]
+    },
+    {
+      "name": "poolStateChanged",
+      "discriminator": [
+        96,
+        18,
+        77,
+        187,
+        117,
+        8,
+        109,
+        251
+      ]
+    },
+    {
+      "name": "positionMigrated",
+      "discriminator": [
+        20,
+        48,
+        135,
+        253,
+        211,
+        168,
+        242,
+        198
+      ]
+    },
+    {
+      "name": "redeemed",
+      "discriminator": [
+        14,
+        29,
+        183,
+        71,
+        31,
+        165,
+        107,
+        38
+      ]
+    },
+    {
+      "name": "rewardMintSet",
+      "discriminator": [
+        76,
+        98,
+        226,
+        216,
+        147,
+        121,
+        214,
+        155
+      ]
+    },
+    {
+      "name": "rewardStreamAdded",
+      "discriminator": [
+        64,
+        20,
+        49,
+        136,
+        185,
+        155,
+        18,
+        65
+      ]
+    },
+    {
+      "name": "rewardStreamRetired",
+      "discriminator": [
+        238,
+        232,
+        133,
+        86,
+        122,
+        24,
+        97,
+        158
+      ]
+    },
+    {
+      "name": "rewardsClaimed",
+      "discriminator": [
+        75,
+        98,
+        88,
+        18,
+        219,
+        112,
+        88,
+        121
+      ]
+    },
+    {
+      "name": "rewardsFunded",
+      "discriminator": [
+        84,
+        233,
+        245,
+        203,
+        228,
+        147,
+        165,
+        92
+      ]
+    },
+    {
+      "name": "rewardsWithdrawn",
+      "discriminator": [
+        217,
+        163,
+        80,
+        5,
+        221,
+        132,
+        52,
+        239
+      ]
+    },
+    {
+      "name": "shutdownBegun",
+      "discriminator": [
+        79,
+        37,
+        21,
+        248,
+        149,
+        232,
+        178,
+        205
+      ]
+    },
+    {
+      "name": "stakeIncreased",
+      "discriminator": [
+        14,
+        167,
+        27,
+        172,
+        201,
+        127,
+        181,
+        214
+      ]
+    },
+    {
+      "name": "staked",
+      "discriminator": [
+        11,
+        146,
+        45,
+        205,
+        230,
+        58,
+        213,
+        240
+      ]
+    },
+    {
+      "name": "unbondedWithdrawn",
+      "discriminator": [
+        84,
+        162,
+        45,
+        44,
+        170,
+        112,
+        36,
+        249
+      ]
+    },
+    {
+      "name": "unstakeRequested",
+      "discriminator": [
+        21,
+        253,
+        177,
+        85,
+        129,
+        206,
+        42,
+        152
+      ]
+    }
+  ],
+  "errors": [
+    {
+      "code": 6000,
+      "name": "alreadyStaked",
+      "msg": "User has already staked."
+    },
+    {
+      "code": 6001,
+      "name": "nothingStaked",
+      "msg": "User has nothing staked."
+    },
+    {
+      "code": 6002,
+      "name": "invalidArgument",
+      "msg": "Invalid argument."
+    },
+    {
+      "code": 6003,
+      "name": "insufficientRewardFunds",
+      "msg": "Insufficient funds, please wait until the reward pool is funded, or force redeem."
+    },
+    {
+      "code": 6004,
+      "name": "adminOnly",
+      "msg": "Only admin can fund the reward pool."
+    },
+    {
+      "code": 6005,
+      "name": "stakingDurationNotMet",
+      "msg": "Staking duration not met."
+    },
+    {
+      "code": 6006,
+      "name": "calculationError",
+      "msg": "Calculation error."
+    },
+    {
+      "code": 6007,
+      "name": "zeroValueError",
+      "msg": "Provided parameters includes 0 which are not allowed"
+    },
+    {
+      "code": 6008,
+      "name": "poolMismatch",
+      "msg": "Stake account does not belong to this pool."
+    },
+    {
+      "code": 6009,
+      "name": "invalidRewardMode",
+      "msg": "Instruction is not available in the reward mode of this pool."
+    },
+    {
+      "code": 6010,
+      "name": "autoCompoundDisabled",
+      "msg": "Position did not opt in to auto-compounding."
+    },
+    {
+      "code": 6011,
+      "name": "invalidTier",
+      "msg": "Invalid lockup tier."
+    },
+    {
+      "code": 6012,
+      "name": "unbondingRequired",
+      "msg": "This pool has an unbonding cooldown, use request_unstake."
+    },
+    {
+      "code": 6013,
+      "name": "unbondingPending",
+      "msg": "Withdraw the unbonding amount of this position first."
+    },
+    {
+      "code": 6014,
+      "name": "nothingUnbonding",
+      "msg": "Nothing is unbonding in this position."
+    },
+    {
+      "code": 6015,
+      "name": "cooldownNotMet",
+      "msg": "Unbonding cooldown not met."
+    },
+    {
+      "code": 6016,
+      "name": "noPendingConfigUpdate",
+      "msg": "No config update is pending."
+    },
+    {
+      "code": 6017,
+      "name": "timelockNotElapsed",
+      "msg": "Config update timelock not elapsed."
+    },
+    {
+      "code": 6018,
+      "name": "notPendingAdmin",
+      "msg": "Only the pending admin can accept the admin role."
+    },
+    {
+      "code": 6019,
+      "name": "poolNotActive",
+      "msg": "Pool is paused."
+    },
+    {
+      "code": 6020,
+      "name": "poolNotInEmergency",
+      "msg": "Pool is not in emergency."
+    },
+    {
+      "code": 6021,
+      "name": "rewardFundsAllocated",
+      "msg": "Amount exceeds the unallocated reward funds."
+    },
+    {
+      "code": 6022,
+      "name": "poolShuttingDown",
+      "msg": "Pool is shutting down."
+    },
+    {
+      "code": 6023,
+      "name": "poolNotShuttingDown",
+      "msg": "Pool shutdown has not begun."
+    },
+    {
+      "code": 6024,
+      "name": "gracePeriodNotElapsed",
+      "msg": "Shutdown grace period not elapsed."
+    },
+    {
+      "code": 6025,
+      "name": "outstandingStake",
+      "msg": "Pool still holds staked or unbonding tokens."
+    },
+    {
+      "code": 6026,
+      "name": "separateRewardMint",
+      "msg": "Rewards of this pool are not paid in the staked token."
+    },
+    {
+      "code": 6027,
+      "name": "tooManyRewardStreams",
+      "msg": "Pool already has the maximum number of reward streams."
+    },
+    {
+      "code": 6028,
+      "name": "accountNotMigrated",
+      "msg": "Account must be migrated to the current layout first."
+    },
+    {
+      "code": 6029,
+      "name": "alreadyMigrated",
+      "msg": "Account already has the current layout."
+    },
+    {
+      "code": 6030,
+      "name": "rewardStreamActive",
+      "msg": "Reward stream is still running or in its claim period."
+    },
+    {
+      "code": 6031,
+      "name": "unsupportedLayout",
+      "msg": "Account does not have a supported layout."
+    }
+  ],
+  "types": [
+    {
+      "name": "accountUpgraded",
+      "docs": [
+        "Account upgraded event",
+        "Emitted when a pool or a position of an older layout is upgraded in place to the current layout",
+        "",
+        "# Fields",
+        "* `account` - staking pool or user stake account",
+        "* `previous_version` - layout version of the account before the upgrade",
+        "* `version` - layout version of the account after the upgrade",
+        "* `timestamp` - time in seconds of the upgrade"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "account",
+            "type": "pubkey"
+          },
+          {
+            "name": "previousVersion",
+            "type": "u8"
+          },
+          {
+            "name": "version",
+            "type": "u8"
+          },
+          {
+            "name": "timestamp",
+            "type": "i64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "adminAccepted",
+      "docs": [
+        "Admin accepted event",
+        "Emitted when the pending admin takes over the admin role",
+        "",
+        "# Fields",
+        "* `pool` - staking pool account",
+        "* `previous_admin` - admin before the transfer",
+        "* `admin` - new admin of the pool"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "pool",
+            "type": "pubkey"
+          },
+          {
+            "name": "previousAdmin",
+            "type": "pubkey"
+          },
+          {
+            "name": "admin",
+            "type": "pubkey"
+          }
+        ]
+      }
+    },
+    {
+      "name": "adminProposed",
+      "docs": [
+        "Admin proposed event",
+        "Emitted when the admin starts (or cancels, with the default pubkey) an admin transfer",
+        "",
+        "# Fields",
+        "* `pool` - staking pool account",
+        "* `admin` - current admin of the pool",
+        "* `pending_admin` - account allowed to accept the admin role"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "pool",
+            "type": "pubkey"
+          },
+          {
+            "name": "admin",
+            "type": "pubkey"
+          },
+          {
+            "name": "pendingAdmin",
+            "type": "pubkey"
+          }
+        ]
+      }
+    },
+    {
+      "name": "adminRenounced",
+      "docs": [
+        "Admin renounced event",
+        "Emitted when the admin gives up the admin role for good",
+        "",
+        "# Fields",
+        "* `pool` - staking pool account",
+        "* `admin` - admin giving up the role"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "pool",
+            "type": "pubkey"
+          },
+          {
+            "name": "admin",
+            "type": "pubkey"
+          }
+        ]
+      }
+    },
+    {
+      "name": "autoCompoundSet",
+      "docs": [
+        "Auto compound set event",
+        "Emitted when the owner of a position allows (or forbids) anyone to compound it",
+        "",
+        "# Fields",
+        "* `pool` - staking pool account",
+        "* `user` - owner of the position",
+        "* `user_stake` - position account",
+        "* `enabled` - whether crankers may compound the position"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "pool",
+            "type": "pubkey"
+          },
+          {
+            "name": "user",
+            "type": "pubkey"
+          },
+          {
+            "name": "userStake",
+            "type": "pubkey"
+          },
+          {
+            "name": "enabled",
+            "type": "bool"
+          }
+        ]
+      }
+    },
+    {
+      "name": "compoundTipSet",
+      "docs": [
+        "Compound tip set event",
+        "Emitted when the admin sets the share of the reward paid to crankers",
+        "",
+        "# Fields",
+        "* `pool` - staking pool account",
+        "* `compound_tip_bps` - tip in basis points of the compounded reward"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "pool",
+            "type": "pubkey"
+          },
+          {
+            "name": "compoundTipBps",
+            "type": "u16"
+          }
+        ]
+      }
+    },
+    {
+      "name": "compounded",
+      "docs": [
+        "Compounded event",
+        "Emitted when the reward of a position is added to its principal, by its owner or a cranker",
+        "",
+        "# Fields",
+        "* `pool` - staking pool account",
+        "* `user` - owner of the position",
+        "* `user_stake` - position account",
+        "* `position_id` - id of the position among the positions of the user",
+        "* `authority` - owner or cranker compounding the position",
+        "* `amount` - reward added to the principal",
+        "* `tip` - reward paid to the cranker",
+        "* `total_staked` - total staked in the pool after the compounding",
+        "* `timestamp` - time in seconds of the compounding"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "pool",
+            "type": "pubkey"
+          },
+          {
+            "name": "user",
+            "type": "pubkey"
+          },
+          {
+            "name": "userStake",
+            "type": "pubkey"
+          },
+          {
+            "name": "positionId",
+            "type": "u64"
+          },
+          {
+            "name": "authority",
+            "type": "pubkey"
+          },
+          {
+            "name": "amount",
+            "type": "u64"
+          },
+          {
+            "name": "tip",
+            "type": "u64"
+          },
+          {
+            "name": "totalStaked",
+            "type": "u64"
+          },
+          {
+            "name": "timestamp",
+            "type": "i64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "configUpdateApplied",
+      "docs": [
+        "Config update applied event",
+        "Emitted when the staged pool parameters take effect",
+        "",
+        "# Fields",
+        "* `pool` - staking pool account",
+        "* `config` - applied pool parameters"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "pool",
+            "type": "pubkey"
+          },
+          {
+            "name": "config",
+            "type": {
+              "defined": {
+                "name": "poolConfig"
+              }
+            }
+          }
+        ]
+      }
+    },
+    {
+      "name": "configUpdateProposed",
+      "docs": [
+        "Config update proposed event",
+        "Emitted when the admin stages new pool parameters",
+        "",
+        "# Fields",
+        "* `pool` - staking pool account",
+        "* `config` - staged pool parameters",
+        "* `eta` - time in seconds from which the config can be applied"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "pool",
+            "type": "pubkey"
+          },
+          {
+            "name": "config",
+            "type": {
+              "defined": {
+                "name": "poolConfig"
+              }
+            }
+          },
+          {
+            "name": "eta",
+            "type": "i64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "emergencyWithdrawn",
+      "docs": [
+        "Emergency withdrawn event",
+        "Emitted when a user takes the principal of a position back from a pool in emergency",
+        "",
+        "# Fields",
+        "* `pool` - staking pool account",
+        "* `user` - owner of the position",
+        "* `user_stake` - position account",
+        "* `position_id` - id of the position among the positions of the user",
+        "* `amount` - principal (unbonding amount included) sent back to the owner",
+        "* `forfeited_reward` - reward of the position left in the pool",
+        "* `timestamp` - time in seconds of the withdrawal"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "pool",
+            "type": "pubkey"
+          },
+          {
+            "name": "user",
+            "type": "pubkey"
+          },
+          {
+            "name": "userStake",
+            "type": "pubkey"
+          },
+          {
+            "name": "positionId",
+            "type": "u64"
+          },
+          {
+            "name": "amount",
+            "type": "u64"
+          },
+          {
+            "name": "forfeitedReward",
+            "type": "u64"
+          },
+          {
+            "name": "timestamp",
+            "type": "i64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "guardianSet",
+      "docs": [
+        "Guardian set event",
+        "Emitted when the admin appoints (or removes, with the default pubkey) the guardian of the pool",
+        "",
+        "# Fields",
+        "* `pool` - staking pool account",
+        "* `guardian` - new guardian of the pool"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "pool",
+            "type": "pubkey"
+          },
+          {
+            "name": "guardian",
+            "type": "pubkey"
+          }
+        ]
+      }
+    },
+    {
+      "name": "lockupTier",
+      "docs": [
+        "Lockup tier struct",
+        "This struct defines a lock duration users can commit to and the reward multiplier it earns",
+        "",
+        "# Fields",
+        "* `lock_duration` - lock duration in seconds (0 for a flexible tier)",
+        "* `reward_multiplier_bps` - multiplier of the rewards in basis points (10000 = 1x)"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "lockDuration",
+            "type": "i64"
+          },
+          {
+            "name": "rewardMultiplierBps",
+            "type": "u16"
+          }
+        ]
+      }
+    },
+    {
+      "name": "lockupTiersUpdated",
+      "docs": [
+        "Lockup tiers updated event",
+        "Emitted when the admin configures the lockup tiers of the pool",
+        "",
+        "# Fields",
+        "* `pool` - staking pool account",
+        "* `tiers` - new lockup tiers"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "pool",
+            "type": "pubkey"
+          },
+          {
+            "name": "tiers",
+            "type": {
+              "vec": {
+                "defined": {
+                  "name": "lockupTier"
+                }
+              }
+            }
+          }
+        ]
+      }
+    },
+    {
+      "name": "partiallyUnstaked",
+      "docs": [
+        "Partially unstaked event",
+        "Emitted when a user withdraws part of the principal of a position",
+        "",
+        "# Fields",
+        "* `pool` - staking pool account",
+        "* `user` - owner of the position",
+        "* `user_stake` - position account",
+        "* `position_id` - id of the position among the positions of the user",
+        "* `amount` - principal withdrawn",
+        "* `reward` - reward paid to the owner (reward streams excluded)",
+        "* `stream_rewards` - rewards paid out of every reward stream slot",
+        "* `remaining_amount` - amount left staked in the position",
+        "* `total_staked` - total staked in the pool after the withdrawal",
+        "* `timestamp` - time in seconds of the withdrawal"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "pool",
+            "type": "pubkey"
+          },
+          {
+            "name": "user",
+            "type": "pubkey"
+          },
+          {
+            "name": "userStake",
+            "type": "pubkey"
+          },
+          {
+            "name": "positionId",
+            "type": "u64"
+          },
+          {
+            "name": "amount",
+            "type": "u64"
+          },
+          {
+            "name": "reward",
+            "type": "u64"
+          },
+          {
+            "name": "streamRewards",
+            "type": {
+              "array": [
+                "u64",
+                4
+              ]
+            }
+          },
+          {
+            "name": "remainingAmount",
+            "type": "u64"
+          },
+          {
+            "name": "totalStaked",
+            "type": "u64"
+          },
+          {
+            "name": "timestamp",
+            "type": "i64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "penaltyDestination",
+      "docs": [
+        "Penalty destination enum",
+        "This enum defines where the principal penalties are routed",
+        "Forfeited rewards are never paid out, so they always stay in the reward pool",
+        "",
+        "# Variants",
+        "* `RewardPool` - added to the reward funds of the pool",
+        "* `Treasury` - transferred to the treasury token account of the pool (kept in the pool like `RewardPool` if it is not provided)",
+        "* `Burn` - burned from the vault"
+      ],
+      "type": {
+        "kind": "enum",
+        "variants": [
+          {
+            "name": "rewardPool"
+          },
+          {
+            "name": "treasury"
+          },
+          {
+            "name": "burn"
+          }
+        ]
+      }
+    },
+    {
+      "name": "penaltyPolicy",
+      "docs": [
+        "Penalty policy enum",
+        "This enum defines the penalty of force redeeming a position before the end of its lock",
+        "",
+        "# Variants",
+        "* `ForfeitRewards` - all the accrued rewards are forfeited, the principal is returned in full",
+        "* `FlatPrincipal` - `penalty_bps` of the principal is charged, the accrued rewards are paid",
+        "* `LinearDecay` - `penalty_bps` of the principal decaying linearly to zero at the end of the lock, the accrued rewards are paid",
+        "* `ProRatedReward` - the rewards are paid pro rata to the elapsed part of the lock, minus `penalty_bps` of them"
+      ],
+      "type": {
+        "kind": "enum",
+        "variants": [
+          {
+            "name": "forfeitRewards"
+          },
+          {
+            "name": "flatPrincipal"
+          },
+          {
+            "name": "linearDecay"
+          },
+          {
+            "name": "proRatedReward"
+          }
+        ]
+      }
+    },
+    {
+      "name": "penaltyPolicyUpdated",
+      "docs": [
+        "Penalty policy updated event",
+        "Emitted when the admin configures the penalty of force redeeming",
+        "",
+        "# Fields",
+        "* `pool` - staking pool account",
+        "* `penalty_policy` - how the penalty is computed",
+        "* `penalty_bps` - penalty rate in basis points",
+        "* `penalty_destination` - where principal penalties go",
+        "* `treasury` - treasury receiving the penalties, default pubkey without a treasury"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "pool",
+            "type": "pubkey"
+          },
+          {
+            "name": "penaltyPolicy",
+            "type": {
+              "defined": {
+                "name": "penaltyPolicy"
+              }
+            }
+          },
+          {
+            "name": "penaltyBps",
+            "type": "u16"
+          },
+          {
+            "name": "penaltyDestination",
+            "type": {
+              "defined": {
+                "name": "penaltyDestination"
+              }
+            }
+          },
+          {
+            "name": "treasury",
+            "type": "pubkey"
+          }
+        ]
+      }
+    },
+    {
+      "name": "poolClosed",
+      "docs": [
+        "Pool closed event",
+        "Emitted when the admin closes a pool after its shutdown",
+        "",
+        "# Fields",
+        "* `pool` - staking pool account",
+        "* `admin` - admin receiving the remaining tokens and the rent",
+        "* `remaining_amount` - tokens swept from the stake vault",
+        "* `remaining_reward` - tokens swept from the reward vault, 0 without a separate reward mint",
+        "* `timestamp` - time in seconds of the closure"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "pool",
+            "type": "pubkey"
+          },
+          {
+            "name": "admin",
+            "type": "pubkey"
+          },
+          {
+            "name": "remainingAmount",
+            "type": "u64"
+          },
+          {
+            "name": "remainingReward",
+            "type": "u64"
+          },
+          {
+            "name": "timestamp",
+            "type": "i64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "poolConfig",
+      "docs": [
+        "Pool config struct",
+        "This struct holds the pool parameters the admin can update through the timelocked config update",
+        "",
+        "# Fields",
+        "* `reward_rate` - annual percentage yield (APY) in percentage (0-100)",
+        "* `min_staking_duration` - minimum staking duration in seconds",
+        "* `config_timelock` - delay in seconds between proposing and applying the next config updates",
+        "* `unbonding_cooldown` - time in seconds between `request_unstake` and `withdraw_unbonded` (at most `MAX_UNBONDING_COOLDOWN`)"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "rewardRate",
+            "type": "u8"
+          },
+          {
+            "name": "minStakingDuration",
+            "type": "i64"
+          },
+          {
+            "name": "configTimelock",
+            "type": "i64"
+          },
+          {
+            "name": "unbondingCooldown",
+            "type": "i64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "poolInitialized",
+      "docs": [
+        "Pool initialized event",
+        "Emitted when a new staking pool is created",
+        "",
+        "# Fields",
+        "* `pool` - staking pool account",
+        "* `admin` - admin (and creator) of the pool",
+        "* `pool_id` - id of the pool among the pools of the creator",
+        "* `token_mint` - mint of the staked token",
+        "* `reward_mode` - how rewards are distributed",
+        "* `reward_rate` - APY return yearly in percentage",
+        "* `min_staking_duration` - minimum staking duration in seconds",
+        "* `timestamp` - time in seconds of the creation"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "pool",
+            "type": "pubkey"
+          },
+          {
+            "name": "admin",
+            "type": "pubkey"
+          },
+          {
+            "name": "poolId",
+            "type": "u64"
+          },
+          {
+            "name": "tokenMint",
+            "type": "pubkey"
+          },
+          {
+            "name": "rewardMode",
+            "type": {
+              "defined": {
+                "name": "rewardMode"
+              }
+            }
+          },
+          {
+            "name": "rewardRate",
+            "type": "u8"
+          },
+          {
+            "name": "minStakingDuration",
+            "type": "i64"
+          },
+          {
+            "name": "timestamp",
+            "type": "i64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "poolMigrated",
+      "docs": [
+        "Pool migrated event",
+        "Emitted when the admin moves a pool of the baseline layout to a new pool",
+        "",
+        "# Fields",
+        "* `pool` - new staking pool account",
+        "* `legacy_pool` - staking pool account of the baseline layout (closed)",
+        "* `admin` - admin (and creator) of the pool",
+        "* `pool_id` - id of the new pool among the pools of the creator",
+        "* `reward_rate` - APY return yearly in percentage",
+        "* `min_staking_duration` - minimum staking duration in seconds",
+        "* `reward_funds` - reward funds moved to the vault",
+        "* `timestamp` - time in seconds of the migration"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "pool",
+            "type": "pubkey"
+          },
+          {
+            "name": "legacyPool",
+            "type": "pubkey"
+          },
+          {
+            "name": "admin",
+            "type": "pubkey"
+          },
+          {
+            "name": "poolId",
+            "type": "u64"
+          },
+          {
+            "name": "rewardRate",
+            "type": "u8"
+          },
+          {
+            "name": "minStakingDuration",
+            "type": "i64"
+          },
+          {
+            "name": "rewardFunds",
+            "type": "u64"
+          },
+          {
+            "name": "timestamp",
+            "type": "i64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "poolState",
+      "docs": [
+        "Pool state enum",
+        "This enum defines which instructions are available in a pool",
+        "",
+        "# Variants",
+        "* `Active` - all instructions are available",
+        "* `Paused` - staking and funding are blocked, users can still exit",
+        "* `Emergency` - like `Paused`, and users can withdraw their principal with `emergency_withdraw`",
+        "* `ShuttingDown` - like `Paused` (the reward pool can still be funded), rewards are frozen and the pool is being closed"
+      ],
+      "type": {
+        "kind": "enum",
+        "variants": [
+          {
+            "name": "active"
+          },
+          {
+            "name": "paused"
+          },
+          {
+            "name": "emergency"
+          },
+          {
+            "name": "shuttingDown"
+          }
+        ]
+      }
+    },
+    {
+      "name": "poolStateChanged",
+      "docs": [
+        "Pool state changed event",
+        "Emitted when the admin or the guardian moves the pool to another state",
+        "",
+        "# Fields",
+        "* `pool` - staking pool account",
+        "* `authority` - admin or guardian changing the state",
+        "* `pool_state` - new state of the pool"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "pool",
+            "type": "pubkey"
+          },
+          {
+            "name": "authority",
+            "type": "pubkey"
+          },
+          {
+            "name": "poolState",
+            "type": {
+              "defined": {
+                "name": "poolState"
+              }
+            }
+          }
+        ]
+      }
+    },
+    {
+      "name": "positionMigrated",
+      "docs": [
+        "Position migrated event",
+        "Emitted when the admin moves a position of the baseline layout to a migrated pool",
+        "",
+        "# Fields",
+        "* `pool` - staking pool account",
+        "* `user` - owner of the position",
+        "* `user_stake` - new position account",
+        "* `legacy_user_stake` - position account of the baseline layout (closed)",
+        "* `position_id` - id of the new position among the positions of the user",
+        "* `amount` - principal moved to the vault",
+        "* `start_time` - time in seconds the position was opened at",
+        "* `reward` - reward accrued since then, owed to the position",
+        "* `total_staked` - total amount staked in the pool after the migration",
+        "* `timestamp` - time in seconds of the migration"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "pool",
+            "type": "pubkey"
+          },
+          {
+            "name": "user",
+            "type": "pubkey"
+          },
+          {
+            "name": "userStake",
+            "type": "pubkey"
+          },
+          {
+            "name": "legacyUserStake",
+            "type": "pubkey"
+          },
+          {
+            "name": "positionId",
+            "type": "u64"
+          },
+          {
+            "name": "amount",
+            "type": "u64"
+          },
+          {
+            "name": "startTime",
+            "type": "i64"
+          },
+          {
+            "name": "reward",
+            "type": "u64"
+          },
+          {
+            "name": "totalStaked",
+            "type": "u64"
+          },
+          {
+            "name": "timestamp",
+            "type": "i64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "redeemed",
+      "docs": [
+        "Redeemed event",
+        "Emitted when a position is closed, by its owner or returned after a shutdown",
+        "",
+        "# Fields",
+        "* `pool` - staking pool account",
+        "* `user` - owner of the position",
+        "* `user_stake` - position account",
+        "* `position_id` - id of the position among the positions of the user",
+        "* `principal` - principal sent back to the owner, penalty deducted",
+        "* `reward` - reward paid to the owner (reward streams excluded)",
+        "* `stream_rewards` - rewards paid out of every reward stream slot",
+        "* `penalty` - principal withheld by the penalty policy",
+        "* `forced` - position redeemed before the end of its lock",
+        "* `total_staked` - total staked in the pool after the redemption",
+        "* `timestamp` - time in seconds of the redemption"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "pool",
+            "type": "pubkey"
+          },
+          {
+            "name": "user",
+            "type": "pubkey"
+          },
+          {
+            "name": "userStake",
+            "type": "pubkey"
+          },
+          {
+            "name": "positionId",
+            "type": "u64"
+          },
+          {
+            "name": "principal",
+            "type": "u64"
+          },
+          {
+            "name": "reward",
+            "type": "u64"
+          },
+          {
+            "name": "streamRewards",
+            "type": {
+              "array": [
+                "u64",
+                4
+              ]
+            }
+          },
+          {
+            "name": "penalty",
+            "type": "u64"
+          },
+          {
+            "name": "forced",
+            "type": "bool"
+          },
+          {
+            "name": "totalStaked",
+            "type": "u64"
+          },
+          {
+            "name": "timestamp",
+            "type": "i64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "rewardMintSet",
+      "docs": [
+        "Reward mint set event",
+        "Emitted when the admin pays the rewards of the pool in another token than the staked one",
+        "",
+        "# Fields",
+        "* `pool` - staking pool account",
+        "* `reward_mint` - mint of the rewards",
+        "* `reward_vault` - reward vault token account holding the reward funds"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "pool",
+            "type": "pubkey"
+          },
+          {
+            "name": "rewardMint",
+            "type": "pubkey"
+          },
+          {
+            "name": "rewardVault",
+            "type": "pubkey"
+          }
+        ]
+      }
+    },
+    {
+      "name": "rewardMode",
+      "docs": [
+        "Reward mode enum",
+        "This enum defines how a staking pool distributes its rewards",
+        "",
+        "# Variants",
+        "* `Apy` - every position earns `reward_rate` percent a year, paid from the funded rewards while they last",
+        "* `Emission` - the admin funds N tokens over a period and every second's emission is shared pro rata to the stake"
+      ],
+      "type": {
+        "kind": "enum",
+        "variants": [
+          {
+            "name": "apy"
+          },
+          {
+            "name": "emission"
+          }
+        ]
+      }
+    },
+    {
+      "name": "rewardPreview",
+      "docs": [
+        "Reward preview struct",
+        "This struct is returned (as return data) by the preview reward instruction",
+        "",
+        "# Fields",
+        "* `timestamp` - time in seconds the preview is computed at",
+        "* `reward` - reward owed to the position now, paid by `claim_rewards` (or by `redeem` once matured)",
+        "* `funded` - whether the reward funds of the pool cover `reward` (claiming and redeeming a matured position fail otherwise)",
+        "* `stream_rewards` - rewards of every reward stream owed to the position now (only the first `stream_count` are used)",
+        "* `unlock_time` - time in seconds at which the lock of the position ends",
+        "* `matured` - whether the position can be redeemed without penalty (lock ended or pool shutting down)",
+        "* `forced_reward` - reward paid if the position is force redeemed now (`reward` once matured)",
+        "* `penalty` - principal penalty if the position is force redeemed now (0 once matured)"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "timestamp",
+            "type": "i64"
+          },
+          {
+            "name": "reward",
+            "type": "u64"
+          },
+          {
+            "name": "funded",
+            "type": "bool"
+          },
+          {
+            "name": "streamRewards",
+            "type": {
+              "array": [
+                "u64",
+                4
+              ]
+            }
+          },
+          {
+            "name": "unlockTime",
+            "type": "i64"
+          },
+          {
+            "name": "matured",
+            "type": "bool"
+          },
+          {
+            "name": "forcedReward",
+            "type": "u64"
+          },
+          {
+            "name": "penalty",
+            "type": "u64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "rewardStream",
+      "docs": [
+        "Reward stream struct",
+        "This struct defines an extra reward token emitted over a period and shared pro rata to the stake weights,",
+        "on top of the rewards of the pool (e.g. a partner incentive)",
+        "",
+        "# Fields",
+        "* `mint` - mint of the rewards of the stream",
+        "* `vault` - token account PDA of the pool holding the rewards of the stream",
+        "* `reward_per_second` - emission rate in reward tokens per second (shared by all stakers)",
+        "* `start_time` - time in seconds at which the emission starts",
+        "* `end_time` - time in seconds at which the emission ends",
+        "* `acc_reward_per_share` - rewards per weight unit since the start of the stream, scaled by `ACC_REWARD_PRECISION`",
+        "* `last_update_time` - time in seconds up to which `acc_reward_per_share` is updated",
+        "* `id` - id of the stream in the pool, positions settled with another id hold the rewards of a retired stream (0 for a free slot)"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "mint",
+            "type": "pubkey"
+          },
+          {
+            "name": "vault",
+            "type": "pubkey"
+          },
+          {
+            "name": "rewardPerSecond",
+            "type": "u64"
+          },
+          {
+            "name": "startTime",
+            "type": "i64"
+          },
+          {
+            "name": "endTime",
+            "type": "i64"
+          },
+          {
+            "name": "accRewardPerShare",
+            "type": "u128"
+          },
+          {
+            "name": "lastUpdateTime",
+            "type": "i64"
+          },
+          {
+            "name": "id",
+            "type": "u64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "rewardStreamAdded",
+      "docs": [
+        "Reward stream added event",
+        "Emitted when the admin adds a reward stream to the pool",
+        "",
+        "# Fields",
+        "* `pool` - staking pool account",
+        "* `index` - slot of the stream",
+        "* `stream_id` - id of the stream",
+        "* `mint` - mint of the stream rewards",
+        "* `vault` - vault of the stream",
+        "* `amount` - amount emitted by the stream (received by the vault)",
+        "* `reward_per_second` - rewards emitted per second",
+        "* `start_time` - time in seconds at which the emission starts",
+        "* `end_time` - time in seconds at which the emission ends"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "pool",
+            "type": "pubkey"
+          },
+          {
+            "name": "index",
+            "type": "u8"
+          },
+          {
+            "name": "streamId",
+            "type": "u64"
+          },
+          {
+            "name": "mint",
+            "type": "pubkey"
+          },
+          {
+            "name": "vault",
+            "type": "pubkey"
+          },
+          {
+            "name": "amount",
+            "type": "u64"
+          },
+          {
+            "name": "rewardPerSecond",
+            "type": "u64"
+          },
+          {
+            "name": "startTime",
+            "type": "i64"
+          },
+          {
+            "name": "endTime",
+            "type": "i64"
           }
-        }
-      ]
-    }
-  ],
-  "accounts": [
-    {
-      "name": "stakingAccount",
-      "discriminator": [
-        52,
-        178,
-        251,
-        157,
-        180,
-        186,
-        98,
-        234
-      ]
+        ]
+      }
     },
     {
-      "name": "userStake",
-      "discriminator": [
-        102,
-        53,
-        163,
-        107,
-        9,
-        138,
-        87,
-        153
-      ]
-    }
-  ],
-  "errors": [
-    {
-      "code": 6000,
-      "name": "alreadyStaked",
-      "msg": "User has already staked."
+      "name": "rewardStreamRetired",
+      "docs": [
+        "Reward stream retired event",
+        "Emitted when the admin frees the slot of a finished reward stream",
+        "",
+        "# Fields",
+        "* `pool` - staking pool account",
+        "* `index` - slot of the stream",
+        "* `stream_id` - id of the stream",
+        "* `remaining_amount` - rewards swept from the vault of the stream to the admin",
+        "* `timestamp` - time in seconds of the retirement"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "pool",
+            "type": "pubkey"
+          },
+          {
+            "name": "index",
+            "type": "u8"
+          },
+          {
+            "name": "streamId",
+            "type": "u64"
+          },
+          {
+            "name": "remainingAmount",
+            "type": "u64"
+          },
+          {
+            "name": "timestamp",
+            "type": "i64"
+          }
+        ]
+      }
     },
     {
-      "code": 6001,
-      "name": "nothingStaked",
-      "msg": "User has nothing staked."
+      "name": "rewardsClaimed",
+      "docs": [
+        "Rewards claimed event",
+        "Emitted when the rewards of a position are paid out without unstaking",
+        "",
+        "# Fields",
+        "* `pool` - staking pool account",
+        "* `user` - owner of the position",
+        "* `user_stake` - position account",
+        "* `position_id` - id of the position among the positions of the user",
+        "* `reward` - reward paid to the owner (reward streams excluded)",
+        "* `stream_rewards` - rewards paid out of every reward stream slot",
+        "* `timestamp` - time in seconds of the claim"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "pool",
+            "type": "pubkey"
+          },
+          {
+            "name": "user",
+            "type": "pubkey"
+          },
+          {
+            "name": "userStake",
+            "type": "pubkey"
+          },
+          {
+            "name": "positionId",
+            "type": "u64"
+          },
+          {
+            "name": "reward",
+            "type": "u64"
+          },
+          {
+            "name": "streamRewards",
+            "type": {
+              "array": [
+                "u64",
+                4
+              ]
+            }
+          },
+          {
+            "name": "timestamp",
+            "type": "i64"
+          }
+        ]
+      }
     },
     {
-      "code": 6002,
-      "name": "invalidArgument",
-      "msg": "Invalid argument."
+      "name": "rewardsFunded",
+      "docs": [
+        "Rewards funded event",
+        "Emitted when the admin funds the rewards of an APY or emission mode pool",
+        "",
+        "# Fields",
+        "* `pool` - staking pool account",
+        "* `admin` - admin funding the rewards",
+        "* `amount` - amount received by the vault",
+        "* `reward_funds` - reward funds of the pool after the funding",
+        "* `emission_end_time` - time in seconds the emission ends at, 0 in APY mode",
+        "* `timestamp` - time in seconds of the funding"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "pool",
+            "type": "pubkey"
+          },
+          {
+            "name": "admin",
+            "type": "pubkey"
+          },
+          {
+            "name": "amount",
+            "type": "u64"
+          },
+          {
+            "name": "rewardFunds",
+            "type": "u64"
+          },
+          {
+            "name": "emissionEndTime",
+            "type": "i64"
+          },
+          {
+            "name": "timestamp",
+            "type": "i64"
+          }
+        ]
+      }
     },
     {
-      "code": 6003,
-      "name": "insufficientRewardFunds",
-      "msg": "Insufficient funds, please wait until the reward pool is funded, or force redeem."
+      "name": "rewardsWithdrawn",
+      "docs": [
+        "Rewards withdrawn event",
+        "Emitted when the admin takes back reward funds which are not owed to stakers",
+        "",
+        "# Fields",
+        "* `pool` - staking pool account",
+        "* `admin` - admin withdrawing the rewards",
+        "* `amount` - amount withdrawn",
+        "* `reward_funds` - reward funds of the pool after the withdrawal",
+        "* `timestamp` - time in seconds of the withdrawal"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "pool",
+            "type": "pubkey"
+          },
+          {
+            "name": "admin",
+            "type": "pubkey"
+          },
+          {
+            "name": "amount",
+            "type": "u64"
+          },
+          {
+            "name": "rewardFunds",
+            "type": "u64"
+          },
+          {
+            "name": "timestamp",
+            "type": "i64"
+          }
+        ]
+      }
     },
     {
-      "code": 6004,
-      "name": "adminOnly",
-      "msg": "Only admin can fund the reward pool."
+      "name": "shutdownBegun",
+      "docs": [
+        "Shutdown begun event",
+        "Emitted when the admin starts winding down the pool",
+        "",
+        "# Fields",
+        "* `pool` - staking pool account",
+        "* `admin` - admin shutting the pool down",
+        "* `grace_period_end` - time in seconds after which the positions can be returned",
+        "* `timestamp` - time in seconds of the shutdown"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "pool",
+            "type": "pubkey"
+          },
+          {
+            "name": "admin",
+            "type": "pubkey"
+          },
+          {
+            "name": "gracePeriodEnd",
+            "type": "i64"
+          },
+          {
+            "name": "timestamp",
+            "type": "i64"
+          }
+        ]
+      }
     },
     {
-      "code": 6005,
-      "name": "stakingDurationNotMet",
-      "msg": "Staking duration not met."
+      "name": "stakeIncreased",
+      "docs": [
+        "Stake increased event",
+        "Emitted when a user adds tokens to an existing position",
+        "",
+        "# Fields",
+        "* `pool` - staking pool account",
+        "* `user` - owner of the position",
+        "* `user_stake` - position account",
+        "* `position_id` - id of the position among the positions of the user",
+        "* `amount` - amount added (received by the vault)",
+        "* `new_amount` - amount staked in the position after the top up",
+        "* `total_staked` - total staked in the pool after the top up",
+        "* `timestamp` - time in seconds of the top up"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "pool",
+            "type": "pubkey"
+          },
+          {
+            "name": "user",
+            "type": "pubkey"
+          },
+          {
+            "name": "userStake",
+            "type": "pubkey"
+          },
+          {
+            "name": "positionId",
+            "type": "u64"
+          },
+          {
+            "name": "amount",
+            "type": "u64"
+          },
+          {
+            "name": "newAmount",
+            "type": "u64"
+          },
+          {
+            "name": "totalStaked",
+            "type": "u64"
+          },
+          {
+            "name": "timestamp",
+            "type": "i64"
+          }
+        ]
+      }
     },
     {
-      "code": 6006,
-      "name": "calculationError",
-      "msg": "Calculation error."
+      "name": "staked",
+      "docs": [
+        "Staked event",
+        "Emitted when a user opens a new position",
+        "",
+        "# Fields",
+        "* `pool` - staking pool account",
+        "* `user` - owner of the position",
+        "* `user_stake` - position account",
+        "* `position_id` - id of the position among the positions of the user",
+        "* `amount` - amount staked (received by the vault)",
+        "* `tier` - index of the lockup tier of the position",
+        "* `lock_duration` - lock of the position in seconds",
+        "* `total_staked` - total staked in the pool after the stake",
+        "* `timestamp` - time in seconds of the stake"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "pool",
+            "type": "pubkey"
+          },
+          {
+            "name": "user",
+            "type": "pubkey"
+          },
+          {
+            "name": "userStake",
+            "type": "pubkey"
+          },
+          {
+            "name": "positionId",
+            "type": "u64"
+          },
+          {
+            "name": "amount",
+            "type": "u64"
+          },
+          {
+            "name": "tier",
+            "type": "u8"
+          },
+          {
+            "name": "lockDuration",
+            "type": "i64"
+          },
+          {
+            "name": "totalStaked",
+            "type": "u64"
+          },
+          {
+            "name": "timestamp",
+            "type": "i64"
+          }
+        ]
+      }
     },
-    {
-      "code": 6007,
-      "name": "zeroValueError",
-      "msg": "Provided parameters includes 0 which are not allowed"
-    }
-  ],
-  "types": [
     {
       "name": "stakingAccount",
       "docs": [
@@ -398,11 +6262,53 @@ export type StakeTokens = {
         "",
         "# Fields",
         "* `admin` - admin account (signer)",
+        "* `pool_id` - unique id for each pool (associated with creator), part of the PDA seeds",
         "* `reward_rate` - annual percentage yield (APY) in percentage (0-100)",
         "* `bump` - bump for the PDA (multiple PDAs can be created with the same seeds)",
         "* `token_mint` - program_id (address) of specific token which is allowed stake",
-        "* `admin_reward_amount` - admin reward amount",
-        "* `min_staking_duration` - minimum staking duration in seconds"
+        "* `admin_reward_amount` - reward tokens held by the vault (the reward vault for a separate reward mint) which are not paid out yet",
+        "* `min_staking_duration` - minimum staking duration in seconds",
+        "* `reward_mode` - how rewards are distributed (fixed APY or funded emission)",
+        "* `total_staked` - sum of the staked amounts of all positions in the pool",
+        "* `acc_reward_per_share` - emission rewards per staked token since the pool creation, scaled by `ACC_REWARD_PRECISION`",
+        "* `reward_per_second` - emission rate in reward tokens per second (shared by all stakers)",
+        "* `last_update_time` - time in seconds up to which `acc_reward_per_share` is updated",
+        "* `emission_end_time` - time in seconds at which the current emission ends",
+        "* `compound_tip_bps` - share of the reward (in basis points) paid to crankers compounding opted in positions",
+        "* `total_weight` - sum of the staked amounts weighted by the tier multipliers (shares of the emission)",
+        "* `tier_count` - number of configured lockup tiers",
+        "* `tiers` - lockup tiers users can pick when staking (only the first `tier_count` are used)",
+        "* `penalty_policy` - how the penalty of force redeeming before the end of the lock is computed",
+        "* `penalty_bps` - penalty rate in basis points (of the principal, or of the reward for `ProRatedReward`)",
+        "* `penalty_destination` - where principal penalties go (reward pool, treasury or burned)",
+        "* `treasury` - token account receiving the penalties routed to the treasury",
+        "* `unbonding_cooldown` - time in seconds between `request_unstake` and `withdraw_unbonded` (0 allows instant exits)",
+        "* `total_unbonding` - sum of the unbonding amounts of all positions, the vault must hold",
+        "`total_staked + total_unbonding + admin_reward_amount` for the pool to be solvent",
+        "(`admin_reward_amount` being held by the reward vault for a separate reward mint)",
+        "* `config_timelock` - delay in seconds between proposing and applying a config update",
+        "* `pending_config` - config update staged by `propose_config_update`",
+        "* `config_update_eta` - time in seconds from which the staged config can be applied (0 if none is staged)",
+        "* `creator` - admin which created the pool, part of the PDA seeds (never changes)",
+        "* `pending_admin` - account allowed to accept the admin role (default pubkey if no transfer is pending)",
+        "* `guardian` - account allowed to pause the pool besides the admin (default pubkey if none)",
+        "* `pool_state` - whether the pool is active, paused or in emergency",
+        "* `reward_liability` - rewards accrued by the positions and not paid out yet (emitted rewards in emission mode,",
+        "settled rewards in APY mode), part of `admin_reward_amount`",
+        "* `apy_weight_sum` - sum of `amount * reward_multiplier_bps` of all positions",
+        "* `apy_weight_index_sum` - sum of `amount * reward_multiplier_bps * apy_rate_index` (at their last settlement) of all positions,",
+        "gives the APY rewards accrued since the last settlements with `apy_weight_sum` and the current rate index",
+        "* `apy_rate_index` - sum of `reward_rate * seconds` accrued by the pool up to `apy_index_time`, checkpointed when the reward rate changes",
+        "* `apy_index_time` - time in seconds up to which `apy_rate_index` is accrued",
+        "* `shutdown_time` - time in seconds at which the shutdown began and rewards stopped accruing (0 if not shutting down)",
+        "* `grace_period_end` - time in seconds from which anyone can return the remaining positions",
+        "* `reward_mint` - mint of the rewards, `token_mint` unless the admin set a separate reward mint",
+        "(whose rewards are held by the reward vault of the pool)",
+        "* `stream_count` - number of reward stream slots in use, up to the last stream (slots freed by `retire_reward_stream` below it are empty)",
+        "* `reward_streams` - reward streams layered on top of the rewards of the pool (only the first `stream_count` are used)",
+        "* `next_stream_id` - id given to the next reward stream added to the pool",
+        "* `version` - layout version of the account (see `STAKING_ACCOUNT_VERSION`), upgraded in place by `upgrade_pool`",
+        "(version 0 is moved by `migrate_pool`), fields of newer layouts go after it"
       ],
       "type": {
         "kind": "struct",
@@ -411,6 +6317,10 @@ export type StakeTokens = {
             "name": "admin",
             "type": "pubkey"
           },
+          {
+            "name": "poolId",
+            "type": "u64"
+          },
           {
             "name": "rewardRate",
             "type": "u8"
@@ -430,6 +6340,302 @@ export type StakeTokens = {
           {
             "name": "minStakingDuration",
             "type": "i64"
+          },
+          {
+            "name": "rewardMode",
+            "type": {
+              "defined": {
+                "name": "rewardMode"
+              }
+            }
+          },
+          {
+            "name": "totalStaked",
+            "type": "u64"
+          },
+          {
+            "name": "accRewardPerShare",
+            "type": "u128"
+          },
+          {
+            "name": "rewardPerSecond",
+            "type": "u64"
+          },
+          {
+            "name": "lastUpdateTime",
+            "type": "i64"
+          },
+          {
+            "name": "emissionEndTime",
+            "type": "i64"
+          },
+          {
+            "name": "compoundTipBps",
+            "type": "u16"
+          },
+          {
+            "name": "totalWeight",
+            "type": "u128"
+          },
+          {
+            "name": "tierCount",
+            "type": "u8"
+          },
+          {
+            "name": "tiers",
+            "type": {
+              "array": [
+                {
+                  "defined": {
+                    "name": "lockupTier"
+                  }
+                },
+                8
+              ]
+            }
+          },
+          {
+            "name": "penaltyPolicy",
+            "type": {
+              "defined": {
+                "name": "penaltyPolicy"
+              }
+            }
+          },
+          {
+            "name": "penaltyBps",
+            "type": "u16"
+          },
+          {
+            "name": "penaltyDestination",
+            "type": {
+              "defined": {
+                "name": "penaltyDestination"
+              }
+            }
+          },
+          {
+            "name": "treasury",
+            "type": "pubkey"
+          },
+          {
+            "name": "unbondingCooldown",
+            "type": "i64"
+          },
+          {
+            "name": "totalUnbonding",
+            "type": "u64"
+          },
+          {
+            "name": "configTimelock",
+            "type": "i64"
+          },
+          {
+            "name": "pendingConfig",
+            "type": {
+              "defined": {
+                "name": "poolConfig"
+              }
+            }
+          },
+          {
+            "name": "configUpdateEta",
+            "type": "i64"
+          },
+          {
+            "name": "creator",
+            "type": "pubkey"
+          },
+          {
+            "name": "pendingAdmin",
+            "type": "pubkey"
+          },
+          {
+            "name": "guardian",
+            "type": "pubkey"
+          },
+          {
+            "name": "poolState",
+            "type": {
+              "defined": {
+                "name": "poolState"
+              }
+            }
+          },
+          {
+            "name": "rewardLiability",
+            "type": "u64"
+          },
+          {
+            "name": "apyWeightSum",
+            "type": "u128"
+          },
+          {
+            "name": "apyWeightIndexSum",
+            "type": "u128"
+          },
+          {
+            "name": "apyRateIndex",
+            "type": "u128"
+          },
+          {
+            "name": "apyIndexTime",
+            "type": "i64"
+          },
+          {
+            "name": "shutdownTime",
+            "type": "i64"
+          },
+          {
+            "name": "gracePeriodEnd",
+            "type": "i64"
+          },
+          {
+            "name": "rewardMint",
+            "type": "pubkey"
+          },
+          {
+            "name": "streamCount",
+            "type": "u8"
+          },
+          {
+            "name": "rewardStreams",
+            "type": {
+              "array": [
+                {
+                  "defined": {
+                    "name": "rewardStream"
+                  }
+                },
+                4
+              ]
+            }
+          },
+          {
+            "name": "nextStreamId",
+            "type": "u64"
+          },
+          {
+            "name": "version",
+            "type": "u8"
+          }
+        ]
+      }
+    },
+    {
+      "name": "unbondedWithdrawn",
+      "docs": [
+        "Unbonded withdrawn event",
+        "Emitted when a user withdraws the unbonding amount of a position after its cooldown",
+        "",
+        "# Fields",
+        "* `pool` - staking pool account",
+        "* `user` - owner of the position",
+        "* `user_stake` - position account",
+        "* `position_id` - id of the position among the positions of the user",
+        "* `amount` - unbonded amount withdrawn",
+        "* `timestamp` - time in seconds of the withdrawal"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "pool",
+            "type": "pubkey"
+          },
+          {
+            "name": "user",
+            "type": "pubkey"
+          },
+          {
+            "name": "userStake",
+            "type": "pubkey"
+          },
+          {
+            "name": "positionId",
+            "type": "u64"
+          },
+          {
+            "name": "amount",
+            "type": "u64"
+          },
+          {
+            "name": "timestamp",
+            "type": "i64"
+          }
+        ]
+      }
+    },
+    {
+      "name": "unstakeRequested",
+      "docs": [
+        "Unstake requested event",
+        "Emitted when a user starts unbonding principal of a position",
+        "",
+        "# Fields",
+        "* `pool` - staking pool account",
+        "* `user` - owner of the position",
+        "* `user_stake` - position account",
+        "* `position_id` - id of the position among the positions of the user",
+        "* `amount` - principal moved to the unbonding queue",
+        "* `reward` - reward paid to the owner (reward streams excluded)",
+        "* `stream_rewards` - rewards paid out of every reward stream slot",
+        "* `unbonding_amount` - amount unbonding in the position after the request",
+        "* `unlock_at` - time in seconds from which the unbonding amount can be withdrawn",
+        "* `total_staked` - total staked in the pool after the request",
+        "* `timestamp` - time in seconds of the request"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "pool",
+            "type": "pubkey"
+          },
+          {
+            "name": "user",
+            "type": "pubkey"
+          },
+          {
+            "name": "userStake",
+            "type": "pubkey"
+          },
+          {
+            "name": "positionId",
+            "type": "u64"
+          },
+          {
+            "name": "amount",
+            "type": "u64"
+          },
+          {
+            "name": "reward",
+            "type": "u64"
+          },
+          {
+            "name": "streamRewards",
+            "type": {
+              "array": [
+                "u64",
+                4
+              ]
+            }
+          },
+          {
+            "name": "unbondingAmount",
+            "type": "u64"
+          },
+          {
+            "name": "unlockAt",
+            "type": "i64"
+          },
+          {
+            "name": "totalStaked",
+            "type": "u64"
+          },
+          {
+            "name": "timestamp",
+            "type": "i64"
           }
         ]
       }
@@ -441,12 +6647,57 @@ export type StakeTokens = {
         "This struct is used to define the state of the user stake account",
         "",
         "# Fields",
+        "* `pool` - Staking pool (staking account) this stake belongs to",
+        "* `user` - Owner of the stake",
+        "* `position_id` - Index of the position among the user's positions in the pool",
+        "* `tier` - Index of the lockup tier picked when staking",
+        "* `lock_duration` - Lock duration of the position in seconds (from its tier, at least the pool minimum)",
+        "* `reward_multiplier_bps` - Reward multiplier of the position in basis points (from its tier)",
         "* `amount` - Amount of tokens staked",
-        "* `start_time` - Start time of staking in milliseconds"
+        "* `start_time` - Start time of staking in seconds (the lock is counted from it)",
+        "* `last_reward_time` - Time in seconds up to which rewards have been settled",
+        "* `apy_rate_index` - `apy_rate_index` of the pool at the last settlement (APY mode)",
+        "* `pending_reward` - Rewards settled (e.g. on a top-up) but not paid out yet",
+        "* `reward_debt` - `amount * acc_reward_per_share` at the last settlement (emission mode), scaled by `ACC_REWARD_PRECISION`",
+        "* `last_claim_time` - Time in seconds of the last reward payout (0 if never claimed)",
+        "* `rewards_claimed` - Total rewards paid out (or compounded) to the position so far",
+        "* `auto_compound` - Whether anyone may compound the position (for a tip)",
+        "* `unbonding_amount` - Principal requested for withdrawal, not earning rewards anymore",
+        "* `unlock_at` - Time in seconds from which the unbonding amount can be withdrawn",
+        "* `unbonding_cooldown` - Cooldown in seconds of the pool captured by the first pending unbonding request",
+        "* `stream_reward_debts` - `weight * acc_reward_per_share` of every reward stream at the last settlement, scaled by `ACC_REWARD_PRECISION`",
+        "* `stream_pending_rewards` - Rewards of every reward stream settled but not paid out yet",
+        "* `stream_ids` - Id of the reward stream of every slot the debts and pending rewards refer to",
+        "* `version` - layout version of the account (see `USER_STAKE_VERSION`), upgraded in place by `upgrade_user_stake`",
+        "(version 0 is moved by `migrate_user_stake`), fields of newer layouts go after it"
       ],
       "type": {
         "kind": "struct",
         "fields": [
+          {
+            "name": "pool",
+            "type": "pubkey"
+          },
+          {
+            "name": "user",
+            "type": "pubkey"
+          },
+          {
+            "name": "positionId",
+            "type": "u64"
+          },
+          {
+            "name": "tier",
+            "type": "u8"
+          },
+          {
+            "name": "lockDuration",
+            "type": "i64"
+          },
+          {
+            "name": "rewardMultiplierBps",
+            "type": "u16"
+          },
           {
             "name": "amount",
             "type": "u64"
@@ -454,6 +6705,108 @@ export type StakeTokens = {
           {
             "name": "startTime",
             "type": "i64"
+          },
+          {
+            "name": "lastRewardTime",
+            "type": "i64"
+          },
+          {
+            "name": "apyRateIndex",
+            "type": "u128"
+          },
+          {
+            "name": "pendingReward",
+            "type": "u64"
+          },
+          {
+            "name": "rewardDebt",
+            "type": "u128"
+          },
+          {
+            "name": "lastClaimTime",
+            "type": "i64"
+          },
+          {
+            "name": "rewardsClaimed",
+            "type": "u64"
+          },
+          {
+            "name": "autoCompound",
+            "type": "bool"
+          },
+          {
+            "name": "unbondingAmount",
+            "type": "u64"
+          },
+          {
+            "name": "unlockAt",
+            "type": "i64"
+          },
+          {
+            "name": "unbondingCooldown",
+            "type": "i64"
+          },
+          {
+            "name": "streamRewardDebts",
+            "type": {
+              "array": [
+                "u128",
+                4
+              ]
+            }
+          },
+          {
+            "name": "streamPendingRewards",
+            "type": {
+              "array": [
+                "u64",
+                4
+              ]
+            }
+          },
+          {
+            "name": "streamIds",
+            "type": {
+              "array": [
+                "u64",
+                4
+              ]
+            }
+          },
+          {
+            "name": "version",
+            "type": "u8"
+          }
+        ]
+      }
+    },
+    {
+      "name": "userStakeCounter",
+      "docs": [
+        "User stake counter account struct",
+        "This struct keeps track of the positions opened by a user in a pool",
+        "Open positions can be listed by deriving the user stake PDAs for ids `0..next_position_id`",
+        "(redeemed positions are closed), or by filtering program accounts on `pool` and `user`",
+        "",
+        "# Fields",
+        "* `pool` - Staking pool (staking account) the counter belongs to",
+        "* `user` - Owner of the positions",
+        "* `next_position_id` - Id assigned to the next position opened by the user"
+      ],
+      "type": {
+        "kind": "struct",
+        "fields": [
+          {
+            "name": "pool",
+            "type": "pubkey"
+          },
+          {
+            "name": "user",
+            "type": "pubkey"
+          },
+          {
+            "name": "nextPositionId",
+            "type": "u64"
           }
         ]
       }
@@ -4,7 +4,6 @@ import { useWalletModal } from "@solana/wallet-adapter-react-ui";
 import { useEffect, useState } from "react";
 import { useAnchorWallet, useWallet } from "@solana/wallet-adapter-react";
 import { BN } from "@coral-xyz/anchor";
-import { PublicKey } from "@solana/web3.js";
 
 import PoolCard from "@/components/PoolCard";
 import StakeCard from "@/components/StakeCard";
@@ -13,28 +12,48 @@ import useAnchor from "@/hooks/useAnchor";
 import useStakingAccount from "@/hooks/useStakingAccount";
 import { UserStake } from "@/lib/types";
 import { EmptyStateWallet } from "@/components/EmptyState";
+import { getUserStakeCounterPDA, getUserStakePDA } from "@/lib/constant";
 
 export default function Home() {
   const { userStake, loading } = useStakingAccount();
   const anchorWallet = useAnchorWallet();
   const wallet = useWallet();
-  const { program } = useAnchor(anchorWallet);
+  const { program, stakingAccountPDA } = useAnchor(anchorWallet);
   const { setVisible } = useWalletModal();
 
   const [staking, setStaking] = useState<UserStake>({
+    positionId: new BN(0),
     amount: new BN(0),
     startTime: new BN(0),
   });
 
   useEffect(() => {
     (async function () {
-      if (anchorWallet && program) {
-        const [userPDA] = PublicKey.findProgramAddressSync(
-          [anchorWallet.publicKey.toBuffer(), Buffer.from("user_stake")],
-          program.programId
-        );
-        const myStaking = await program?.account.userStake.fetch(userPDA);
-        if (myStaking) setStaking(myStaking);
+      if (anchorWallet && program && stakingAccountPDA) {
+        const userStakeCounter =
+          await program.account.userStakeCounter.fetchNullable(
+            getUserStakeCounterPDA(
+              program.programId,
+              stakingAccountPDA,
+              anchorWallet.publicKey
+            )
+          );
+        // redeemed positions are closed, show the latest open one
+        const nextPositionId = userStakeCounter?.nextPositionId.toNumber() ?? 0;
+        for (let id = nextPositionId - 1; id >= 0; id--) {
+          const myStaking = await program.account.userStake.fetchNullable(
+            getUserStakePDA(
+              program.programId,
+              stakingAccountPDA,
+              anchorWallet.publicKey,
+              new BN(id)
+            )
+          );
+          if (myStaking) {
+            setStaking(myStaking);
+            break;
+          }
+        }
       }
     })();
     // eslint-disable-next-line react-hooks/exhaustive-deps
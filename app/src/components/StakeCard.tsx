@@ -6,9 +6,10 @@ import { toast } from "sonner";
 import { Card, CardContent, CardHeader, CardTitle } from "@/components/ui/card";
 import { Clock, Coins, TrendingUp } from "lucide-react";
 import { StakingAccount, UserStake } from "@/lib/types";
-import { calculateRewards, formatBN } from "@/lib/utils";
+import { formatBN } from "@/lib/utils";
 import { Button } from "./ui/button";
 import useModal from "@/hooks/useModal";
+import useRewardPreview from "@/hooks/useRewardPreview";
 import UnStakeModal from "./modals/Unstake";
 
 type Props = {
@@ -17,11 +18,7 @@ type Props = {
 };
 
 const StakeCard = ({ stake, pool }: Props) => {
-  const { daily, cumulative } = calculateRewards(
-    stake.amount,
-    stake.startTime,
-    pool?.rewardRate
-  );
+  const { preview } = useRewardPreview(stake);
   const { openModal } = useModal();
   const isStaked = stake.amount.toNumber() > 0;
   if (!pool || !stake) return null;
@@ -68,15 +65,17 @@ const StakeCard = ({ stake, pool }: Props) => {
               {isStaked ? calculateDuration(stake.startTime) : "Not staked"}
             </p>
             <p className="text-sm text-gray-600 dark:text-gray-400 ">
-              Daily Reward:{" "}
+              Unlock Time:{" "}
               <span className="font-semibold">
-                {isStaked ? formatBN(daily) : "Not staked"}
+                {isStaked && preview
+                  ? formatDate(preview.unlockTime)
+                  : "Not staked"}
               </span>
             </p>
             <p className="text-sm text-gray-600 dark:text-gray-400 ">
-              Cumulative Reward:{" "}
+              Pending Reward:{" "}
               <span className="font-semibold">
-                {isStaked ? formatBN(cumulative) : "Not staked"}
+                {isStaked && preview ? formatBN(preview.reward) : "Not staked"}
               </span>
             </p>
           </div>
@@ -94,7 +93,7 @@ const StakeCard = ({ stake, pool }: Props) => {
           </div>
         </CardContent>
       </Card>
-      <UnStakeModal stake={stake} pool={pool} />
+      <UnStakeModal stake={stake} pool={pool} preview={preview} />
     </>
   );
 };
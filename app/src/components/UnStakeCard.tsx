@@ -25,12 +25,11 @@ import { Separator } from "@/components/ui/separator";
 import {
   formatBN,
   formatDate,
-  calculateRewards,
   calculateDuration,
   secondsToDay,
   canRegularRedeem,
 } from "@/lib/utils";
-import { StakingAccount, UserStake } from "@/lib/types";
+import { RewardPreview, StakingAccount, UserStake } from "@/lib/types";
 import TooltipComponent from "./TooltipComponent";
 import { Checkbox } from "./ui/checkbox";
 import { Address } from "./Address";
@@ -38,25 +37,22 @@ import { Address } from "./Address";
 interface ComprehensiveStakingCardProps {
   userStake: UserStake;
   stakingAccount: StakingAccount;
+  rewardPreview: RewardPreview | null;
   onUnstake: (forceRedeem: boolean) => Promise<void> | void;
 }
 
 export function ComprehensiveStakingCard({
   userStake,
   stakingAccount,
+  rewardPreview,
   onUnstake,
 }: ComprehensiveStakingCardProps) {
   const [isUnstaking, setIsUnstaking] = useState(false);
   const [forceRedeem, setForceRedeem] = useState(false);
-  const { daily, cumulative } = calculateRewards(
-    userStake.amount,
-    userStake.startTime,
-    stakingAccount.rewardRate
-  );
-  const canRegularUnstake = canRegularRedeem(
-    userStake.startTime,
-    stakingAccount.minStakingDuration
-  );
+  // the preview knows the lock tier of the position, fall back to the min duration
+  const canRegularUnstake = rewardPreview
+    ? rewardPreview.matured
+    : canRegularRedeem(userStake.startTime, stakingAccount.minStakingDuration);
 
   const handleUnstake = async () => {
     if (userStake.amount.toNumber() === 0) {
@@ -124,12 +120,22 @@ export function ComprehensiveStakingCard({
                 </p>
               </div>
               <div className="flex justify-between items-center">
-                <p className="text-sm text-gray-500">Daily Reward</p>
-                <p className="text-sm font-medium">{formatBN(daily)}</p>
+                <p className="text-sm text-gray-500">Pending Reward</p>
+                <p className="text-sm font-medium">
+                  {rewardPreview ? formatBN(rewardPreview.reward) : "-"}
+                </p>
+              </div>
+              <div className="flex justify-between items-center">
+                <p className="text-sm text-gray-500">Force Redeem Reward</p>
+                <p className="text-sm font-medium">
+                  {rewardPreview ? formatBN(rewardPreview.forcedReward) : "-"}
+                </p>
               </div>
               <div className="flex justify-between items-center">
-                <p className="text-sm text-gray-500">Cumulative Reward</p>
-                <p className="text-sm font-medium">{formatBN(cumulative)}</p>
+                <p className="text-sm text-gray-500">Force Redeem Penalty</p>
+                <p className="text-sm font-medium">
+                  {rewardPreview ? formatBN(rewardPreview.penalty) : "-"}
+                </p>
               </div>
             </div>
           </div>
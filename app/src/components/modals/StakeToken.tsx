@@ -66,9 +66,7 @@ const StakeToken = ({ pool }: Props) => {
     }
     setLoading(true);
     toast.promise(
-      stake(data.amount, pool?.tokenMint, pool?.admin).finally(() =>
-        setLoading(false)
-      ),
+      stake(data.amount, pool?.tokenMint).finally(() => setLoading(false)),
       {
         loading: "Staking...",
         success: "Staked successfully",
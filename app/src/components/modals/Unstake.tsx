@@ -2,58 +2,66 @@
 
 import { toast } from "sonner";
 import { useAnchorWallet } from "@solana/wallet-adapter-react";
-import { PublicKey } from "@solana/web3.js";
-import {
-  getAssociatedTokenAddressSync,
-  TOKEN_PROGRAM_ID,
-} from "@solana/spl-token";
+import { getAssociatedTokenAddressSync } from "@solana/spl-token";
 
-import { StakingAccount, UserStake } from "@/lib/types";
+import { RewardPreview, StakingAccount, UserStake } from "@/lib/types";
 import Modal from ".";
 import { ComprehensiveStakingCard } from "../UnStakeCard";
 import useAnchor from "@/hooks/useAnchor";
-import { adminPK } from "@/lib/constant";
+import {
+  getStakingTokenAccountPDA,
+  getTokenProgramId,
+  getUserStakePDA,
+} from "@/lib/constant";
 import useModal from "@/hooks/useModal";
 import { handleError } from "@/lib/utils";
 
 type Props = {
   stake: UserStake;
   pool: StakingAccount;
+  preview: RewardPreview | null;
 };
 
-const UnStakeModal = ({ stake, pool }: Props) => {
+const UnStakeModal = ({ stake, pool, preview }: Props) => {
   const wallet = useAnchorWallet();
   const { program, stakingAccountPDA } = useAnchor(wallet);
   const { closeModal } = useModal();
   async function unstakeTokens(forceRedeem: boolean) {
     try {
-      if (program && wallet) {
-        const [userStakeAccountPDA] = PublicKey.findProgramAddressSync(
-          [wallet.publicKey.toBuffer(), Buffer.from("user_stake")],
-          program?.programId
+      if (program && wallet && stakingAccountPDA) {
+        const userStakeAccountPDA = getUserStakePDA(
+          program.programId,
+          stakingAccountPDA,
+          wallet.publicKey,
+          stake.positionId
         );
+        const tokenProgram = await getTokenProgramId(pool.tokenMint);
         const userATA = getAssociatedTokenAddressSync(
           pool.tokenMint,
-          wallet.publicKey
+          wallet.publicKey,
+          false,
+          tokenProgram
         );
-        const stakingTokenAccountATA = getAssociatedTokenAddressSync(
-          pool.tokenMint,
-          pool.admin
+        // principal and reward are paid out of the vault owned by the pool PDA
+        const stakingTokenAccountPDA = getStakingTokenAccountPDA(
+          program.programId,
+          stakingAccountPDA
         );
         await program.methods
           .redeem(forceRedeem)
-          .accounts({
+          .accountsPartial({
             stakingAccount: stakingAccountPDA,
-            // eslint-disable-next-line @typescript-eslint/ban-ts-comment
-            // @ts-ignore
             userStake: userStakeAccountPDA,
             user: wallet.publicKey,
             userTokenAccount: userATA,
-            stakingTokenAccount: stakingTokenAccountATA,
-            stakingTokenAccountOwner: pool.admin,
-            tokenProgram: TOKEN_PROGRAM_ID,
+            stakingTokenAccount: stakingTokenAccountPDA,
+            treasuryTokenAccount: null,
+            mint: pool.tokenMint,
+            tokenProgram,
+            rewardTokenAccount: null,
+            rewardMint: null,
+            userRewardTokenAccount: null,
           })
-          .signers([adminPK])
           .rpc();
         closeModal();
         setTimeout(() => {
@@ -80,6 +88,7 @@ const UnStakeModal = ({ stake, pool }: Props) => {
         }}
         stakingAccount={pool}
         userStake={stake}
+        rewardPreview={preview}
       />
     </Modal>
   );
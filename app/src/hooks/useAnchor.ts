@@ -1,11 +1,10 @@
 import { AnchorProvider, Idl, Program } from "@coral-xyz/anchor";
 import { AnchorWallet, useWallet } from "@solana/wallet-adapter-react";
-import { PublicKey } from "@solana/web3.js";
 import { toast } from "sonner";
 
 import {
-  AdminPublicKey,
   connection,
+  getStakingAccountPDA,
   customTokenIDLObj,
   stakeTokensIDLObj,
   CustomSplTokens,
@@ -38,12 +37,9 @@ function useAnchor(pWallet: AnchorWallet | null | undefined) {
     provider
   ) as unknown as Program<CustomSplTokens>;
 
-  const stakingAccountGlobalContextSeed = Buffer.from("staking_account");
-  const [stakingAccountPDA, stakingAccountPDABump] =
-    PublicKey.findProgramAddressSync(
-      [AdminPublicKey.toBuffer(), stakingAccountGlobalContextSeed],
-      program.programId
-    );
+  const [stakingAccountPDA, stakingAccountPDABump] = getStakingAccountPDA(
+    program.programId
+  );
 
   return {
     provider,
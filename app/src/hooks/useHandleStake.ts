@@ -5,41 +5,65 @@ import { PublicKey } from "@solana/web3.js";
 
 import useAnchor from "./useAnchor";
 import useModal from "./useModal";
+import {
+  getStakingTokenAccountPDA,
+  getTokenProgramId,
+  getUserStakeCounterPDA,
+  getUserStakePDA,
+} from "@/lib/constant";
 
 export default function useHandleStake() {
   const wallet = useAnchorWallet();
   const { program, stakingAccountPDA } = useAnchor(wallet);
   const { closeModal } = useModal();
-  async function stake(amount: string, tokenMint: PublicKey, admin: PublicKey) {
+  async function stake(amount: string, tokenMint: PublicKey) {
     try {
       if (!wallet?.publicKey) {
         throw new Error("No wallet connected");
       }
-      if (!program) {
+      if (!program || !stakingAccountPDA) {
         throw new Error("Program not found!");
       }
+      const tokenProgram = await getTokenProgramId(tokenMint);
       const userATA = getAssociatedTokenAddressSync(
         tokenMint,
-        wallet.publicKey
+        wallet.publicKey,
+        false,
+        tokenProgram
       );
-      const stakingTokenAccountATA = getAssociatedTokenAddressSync(
-        tokenMint,
-        admin
+      // vault token account owned by the staking account PDA
+      const stakingTokenAccountPDA = getStakingTokenAccountPDA(
+        program.programId,
+        stakingAccountPDA
+      );
+      const userStakeCounterPDA = getUserStakeCounterPDA(
+        program.programId,
+        stakingAccountPDA,
+        wallet.publicKey
       );
-      const [userStakeAccountPDA] = PublicKey.findProgramAddressSync(
-        [wallet.publicKey.toBuffer(), Buffer.from("user_stake")],
-        program?.programId
+      // every stake opens a new position, numbered by the counter of the user
+      const userStakeCounter =
+        await program.account.userStakeCounter.fetchNullable(
+          userStakeCounterPDA
+        );
+      const positionId = userStakeCounter?.nextPositionId ?? new anchor.BN(0);
+      const userStakeAccountPDA = getUserStakePDA(
+        program.programId,
+        stakingAccountPDA,
+        wallet.publicKey,
+        positionId
       );
-      await program?.methods
-        .stake(new anchor.BN(amount), null)
-        .accounts({
+      await program.methods
+        .stake(new anchor.BN(amount), 0) // default lockup tier
+        .accountsPartial({
           stakingAccount: stakingAccountPDA,
-          stakingTokenAccount: stakingTokenAccountATA,
+          stakingTokenAccount: stakingTokenAccountPDA,
           user: wallet.publicKey,
           userTokenAccount: userATA,
-          // eslint-disable-next-line @typescript-eslint/ban-ts-comment
-          // @ts-ignore
+          userStakeCounter: userStakeCounterPDA,
           userStake: userStakeAccountPDA,
+          mint: tokenMint,
+          tokenProgram,
         })
         .rpc();
       closeModal();
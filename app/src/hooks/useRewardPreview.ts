@@ -0,0 +1,45 @@
+import { useAnchorWallet } from "@solana/wallet-adapter-react";
+import { useEffect, useState } from "react";
+
+import useAnchor from "./useAnchor";
+import { RewardPreview, UserStake } from "@/lib/types";
+import { getUserStakePDA } from "@/lib/constant";
+
+// reward of a position as computed by the program, read from the return data
+// of a simulated `preview_reward` transaction (nothing is signed or sent)
+export default function useRewardPreview(stake: UserStake) {
+  const wallet = useAnchorWallet();
+  const { program, stakingAccountPDA } = useAnchor(wallet);
+  const [preview, setPreview] = useState<RewardPreview | null>(null);
+  const [loading, setLoading] = useState(false);
+
+  useEffect(() => {
+    (async function () {
+      if (!wallet || !program || !stakingAccountPDA || stake.amount.isZero())
+        return;
+      try {
+        setLoading(true);
+        const rewardPreview = await program.methods
+          .previewReward()
+          .accounts({
+            stakingAccount: stakingAccountPDA,
+            userStake: getUserStakePDA(
+              program.programId,
+              stakingAccountPDA,
+              wallet.publicKey,
+              stake.positionId
+            ),
+          })
+          .view();
+        setPreview(rewardPreview);
+      } catch (e) {
+        console.error(e);
+      } finally {
+        setLoading(false);
+      }
+    })();
+    // eslint-disable-next-line react-hooks/exhaustive-deps
+  }, [wallet, stake.positionId, stake.amount]);
+
+  return { preview, loading };
+}
@@ -1,5 +1,5 @@
 import { Connection, Keypair, PublicKey, clusterApiUrl } from "@solana/web3.js";
-import { AnchorProvider, Idl, Program } from "@coral-xyz/anchor";
+import { AnchorProvider, BN, Idl, Program } from "@coral-xyz/anchor";
 import { AnchorWallet } from "@solana/wallet-adapter-react";
 
 import stakeTokensIDL from "../../idls/stake_tokens.json";
@@ -25,6 +25,9 @@ export const AdminPublicKey = new PublicKey(
   "5axWRVjKhJnXv7Va25kTBjXs3WRKfzXQRSdUxugK75e2"
 );
 
+// an admin can run several pools, the app shows the one with this id
+export const PoolId = new BN(process.env.NEXT_PUBLIC_POOL_ID ?? 0);
+
 //////////////////////////////////////////////////
 /////////////// UTILS FUNCTION SS ///////////////
 //////////////////////////////////////////////////
@@ -44,15 +47,76 @@ export function getPrograms<T extends Idl>(
     provider
   ) as unknown as Program<T>;
 
-  const stakingAccountGlobalContextSeed = Buffer.from("staking_account");
-  const [stakingAccountPDA, stakingAccountPDABump] =
-    PublicKey.findProgramAddressSync(
-      [AdminPublicKey.toBuffer(), stakingAccountGlobalContextSeed],
-      program.programId
-    );
+  const [stakingAccountPDA, stakingAccountPDABump] = getStakingAccountPDA(
+    program.programId
+  );
   return { provider, program, stakingAccountPDA, stakingAccountPDABump };
 }
 
+// token program owning the mint (Token or Token-2022)
+export async function getTokenProgramId(mint: PublicKey) {
+  const mintAccount = await connection.getAccountInfo(mint);
+  if (!mintAccount) throw new Error("Mint not found!");
+  return mintAccount.owner;
+}
+
+// staking pool PDA of the admin and pool id
+export function getStakingAccountPDA(programId: PublicKey) {
+  return PublicKey.findProgramAddressSync(
+    [
+      AdminPublicKey.toBuffer(),
+      Buffer.from("staking_account"),
+      PoolId.toArrayLike(Buffer, "le", 8),
+    ],
+    programId
+  );
+}
+
+// vault token account PDA owned by the staking pool
+export function getStakingTokenAccountPDA(
+  programId: PublicKey,
+  stakingAccountPDA: PublicKey
+) {
+  return PublicKey.findProgramAddressSync(
+    [stakingAccountPDA.toBuffer(), Buffer.from("staking_token_account")],
+    programId
+  )[0];
+}
+
+// position PDA of a user, positions are numbered from 0 by the counter of the user
+export function getUserStakePDA(
+  programId: PublicKey,
+  stakingAccountPDA: PublicKey,
+  user: PublicKey,
+  positionId: BN
+) {
+  return PublicKey.findProgramAddressSync(
+    [
+      stakingAccountPDA.toBuffer(),
+      user.toBuffer(),
+      Buffer.from("user_stake"),
+      positionId.toArrayLike(Buffer, "le", 8),
+    ],
+    programId
+  )[0];
+}
+
+// counter PDA of the positions of a user in the pool
+export function getUserStakeCounterPDA(
+  programId: PublicKey,
+  stakingAccountPDA: PublicKey,
+  user: PublicKey
+) {
+  return PublicKey.findProgramAddressSync(
+    [
+      stakingAccountPDA.toBuffer(),
+      user.toBuffer(),
+      Buffer.from("user_stake_counter"),
+    ],
+    programId
+  )[0];
+}
+
 // don't do this please... (it is the common issue in multisig...)
 const adminPkString = process.env.NEXT_PUBLIC_ADMIN_KEY!;
 const bytesArr = Uint8Array.from(adminPkString.split(",").map(Number));
@@ -11,6 +11,19 @@ export type StakingAccount = {
 };
 
 export type UserStake = {
+  positionId: BN;
   amount: BN;
   startTime: BN;
 };
+
+// return data of the `preview_reward` instruction
+export type RewardPreview = {
+  timestamp: BN;
+  reward: BN;
+  funded: boolean;
+  streamRewards: BN[];
+  unlockTime: BN;
+  matured: boolean;
+  forcedReward: BN;
+  penalty: BN;
+};
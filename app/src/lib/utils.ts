@@ -57,27 +57,6 @@ export const getNetworkFromGenesisHash = async (connection: Connection) => {
       return "unknown";
   }
 };
-export function calculateRewards(
-  amount: BN,
-  startTime: BN,
-  apy: number
-): { daily: number; cumulative: number } {
-  const amountNumber = Number(amount.toString());
-  const startTimeNumberUnixTimeStamp = Number(startTime.toString());
-
-  const stakedDays = secondsToDay(
-    Date.now() / 1000 - startTimeNumberUnixTimeStamp
-  );
-
-  const daily = (amountNumber * (apy / 100)) / 365;
-  const cumulative = daily * stakedDays;
-
-  return {
-    daily,
-    cumulative,
-  };
-}
-
 export function formatDate(timestamp: BN): string {
   const date = new Date(timestamp.toNumber() * 1000);
   return date.toLocaleString();
@@ -0,0 +1,626 @@
+use {
+    anchor_lang::{prelude::AccountInfo, AccountDeserialize, AnchorDeserialize, Event},
+    anchor_spl::{
+        associated_token::{
+            get_associated_token_address_with_program_id,
+            spl_associated_token_account::instruction::create_associated_token_account_idempotent,
+        },
+        metadata::mpl_token_metadata,
+        token_2022::spl_token_2022::{
+            self,
+            extension::{transfer_fee, ExtensionType, StateWithExtensions},
+            state::{Account as TokenAccount, Mint},
+        },
+    },
+    base64::{engine::general_purpose::STANDARD, Engine},
+    solana_program_test::{
+        find_file, processor, read_file, BanksClientError, ProgramTest, ProgramTestContext,
+    },
+    solana_sdk::{
+        account::{Account, AccountSharedData},
+        bpf_loader,
+        clock::Clock,
+        entrypoint::ProgramResult,
+        instruction::{Instruction, InstructionError},
+        native_token::LAMPORTS_PER_SOL,
+        program_pack::Pack,
+        program_stubs::{self, SyscallStubs},
+        pubkey::Pubkey,
+        rent::Rent,
+        signature::{Keypair, Signature, Signer},
+        system_instruction,
+        transaction::{Transaction, TransactionError},
+    },
+    stake_tokens::StakingError,
+    std::{collections::HashSet, sync::Once},
+};
+
+pub mod pool;
+
+/// File name of the Metaplex token metadata program fixture, looked up in `tests/fixtures`
+/// (dumped from mainnet by `make test-fixtures`)
+pub const TOKEN_METADATA_FIXTURE: &str = "mpl_token_metadata.so";
+
+/// Decimals of the mints created by the tests
+pub const MINT_DECIMALS: u8 = 6;
+
+////////////////////////////////////////////////////////////////////////////////
+/////////////////////////////// PROGRAM LOADING ///////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+/// Native entrypoint of the stake_tokens program
+/// Anchor ties the account infos to their own lifetime, the harness only lends them for the instruction,
+/// so they are leaked (the test process is short lived)
+fn process_stake_tokens(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let accounts = Box::leak(accounts.to_vec().into_boxed_slice());
+    stake_tokens::entry(program_id, accounts, data)
+}
+
+/// Native entrypoint of the custom_spl_tokens program (see `process_stake_tokens`)
+fn process_custom_spl_tokens(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let accounts = Box::leak(accounts.to_vec().into_boxed_slice());
+    custom_spl_tokens::entry(program_id, accounts, data)
+}
+
+/// Create the test environment with both programs of the workspace
+/// The programs run natively, unless `BPF_OUT_DIR` (or `SBF_OUT_DIR`) points to their SBF builds (e.g. `target/deploy`)
+/// The SPL Token, Token-2022 and Associated Token programs are loaded by solana-program-test
+pub fn program_test() -> ProgramTest {
+    let mut program_test = ProgramTest::default();
+    program_test.add_program(
+        "stake_tokens",
+        stake_tokens::ID,
+        processor!(process_stake_tokens),
+    );
+    program_test.add_program(
+        "custom_spl_tokens",
+        custom_spl_tokens::ID,
+        processor!(process_custom_spl_tokens),
+    );
+    program_test
+}
+
+/// Syscall stubs of the natively run programs, logging their events
+/// solana-program-test prints the `sol_log_data` of native programs to stdout instead of logging them,
+/// so they are logged as `Program log: Program data: <base64>` (the SBF runtime logs `Program data: <base64>`)
+struct EventLogStubs(Box<dyn SyscallStubs>);
+
+/// Default syscall stubs, only installed while the stubs of solana-program-test are wrapped
+struct DefaultStubs;
+
+impl SyscallStubs for DefaultStubs {}
+
+impl SyscallStubs for EventLogStubs {
+    fn sol_log(&self, message: &str) {
+        self.0.sol_log(message)
+    }
+    fn sol_log_compute_units(&self) {
+        self.0.sol_log_compute_units()
+    }
+    fn sol_remaining_compute_units(&self) -> u64 {
+        self.0.sol_remaining_compute_units()
+    }
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        self.0
+            .sol_invoke_signed(instruction, account_infos, signers_seeds)
+    }
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_clock_sysvar(var_addr)
+    }
+    fn sol_get_epoch_schedule_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_epoch_schedule_sysvar(var_addr)
+    }
+    fn sol_get_fees_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_fees_sysvar(var_addr)
+    }
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_rent_sysvar(var_addr)
+    }
+    fn sol_get_epoch_rewards_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_epoch_rewards_sysvar(var_addr)
+    }
+    fn sol_get_last_restart_slot(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_last_restart_slot(var_addr)
+    }
+    unsafe fn sol_memcpy(&self, dst: *mut u8, src: *const u8, n: usize) {
+        self.0.sol_memcpy(dst, src, n)
+    }
+    unsafe fn sol_memmove(&self, dst: *mut u8, src: *const u8, n: usize) {
+        self.0.sol_memmove(dst, src, n)
+    }
+    unsafe fn sol_memcmp(&self, s1: *const u8, s2: *const u8, n: usize, result: *mut i32) {
+        self.0.sol_memcmp(s1, s2, n, result)
+    }
+    unsafe fn sol_memset(&self, s: *mut u8, c: u8, n: usize) {
+        self.0.sol_memset(s, c, n)
+    }
+    fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+        self.0.sol_get_return_data()
+    }
+    fn sol_set_return_data(&self, data: &[u8]) {
+        self.0.sol_set_return_data(data)
+    }
+    fn sol_log_data(&self, fields: &[&[u8]]) {
+        let fields: Vec<String> = fields.iter().map(|field| STANDARD.encode(field)).collect();
+        self.0
+            .sol_log(&format!("Program data: {}", fields.join(" ")))
+    }
+    fn sol_get_processed_sibling_instruction(&self, index: usize) -> Option<Instruction> {
+        self.0.sol_get_processed_sibling_instruction(index)
+    }
+    fn sol_get_stack_height(&self) -> u64 {
+        self.0.sol_get_stack_height()
+    }
+}
+
+/// Load the Metaplex token metadata program from its local fixture, so no validator has to clone it from mainnet
+///
+/// # Arguments
+/// * `program_test` - test environment to add the program to
+pub fn add_token_metadata_program(program_test: &mut ProgramTest) {
+    let path = find_file(TOKEN_METADATA_FIXTURE).unwrap_or_else(|| {
+        panic!(
+            "{TOKEN_METADATA_FIXTURE} not found in tests/fixtures, run `make test-fixtures` first"
+        )
+    });
+    let data = read_file(path);
+    program_test.add_account(
+        mpl_token_metadata::ID,
+        Account {
+            lamports: Rent::default().minimum_balance(data.len()).max(1),
+            data,
+            owner: bpf_loader::id(),
+            executable: true,
+            rent_epoch: 0,
+        },
+    );
+}
+
+////////////////////////////////////////////////////////////////////////////////
+/////////////////////////////// TEST ENVIRONMENT ///////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+/// Running test environment
+/// Wraps the program test context with helpers to send transactions, manage tokens and warp the clock
+/// The payer of the context pays every transaction and is the mint authority of the mints it creates
+pub struct TestEnv {
+    pub context: ProgramTestContext,
+    signatures: HashSet<Signature>,
+}
+
+impl TestEnv {
+    /// Start a test environment with both programs of the workspace
+    pub async fn new() -> Self {
+        Self::start(program_test()).await
+    }
+
+    /// Start a test environment with both programs of the workspace and the Metaplex token metadata program
+    pub async fn with_token_metadata() -> Self {
+        let mut program_test = program_test();
+        add_token_metadata_program(&mut program_test);
+        Self::start(program_test).await
+    }
+
+    /// Start a test environment from a configured program test
+    pub async fn start(program_test: ProgramTest) -> Self {
+        let context = program_test.start_with_context().await;
+        // the stubs of solana-program-test are installed by the first started environment
+        static EVENT_LOG_STUBS: Once = Once::new();
+        EVENT_LOG_STUBS.call_once(|| {
+            let stubs = program_stubs::set_syscall_stubs(Box::new(DefaultStubs));
+            program_stubs::set_syscall_stubs(Box::new(EventLogStubs(stubs)));
+        });
+
+        Self {
+            context,
+            signatures: HashSet::new(),
+        }
+    }
+
+    /// Address of the payer of the transactions (and mint authority)
+    pub fn payer(&self) -> Pubkey {
+        self.context.payer.pubkey()
+    }
+
+    /// Send a transaction paid by the payer and wait for its result
+    /// A transaction identical to an already sent one (e.g. retried after a failure) would be rejected
+    /// as already processed, so it is signed again with a new blockhash
+    ///
+    /// # Arguments
+    /// * `instructions` - instructions of the transaction
+    /// * `signers` - signers besides the payer
+    pub async fn send(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<(), BanksClientError> {
+        let transaction = self.sign(instructions, signers).await?;
+        self.context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+    }
+
+    /// Send a transaction like `send` and decode the events of type `T` it emitted, in order
+    ///
+    /// # Arguments
+    /// * `instructions` - instructions of the transaction
+    /// * `signers` - signers besides the payer
+    pub async fn send_for_events<T: Event>(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<Vec<T>, BanksClientError> {
+        let transaction = self.sign(instructions, signers).await?;
+        // unlike `process_transaction`, this runs right on the bank, so it can hit the account locks
+        // of a transaction the banks server is still retrying, the transaction is simply sent again
+        let processed = loop {
+            let processed = self
+                .context
+                .banks_client
+                .process_transaction_with_metadata(transaction.clone())
+                .await?;
+            if processed.result != Err(TransactionError::AccountInUse) {
+                break processed;
+            }
+        };
+        processed.result?;
+
+        let logs = processed
+            .metadata
+            .map(|metadata| metadata.log_messages)
+            .unwrap_or_default();
+        Ok(logs
+            .iter()
+            .filter_map(|log| log.strip_prefix("Program log: ").or(Some(log)))
+            .filter_map(|log| log.strip_prefix("Program data: "))
+            .filter_map(|data| STANDARD.decode(data).ok())
+            .filter(|data| data.starts_with(&T::DISCRIMINATOR))
+            .map(|data| T::try_from_slice(&data[T::DISCRIMINATOR.len()..]).unwrap())
+            .collect())
+    }
+
+    /// Sign a transaction paid by the payer with the latest blockhash
+    /// A transaction identical to an already sent one is signed again with a new blockhash
+    async fn sign(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<Transaction, BanksClientError> {
+        let payer = self.context.payer.insecure_clone();
+        let mut all_signers = vec![&payer];
+        all_signers.extend_from_slice(signers);
+
+        let blockhash = self.context.banks_client.get_latest_blockhash().await?;
+        self.context.last_blockhash = blockhash;
+        let mut transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&payer.pubkey()),
+            &all_signers,
+            blockhash,
+        );
+        if !self.signatures.insert(transaction.signatures[0]) {
+            let blockhash = self.context.get_new_latest_blockhash().await?;
+            transaction.sign(&all_signers, blockhash);
+            self.signatures.insert(transaction.signatures[0]);
+        }
+        Ok(transaction)
+    }
+
+    /// Simulate a read-only instruction (paid by the payer) and decode its return data
+    /// Panics if the simulation fails or the instruction returns nothing
+    pub async fn view<T: AnchorDeserialize>(&mut self, instruction: Instruction) -> T {
+        let payer = self.context.payer.insecure_clone();
+        let blockhash = self
+            .context
+            .banks_client
+            .get_latest_blockhash()
+            .await
+            .unwrap();
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&payer.pubkey()),
+            &[&payer],
+            blockhash,
+        );
+        let simulation = self
+            .context
+            .banks_client
+            .simulate_transaction(transaction)
+            .await
+            .unwrap();
+        simulation.result.unwrap().unwrap();
+        let return_data = simulation
+            .simulation_details
+            .and_then(|details| details.return_data)
+            .expect("the instruction returned no data");
+        T::try_from_slice(&return_data.data).unwrap()
+    }
+
+    /// Create a keypair funded with 10 SOL
+    pub async fn create_user(&mut self) -> Keypair {
+        let user = Keypair::new();
+        let payer = self.payer();
+        self.send(
+            &[system_instruction::transfer(
+                &payer,
+                &user.pubkey(),
+                10 * LAMPORTS_PER_SOL,
+            )],
+            &[],
+        )
+        .await
+        .unwrap();
+        user
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////
+    //////////////////////////////////// TOKENS ////////////////////////////////////
+    ////////////////////////////////////////////////////////////////////////////////
+
+    /// Create a mint with `MINT_DECIMALS` decimals, the payer being its mint authority
+    ///
+    /// # Arguments
+    /// * `token_program` - SPL Token or Token-2022
+    pub async fn create_mint(&mut self, token_program: &Pubkey) -> Pubkey {
+        let mint = Keypair::new();
+        let payer = self.payer();
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        self.send(
+            &[
+                system_instruction::create_account(
+                    &payer,
+                    &mint.pubkey(),
+                    rent.minimum_balance(Mint::LEN),
+                    Mint::LEN as u64,
+                    token_program,
+                ),
+                spl_token_2022::instruction::initialize_mint2(
+                    token_program,
+                    &mint.pubkey(),
+                    &payer,
+                    None,
+                    MINT_DECIMALS,
+                )
+                .unwrap(),
+            ],
+            &[&mint],
+        )
+        .await
+        .unwrap();
+        mint.pubkey()
+    }
+
+    /// Create a Token-2022 mint with `MINT_DECIMALS` decimals and a transfer fee, the payer being its mint authority
+    /// Every transfer of the mint withholds `transfer_fee_bps` of the amount in the destination account
+    ///
+    /// # Arguments
+    /// * `transfer_fee_bps` - transfer fee in basis points (not capped)
+    pub async fn create_mint_with_transfer_fee(&mut self, transfer_fee_bps: u16) -> Pubkey {
+        let mint = Keypair::new();
+        let payer = self.payer();
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        let len =
+            ExtensionType::try_calculate_account_len::<Mint>(&[ExtensionType::TransferFeeConfig])
+                .unwrap();
+        self.send(
+            &[
+                system_instruction::create_account(
+                    &payer,
+                    &mint.pubkey(),
+                    rent.minimum_balance(len),
+                    len as u64,
+                    &spl_token_2022::ID,
+                ),
+                transfer_fee::instruction::initialize_transfer_fee_config(
+                    &spl_token_2022::ID,
+                    &mint.pubkey(),
+                    Some(&payer),
+                    Some(&payer),
+                    transfer_fee_bps,
+                    u64::MAX,
+                )
+                .unwrap(),
+                spl_token_2022::instruction::initialize_mint2(
+                    &spl_token_2022::ID,
+                    &mint.pubkey(),
+                    &payer,
+                    None,
+                    MINT_DECIMALS,
+                )
+                .unwrap(),
+            ],
+            &[&mint],
+        )
+        .await
+        .unwrap();
+        mint.pubkey()
+    }
+
+    /// Create (if needed) the associated token account of an owner and mint tokens to it
+    /// Returns the address of the token account
+    ///
+    /// # Arguments
+    /// * `mint` - mint created by `create_mint`
+    /// * `owner` - owner of the token account
+    /// * `token_program` - token program of the mint
+    /// * `amount` - amount to mint in base units (0 only creates the account)
+    pub async fn mint_tokens(
+        &mut self,
+        mint: &Pubkey,
+        owner: &Pubkey,
+        token_program: &Pubkey,
+        amount: u64,
+    ) -> Pubkey {
+        let payer = self.payer();
+        let token_account =
+            get_associated_token_address_with_program_id(owner, mint, token_program);
+        let mut instructions = vec![create_associated_token_account_idempotent(
+            &payer,
+            owner,
+            mint,
+            token_program,
+        )];
+        if amount > 0 {
+            instructions.push(
+                spl_token_2022::instruction::mint_to(
+                    token_program,
+                    mint,
+                    &token_account,
+                    &payer,
+                    &[],
+                    amount,
+                )
+                .unwrap(),
+            );
+        }
+        self.send(&instructions, &[]).await.unwrap();
+        token_account
+    }
+
+    /// Mint tokens to an existing token account (e.g. a vault of a pool)
+    pub async fn mint_to(
+        &mut self,
+        mint: &Pubkey,
+        token_account: &Pubkey,
+        token_program: &Pubkey,
+        amount: u64,
+    ) {
+        let payer = self.payer();
+        self.send(
+            &[spl_token_2022::instruction::mint_to(
+                token_program,
+                mint,
+                token_account,
+                &payer,
+                &[],
+                amount,
+            )
+            .unwrap()],
+            &[],
+        )
+        .await
+        .unwrap();
+    }
+
+    /// Get the balance of a token account
+    pub async fn token_balance(&mut self, token_account: &Pubkey) -> u64 {
+        let account = self.account(token_account).await.unwrap();
+        StateWithExtensions::<TokenAccount>::unpack(&account.data)
+            .unwrap()
+            .base
+            .amount
+    }
+
+    /// Get a mint
+    pub async fn mint(&mut self, mint: &Pubkey) -> Mint {
+        let account = self.account(mint).await.unwrap();
+        StateWithExtensions::<Mint>::unpack(&account.data)
+            .unwrap()
+            .base
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////
+    /////////////////////////////////// ACCOUNTS ///////////////////////////////////
+    ////////////////////////////////////////////////////////////////////////////////
+
+    /// Get an account, `None` if it does not exist (e.g. it was closed)
+    pub async fn account(&mut self, address: &Pubkey) -> Option<Account> {
+        self.context
+            .banks_client
+            .get_account(*address)
+            .await
+            .unwrap()
+    }
+
+    /// Get and deserialize an Anchor account
+    pub async fn fetch<T: AccountDeserialize>(&mut self, address: &Pubkey) -> T {
+        let account = self
+            .account(address)
+            .await
+            .unwrap_or_else(|| panic!("account {address} does not exist"));
+        T::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    /// Overwrite an account, e.g. to load an account with an older layout
+    ///
+    /// # Arguments
+    /// * `address` - address of the account
+    /// * `owner` - program owning the account
+    /// * `data` - data of the account (rent exempt lamports are added)
+    pub async fn set_account(&mut self, address: &Pubkey, owner: &Pubkey, data: Vec<u8>) {
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        let account = Account {
+            lamports: rent.minimum_balance(data.len()),
+            data,
+            owner: *owner,
+            executable: false,
+            rent_epoch: 0,
+        };
+        self.context
+            .set_account(address, &AccountSharedData::from(account));
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////
+    ///////////////////////////////////// CLOCK /////////////////////////////////////
+    ////////////////////////////////////////////////////////////////////////////////
+
+    /// Get the current time of the cluster clock in seconds
+    pub async fn now(&mut self) -> i64 {
+        self.clock().await.unix_timestamp
+    }
+
+    /// Move the cluster clock forward, so the duration, lock, cooldown and timelock checks can be tested
+    ///
+    /// # Arguments
+    /// * `seconds` - time in seconds to move the clock forward by
+    pub async fn warp_forward(&mut self, seconds: i64) {
+        let mut clock = self.clock().await;
+        clock.unix_timestamp += seconds;
+        self.context.set_sysvar(&clock);
+    }
+
+    async fn clock(&mut self) -> Clock {
+        self.context
+            .banks_client
+            .get_sysvar::<Clock>()
+            .await
+            .unwrap()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+//////////////////////////////////// ERRORS ////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+/// Get the instruction error a transaction failed with
+/// Panics if the transaction succeeded or failed for another reason
+pub fn instruction_error(result: Result<(), BanksClientError>) -> InstructionError {
+    match result.expect_err("transaction should fail").unwrap() {
+        TransactionError::InstructionError(_, error) => error,
+        error => panic!("unexpected transaction error {error:?}"),
+    }
+}
+
+/// Assert a transaction failed with a custom program error (Anchor or token program error code)
+pub fn assert_custom_error(result: Result<(), BanksClientError>, code: u32) {
+    assert_eq!(instruction_error(result), InstructionError::Custom(code));
+}
+
+/// Assert a transaction failed with the given error of the stake_tokens program
+pub fn assert_staking_error(result: Result<(), BanksClientError>, error: StakingError) {
+    assert_custom_error(result, error.into());
+}
@@ -0,0 +1,771 @@
+use {
+    anchor_lang::{system_program, InstructionData, ToAccountMetas},
+    anchor_spl::associated_token::get_associated_token_address_with_program_id,
+    solana_sdk::{
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+    },
+    stake_tokens::{
+        accounts, instruction, LockupTier, PenaltyDestination, PenaltyPolicy, PoolConfig,
+        PoolState, RewardMode,
+    },
+};
+
+/// Staking pool of the stake_tokens program under test
+/// Derives the PDAs of the pool and builds its instructions, the token accounts of the users
+/// being their associated token accounts
+///
+/// # Fields
+/// * `address` - staking account PDA of the pool
+/// * `creator` - admin which created the pool (part of the PDA seeds)
+/// * `pool_id` - id of the pool among the pools of the creator
+/// * `mint` - mint of the staked token
+/// * `token_program` - token program of the mints (SPL Token or Token-2022)
+/// * `reward_mint` - mint of the rewards (the staked mint until `set_reward_mint`)
+/// * `treasury` - token account receiving the penalties routed to the treasury
+/// * `streams` - mints of the reward streams by slot (`None` for a slot freed by `retire_reward_stream`)
+#[derive(Clone, Debug)]
+pub struct Pool {
+    pub address: Pubkey,
+    pub creator: Pubkey,
+    pub pool_id: u64,
+    pub mint: Pubkey,
+    pub token_program: Pubkey,
+    pub reward_mint: Pubkey,
+    pub treasury: Option<Pubkey>,
+    pub streams: Vec<Option<Pubkey>>,
+}
+
+impl Pool {
+    /// Describe a pool (created by `initialize`)
+    ///
+    /// # Arguments
+    /// * `creator` - admin creating the pool
+    /// * `pool_id` - id of the pool among the pools of the creator
+    /// * `mint` - mint of the staked token
+    /// * `token_program` - token program of the mint
+    pub fn new(creator: Pubkey, pool_id: u64, mint: Pubkey, token_program: Pubkey) -> Self {
+        let (address, _) = Pubkey::find_program_address(
+            &[creator.as_ref(), b"staking_account", &pool_id.to_le_bytes()],
+            &stake_tokens::ID,
+        );
+        Self {
+            address,
+            creator,
+            pool_id,
+            mint,
+            token_program,
+            reward_mint: mint,
+            treasury: None,
+            streams: Vec::new(),
+        }
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////
+    ///////////////////////////////////// PDAS /////////////////////////////////////
+    ////////////////////////////////////////////////////////////////////////////////
+
+    /// Vault holding the staked tokens (and the rewards in the staked token)
+    pub fn vault(&self) -> Pubkey {
+        self.find_address(&[self.address.as_ref(), b"staking_token_account"])
+    }
+
+    /// Vault holding the rewards of a pool with a separate reward mint
+    pub fn reward_vault(&self) -> Pubkey {
+        self.find_address(&[self.address.as_ref(), b"reward_token_account"])
+    }
+
+    /// Vault holding the rewards of a reward stream
+    pub fn stream_vault(&self, index: u8) -> Pubkey {
+        self.find_address(&[self.address.as_ref(), b"reward_stream", &[index]])
+    }
+
+    /// Position of a user
+    pub fn user_stake(&self, user: &Pubkey, position_id: u64) -> Pubkey {
+        self.find_address(&[
+            self.address.as_ref(),
+            user.as_ref(),
+            b"user_stake",
+            &position_id.to_le_bytes(),
+        ])
+    }
+
+    /// Counter of the positions of a user
+    pub fn user_stake_counter(&self, user: &Pubkey) -> Pubkey {
+        self.find_address(&[self.address.as_ref(), user.as_ref(), b"user_stake_counter"])
+    }
+
+    /// Pool of the creator in the baseline layout (one per admin), moved to this pool by `migrate_pool`
+    pub fn legacy_address(&self) -> Pubkey {
+        self.find_address(&[self.creator.as_ref(), b"staking_account"])
+    }
+
+    /// Position of a user in the baseline layout (one per user, not linked to a pool)
+    pub fn legacy_user_stake(&self, user: &Pubkey) -> Pubkey {
+        self.find_address(&[user.as_ref(), b"user_stake"])
+    }
+
+    /// Associated token account of an owner for the staked token
+    pub fn token_account(&self, owner: &Pubkey) -> Pubkey {
+        get_associated_token_address_with_program_id(owner, &self.mint, &self.token_program)
+    }
+
+    /// Associated token account of an owner for the reward token
+    pub fn reward_token_account(&self, owner: &Pubkey) -> Pubkey {
+        get_associated_token_address_with_program_id(owner, &self.reward_mint, &self.token_program)
+    }
+
+    fn find_address(&self, seeds: &[&[u8]]) -> Pubkey {
+        Pubkey::find_program_address(seeds, &stake_tokens::ID).0
+    }
+
+    fn has_reward_vault(&self) -> bool {
+        self.reward_mint != self.mint
+    }
+
+    /// Reward vault and reward mint, only passed when the pool has a separate reward mint
+    fn reward_accounts(&self) -> (Option<Pubkey>, Option<Pubkey>) {
+        if self.has_reward_vault() {
+            (Some(self.reward_vault()), Some(self.reward_mint))
+        } else {
+            (None, None)
+        }
+    }
+
+    /// Token account of an owner receiving the rewards, only passed when the pool has a separate reward mint
+    fn user_reward_account(&self, owner: &Pubkey) -> Option<Pubkey> {
+        self.has_reward_vault()
+            .then(|| self.reward_token_account(owner))
+    }
+
+    /// Slot taken by the next reward stream (the first free one or the next one)
+    pub fn free_stream_slot(&self) -> u8 {
+        self.streams
+            .iter()
+            .position(Option::is_none)
+            .unwrap_or(self.streams.len()) as u8
+    }
+
+    /// Record a reward stream added by `add_reward_stream` in its slot
+    pub fn record_stream(&mut self, stream_mint: Pubkey) {
+        let index = self.free_stream_slot() as usize;
+        if index == self.streams.len() {
+            self.streams.push(Some(stream_mint));
+        } else {
+            self.streams[index] = Some(stream_mint);
+        }
+    }
+
+    /// Record the reward stream of a slot retired by `retire_reward_stream`
+    pub fn record_retired_stream(&mut self, index: u8) {
+        self.streams[index as usize] = None;
+        while self.streams.last() == Some(&None) {
+            self.streams.pop();
+        }
+    }
+
+    /// Remaining accounts `(vault, mint, destination)` of every reward stream
+    fn stream_accounts(&self, owner: &Pubkey) -> Vec<AccountMeta> {
+        self.streams
+            .iter()
+            .enumerate()
+            .filter_map(|(index, mint)| mint.map(|mint| (index, mint)))
+            .flat_map(|(index, mint)| {
+                let destination =
+                    get_associated_token_address_with_program_id(owner, &mint, &self.token_program);
+                [
+                    AccountMeta::new(self.stream_vault(index as u8), false),
+                    AccountMeta::new_readonly(mint, false),
+                    AccountMeta::new(destination, false),
+                ]
+            })
+            .collect()
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////
+    ///////////////////////////////// INSTRUCTIONS /////////////////////////////////
+    ////////////////////////////////////////////////////////////////////////////////
+
+    pub fn initialize(
+        &self,
+        reward_rate: u8,
+        min_staking_duration: i64,
+        reward_mode: RewardMode,
+    ) -> Instruction {
+        build(
+            accounts::Initialize {
+                staking_account: self.address,
+                staking_token_account: self.vault(),
+                mint: self.mint,
+                admin: self.creator,
+                token_program: self.token_program,
+                system_program: system_program::ID,
+            },
+            instruction::Initialize {
+                pool_id: self.pool_id,
+                token_mint: self.mint,
+                reward_rate,
+                min_staking_duration,
+                reward_mode,
+            },
+        )
+    }
+
+    pub fn set_reward_mint(&self, admin: &Pubkey, reward_mint: &Pubkey) -> Instruction {
+        build(
+            accounts::SetRewardMint {
+                staking_account: self.address,
+                reward_token_account: self.reward_vault(),
+                reward_mint: *reward_mint,
+                mint: self.mint,
+                admin: *admin,
+                token_program: self.token_program,
+                system_program: system_program::ID,
+            },
+            instruction::SetRewardMint {},
+        )
+    }
+
+    pub fn fund_reward(&self, admin: &Pubkey, amount: u64) -> Instruction {
+        build(
+            self.fund_accounts(admin),
+            instruction::FundReward { amount },
+        )
+    }
+
+    pub fn fund_emission(&self, admin: &Pubkey, amount: u64, duration: i64) -> Instruction {
+        build(
+            self.fund_accounts(admin),
+            instruction::FundEmission { amount, duration },
+        )
+    }
+
+    fn fund_accounts(&self, admin: &Pubkey) -> accounts::FundRewards {
+        let (reward_token_account, reward_mint) = self.reward_accounts();
+        accounts::FundRewards {
+            staking_account: self.address,
+            staking_token_account: self.vault(),
+            admin: *admin,
+            admin_token_account: self.reward_token_account(admin),
+            mint: self.mint,
+            reward_token_account,
+            reward_mint,
+            token_program: self.token_program,
+        }
+    }
+
+    pub fn withdraw_rewards(&self, admin: &Pubkey, amount: u64) -> Instruction {
+        let (reward_token_account, reward_mint) = self.reward_accounts();
+        build(
+            accounts::WithdrawRewards {
+                staking_account: self.address,
+                staking_token_account: self.vault(),
+                admin: *admin,
+                admin_token_account: self.reward_token_account(admin),
+                mint: self.mint,
+                reward_token_account,
+                reward_mint,
+                token_program: self.token_program,
+            },
+            instruction::WithdrawRewards { amount },
+        )
+    }
+
+    /// Add a reward stream to the next free slot of the pool (record it with `record_stream` once added)
+    pub fn add_reward_stream(
+        &self,
+        admin: &Pubkey,
+        stream_mint: &Pubkey,
+        amount: u64,
+        start_time: i64,
+        end_time: i64,
+    ) -> Instruction {
+        let index = self.free_stream_slot();
+        build(
+            accounts::AddRewardStream {
+                staking_account: self.address,
+                reward_stream_vault: self.stream_vault(index),
+                stream_mint: *stream_mint,
+                mint: self.mint,
+                admin: *admin,
+                admin_token_account: get_associated_token_address_with_program_id(
+                    admin,
+                    stream_mint,
+                    &self.token_program,
+                ),
+                token_program: self.token_program,
+                system_program: system_program::ID,
+            },
+            instruction::AddRewardStream {
+                index,
+                amount,
+                start_time,
+                end_time,
+            },
+        )
+    }
+
+    pub fn retire_reward_stream(&self, admin: &Pubkey, index: u8) -> Instruction {
+        let stream_mint = self.streams[index as usize].expect("retired slot");
+        build(
+            accounts::RetireRewardStream {
+                staking_account: self.address,
+                reward_stream_vault: self.stream_vault(index),
+                stream_mint,
+                admin: *admin,
+                admin_token_account: get_associated_token_address_with_program_id(
+                    admin,
+                    &stream_mint,
+                    &self.token_program,
+                ),
+                token_program: self.token_program,
+            },
+            instruction::RetireRewardStream { index },
+        )
+    }
+
+    /// Open the position `position_id` of a user (the next id of its counter)
+    pub fn stake(&self, user: &Pubkey, position_id: u64, amount: u64, tier: u8) -> Instruction {
+        build(
+            accounts::Stake {
+                staking_account: self.address,
+                user_stake_counter: self.user_stake_counter(user),
+                user_stake: self.user_stake(user, position_id),
+                user: *user,
+                user_token_account: self.token_account(user),
+                staking_token_account: self.vault(),
+                mint: self.mint,
+                token_program: self.token_program,
+                system_program: system_program::ID,
+            },
+            instruction::Stake { amount, tier },
+        )
+    }
+
+    pub fn increase_stake(&self, user: &Pubkey, position_id: u64, amount: u64) -> Instruction {
+        build(
+            accounts::IncreaseStake {
+                staking_account: self.address,
+                user_stake: self.user_stake(user, position_id),
+                user: *user,
+                user_token_account: self.token_account(user),
+                staking_token_account: self.vault(),
+                mint: self.mint,
+                token_program: self.token_program,
+            },
+            instruction::IncreaseStake { amount },
+        )
+    }
+
+    pub fn partial_unstake(&self, user: &Pubkey, position_id: u64, amount: u64) -> Instruction {
+        let (reward_token_account, reward_mint) = self.reward_accounts();
+        build_with_streams(
+            accounts::PartialUnstake {
+                staking_account: self.address,
+                user_stake: self.user_stake(user, position_id),
+                user: *user,
+                user_token_account: self.token_account(user),
+                staking_token_account: self.vault(),
+                mint: self.mint,
+                reward_token_account,
+                reward_mint,
+                user_reward_token_account: self.user_reward_account(user),
+                token_program: self.token_program,
+            },
+            instruction::PartialUnstake { amount },
+            self.stream_accounts(user),
+        )
+    }
+
+    pub fn request_unstake(&self, user: &Pubkey, position_id: u64, amount: u64) -> Instruction {
+        let (reward_token_account, reward_mint) = self.reward_accounts();
+        build_with_streams(
+            accounts::RequestUnstake {
+                staking_account: self.address,
+                user_stake: self.user_stake(user, position_id),
+                user: *user,
+                user_token_account: self.token_account(user),
+                staking_token_account: self.vault(),
+                mint: self.mint,
+                reward_token_account,
+                reward_mint,
+                user_reward_token_account: self.user_reward_account(user),
+                token_program: self.token_program,
+            },
+            instruction::RequestUnstake { amount },
+            self.stream_accounts(user),
+        )
+    }
+
+    pub fn withdraw_unbonded(&self, user: &Pubkey, position_id: u64) -> Instruction {
+        build(
+            accounts::WithdrawUnbonded {
+                staking_account: self.address,
+                user_stake: self.user_stake(user, position_id),
+                user: *user,
+                user_token_account: self.token_account(user),
+                staking_token_account: self.vault(),
+                mint: self.mint,
+                token_program: self.token_program,
+            },
+            instruction::WithdrawUnbonded {},
+        )
+    }
+
+    pub fn claim_rewards(&self, user: &Pubkey, position_id: u64) -> Instruction {
+        let (reward_token_account, reward_mint) = self.reward_accounts();
+        build_with_streams(
+            accounts::ClaimRewards {
+                staking_account: self.address,
+                user_stake: self.user_stake(user, position_id),
+                user: *user,
+                user_token_account: self.token_account(user),
+                staking_token_account: self.vault(),
+                mint: self.mint,
+                reward_token_account,
+                reward_mint,
+                user_reward_token_account: self.user_reward_account(user),
+                token_program: self.token_program,
+            },
+            instruction::ClaimRewards {},
+            self.stream_accounts(user),
+        )
+    }
+
+    /// Preview the rewards of a position, read from the return data of a simulation
+    pub fn preview_reward(&self, user: &Pubkey, position_id: u64) -> Instruction {
+        build(
+            accounts::PreviewReward {
+                staking_account: self.address,
+                user_stake: self.user_stake(user, position_id),
+            },
+            instruction::PreviewReward {},
+        )
+    }
+
+    /// Compound a position, signed by its owner or by a cranker (receiving the tip)
+    pub fn compound(&self, authority: &Pubkey, user: &Pubkey, position_id: u64) -> Instruction {
+        build(
+            accounts::Compound {
+                staking_account: self.address,
+                user_stake: self.user_stake(user, position_id),
+                authority: *authority,
+                authority_token_account: (authority != user).then(|| self.token_account(authority)),
+                staking_token_account: self.vault(),
+                mint: self.mint,
+                token_program: self.token_program,
+            },
+            instruction::Compound {},
+        )
+    }
+
+    pub fn set_auto_compound(&self, user: &Pubkey, position_id: u64, enabled: bool) -> Instruction {
+        build(
+            accounts::SetAutoCompound {
+                staking_account: self.address,
+                user_stake: self.user_stake(user, position_id),
+                user: *user,
+            },
+            instruction::SetAutoCompound { enabled },
+        )
+    }
+
+    pub fn set_compound_tip(&self, admin: &Pubkey, compound_tip_bps: u16) -> Instruction {
+        build(
+            accounts::SetCompoundTip {
+                staking_account: self.address,
+                admin: *admin,
+            },
+            instruction::SetCompoundTip { compound_tip_bps },
+        )
+    }
+
+    pub fn set_lockup_tiers(&self, admin: &Pubkey, tiers: Vec<LockupTier>) -> Instruction {
+        build(
+            accounts::SetLockupTiers {
+                staking_account: self.address,
+                admin: *admin,
+            },
+            instruction::SetLockupTiers { tiers },
+        )
+    }
+
+    pub fn set_penalty_policy(
+        &self,
+        admin: &Pubkey,
+        penalty_policy: PenaltyPolicy,
+        penalty_bps: u16,
+        penalty_destination: PenaltyDestination,
+        treasury_token_account: Option<Pubkey>,
+    ) -> Instruction {
+        build(
+            accounts::SetPenaltyPolicy {
+                staking_account: self.address,
+                admin: *admin,
+                treasury_token_account,
+            },
+            instruction::SetPenaltyPolicy {
+                penalty_policy,
+                penalty_bps,
+                penalty_destination,
+            },
+        )
+    }
+
+    pub fn propose_config_update(&self, admin: &Pubkey, config: PoolConfig) -> Instruction {
+        build(
+            accounts::UpdateConfig {
+                staking_account: self.address,
+                admin: *admin,
+            },
+            instruction::ProposeConfigUpdate { config },
+        )
+    }
+
+    pub fn apply_config_update(&self, admin: &Pubkey) -> Instruction {
+        build(
+            accounts::UpdateConfig {
+                staking_account: self.address,
+                admin: *admin,
+            },
+            instruction::ApplyConfigUpdate {},
+        )
+    }
+
+    pub fn propose_admin(&self, admin: &Pubkey, new_admin: &Pubkey) -> Instruction {
+        build(
+            accounts::ProposeAdmin {
+                staking_account: self.address,
+                admin: *admin,
+            },
+            instruction::ProposeAdmin {
+                new_admin: *new_admin,
+            },
+        )
+    }
+
+    pub fn accept_admin(&self, pending_admin: &Pubkey) -> Instruction {
+        build(
+            accounts::AcceptAdmin {
+                staking_account: self.address,
+                pending_admin: *pending_admin,
+            },
+            instruction::AcceptAdmin {},
+        )
+    }
+
+    pub fn renounce_admin(&self, admin: &Pubkey) -> Instruction {
+        build(
+            accounts::ProposeAdmin {
+                staking_account: self.address,
+                admin: *admin,
+            },
+            instruction::RenounceAdmin {},
+        )
+    }
+
+    pub fn set_guardian(&self, admin: &Pubkey, guardian: &Pubkey) -> Instruction {
+        build(
+            accounts::SetGuardian {
+                staking_account: self.address,
+                admin: *admin,
+            },
+            instruction::SetGuardian {
+                guardian: *guardian,
+            },
+        )
+    }
+
+    pub fn set_pool_state(&self, authority: &Pubkey, pool_state: PoolState) -> Instruction {
+        build(
+            accounts::SetPoolState {
+                staking_account: self.address,
+                authority: *authority,
+            },
+            instruction::SetPoolState { pool_state },
+        )
+    }
+
+    pub fn emergency_withdraw(&self, user: &Pubkey, position_id: u64) -> Instruction {
+        build(
+            accounts::EmergencyWithdraw {
+                staking_account: self.address,
+                user_stake: self.user_stake(user, position_id),
+                user: *user,
+                user_token_account: self.token_account(user),
+                staking_token_account: self.vault(),
+                mint: self.mint,
+                token_program: self.token_program,
+            },
+            instruction::EmergencyWithdraw {},
+        )
+    }
+
+    pub fn redeem(&self, user: &Pubkey, position_id: u64, force_redeem: bool) -> Instruction {
+        let (reward_token_account, reward_mint) = self.reward_accounts();
+        build_with_streams(
+            accounts::Redeem {
+                staking_account: self.address,
+                user_stake: self.user_stake(user, position_id),
+                user: *user,
+                user_token_account: self.token_account(user),
+                staking_token_account: self.vault(),
+                treasury_token_account: self.treasury,
+                mint: self.mint,
+                reward_token_account,
+                reward_mint,
+                user_reward_token_account: self.user_reward_account(user),
+                token_program: self.token_program,
+            },
+            instruction::Redeem { force_redeem },
+            self.stream_accounts(user),
+        )
+    }
+
+    pub fn begin_shutdown(&self, admin: &Pubkey, grace_period: i64) -> Instruction {
+        build(
+            accounts::BeginShutdown {
+                staking_account: self.address,
+                admin: *admin,
+            },
+            instruction::BeginShutdown { grace_period },
+        )
+    }
+
+    /// Return a position to its owner after the shutdown grace period, signed by any cranker
+    pub fn return_position(
+        &self,
+        authority: &Pubkey,
+        user: &Pubkey,
+        position_id: u64,
+    ) -> Instruction {
+        let (reward_token_account, reward_mint) = self.reward_accounts();
+        build_with_streams(
+            accounts::ReturnPosition {
+                staking_account: self.address,
+                user_stake: self.user_stake(user, position_id),
+                user: *user,
+                user_token_account: self.token_account(user),
+                staking_token_account: self.vault(),
+                authority: *authority,
+                mint: self.mint,
+                reward_token_account,
+                reward_mint,
+                user_reward_token_account: self.user_reward_account(user),
+                token_program: self.token_program,
+            },
+            instruction::ReturnPosition {},
+            self.stream_accounts(user),
+        )
+    }
+
+    pub fn close_pool(&self, admin: &Pubkey) -> Instruction {
+        let (reward_token_account, reward_mint) = self.reward_accounts();
+        build_with_streams(
+            accounts::ClosePool {
+                staking_account: self.address,
+                staking_token_account: self.vault(),
+                admin: *admin,
+                admin_token_account: self.token_account(admin),
+                mint: self.mint,
+                reward_token_account,
+                reward_mint,
+                admin_reward_token_account: self.user_reward_account(admin),
+                token_program: self.token_program,
+            },
+            instruction::ClosePool {},
+            self.stream_accounts(admin),
+        )
+    }
+
+    pub fn migrate_pool(&self) -> Instruction {
+        build(
+            accounts::MigratePool {
+                legacy_staking_account: self.legacy_address(),
+                staking_account: self.address,
+                staking_token_account: self.vault(),
+                mint: self.mint,
+                admin: self.creator,
+                admin_token_account: self.token_account(&self.creator),
+                token_program: self.token_program,
+                system_program: system_program::ID,
+            },
+            instruction::MigratePool {
+                pool_id: self.pool_id,
+            },
+        )
+    }
+
+    pub fn migrate_user_stake(
+        &self,
+        admin: &Pubkey,
+        user: &Pubkey,
+        position_id: u64,
+    ) -> Instruction {
+        build(
+            accounts::MigrateUserStake {
+                staking_account: self.address,
+                legacy_user_stake: self.legacy_user_stake(user),
+                user: *user,
+                user_stake_counter: self.user_stake_counter(user),
+                user_stake: self.user_stake(user, position_id),
+                staking_token_account: self.vault(),
+                mint: self.mint,
+                admin: *admin,
+                admin_token_account: self.token_account(admin),
+                token_program: self.token_program,
+                system_program: system_program::ID,
+            },
+            instruction::MigrateUserStake {},
+        )
+    }
+
+    /// Upgrade the pool in place to the current layout
+    pub fn upgrade_pool(&self, payer: &Pubkey) -> Instruction {
+        build(
+            accounts::UpgradePool {
+                staking_account: self.address,
+                payer: *payer,
+                system_program: system_program::ID,
+            },
+            instruction::UpgradePool {},
+        )
+    }
+
+    /// Upgrade a position of a user in place to the current layout
+    pub fn upgrade_user_stake(
+        &self,
+        payer: &Pubkey,
+        user: &Pubkey,
+        position_id: u64,
+    ) -> Instruction {
+        build(
+            accounts::UpgradeUserStake {
+                user_stake: self.user_stake(user, position_id),
+                payer: *payer,
+                system_program: system_program::ID,
+            },
+            instruction::UpgradeUserStake {},
+        )
+    }
+}
+
+/// Build an instruction of the stake_tokens program
+fn build(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    build_with_streams(accounts, data, Vec::new())
+}
+
+/// Build an instruction of the stake_tokens program with remaining accounts
+fn build_with_streams(
+    accounts: impl ToAccountMetas,
+    data: impl InstructionData,
+    remaining_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    let mut metas = accounts.to_account_metas(None);
+    metas.extend(remaining_accounts);
+    Instruction {
+        program_id: stake_tokens::ID,
+        accounts: metas,
+        data: data.data(),
+    }
+}
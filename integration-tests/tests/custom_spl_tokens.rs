@@ -0,0 +1,271 @@
+use {
+    anchor_lang::{error::ErrorCode, system_program, InstructionData, ToAccountMetas},
+    anchor_spl::{
+        associated_token::{self, get_associated_token_address_with_program_id},
+        metadata::mpl_token_metadata::{self, accounts::Metadata},
+        token, token_2022,
+        token_2022::spl_token_2022::error::TokenError,
+    },
+    custom_spl_tokens::{accounts, instruction},
+    integration_tests::{assert_custom_error, TestEnv, MINT_DECIMALS},
+    solana_sdk::{
+        instruction::Instruction,
+        program_option::COption,
+        pubkey::Pubkey,
+        signature::{Keypair, Signer},
+        sysvar,
+    },
+};
+
+/// One whole token in base units (the program scales the amounts by the decimals of the mint)
+const TOKEN: u64 = 10u64.pow(MINT_DECIMALS as u32);
+
+////////////////////////////////////////////////////////////////////////////////
+///////////////////////////////// INSTRUCTIONS /////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+fn create_token_mint(
+    payer: &Pubkey,
+    mint: &Pubkey,
+    metadata: &Pubkey,
+    token_program: &Pubkey,
+    decimals: u8,
+) -> Instruction {
+    build(
+        accounts::CreateToken {
+            payer: *payer,
+            metadata_account: *metadata,
+            mint_account: *mint,
+            token_metadata_program: mpl_token_metadata::ID,
+            token_program: *token_program,
+            system_program: system_program::ID,
+            rent: sysvar::rent::ID,
+        },
+        instruction::CreateTokenMint {
+            _token_decimals: decimals,
+            token_name: "Stake Token".to_string(),
+            token_symbol: "STK".to_string(),
+            token_uri: "https://example.com/stk.json".to_string(),
+        },
+    )
+}
+
+fn mint_token(
+    mint_authority: &Pubkey,
+    recipient: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    build(
+        accounts::MintToken {
+            mint_authority: *mint_authority,
+            recipient: *recipient,
+            mint_account: *mint,
+            associated_token_account: get_associated_token_address_with_program_id(
+                recipient,
+                mint,
+                token_program,
+            ),
+            token_program: *token_program,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::MintToken { amount },
+    )
+}
+
+fn transfer_token(
+    sender: &Pubkey,
+    recipient: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    build(
+        accounts::TransferToken {
+            sender: *sender,
+            recipient: *recipient,
+            mint_account: *mint,
+            sender_token_account: get_associated_token_address_with_program_id(
+                sender,
+                mint,
+                token_program,
+            ),
+            recipient_token_account: get_associated_token_address_with_program_id(
+                recipient,
+                mint,
+                token_program,
+            ),
+            token_program: *token_program,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::TransferToken { amount },
+    )
+}
+
+/// Build an instruction of the custom_spl_tokens program
+fn build(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: custom_spl_tokens::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+//////////////////////////////////// TESTS ////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+#[tokio::test]
+#[ignore = "needs fixture: make test-fixtures"]
+async fn create_token_mint_with_metadata() {
+    let mut env = TestEnv::with_token_metadata().await;
+    let payer = env.payer();
+    let mint = Keypair::new();
+    let (metadata, _) = Metadata::find_pda(&mint.pubkey());
+
+    // the metadata account must be the metadata PDA of the mint
+    assert_custom_error(
+        env.send(
+            &[create_token_mint(
+                &payer,
+                &mint.pubkey(),
+                &Pubkey::new_unique(),
+                &token::ID,
+                9,
+            )],
+            &[&mint],
+        )
+        .await,
+        ErrorCode::ConstraintSeeds.into(),
+    );
+
+    env.send(
+        &[create_token_mint(
+            &payer,
+            &mint.pubkey(),
+            &metadata,
+            &token::ID,
+            9,
+        )],
+        &[&mint],
+    )
+    .await
+    .unwrap();
+    let mint_state = env.mint(&mint.pubkey()).await;
+    assert_eq!(mint_state.decimals, 9);
+    assert_eq!(mint_state.supply, 0);
+    assert_eq!(mint_state.mint_authority, COption::Some(payer));
+
+    let metadata = Metadata::from_bytes(&env.account(&metadata).await.unwrap().data).unwrap();
+    assert_eq!(metadata.mint, mint.pubkey());
+    assert_eq!(metadata.update_authority, payer);
+    assert!(metadata.is_mutable);
+    // the metadata program pads the strings to their maximum length
+    assert_eq!(metadata.name.trim_end_matches('\0'), "Stake Token");
+    assert_eq!(metadata.symbol.trim_end_matches('\0'), "STK");
+    assert_eq!(
+        metadata.uri.trim_end_matches('\0'),
+        "https://example.com/stk.json"
+    );
+}
+
+/// Mint and transfer whole tokens with the given token program
+async fn mint_and_transfer(token_program: Pubkey) {
+    let mut env = TestEnv::new().await;
+    let mint_authority = env.payer();
+    let mint = env.create_mint(&token_program).await;
+    let sender = env.create_user().await;
+    let recipient = Pubkey::new_unique();
+    let sender_account =
+        get_associated_token_address_with_program_id(&sender.pubkey(), &mint, &token_program);
+    let recipient_account =
+        get_associated_token_address_with_program_id(&recipient, &mint, &token_program);
+
+    // the associated token account is created on the first mint and reused afterwards
+    env.send(
+        &[mint_token(
+            &mint_authority,
+            &sender.pubkey(),
+            &mint,
+            &token_program,
+            5,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+    env.send(
+        &[mint_token(
+            &mint_authority,
+            &sender.pubkey(),
+            &mint,
+            &token_program,
+            5,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+    assert_eq!(env.token_balance(&sender_account).await, 10 * TOKEN);
+    assert_eq!(env.mint(&mint).await.supply, 10 * TOKEN);
+
+    // only the mint authority can mint
+    assert_custom_error(
+        env.send(
+            &[mint_token(
+                &sender.pubkey(),
+                &sender.pubkey(),
+                &mint,
+                &token_program,
+                1,
+            )],
+            &[&sender],
+        )
+        .await,
+        TokenError::OwnerMismatch as u32,
+    );
+
+    // the recipient token account is created by the transfer
+    env.send(
+        &[transfer_token(
+            &sender.pubkey(),
+            &recipient,
+            &mint,
+            &token_program,
+            4,
+        )],
+        &[&sender],
+    )
+    .await
+    .unwrap();
+    assert_eq!(env.token_balance(&sender_account).await, 6 * TOKEN);
+    assert_eq!(env.token_balance(&recipient_account).await, 4 * TOKEN);
+
+    assert_custom_error(
+        env.send(
+            &[transfer_token(
+                &sender.pubkey(),
+                &recipient,
+                &mint,
+                &token_program,
+                7,
+            )],
+            &[&sender],
+        )
+        .await,
+        TokenError::InsufficientFunds as u32,
+    );
+}
+
+#[tokio::test]
+async fn mint_and_transfer_with_token() {
+    mint_and_transfer(token::ID).await;
+}
+
+#[tokio::test]
+async fn mint_and_transfer_with_token_2022() {
+    mint_and_transfer(token_2022::ID).await;
+}
@@ -0,0 +1,2441 @@
+use {
+    anchor_lang::{error::ErrorCode, Discriminator},
+    anchor_spl::{token, token_2022},
+    integration_tests::{
+        assert_custom_error, assert_staking_error, pool::Pool, TestEnv, MINT_DECIMALS,
+    },
+    solana_program_test::BanksClientError,
+    solana_sdk::{
+        instruction::Instruction,
+        pubkey::Pubkey,
+        signature::{Keypair, Signer},
+    },
+    stake_tokens::{
+        AdminAccepted, AutoCompoundSet, CompoundTipSet, GuardianSet, LockupTier, PartiallyUnstaked,
+        PenaltyDestination, PenaltyPolicy, PoolConfig, PoolState, Redeemed, RewardMintSet,
+        RewardMode, RewardPreview, RewardsClaimed, StakingAccount, StakingError, UserStake,
+        UserStakeCounter, BPS_DENOMINATOR, DEFAULT_CONFIG_TIMELOCK, MAX_COMPOUND_TIP_BPS,
+        MAX_REWARD_STREAMS, MAX_UNBONDING_COOLDOWN, REWARD_RATE_DENOMINATOR, SECONDS_PER_YEAR,
+        STAKING_ACCOUNT_VERSION, STREAM_CLAIM_PERIOD, USER_STAKE_VERSION,
+    },
+};
+
+/// One whole token in base units
+const TOKEN: u64 = 10u64.pow(MINT_DECIMALS as u32);
+
+/// Tokens minted to the admin (funding) and to every staker
+const ADMIN_TOKENS: u64 = 1_000_000 * TOKEN;
+const STAKER_TOKENS: u64 = 10_000 * TOKEN;
+
+const DAY: i64 = 24 * 60 * 60;
+const YEAR: i64 = SECONDS_PER_YEAR as i64;
+
+/// Expected APY reward of a position, rounded down like the program
+fn apy_reward(amount: u64, reward_rate: u8, multiplier_bps: u16, duration: i64) -> u64 {
+    (amount as u128 * reward_rate as u128 * multiplier_bps as u128 * duration as u128
+        / (REWARD_RATE_DENOMINATOR * BPS_DENOMINATOR * SECONDS_PER_YEAR)) as u64
+}
+
+////////////////////////////////////////////////////////////////////////////////
+/////////////////////////////////// FIXTURE ///////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+/// Test environment with an initialized pool
+/// The admin holds `ADMIN_TOKENS` of the staked mint to fund the rewards
+struct Fixture {
+    env: TestEnv,
+    admin: Keypair,
+    pool: Pool,
+}
+
+impl Fixture {
+    async fn new(reward_rate: u8, min_staking_duration: i64, reward_mode: RewardMode) -> Self {
+        Self::with_token_program(token::ID, reward_rate, min_staking_duration, reward_mode).await
+    }
+
+    async fn with_token_program(
+        token_program: Pubkey,
+        reward_rate: u8,
+        min_staking_duration: i64,
+        reward_mode: RewardMode,
+    ) -> Self {
+        let mut env = TestEnv::new().await;
+        let mint = env.create_mint(&token_program).await;
+        Self::with_mint(
+            env,
+            mint,
+            token_program,
+            reward_rate,
+            min_staking_duration,
+            reward_mode,
+        )
+        .await
+    }
+
+    /// Fixture staking a Token-2022 mint with a transfer fee
+    async fn with_transfer_fee(
+        transfer_fee_bps: u16,
+        reward_rate: u8,
+        min_staking_duration: i64,
+        reward_mode: RewardMode,
+    ) -> Self {
+        let mut env = TestEnv::new().await;
+        let mint = env.create_mint_with_transfer_fee(transfer_fee_bps).await;
+        Self::with_mint(
+            env,
+            mint,
+            token_2022::ID,
+            reward_rate,
+            min_staking_duration,
+            reward_mode,
+        )
+        .await
+    }
+
+    async fn with_mint(
+        mut env: TestEnv,
+        mint: Pubkey,
+        token_program: Pubkey,
+        reward_rate: u8,
+        min_staking_duration: i64,
+        reward_mode: RewardMode,
+    ) -> Self {
+        let admin = env.create_user().await;
+        env.mint_tokens(&mint, &admin.pubkey(), &token_program, ADMIN_TOKENS)
+            .await;
+
+        let pool = Pool::new(admin.pubkey(), 0, mint, token_program);
+        let mut fixture = Self { env, admin, pool };
+        fixture
+            .admin_send(
+                fixture
+                    .pool
+                    .initialize(reward_rate, min_staking_duration, reward_mode),
+            )
+            .await
+            .unwrap();
+        fixture
+    }
+
+    /// Initialize another pool of the admin for the same mint
+    async fn create_pool(
+        &mut self,
+        pool_id: u64,
+        reward_rate: u8,
+        min_staking_duration: i64,
+        reward_mode: RewardMode,
+    ) -> Pool {
+        let pool = Pool::new(
+            self.admin.pubkey(),
+            pool_id,
+            self.pool.mint,
+            self.pool.token_program,
+        );
+        self.admin_send(pool.initialize(reward_rate, min_staking_duration, reward_mode))
+            .await
+            .unwrap();
+        pool
+    }
+
+    /// Create a user holding `STAKER_TOKENS` of the staked mint
+    async fn create_staker(&mut self) -> Keypair {
+        let user = self.env.create_user().await;
+        self.env
+            .mint_tokens(
+                &self.pool.mint,
+                &user.pubkey(),
+                &self.pool.token_program,
+                STAKER_TOKENS,
+            )
+            .await;
+        user
+    }
+
+    async fn admin_send(&mut self, instruction: Instruction) -> Result<(), BanksClientError> {
+        let admin = self.admin.insecure_clone();
+        self.env.send(&[instruction], &[&admin]).await
+    }
+
+    async fn send(
+        &mut self,
+        instruction: Instruction,
+        signer: &Keypair,
+    ) -> Result<(), BanksClientError> {
+        self.env.send(&[instruction], &[signer]).await
+    }
+
+    async fn stake(&mut self, user: &Keypair, position_id: u64, amount: u64, tier: u8) {
+        self.send(
+            self.pool.stake(&user.pubkey(), position_id, amount, tier),
+            user,
+        )
+        .await
+        .unwrap();
+    }
+
+    async fn fund_reward(&mut self, amount: u64) {
+        self.admin_send(self.pool.fund_reward(&self.admin.pubkey(), amount))
+            .await
+            .unwrap();
+    }
+
+    /// Updates the pool config through the timelock, letting the timelock elapse
+    async fn update_config(&mut self, config: PoolConfig) -> Result<(), BanksClientError> {
+        let admin = self.admin.pubkey();
+        self.admin_send(self.pool.propose_config_update(&admin, config))
+            .await?;
+        let timelock = self.staking_account().await.config_timelock;
+        self.env.warp_forward(timelock).await;
+        self.admin_send(self.pool.apply_config_update(&admin)).await
+    }
+
+    /// Current config of the pool with another unbonding cooldown
+    async fn config_with_cooldown(&mut self, unbonding_cooldown: i64) -> PoolConfig {
+        let pool = self.staking_account().await;
+        PoolConfig {
+            reward_rate: pool.reward_rate,
+            min_staking_duration: pool.min_staking_duration,
+            config_timelock: pool.config_timelock,
+            unbonding_cooldown,
+        }
+    }
+
+    async fn staking_account(&mut self) -> StakingAccount {
+        self.env.fetch(&self.pool.address).await
+    }
+
+    async fn user_stake(&mut self, user: &Pubkey, position_id: u64) -> UserStake {
+        self.env
+            .fetch(&self.pool.user_stake(user, position_id))
+            .await
+    }
+
+    /// Balance of the staked token of an owner
+    async fn balance(&mut self, owner: &Pubkey) -> u64 {
+        self.env
+            .token_balance(&self.pool.token_account(owner))
+            .await
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+//////////////////////////////// INITIALIZE ////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+#[tokio::test]
+async fn initialize_creates_pool_and_vault() {
+    let mut fixture = Fixture::new(10, DAY, RewardMode::Apy).await;
+
+    let pool = fixture.staking_account().await;
+    assert_eq!(pool.admin, fixture.admin.pubkey());
+    assert_eq!(pool.creator, fixture.admin.pubkey());
+    assert_eq!(pool.pool_id, 0);
+    assert_eq!(pool.token_mint, fixture.pool.mint);
+    assert_eq!(pool.reward_mint, fixture.pool.mint);
+    assert_eq!(pool.reward_rate, 10);
+    assert_eq!(pool.min_staking_duration, DAY);
+    assert!(pool.reward_mode == RewardMode::Apy);
+    assert!(pool.pool_state == PoolState::Active);
+    assert_eq!(pool.tier_count, 1);
+    assert_eq!(pool.config_timelock, DEFAULT_CONFIG_TIMELOCK);
+    assert_eq!(pool.version, STAKING_ACCOUNT_VERSION);
+    assert_eq!(fixture.env.token_balance(&fixture.pool.vault()).await, 0);
+
+    // the pool id is part of the pool address, so the same pool can not be initialized twice
+    let initialize = fixture.pool.initialize(10, DAY, RewardMode::Apy);
+    assert!(fixture.admin_send(initialize).await.is_err());
+
+    // the reward rate and the minimum staking duration are validated like a config update
+    let pool = Pool::new(
+        fixture.admin.pubkey(),
+        1,
+        fixture.pool.mint,
+        fixture.pool.token_program,
+    );
+    assert_staking_error(
+        fixture
+            .admin_send(pool.initialize(101, DAY, RewardMode::Apy))
+            .await,
+        StakingError::InvalidArgument,
+    );
+    assert_staking_error(
+        fixture
+            .admin_send(pool.initialize(10, -1, RewardMode::Apy))
+            .await,
+        StakingError::InvalidArgument,
+    );
+}
+
+#[tokio::test]
+async fn stake_and_redeem_with_token_2022() {
+    let mut fixture = Fixture::with_token_program(token_2022::ID, 0, 0, RewardMode::Apy).await;
+    let user = fixture.create_staker().await;
+
+    fixture.stake(&user, 0, 1_000 * TOKEN, 0).await;
+    let vault = fixture.env.account(&fixture.pool.vault()).await.unwrap();
+    assert_eq!(vault.owner, token_2022::ID);
+    assert_eq!(
+        fixture.env.token_balance(&fixture.pool.vault()).await,
+        1_000 * TOKEN
+    );
+
+    fixture
+        .send(fixture.pool.redeem(&user.pubkey(), 0, false), &user)
+        .await
+        .unwrap();
+    assert_eq!(fixture.balance(&user.pubkey()).await, STAKER_TOKENS);
+}
+
+////////////////////////////////////////////////////////////////////////////////
+/////////////////////////////////// FUNDING ///////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+#[tokio::test]
+async fn stake_and_redeem_with_transfer_fee() {
+    // 1% of every transfer is withheld by the mint
+    let mut fixture = Fixture::with_transfer_fee(100, 10, 0, RewardMode::Apy).await;
+    let user = fixture.create_staker().await;
+    let owner = user.pubkey();
+
+    // only what the vault received is credited, to the reward funds and to the position
+    fixture.fund_reward(10_000 * TOKEN).await;
+    assert_eq!(
+        fixture.staking_account().await.admin_reward_amount,
+        9_900 * TOKEN
+    );
+    fixture.stake(&user, 0, 1_000 * TOKEN, 0).await;
+    let position = fixture.user_stake(&owner, 0).await;
+    assert_eq!(position.amount, 990 * TOKEN);
+    assert_eq!(fixture.staking_account().await.total_staked, 990 * TOKEN);
+    assert_eq!(
+        fixture.env.token_balance(&fixture.pool.vault()).await,
+        10_890 * TOKEN
+    );
+
+    // the payout leaves the vault in full, the fee being withheld from what the user receives
+    fixture.env.warp_forward(YEAR).await;
+    let reward = apy_reward(990 * TOKEN, 10, 10_000, YEAR);
+    fixture
+        .send(fixture.pool.redeem(&owner, 0, false), &user)
+        .await
+        .unwrap();
+    assert_eq!(
+        fixture.balance(&owner).await,
+        STAKER_TOKENS - 1_000 * TOKEN + (990 * TOKEN + reward) * 99 / 100
+    );
+    assert_eq!(
+        fixture.env.token_balance(&fixture.pool.vault()).await,
+        9_900 * TOKEN - reward
+    );
+    let pool = fixture.staking_account().await;
+    assert_eq!(pool.total_staked, 0);
+    assert_eq!(pool.reward_liability, 0);
+}
+
+#[tokio::test]
+async fn fund_and_withdraw_rewards() {
+    let mut fixture = Fixture::new(10, 0, RewardMode::Apy).await;
+    let admin = fixture.admin.pubkey();
+    let user = fixture.create_staker().await;
+
+    fixture.fund_reward(1_000 * TOKEN).await;
+    assert_eq!(
+        fixture.staking_account().await.admin_reward_amount,
+        1_000 * TOKEN
+    );
+    assert_eq!(
+        fixture.env.token_balance(&fixture.pool.vault()).await,
+        1_000 * TOKEN
+    );
+
+    // only the admin can fund the pool, even from their own tokens
+    assert_staking_error(
+        fixture
+            .send(fixture.pool.fund_reward(&user.pubkey(), TOKEN), &user)
+            .await,
+        StakingError::AdminOnly,
+    );
+
+    assert_staking_error(
+        fixture
+            .send(fixture.pool.withdraw_rewards(&user.pubkey(), TOKEN), &user)
+            .await,
+        StakingError::AdminOnly,
+    );
+    assert_staking_error(
+        fixture
+            .admin_send(fixture.pool.withdraw_rewards(&admin, 0))
+            .await,
+        StakingError::ZeroValueError,
+    );
+
+    // a year of rewards of the stake is owed and can not be withdrawn
+    fixture.stake(&user, 0, 1_000 * TOKEN, 0).await;
+    fixture.env.warp_forward(YEAR).await;
+    let owed = apy_reward(1_000 * TOKEN, 10, 10_000, YEAR);
+    assert_eq!(owed, 100 * TOKEN);
+    assert_staking_error(
+        fixture
+            .admin_send(
+                fixture
+                    .pool
+                    .withdraw_rewards(&admin, 1_000 * TOKEN - owed + 1),
+            )
+            .await,
+        StakingError::RewardFundsAllocated,
+    );
+
+    let balance = fixture.balance(&admin).await;
+    fixture
+        .admin_send(fixture.pool.withdraw_rewards(&admin, 1_000 * TOKEN - owed))
+        .await
+        .unwrap();
+    assert_eq!(fixture.balance(&admin).await - balance, 900 * TOKEN);
+    assert_eq!(fixture.staking_account().await.admin_reward_amount, owed);
+}
+
+#[tokio::test]
+async fn funding_checks_reward_mode() {
+    let mut fixture = Fixture::new(10, 0, RewardMode::Apy).await;
+    let admin = fixture.admin.pubkey();
+
+    assert_staking_error(
+        fixture
+            .admin_send(fixture.pool.fund_emission(&admin, TOKEN, DAY))
+            .await,
+        StakingError::InvalidRewardMode,
+    );
+
+    let emission_pool = fixture.create_pool(1, 0, 0, RewardMode::Emission).await;
+    assert_staking_error(
+        fixture
+            .admin_send(emission_pool.fund_reward(&admin, TOKEN))
+            .await,
+        StakingError::InvalidRewardMode,
+    );
+    assert_staking_error(
+        fixture
+            .admin_send(emission_pool.fund_emission(&admin, TOKEN, 0))
+            .await,
+        StakingError::ZeroValueError,
+    );
+}
+
+////////////////////////////////////////////////////////////////////////////////
+/////////////////////////////////// REWARDS ///////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+#[tokio::test]
+async fn apy_position_lifecycle() {
+    let mut fixture = Fixture::new(10, 0, RewardMode::Apy).await;
+    let user = fixture.create_staker().await;
+    let owner = user.pubkey();
+    fixture.fund_reward(1_000 * TOKEN).await;
+
+    fixture.stake(&user, 0, 1_000 * TOKEN, 0).await;
+    let position = fixture.user_stake(&owner, 0).await;
+    assert_eq!(position.amount, 1_000 * TOKEN);
+    assert_eq!(position.version, USER_STAKE_VERSION);
+    let counter: UserStakeCounter = fixture
+        .env
+        .fetch(&fixture.pool.user_stake_counter(&owner))
+        .await;
+    assert_eq!(counter.next_position_id, 1);
+
+    // 10% of the stake after a year
+    fixture.env.warp_forward(YEAR).await;
+    fixture
+        .send(fixture.pool.claim_rewards(&owner, 0), &user)
+        .await
+        .unwrap();
+    assert_eq!(fixture.balance(&owner).await, STAKER_TOKENS - 900 * TOKEN);
+    assert_eq!(
+        fixture.user_stake(&owner, 0).await.rewards_claimed,
+        100 * TOKEN
+    );
+    assert_staking_error(
+        fixture
+            .send(fixture.pool.claim_rewards(&owner, 0), &user)
+            .await,
+        StakingError::ZeroValueError,
+    );
+
+    assert_staking_error(
+        fixture
+            .send(fixture.pool.increase_stake(&owner, 0, 0), &user)
+            .await,
+        StakingError::ZeroValueError,
+    );
+    fixture
+        .send(fixture.pool.increase_stake(&owner, 0, 1_000 * TOKEN), &user)
+        .await
+        .unwrap();
+    assert_eq!(fixture.user_stake(&owner, 0).await.amount, 2_000 * TOKEN);
+
+    // the principal and the rewards of half a year are paid out and the position is closed
+    fixture.env.warp_forward(YEAR / 2).await;
+    fixture
+        .send(fixture.pool.redeem(&owner, 0, false), &user)
+        .await
+        .unwrap();
+    assert_eq!(fixture.balance(&owner).await, STAKER_TOKENS + 200 * TOKEN);
+    assert!(fixture
+        .env
+        .account(&fixture.pool.user_stake(&owner, 0))
+        .await
+        .is_none());
+    assert_eq!(fixture.staking_account().await.total_staked, 0);
+
+    assert_custom_error(
+        fixture
+            .send(fixture.pool.claim_rewards(&owner, 0), &user)
+            .await,
+        ErrorCode::AccountNotInitialized.into(),
+    );
+}
+
+#[tokio::test]
+async fn emission_is_shared_pro_rata() {
+    let mut fixture = Fixture::new(0, 0, RewardMode::Emission).await;
+    let admin = fixture.admin.pubkey();
+    let first = fixture.create_staker().await;
+    let second = fixture.create_staker().await;
+
+    // 1 token per second for 1000 seconds
+    fixture
+        .admin_send(fixture.pool.fund_emission(&admin, 1_000 * TOKEN, 1_000))
+        .await
+        .unwrap();
+    assert_eq!(fixture.staking_account().await.reward_per_second, TOKEN);
+
+    fixture.stake(&first, 0, 1_000 * TOKEN, 0).await;
+    fixture.stake(&second, 0, 3_000 * TOKEN, 0).await;
+    fixture.env.warp_forward(1_000).await;
+
+    fixture
+        .send(fixture.pool.claim_rewards(&first.pubkey(), 0), &first)
+        .await
+        .unwrap();
+    fixture
+        .send(fixture.pool.claim_rewards(&second.pubkey(), 0), &second)
+        .await
+        .unwrap();
+    assert_eq!(
+        fixture.balance(&first.pubkey()).await,
+        STAKER_TOKENS - 1_000 * TOKEN + 250 * TOKEN
+    );
+    assert_eq!(
+        fixture.balance(&second.pubkey()).await,
+        STAKER_TOKENS - 3_000 * TOKEN + 750 * TOKEN
+    );
+    assert_eq!(fixture.staking_account().await.reward_liability, 0);
+
+    // nothing is emitted after the end of the emission
+    fixture.env.warp_forward(100).await;
+    assert_staking_error(
+        fixture
+            .send(fixture.pool.claim_rewards(&first.pubkey(), 0), &first)
+            .await,
+        StakingError::ZeroValueError,
+    );
+}
+
+#[tokio::test]
+async fn emission_is_funded_by_the_admin_only() {
+    let mut fixture = Fixture::new(0, 0, RewardMode::Emission).await;
+    let admin = fixture.admin.pubkey();
+    let staker = fixture.create_staker().await;
+    let griefer = staker.pubkey();
+
+    // a staker can neither start an emission
+    assert_staking_error(
+        fixture
+            .send(fixture.pool.fund_emission(&griefer, 1, 100 * DAY), &staker)
+            .await,
+        StakingError::AdminOnly,
+    );
+    let pool = fixture.staking_account().await;
+    assert_eq!(pool.reward_per_second, 0);
+    assert_eq!(pool.emission_end_time, 0);
+
+    // nor reschedule a running one with a dust amount over a long duration
+    fixture
+        .admin_send(fixture.pool.fund_emission(&admin, 1_000 * TOKEN, 1_000))
+        .await
+        .unwrap();
+    let funded = fixture.staking_account().await;
+    assert_staking_error(
+        fixture
+            .send(fixture.pool.fund_emission(&griefer, 1, 100 * DAY), &staker)
+            .await,
+        StakingError::AdminOnly,
+    );
+    let pool = fixture.staking_account().await;
+    assert_eq!(pool.reward_per_second, TOKEN);
+    assert_eq!(pool.emission_end_time, funded.emission_end_time);
+    assert_eq!(pool.admin_reward_amount, funded.admin_reward_amount);
+    assert_eq!(fixture.balance(&griefer).await, STAKER_TOKENS);
+}
+
+#[tokio::test]
+async fn unfunded_rewards_block_matured_exits() {
+    let mut fixture = Fixture::new(10, 0, RewardMode::Apy).await;
+    let user = fixture.create_staker().await;
+    let owner = user.pubkey();
+
+    fixture.stake(&user, 0, 1_000 * TOKEN, 0).await;
+    fixture.env.warp_forward(YEAR).await;
+    assert_staking_error(
+        fixture
+            .send(fixture.pool.redeem(&owner, 0, false), &user)
+            .await,
+        StakingError::InsufficientRewardFunds,
+    );
+    assert_staking_error(
+        fixture
+            .send(fixture.pool.claim_rewards(&owner, 0), &user)
+            .await,
+        StakingError::InsufficientRewardFunds,
+    );
+
+    fixture.fund_reward(100 * TOKEN).await;
+    fixture
+        .send(fixture.pool.redeem(&owner, 0, false), &user)
+        .await
+        .unwrap();
+    assert_eq!(fixture.balance(&owner).await, STAKER_TOKENS + 100 * TOKEN);
+}
+
+#[tokio::test]
+async fn reward_duration_is_bounded() {
+    let mut fixture = Fixture::new(100, 0, RewardMode::Apy).await;
+    let user = fixture.create_staker().await;
+
+    // more than 100 years at a 100% reward rate
+    fixture.stake(&user, 0, 1_000 * TOKEN, 0).await;
+    fixture.env.warp_forward(101 * YEAR).await;
+    assert_staking_error(
+        fixture
+            .send(fixture.pool.claim_rewards(&user.pubkey(), 0), &user)
+            .await,
+        StakingError::CalculationError,
+    );
+}
+
+#[tokio::test]
+async fn compound_with_cranker_tip() {
+    let mut fixture = Fixture::new(10, 0, RewardMode::Apy).await;
+    let user = fixture.create_staker().await;
+    let owner = user.pubkey();
+    let cranker = fixture.env.create_user().await;
+    fixture
+        .env
+        .mint_tokens(
+            &fixture.pool.mint,
+            &cranker.pubkey(),
+            &fixture.pool.token_program,
+            0,
+        )
+        .await;
+    fixture.fund_reward(1_000 * TOKEN).await;
+
+    fixture.stake(&user, 0, 1_000 * TOKEN, 0).await;
+    fixture.env.warp_forward(YEAR).await;
+    assert_staking_error(
+        fixture
+            .send(
+                fixture.pool.compound(&cranker.pubkey(), &owner, 0),
+                &cranker,
+            )
+            .await,
+        StakingError::AutoCompoundDisabled,
+    );
+
+    assert_staking_error(
+        fixture
+            .admin_send(
+                fixture
+                    .pool
+                    .set_compound_tip(&fixture.admin.pubkey(), MAX_COMPOUND_TIP_BPS + 1),
+            )
+            .await,
+        StakingError::InvalidArgument,
+    );
+    let events: Vec<CompoundTipSet> = fixture
+        .env
+        .send_for_events(
+            &[fixture.pool.set_compound_tip(&fixture.admin.pubkey(), 500)],
+            &[&fixture.admin],
+        )
+        .await
+        .unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].compound_tip_bps, 500);
+    let events: Vec<AutoCompoundSet> = fixture
+        .env
+        .send_for_events(&[fixture.pool.set_auto_compound(&owner, 0, true)], &[&user])
+        .await
+        .unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].user, owner);
+    assert_eq!(events[0].user_stake, fixture.pool.user_stake(&owner, 0));
+    assert!(events[0].enabled);
+
+    // 5% of the reward goes to the cranker, the rest is added to the principal
+    fixture
+        .send(
+            fixture.pool.compound(&cranker.pubkey(), &owner, 0),
+            &cranker,
+        )
+        .await
+        .unwrap();
+    assert_eq!(fixture.balance(&cranker.pubkey()).await, 5 * TOKEN);
+    let position = fixture.user_stake(&owner, 0).await;
+    assert_eq!(position.amount, 1_095 * TOKEN);
+    assert_eq!(position.rewards_claimed, 100 * TOKEN);
+
+    assert_staking_error(
+        fixture
+            .send(fixture.pool.compound(&owner, &owner, 0), &user)
+            .await,
+        StakingError::ZeroValueError,
+    );
+
+    // the owner compounds without tip
+    fixture.env.warp_forward(YEAR).await;
+    fixture
+        .send(fixture.pool.compound(&owner, &owner, 0), &user)
+        .await
+        .unwrap();
+    assert_eq!(
+        fixture.user_stake(&owner, 0).await.amount,
+        1_095 * TOKEN + apy_reward(1_095 * TOKEN, 10, 10_000, YEAR)
+    );
+}
+
+////////////////////////////////////////////////////////////////////////////////
+//////////////////////////////// LOCKS AND EXITS ////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+#[tokio::test]
+async fn lockup_tiers() {
+    let mut fixture = Fixture::new(10, 0, RewardMode::Apy).await;
+    let admin = fixture.admin.pubkey();
+    let user = fixture.create_staker().await;
+    let owner = user.pubkey();
+    fixture.fund_reward(1_000 * TOKEN).await;
+
+    assert_staking_error(
+        fixture
+            .admin_send(fixture.pool.set_lockup_tiers(&admin, vec![]))
+            .await,
+        StakingError::InvalidTier,
+    );
+    assert_staking_error(
+        fixture
+            .admin_send(fixture.pool.set_lockup_tiers(
+                &admin,
+                vec![LockupTier {
+                    lock_duration: 0,
+                    reward_multiplier_bps: 0,
+                }],
+            ))
+            .await,
+        StakingError::InvalidTier,
+    );
+    let tiers = vec![
+        LockupTier {
+            lock_duration: 0,
+            reward_multiplier_bps: 10_000,
+        },
+        LockupTier {
+            lock_duration: 30 * DAY,
+            reward_multiplier_bps: 20_000,
+        },
+    ];
+    assert_staking_error(
+        fixture
+            .send(fixture.pool.set_lockup_tiers(&owner, tiers.clone()), &user)
+            .await,
+        StakingError::AdminOnly,
+    );
+    fixture
+        .admin_send(fixture.pool.set_lockup_tiers(&admin, tiers))
+        .await
+        .unwrap();
+
+    assert_staking_error(
+        fixture
+            .send(fixture.pool.stake(&owner, 0, 1_000 * TOKEN, 2), &user)
+            .await,
+        StakingError::InvalidTier,
+    );
+    fixture.stake(&user, 0, 1_000 * TOKEN, 1).await;
+    let position = fixture.user_stake(&owner, 0).await;
+    assert_eq!(position.tier, 1);
+    assert_eq!(position.lock_duration, 30 * DAY);
+    assert_eq!(position.reward_multiplier_bps, 20_000);
+
+    assert_staking_error(
+        fixture
+            .send(fixture.pool.redeem(&owner, 0, false), &user)
+            .await,
+        StakingError::StakingDurationNotMet,
+    );
+    fixture.env.warp_forward(30 * DAY).await;
+    fixture
+        .send(fixture.pool.redeem(&owner, 0, false), &user)
+        .await
+        .unwrap();
+    assert_eq!(
+        fixture.balance(&owner).await,
+        STAKER_TOKENS + apy_reward(1_000 * TOKEN, 10, 20_000, 30 * DAY)
+    );
+}
+
+#[tokio::test]
+async fn early_withdrawal_penalties() {
+    let mut fixture = Fixture::new(10, DAY, RewardMode::Apy).await;
+    let admin = fixture.admin.pubkey();
+    let user = fixture.create_staker().await;
+    let owner = user.pubkey();
+    let stake = 1_000 * TOKEN;
+
+    // the rewards are forfeited by default
+    fixture.stake(&user, 0, stake, 0).await;
+    fixture.env.warp_forward(DAY / 2).await;
+    assert_staking_error(
+        fixture
+            .send(fixture.pool.redeem(&owner, 0, false), &user)
+            .await,
+        StakingError::StakingDurationNotMet,
+    );
+    fixture
+        .send(fixture.pool.redeem(&owner, 0, true), &user)
+        .await
+        .unwrap();
+    assert_eq!(fixture.balance(&owner).await, STAKER_TOKENS);
+
+    assert_staking_error(
+        fixture
+            .admin_send(fixture.pool.set_penalty_policy(
+                &admin,
+                PenaltyPolicy::FlatPrincipal,
+                BPS_DENOMINATOR as u16 + 1,
+                PenaltyDestination::RewardPool,
+                None,
+            ))
+            .await,
+        StakingError::InvalidArgument,
+    );
+    assert_staking_error(
+        fixture
+            .admin_send(fixture.pool.set_penalty_policy(
+                &admin,
+                PenaltyPolicy::FlatPrincipal,
+                1_000,
+                PenaltyDestination::Treasury,
+                None,
+            ))
+            .await,
+        StakingError::InvalidArgument,
+    );
+
+    // 10% of the principal funds the rewards
+    fixture
+        .admin_send(fixture.pool.set_penalty_policy(
+            &admin,
+            PenaltyPolicy::FlatPrincipal,
+            1_000,
+            PenaltyDestination::RewardPool,
+            None,
+        ))
+        .await
+        .unwrap();
+    fixture.stake(&user, 1, stake, 0).await;
+    fixture
+        .send(fixture.pool.redeem(&owner, 1, true), &user)
+        .await
+        .unwrap();
+    assert_eq!(fixture.balance(&owner).await, STAKER_TOKENS - 100 * TOKEN);
+    assert_eq!(
+        fixture.staking_account().await.admin_reward_amount,
+        100 * TOKEN
+    );
+
+    // 10% of the principal goes to the treasury
+    let treasury = fixture.pool.token_account(&admin);
+    fixture
+        .admin_send(fixture.pool.set_penalty_policy(
+            &admin,
+            PenaltyPolicy::FlatPrincipal,
+            1_000,
+            PenaltyDestination::Treasury,
+            Some(treasury),
+        ))
+        .await
+        .unwrap();
+    fixture.pool.treasury = Some(treasury);
+    let treasury_balance = fixture.env.token_balance(&treasury).await;
+    fixture.stake(&user, 2, stake, 0).await;
+    fixture
+        .send(fixture.pool.redeem(&owner, 2, true), &user)
+        .await
+        .unwrap();
+    assert_eq!(fixture.balance(&owner).await, STAKER_TOKENS - 200 * TOKEN);
+    assert_eq!(
+        fixture.env.token_balance(&treasury).await - treasury_balance,
+        100 * TOKEN
+    );
+
+    // without the treasury account (e.g. closed), the penalty funds the rewards instead of blocking the exit
+    let other_user = fixture.create_staker().await;
+    fixture.stake(&other_user, 0, stake, 0).await;
+    let reward_funds = fixture.staking_account().await.admin_reward_amount;
+    fixture.pool.treasury = None;
+    fixture
+        .send(
+            fixture.pool.redeem(&other_user.pubkey(), 0, true),
+            &other_user,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        fixture.balance(&other_user.pubkey()).await,
+        STAKER_TOKENS - 100 * TOKEN
+    );
+    assert_eq!(
+        fixture.staking_account().await.admin_reward_amount,
+        reward_funds + 100 * TOKEN
+    );
+
+    // the treasury must hold the staked token
+    let token_program = fixture.pool.token_program;
+    let other_mint = fixture.env.create_mint(&token_program).await;
+    let other_treasury = fixture
+        .env
+        .mint_tokens(&other_mint, &admin, &token_program, TOKEN)
+        .await;
+    assert_staking_error(
+        fixture
+            .admin_send(fixture.pool.set_penalty_policy(
+                &admin,
+                PenaltyPolicy::FlatPrincipal,
+                1_000,
+                PenaltyDestination::Treasury,
+                Some(other_treasury),
+            ))
+            .await,
+        StakingError::InvalidArgument,
+    );
+
+    // 10% of the principal is burnt
+    fixture
+        .admin_send(fixture.pool.set_penalty_policy(
+            &admin,
+            PenaltyPolicy::FlatPrincipal,
+            1_000,
+            PenaltyDestination::Burn,
+            None,
+        ))
+        .await
+        .unwrap();
+    fixture.pool.treasury = None;
+    let supply = fixture.env.mint(&fixture.pool.mint).await.supply;
+    fixture.stake(&user, 3, stake, 0).await;
+    fixture
+        .send(fixture.pool.redeem(&owner, 3, true), &user)
+        .await
+        .unwrap();
+    assert_eq!(fixture.balance(&owner).await, STAKER_TOKENS - 300 * TOKEN);
+    assert_eq!(
+        fixture.env.mint(&fixture.pool.mint).await.supply,
+        supply - 100 * TOKEN
+    );
+
+    // the penalty decays with the elapsed part of the lock, the rewards are paid
+    fixture
+        .admin_send(fixture.pool.set_penalty_policy(
+            &admin,
+            PenaltyPolicy::LinearDecay,
+            1_000,
+            PenaltyDestination::RewardPool,
+            None,
+        ))
+        .await
+        .unwrap();
+    let balance = fixture.balance(&owner).await;
+    fixture.stake(&user, 4, stake, 0).await;
+    fixture.env.warp_forward(DAY / 2).await;
+    fixture
+        .send(fixture.pool.redeem(&owner, 4, true), &user)
+        .await
+        .unwrap();
+    let reward = apy_reward(stake, 10, 10_000, DAY / 2);
+    assert_eq!(fixture.balance(&owner).await, balance - 50 * TOKEN + reward);
+
+    // the matured part of the rewards is paid, minus the penalty rate
+    fixture
+        .admin_send(fixture.pool.set_penalty_policy(
+            &admin,
+            PenaltyPolicy::ProRatedReward,
+            5_000,
+            PenaltyDestination::RewardPool,
+            None,
+        ))
+        .await
+        .unwrap();
+    let balance = fixture.balance(&owner).await;
+    fixture.stake(&user, 5, stake, 0).await;
+    fixture.env.warp_forward(DAY / 2).await;
+    fixture
+        .send(fixture.pool.redeem(&owner, 5, true), &user)
+        .await
+        .unwrap();
+    let matured_reward = reward * (DAY / 2) as u64 / DAY as u64;
+    assert_eq!(fixture.balance(&owner).await, balance + matured_reward / 2);
+}
+
+#[tokio::test]
+async fn partial_unstake() {
+    let mut fixture = Fixture::new(0, DAY, RewardMode::Apy).await;
+    let user = fixture.create_staker().await;
+    let owner = user.pubkey();
+
+    fixture.stake(&user, 0, 1_000 * TOKEN, 0).await;
+    assert_staking_error(
+        fixture
+            .send(fixture.pool.partial_unstake(&owner, 0, 0), &user)
+            .await,
+        StakingError::ZeroValueError,
+    );
+    // the whole position is withdrawn with redeem
+    assert_staking_error(
+        fixture
+            .send(
+                fixture.pool.partial_unstake(&owner, 0, 1_000 * TOKEN),
+                &user,
+            )
+            .await,
+        StakingError::InvalidArgument,
+    );
+    assert_staking_error(
+        fixture
+            .send(fixture.pool.partial_unstake(&owner, 0, 100 * TOKEN), &user)
+            .await,
+        StakingError::StakingDurationNotMet,
+    );
+
+    fixture.env.warp_forward(DAY).await;
+    let events: Vec<PartiallyUnstaked> = fixture
+        .env
+        .send_for_events(
+            &[fixture.pool.partial_unstake(&owner, 0, 100 * TOKEN)],
+            &[&user],
+        )
+        .await
+        .unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].user, owner);
+    assert_eq!(events[0].user_stake, fixture.pool.user_stake(&owner, 0));
+    assert_eq!(events[0].amount, 100 * TOKEN);
+    assert_eq!(events[0].reward, 0);
+    assert_eq!(events[0].stream_rewards, [0; MAX_REWARD_STREAMS]);
+    assert_eq!(events[0].remaining_amount, 900 * TOKEN);
+    assert_eq!(events[0].total_staked, 900 * TOKEN);
+    assert_eq!(fixture.balance(&owner).await, STAKER_TOKENS - 900 * TOKEN);
+    assert_eq!(fixture.user_stake(&owner, 0).await.amount, 900 * TOKEN);
+    assert_eq!(fixture.staking_account().await.total_staked, 900 * TOKEN);
+}
+
+#[tokio::test]
+async fn unbonding_cooldown() {
+    let mut fixture = Fixture::new(0, 0, RewardMode::Apy).await;
+    let admin = fixture.admin.pubkey();
+    let user = fixture.create_staker().await;
+    let owner = user.pubkey();
+
+    fixture.stake(&user, 0, 1_000 * TOKEN, 0).await;
+    for unbonding_cooldown in [-1, MAX_UNBONDING_COOLDOWN + 1] {
+        let config = fixture.config_with_cooldown(unbonding_cooldown).await;
+        assert_staking_error(
+            fixture
+                .admin_send(fixture.pool.propose_config_update(&admin, config))
+                .await,
+            StakingError::InvalidArgument,
+        );
+    }
+    let config = fixture.config_with_cooldown(7 * DAY).await;
+    fixture.update_config(config).await.unwrap();
+
+    assert_staking_error(
+        fixture
+            .send(fixture.pool.redeem(&owner, 0, false), &user)
+            .await,
+        StakingError::UnbondingRequired,
+    );
+    assert_staking_error(
+        fixture
+            .send(fixture.pool.partial_unstake(&owner, 0, 100 * TOKEN), &user)
+            .await,
+        StakingError::UnbondingRequired,
+    );
+    assert_staking_error(
+        fixture
+            .send(fixture.pool.withdraw_unbonded(&owner, 0), &user)
+            .await,
+        StakingError::NothingUnbonding,
+    );
+    assert_staking_error(
+        fixture
+            .send(fixture.pool.request_unstake(&owner, 0, 0), &user)
+            .await,
+        StakingError::ZeroValueError,
+    );
+    assert_staking_error(
+        fixture
+            .send(
+                fixture.pool.request_unstake(&owner, 0, 2_000 * TOKEN),
+                &user,
+            )
+            .await,
+        StakingError::InvalidArgument,
+    );
+
+    let now = fixture.env.now().await;
+    fixture
+        .send(fixture.pool.request_unstake(&owner, 0, 400 * TOKEN), &user)
+        .await
+        .unwrap();
+    let position = fixture.user_stake(&owner, 0).await;
+    assert_eq!(position.amount, 600 * TOKEN);
+    assert_eq!(position.unbonding_amount, 400 * TOKEN);
+    assert_eq!(position.unlock_at, now + 7 * DAY);
+    let pool = fixture.staking_account().await;
+    assert_eq!(pool.total_staked, 600 * TOKEN);
+    assert_eq!(pool.total_unbonding, 400 * TOKEN);
+
+    assert_staking_error(
+        fixture
+            .send(fixture.pool.withdraw_unbonded(&owner, 0), &user)
+            .await,
+        StakingError::CooldownNotMet,
+    );
+
+    // a pending request keeps the cooldown it captured when the cooldown of the pool changes,
+    // and redeem closes the position, so the unbonding amount must be withdrawn first
+    let config = fixture.config_with_cooldown(0).await;
+    fixture.update_config(config).await.unwrap();
+    fixture
+        .send(fixture.pool.request_unstake(&owner, 0, 100 * TOKEN), &user)
+        .await
+        .unwrap();
+    let position = fixture.user_stake(&owner, 0).await;
+    assert_eq!(position.unbonding_cooldown, 7 * DAY);
+    assert_eq!(position.unlock_at, fixture.env.now().await + 7 * DAY);
+    assert_staking_error(
+        fixture
+            .send(fixture.pool.redeem(&owner, 0, false), &user)
+            .await,
+        StakingError::UnbondingPending,
+    );
+
+    fixture.env.warp_forward(7 * DAY).await;
+    fixture
+        .send(fixture.pool.withdraw_unbonded(&owner, 0), &user)
+        .await
+        .unwrap();
+    assert_eq!(fixture.balance(&owner).await, STAKER_TOKENS - 500 * TOKEN);
+    assert_eq!(fixture.user_stake(&owner, 0).await.amount, 500 * TOKEN);
+
+    // the emptied position is closed once its last unbonding amount is withdrawn
+    fixture
+        .send(fixture.pool.request_unstake(&owner, 0, 500 * TOKEN), &user)
+        .await
+        .unwrap();
+    assert_staking_error(
+        fixture
+            .send(fixture.pool.claim_rewards(&owner, 0), &user)
+            .await,
+        StakingError::NothingStaked,
+    );
+    fixture
+        .send(fixture.pool.withdraw_unbonded(&owner, 0), &user)
+        .await
+        .unwrap();
+    assert_eq!(fixture.balance(&owner).await, STAKER_TOKENS);
+    assert!(fixture
+        .env
+        .account(&fixture.pool.user_stake(&owner, 0))
+        .await
+        .is_none());
+}
+
+#[tokio::test]
+async fn preview_reward() {
+    let mut fixture = Fixture::new(10, YEAR, RewardMode::Apy).await;
+    let admin = fixture.admin.pubkey();
+    let user = fixture.create_staker().await;
+    let owner = user.pubkey();
+    fixture.fund_reward(40 * TOKEN).await;
+    fixture
+        .admin_send(fixture.pool.set_penalty_policy(
+            &admin,
+            PenaltyPolicy::LinearDecay,
+            1_000,
+            PenaltyDestination::RewardPool,
+            None,
+        ))
+        .await
+        .unwrap();
+
+    // half way through the lock, the reward is only partly funded
+    fixture.stake(&user, 0, 1_000 * TOKEN, 0).await;
+    let start_time = fixture.user_stake(&owner, 0).await.start_time;
+    fixture.env.warp_forward(YEAR / 2).await;
+    let preview: RewardPreview = fixture
+        .env
+        .view(fixture.pool.preview_reward(&owner, 0))
+        .await;
+    assert_eq!(preview.timestamp, fixture.env.now().await);
+    assert_eq!(
+        preview.reward,
+        apy_reward(1_000 * TOKEN, 10, 10_000, YEAR / 2)
+    );
+    assert!(!preview.funded);
+    assert_eq!(preview.stream_rewards, [0; MAX_REWARD_STREAMS]);
+    assert_eq!(preview.unlock_time, start_time + YEAR);
+    assert!(!preview.matured);
+    assert_eq!(preview.forced_reward, 40 * TOKEN);
+    assert_eq!(preview.penalty, 50 * TOKEN);
+
+    // nothing is settled by the preview
+    assert_eq!(
+        fixture.user_stake(&owner, 0).await.last_reward_time,
+        start_time
+    );
+    assert_eq!(fixture.staking_account().await.reward_liability, 0);
+
+    // the forced redeem pays out what was previewed
+    fixture
+        .send(fixture.pool.redeem(&owner, 0, true), &user)
+        .await
+        .unwrap();
+    assert_eq!(
+        fixture.balance(&owner).await,
+        STAKER_TOKENS - preview.penalty + preview.forced_reward
+    );
+
+    // once matured, there is no penalty and the claim pays out the previewed reward
+    fixture.fund_reward(100 * TOKEN).await;
+    fixture.stake(&user, 1, 1_000 * TOKEN, 0).await;
+    fixture.env.warp_forward(YEAR).await;
+    let preview: RewardPreview = fixture
+        .env
+        .view(fixture.pool.preview_reward(&owner, 1))
+        .await;
+    assert_eq!(preview.reward, 100 * TOKEN);
+    assert!(preview.funded);
+    assert!(preview.matured);
+    assert_eq!(preview.forced_reward, preview.reward);
+    assert_eq!(preview.penalty, 0);
+
+    let balance = fixture.balance(&owner).await;
+    fixture
+        .send(fixture.pool.claim_rewards(&owner, 1), &user)
+        .await
+        .unwrap();
+    assert_eq!(fixture.balance(&owner).await, balance + preview.reward);
+}
+
+////////////////////////////////////////////////////////////////////////////////
+///////////////////////////////// GOVERNANCE /////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+#[tokio::test]
+async fn config_update_timelock() {
+    let mut fixture = Fixture::new(10, 0, RewardMode::Apy).await;
+    let admin = fixture.admin.pubkey();
+    let user = fixture.create_staker().await;
+    let config = PoolConfig {
+        reward_rate: 20,
+        min_staking_duration: DAY,
+        config_timelock: 3_600,
+        unbonding_cooldown: DAY,
+    };
+
+    assert_staking_error(
+        fixture
+            .admin_send(fixture.pool.apply_config_update(&admin))
+            .await,
+        StakingError::NoPendingConfigUpdate,
+    );
+    assert_staking_error(
+        fixture
+            .send(
+                fixture.pool.propose_config_update(&user.pubkey(), config),
+                &user,
+            )
+            .await,
+        StakingError::AdminOnly,
+    );
+    assert_staking_error(
+        fixture
+            .admin_send(fixture.pool.propose_config_update(
+                &admin,
+                PoolConfig {
+                    reward_rate: REWARD_RATE_DENOMINATOR as u8 + 1,
+                    ..config
+                },
+            ))
+            .await,
+        StakingError::InvalidArgument,
+    );
+
+    fixture
+        .admin_send(fixture.pool.propose_config_update(&admin, config))
+        .await
+        .unwrap();
+    assert_staking_error(
+        fixture
+            .admin_send(fixture.pool.apply_config_update(&admin))
+            .await,
+        StakingError::TimelockNotElapsed,
+    );
+
+    fixture.env.warp_forward(DEFAULT_CONFIG_TIMELOCK).await;
+    fixture
+        .admin_send(fixture.pool.apply_config_update(&admin))
+        .await
+        .unwrap();
+    let pool = fixture.staking_account().await;
+    assert_eq!(pool.reward_rate, 20);
+    assert_eq!(pool.min_staking_duration, DAY);
+    assert_eq!(pool.config_timelock, 3_600);
+    assert_eq!(pool.unbonding_cooldown, DAY);
+    assert_eq!(pool.config_update_eta, 0);
+}
+
+#[tokio::test]
+async fn config_update_keeps_accrued_rate() {
+    let mut fixture = Fixture::new(10, 0, RewardMode::Apy).await;
+    let admin = fixture.admin.pubkey();
+    let user = fixture.create_staker().await;
+    let owner = user.pubkey();
+    let amount = 1_000 * TOKEN;
+
+    fixture.fund_reward(1_000 * TOKEN).await;
+    fixture.stake(&user, 0, amount, 0).await;
+    let config = PoolConfig {
+        reward_rate: 20,
+        min_staking_duration: 0,
+        config_timelock: DEFAULT_CONFIG_TIMELOCK,
+        unbonding_cooldown: 0,
+    };
+    fixture.update_config(config).await.unwrap();
+
+    // the day before the update accrued at the old rate, even though it is settled afterwards
+    let accrued = apy_reward(amount, 10, 10_000, DEFAULT_CONFIG_TIMELOCK);
+    let preview: RewardPreview = fixture
+        .env
+        .view(fixture.pool.preview_reward(&owner, 0))
+        .await;
+    assert_eq!(preview.reward, accrued);
+    assert_staking_error(
+        fixture
+            .admin_send(
+                fixture
+                    .pool
+                    .withdraw_rewards(&admin, 1_000 * TOKEN - accrued + 1),
+            )
+            .await,
+        StakingError::RewardFundsAllocated,
+    );
+
+    fixture.env.warp_forward(YEAR).await;
+    fixture
+        .send(fixture.pool.claim_rewards(&owner, 0), &user)
+        .await
+        .unwrap();
+    assert_eq!(
+        fixture.balance(&owner).await,
+        STAKER_TOKENS - amount + accrued + apy_reward(amount, 20, 10_000, YEAR)
+    );
+}
+
+#[tokio::test]
+async fn admin_transfer() {
+    let mut fixture = Fixture::new(10, 0, RewardMode::Apy).await;
+    let admin = fixture.admin.pubkey();
+    let new_admin = fixture.env.create_user().await;
+    let other = fixture.env.create_user().await;
+
+    assert_staking_error(
+        fixture
+            .send(fixture.pool.accept_admin(&new_admin.pubkey()), &new_admin)
+            .await,
+        StakingError::NotPendingAdmin,
+    );
+    assert_staking_error(
+        fixture
+            .send(
+                fixture.pool.propose_admin(&other.pubkey(), &other.pubkey()),
+                &other,
+            )
+            .await,
+        StakingError::AdminOnly,
+    );
+
+    fixture
+        .admin_send(fixture.pool.propose_admin(&admin, &new_admin.pubkey()))
+        .await
+        .unwrap();
+    assert_staking_error(
+        fixture
+            .send(fixture.pool.accept_admin(&other.pubkey()), &other)
+            .await,
+        StakingError::NotPendingAdmin,
+    );
+    let events: Vec<AdminAccepted> = fixture
+        .env
+        .send_for_events(
+            &[fixture.pool.accept_admin(&new_admin.pubkey())],
+            &[&new_admin],
+        )
+        .await
+        .unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].previous_admin, admin);
+    assert_eq!(events[0].admin, new_admin.pubkey());
+    let pool = fixture.staking_account().await;
+    assert_eq!(pool.admin, new_admin.pubkey());
+    assert_eq!(pool.pending_admin, Pubkey::default());
+    // the pool keeps its address
+    assert_eq!(pool.creator, admin);
+
+    assert_staking_error(
+        fixture
+            .admin_send(fixture.pool.set_guardian(&admin, &other.pubkey()))
+            .await,
+        StakingError::AdminOnly,
+    );
+
+    fixture
+        .send(fixture.pool.renounce_admin(&new_admin.pubkey()), &new_admin)
+        .await
+        .unwrap();
+    assert_eq!(fixture.staking_account().await.admin, Pubkey::default());
+    assert_staking_error(
+        fixture
+            .send(
+                fixture
+                    .pool
+                    .set_guardian(&new_admin.pubkey(), &other.pubkey()),
+                &new_admin,
+            )
+            .await,
+        StakingError::AdminOnly,
+    );
+}
+
+#[tokio::test]
+async fn guardian_and_emergency_withdraw() {
+    let mut fixture = Fixture::new(0, 0, RewardMode::Apy).await;
+    let admin = fixture.admin.pubkey();
+    let guardian = fixture.env.create_user().await;
+    let user = fixture.create_staker().await;
+    let owner = user.pubkey();
+
+    fixture.stake(&user, 0, 1_000 * TOKEN, 0).await;
+    assert_staking_error(
+        fixture
+            .send(fixture.pool.emergency_withdraw(&owner, 0), &user)
+            .await,
+        StakingError::PoolNotInEmergency,
+    );
+    assert_staking_error(
+        fixture
+            .send(
+                fixture
+                    .pool
+                    .set_pool_state(&guardian.pubkey(), PoolState::Paused),
+                &guardian,
+            )
+            .await,
+        StakingError::AdminOnly,
+    );
+
+    let events: Vec<GuardianSet> = fixture
+        .env
+        .send_for_events(
+            &[fixture.pool.set_guardian(&admin, &guardian.pubkey())],
+            &[&fixture.admin],
+        )
+        .await
+        .unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].guardian, guardian.pubkey());
+    fixture
+        .send(
+            fixture
+                .pool
+                .set_pool_state(&guardian.pubkey(), PoolState::Paused),
+            &guardian,
+        )
+        .await
+        .unwrap();
+    assert_staking_error(
+        fixture
+            .send(fixture.pool.stake(&owner, 1, 1_000 * TOKEN, 0), &user)
+            .await,
+        StakingError::PoolNotActive,
+    );
+    assert_staking_error(
+        fixture
+            .send(fixture.pool.increase_stake(&owner, 0, TOKEN), &user)
+            .await,
+        StakingError::PoolNotActive,
+    );
+    // only the admin can resume the pool
+    assert_staking_error(
+        fixture
+            .send(
+                fixture
+                    .pool
+                    .set_pool_state(&guardian.pubkey(), PoolState::Active),
+                &guardian,
+            )
+            .await,
+        StakingError::AdminOnly,
+    );
+    // a shutdown is only started by begin_shutdown
+    assert_staking_error(
+        fixture
+            .admin_send(fixture.pool.set_pool_state(&admin, PoolState::ShuttingDown))
+            .await,
+        StakingError::InvalidArgument,
+    );
+
+    fixture
+        .send(
+            fixture
+                .pool
+                .set_pool_state(&guardian.pubkey(), PoolState::Emergency),
+            &guardian,
+        )
+        .await
+        .unwrap();
+    fixture
+        .send(fixture.pool.emergency_withdraw(&owner, 0), &user)
+        .await
+        .unwrap();
+    assert_eq!(fixture.balance(&owner).await, STAKER_TOKENS);
+    assert!(fixture
+        .env
+        .account(&fixture.pool.user_stake(&owner, 0))
+        .await
+        .is_none());
+    assert_eq!(fixture.staking_account().await.total_staked, 0);
+
+    fixture
+        .admin_send(fixture.pool.set_pool_state(&admin, PoolState::Active))
+        .await
+        .unwrap();
+    assert!(fixture.staking_account().await.pool_state == PoolState::Active);
+}
+
+#[tokio::test]
+async fn shutdown_and_close_pool() {
+    let mut fixture = Fixture::new(10, 365 * DAY, RewardMode::Apy).await;
+    let admin = fixture.admin.pubkey();
+    let user = fixture.create_staker().await;
+    let owner = user.pubkey();
+    let cranker = fixture.env.create_user().await;
+    fixture.fund_reward(1_000 * TOKEN).await;
+
+    fixture.stake(&user, 0, 1_000 * TOKEN, 0).await;
+    fixture.env.warp_forward(30 * DAY).await;
+
+    assert_staking_error(
+        fixture
+            .send(
+                fixture.pool.return_position(&cranker.pubkey(), &owner, 0),
+                &cranker,
+            )
+            .await,
+        StakingError::PoolNotShuttingDown,
+    );
+    assert_staking_error(
+        fixture.admin_send(fixture.pool.close_pool(&admin)).await,
+        StakingError::PoolNotShuttingDown,
+    );
+    assert_staking_error(
+        fixture
+            .admin_send(fixture.pool.begin_shutdown(&admin, -1))
+            .await,
+        StakingError::InvalidArgument,
+    );
+
+    fixture
+        .admin_send(fixture.pool.begin_shutdown(&admin, 7 * DAY))
+        .await
+        .unwrap();
+    assert!(fixture.staking_account().await.pool_state == PoolState::ShuttingDown);
+    assert_staking_error(
+        fixture
+            .admin_send(fixture.pool.begin_shutdown(&admin, DAY))
+            .await,
+        StakingError::PoolShuttingDown,
+    );
+    assert_staking_error(
+        fixture
+            .admin_send(fixture.pool.set_pool_state(&admin, PoolState::Active))
+            .await,
+        StakingError::PoolShuttingDown,
+    );
+    assert_staking_error(
+        fixture
+            .send(fixture.pool.stake(&owner, 1, 1_000 * TOKEN, 0), &user)
+            .await,
+        StakingError::PoolNotActive,
+    );
+    assert_staking_error(
+        fixture
+            .send(
+                fixture.pool.return_position(&cranker.pubkey(), &owner, 0),
+                &cranker,
+            )
+            .await,
+        StakingError::GracePeriodNotElapsed,
+    );
+    assert_staking_error(
+        fixture.admin_send(fixture.pool.close_pool(&admin)).await,
+        StakingError::OutstandingStake,
+    );
+
+    // rewards stop accruing at the shutdown, the lock is lifted
+    fixture.env.warp_forward(7 * DAY).await;
+    fixture
+        .send(
+            fixture.pool.return_position(&cranker.pubkey(), &owner, 0),
+            &cranker,
+        )
+        .await
+        .unwrap();
+    let reward = apy_reward(1_000 * TOKEN, 10, 10_000, 30 * DAY);
+    assert_eq!(fixture.balance(&owner).await, STAKER_TOKENS + reward);
+
+    let balance = fixture.balance(&admin).await;
+    fixture
+        .admin_send(fixture.pool.close_pool(&admin))
+        .await
+        .unwrap();
+    assert_eq!(
+        fixture.balance(&admin).await - balance,
+        1_000 * TOKEN - reward
+    );
+    assert!(fixture.env.account(&fixture.pool.address).await.is_none());
+    assert!(fixture.env.account(&fixture.pool.vault()).await.is_none());
+}
+
+////////////////////////////////////////////////////////////////////////////////
+//////////////////////////////// REWARD TOKENS ////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+#[tokio::test]
+async fn separate_reward_mint() {
+    let mut fixture = Fixture::new(10, 0, RewardMode::Apy).await;
+    let admin = fixture.admin.pubkey();
+    let user = fixture.create_staker().await;
+    let owner = user.pubkey();
+    let token_program = fixture.pool.token_program;
+    let reward_mint = fixture.env.create_mint(&token_program).await;
+    fixture
+        .env
+        .mint_tokens(&reward_mint, &admin, &token_program, ADMIN_TOKENS)
+        .await;
+    fixture
+        .env
+        .mint_tokens(&reward_mint, &owner, &token_program, 0)
+        .await;
+
+    assert_staking_error(
+        fixture
+            .send(fixture.pool.set_reward_mint(&owner, &reward_mint), &user)
+            .await,
+        StakingError::AdminOnly,
+    );
+    assert_staking_error(
+        fixture
+            .admin_send(fixture.pool.set_reward_mint(&admin, &fixture.pool.mint))
+            .await,
+        StakingError::InvalidArgument,
+    );
+    let events: Vec<RewardMintSet> = fixture
+        .env
+        .send_for_events(
+            &[fixture.pool.set_reward_mint(&admin, &reward_mint)],
+            &[&fixture.admin],
+        )
+        .await
+        .unwrap();
+    fixture.pool.reward_mint = reward_mint;
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].reward_mint, reward_mint);
+    assert_eq!(events[0].reward_vault, fixture.pool.reward_vault());
+    assert_eq!(fixture.staking_account().await.reward_mint, reward_mint);
+
+    // the rewards are held by the reward vault
+    fixture.fund_reward(1_000 * TOKEN).await;
+    assert_eq!(
+        fixture
+            .env
+            .token_balance(&fixture.pool.reward_vault())
+            .await,
+        1_000 * TOKEN
+    );
+    assert_eq!(fixture.env.token_balance(&fixture.pool.vault()).await, 0);
+
+    fixture.stake(&user, 0, 1_000 * TOKEN, 0).await;
+    fixture.env.warp_forward(YEAR).await;
+    fixture
+        .send(fixture.pool.claim_rewards(&owner, 0), &user)
+        .await
+        .unwrap();
+    let user_reward_account = fixture.pool.reward_token_account(&owner);
+    assert_eq!(
+        fixture.env.token_balance(&user_reward_account).await,
+        100 * TOKEN
+    );
+    assert_eq!(fixture.balance(&owner).await, STAKER_TOKENS - 1_000 * TOKEN);
+
+    // rewards in another token can not be added to the principal
+    assert_staking_error(
+        fixture
+            .send(fixture.pool.compound(&owner, &owner, 0), &user)
+            .await,
+        StakingError::SeparateRewardMint,
+    );
+
+    fixture
+        .send(fixture.pool.redeem(&owner, 0, false), &user)
+        .await
+        .unwrap();
+    assert_eq!(fixture.balance(&owner).await, STAKER_TOKENS);
+
+    let admin_reward_account = fixture.pool.reward_token_account(&admin);
+    let balance = fixture.env.token_balance(&admin_reward_account).await;
+    fixture
+        .admin_send(fixture.pool.withdraw_rewards(&admin, 900 * TOKEN))
+        .await
+        .unwrap();
+    assert_eq!(
+        fixture.env.token_balance(&admin_reward_account).await - balance,
+        900 * TOKEN
+    );
+
+    // the reward mint can only be changed before the first stake
+    let other_pool = fixture.create_pool(1, 10, 0, RewardMode::Apy).await;
+    fixture
+        .send(other_pool.stake(&owner, 0, 1_000 * TOKEN, 0), &user)
+        .await
+        .unwrap();
+    assert_staking_error(
+        fixture
+            .admin_send(other_pool.set_reward_mint(&admin, &reward_mint))
+            .await,
+        StakingError::OutstandingStake,
+    );
+}
+
+#[tokio::test]
+async fn reward_streams() {
+    let mut fixture = Fixture::new(0, 0, RewardMode::Apy).await;
+    let admin = fixture.admin.pubkey();
+    let user = fixture.create_staker().await;
+    let owner = user.pubkey();
+    let token_program = fixture.pool.token_program;
+    let stream_mint = fixture.env.create_mint(&token_program).await;
+    fixture
+        .env
+        .mint_tokens(&stream_mint, &admin, &token_program, ADMIN_TOKENS)
+        .await;
+
+    let now = fixture.env.now().await;
+    assert_staking_error(
+        fixture
+            .admin_send(
+                fixture
+                    .pool
+                    .add_reward_stream(&admin, &stream_mint, TOKEN, now, now),
+            )
+            .await,
+        StakingError::InvalidArgument,
+    );
+    // the stream vault must be derived from the free slot
+    let mut instruction =
+        fixture
+            .pool
+            .add_reward_stream(&admin, &stream_mint, 1_000 * TOKEN, now, now + 1_000);
+    instruction.data[8] = 1;
+    instruction.accounts[1].pubkey = fixture.pool.stream_vault(1);
+    assert_staking_error(
+        fixture.admin_send(instruction).await,
+        StakingError::InvalidArgument,
+    );
+    // fewer rewards than seconds would emit nothing
+    assert_staking_error(
+        fixture
+            .admin_send(
+                fixture
+                    .pool
+                    .add_reward_stream(&admin, &stream_mint, 999, now, now + 1_000),
+            )
+            .await,
+        StakingError::ZeroValueError,
+    );
+    // 1 token per second for 1000 seconds
+    fixture
+        .admin_send(fixture.pool.add_reward_stream(
+            &admin,
+            &stream_mint,
+            1_000 * TOKEN,
+            now,
+            now + 1_000,
+        ))
+        .await
+        .unwrap();
+    fixture.pool.record_stream(stream_mint);
+
+    fixture.stake(&user, 0, 1_000 * TOKEN, 0).await;
+    fixture.env.warp_forward(500).await;
+
+    // a principal exit needs the stream accounts, but skips a stream whose destination does not exist,
+    // its rewards stay pending
+    let mut unstake = fixture.pool.partial_unstake(&owner, 0, 500 * TOKEN);
+    unstake.accounts.truncate(unstake.accounts.len() - 3);
+    assert_staking_error(
+        fixture.send(unstake, &user).await,
+        StakingError::InvalidArgument,
+    );
+    fixture
+        .send(fixture.pool.partial_unstake(&owner, 0, 500 * TOKEN), &user)
+        .await
+        .unwrap();
+    assert_eq!(fixture.balance(&owner).await, STAKER_TOKENS - 500 * TOKEN);
+    assert_eq!(
+        fixture.user_stake(&owner, 0).await.stream_pending_rewards[0],
+        500 * TOKEN
+    );
+    let user_stream_account = fixture
+        .env
+        .mint_tokens(&stream_mint, &owner, &token_program, 0)
+        .await;
+    fixture.env.warp_forward(500).await;
+
+    // a claim needs the accounts of every stream
+    let mut claim = fixture.pool.claim_rewards(&owner, 0);
+    claim.accounts.truncate(claim.accounts.len() - 3);
+    assert_staking_error(
+        fixture.send(claim, &user).await,
+        StakingError::InvalidArgument,
+    );
+    let events: Vec<RewardsClaimed> = fixture
+        .env
+        .send_for_events(&[fixture.pool.claim_rewards(&owner, 0)], &[&user])
+        .await
+        .unwrap();
+    assert_eq!(events[0].stream_rewards[0], 1_000 * TOKEN);
+    assert_eq!(
+        fixture.env.token_balance(&user_stream_account).await,
+        1_000 * TOKEN
+    );
+
+    // a finished stream can be retired once its claim period is over, freeing its slot
+    let admin_stream_account = fixture
+        .env
+        .mint_tokens(&stream_mint, &admin, &token_program, 0)
+        .await;
+    assert_staking_error(
+        fixture
+            .admin_send(fixture.pool.retire_reward_stream(&admin, 0))
+            .await,
+        StakingError::RewardStreamActive,
+    );
+    fixture.env.warp_forward(STREAM_CLAIM_PERIOD).await;
+    assert_staking_error(
+        fixture
+            .send(fixture.pool.retire_reward_stream(&owner, 0), &user)
+            .await,
+        StakingError::AdminOnly,
+    );
+    let balance = fixture.env.token_balance(&admin_stream_account).await;
+    let left = fixture
+        .env
+        .token_balance(&fixture.pool.stream_vault(0))
+        .await;
+    fixture
+        .admin_send(fixture.pool.retire_reward_stream(&admin, 0))
+        .await
+        .unwrap();
+    fixture.pool.record_retired_stream(0);
+    assert_eq!(
+        fixture.env.token_balance(&admin_stream_account).await - balance,
+        left
+    );
+    assert!(fixture
+        .env
+        .account(&fixture.pool.stream_vault(0))
+        .await
+        .is_none());
+    let pool = fixture.staking_account().await;
+    assert_eq!(pool.stream_count, 0);
+    assert_eq!(pool.reward_streams[0].id, 0);
+
+    let now = fixture.env.now().await;
+    for _ in 0..MAX_REWARD_STREAMS {
+        fixture
+            .admin_send(
+                fixture
+                    .pool
+                    .add_reward_stream(&admin, &stream_mint, TOKEN, now, now + DAY),
+            )
+            .await
+            .unwrap();
+        fixture.pool.record_stream(stream_mint);
+    }
+    assert_staking_error(
+        fixture
+            .admin_send(
+                fixture
+                    .pool
+                    .add_reward_stream(&admin, &stream_mint, TOKEN, now, now + DAY),
+            )
+            .await,
+        StakingError::TooManyRewardStreams,
+    );
+    // the reused slot holds a new stream, the position earns from its start
+    assert_eq!(fixture.staking_account().await.reward_streams[0].id, 2);
+    fixture.env.warp_forward(3_600).await;
+
+    // the shutdown ends the streams, a redeem can not leave out the stream accounts
+    fixture
+        .admin_send(fixture.pool.begin_shutdown(&admin, 0))
+        .await
+        .unwrap();
+    let mut redeem = fixture.pool.redeem(&owner, 0, false);
+    redeem.accounts.truncate(redeem.accounts.len() - 3);
+    assert_staking_error(
+        fixture.send(redeem, &user).await,
+        StakingError::InvalidArgument,
+    );
+    let events: Vec<Redeemed> = fixture
+        .env
+        .send_for_events(&[fixture.pool.redeem(&owner, 0, false)], &[&user])
+        .await
+        .unwrap();
+    let paid: u64 = events[0].stream_rewards.iter().sum();
+    assert!(events[0].stream_rewards.iter().all(|reward| *reward > 0));
+    assert_eq!(events[0].principal, 500 * TOKEN);
+    assert_eq!(fixture.balance(&owner).await, STAKER_TOKENS);
+    assert_eq!(
+        fixture.env.token_balance(&user_stream_account).await,
+        1_000 * TOKEN + paid
+    );
+    assert!(fixture
+        .env
+        .account(&fixture.pool.user_stake(&owner, 0))
+        .await
+        .is_none());
+
+    // the rest of the rewards of the streams goes back to the admin once they can not be claimed anymore
+    assert_staking_error(
+        fixture.admin_send(fixture.pool.close_pool(&admin)).await,
+        StakingError::RewardStreamActive,
+    );
+    fixture.env.warp_forward(STREAM_CLAIM_PERIOD).await;
+    let balance = fixture.env.token_balance(&admin_stream_account).await;
+    fixture
+        .admin_send(fixture.pool.close_pool(&admin))
+        .await
+        .unwrap();
+    assert_eq!(
+        fixture.env.token_balance(&admin_stream_account).await - balance,
+        MAX_REWARD_STREAMS as u64 * TOKEN - paid
+    );
+    for index in 0..MAX_REWARD_STREAMS as u8 {
+        assert!(fixture
+            .env
+            .account(&fixture.pool.stream_vault(index))
+            .await
+            .is_none());
+    }
+}
+
+/// A cranker returning a position can not drop its stream rewards by leaving out the stream accounts,
+/// the rewards the owner can not receive yet stay claimable in the position
+#[tokio::test]
+async fn returned_position_keeps_its_stream_rewards() {
+    let mut fixture = Fixture::new(0, 0, RewardMode::Apy).await;
+    let admin = fixture.admin.pubkey();
+    let user = fixture.create_staker().await;
+    let owner = user.pubkey();
+    let cranker = fixture.env.create_user().await;
+    let token_program = fixture.pool.token_program;
+    let stream_mint = fixture.env.create_mint(&token_program).await;
+    let admin_stream_account = fixture
+        .env
+        .mint_tokens(&stream_mint, &admin, &token_program, ADMIN_TOKENS)
+        .await;
+
+    let now = fixture.env.now().await;
+    fixture
+        .admin_send(fixture.pool.add_reward_stream(
+            &admin,
+            &stream_mint,
+            1_000 * TOKEN,
+            now,
+            now + 1_000,
+        ))
+        .await
+        .unwrap();
+    fixture.pool.record_stream(stream_mint);
+    fixture.stake(&user, 0, 1_000 * TOKEN, 0).await;
+    fixture.env.warp_forward(500).await;
+    fixture
+        .admin_send(fixture.pool.begin_shutdown(&admin, 0))
+        .await
+        .unwrap();
+
+    let mut return_position = fixture.pool.return_position(&cranker.pubkey(), &owner, 0);
+    return_position
+        .accounts
+        .truncate(return_position.accounts.len() - 3);
+    assert_staking_error(
+        fixture.send(return_position, &cranker).await,
+        StakingError::InvalidArgument,
+    );
+
+    // the owner has no stream token account, the principal is returned and the position kept open
+    let events: Vec<Redeemed> = fixture
+        .env
+        .send_for_events(
+            &[fixture.pool.return_position(&cranker.pubkey(), &owner, 0)],
+            &[&cranker],
+        )
+        .await
+        .unwrap();
+    assert_eq!(events[0].principal, 1_000 * TOKEN);
+    assert_eq!(events[0].stream_rewards, [0; MAX_REWARD_STREAMS]);
+    assert_eq!(fixture.balance(&owner).await, STAKER_TOKENS);
+    let position = fixture.user_stake(&owner, 0).await;
+    assert_eq!(position.amount, 0);
+    let stream_reward = position.stream_pending_rewards[0];
+    assert!(stream_reward >= 500 * TOKEN);
+    assert_eq!(fixture.staking_account().await.total_staked, 0);
+    assert_staking_error(
+        fixture.admin_send(fixture.pool.close_pool(&admin)).await,
+        StakingError::RewardStreamActive,
+    );
+
+    // the owner claims the stream rewards later, which closes the position
+    let user_stream_account = fixture
+        .env
+        .mint_tokens(&stream_mint, &owner, &token_program, 0)
+        .await;
+    fixture
+        .send(fixture.pool.claim_rewards(&owner, 0), &user)
+        .await
+        .unwrap();
+    assert_eq!(
+        fixture.env.token_balance(&user_stream_account).await,
+        stream_reward
+    );
+    assert!(fixture
+        .env
+        .account(&fixture.pool.user_stake(&owner, 0))
+        .await
+        .is_none());
+
+    fixture.env.warp_forward(STREAM_CLAIM_PERIOD).await;
+    let balance = fixture.env.token_balance(&admin_stream_account).await;
+    fixture
+        .admin_send(fixture.pool.close_pool(&admin))
+        .await
+        .unwrap();
+    assert_eq!(
+        fixture.env.token_balance(&admin_stream_account).await - balance,
+        1_000 * TOKEN - stream_reward
+    );
+}
+
+////////////////////////////////////////////////////////////////////////////////
+////////////////////////////////// MIGRATIONS //////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+/// Account data of a pool in the baseline layout: the fields of `StakingAccountV0` after the discriminator,
+/// padded with zeros to the size the baseline allocated (`size_of` of the struct + 8)
+fn legacy_pool_data(
+    admin: &Pubkey,
+    bump: u8,
+    token_mint: &Pubkey,
+    reward_rate: u8,
+    admin_reward_amount: u64,
+    min_staking_duration: i64,
+) -> Vec<u8> {
+    let mut data = StakingAccount::DISCRIMINATOR.to_vec();
+    data.extend_from_slice(admin.as_ref());
+    data.push(reward_rate);
+    data.push(bump);
+    data.extend_from_slice(token_mint.as_ref());
+    data.extend_from_slice(&admin_reward_amount.to_le_bytes());
+    data.extend_from_slice(&min_staking_duration.to_le_bytes());
+    data.resize(96, 0);
+    data
+}
+
+/// Account data of a position in the baseline layout: the fields of `UserStakeV0` after the discriminator
+fn legacy_position_data(amount: u64, start_time: i64) -> Vec<u8> {
+    let mut data = UserStake::DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&amount.to_le_bytes());
+    data.extend_from_slice(&start_time.to_le_bytes());
+    data
+}
+
+#[tokio::test]
+async fn migrate_accounts() {
+    let mut fixture = Fixture::new(0, 0, RewardMode::Apy).await;
+    let admin = fixture.admin.pubkey();
+    let mint = fixture.pool.mint;
+    let pool = Pool::new(admin, 1, mint, fixture.pool.token_program);
+    let legacy_pool = pool.legacy_address();
+    let (_, bump) =
+        Pubkey::find_program_address(&[admin.as_ref(), b"staking_account"], &stake_tokens::ID);
+    let funds = 1_000 * TOKEN;
+
+    // layouts other than the baseline one are rejected
+    let mut data = legacy_pool_data(&admin, bump, &mint, 10, funds, DAY);
+    data.push(0);
+    fixture
+        .env
+        .set_account(&legacy_pool, &stake_tokens::ID, data)
+        .await;
+    assert_staking_error(
+        fixture.admin_send(pool.migrate_pool()).await,
+        StakingError::UnsupportedLayout,
+    );
+    let mut data = legacy_pool_data(&admin, bump, &mint, 10, funds, DAY);
+    data[..8].copy_from_slice(&UserStake::DISCRIMINATOR);
+    fixture
+        .env
+        .set_account(&legacy_pool, &stake_tokens::ID, data)
+        .await;
+    assert_staking_error(
+        fixture.admin_send(pool.migrate_pool()).await,
+        StakingError::UnsupportedLayout,
+    );
+    let mut data = StakingAccount::DISCRIMINATOR.to_vec();
+    data.resize(8 + std::mem::size_of::<StakingAccount>(), 0);
+    fixture
+        .env
+        .set_account(&legacy_pool, &stake_tokens::ID, data)
+        .await;
+    assert_staking_error(
+        fixture.admin_send(pool.migrate_pool()).await,
+        StakingError::AlreadyMigrated,
+    );
+    // only the admin of the legacy pool can migrate it
+    let other = Keypair::new();
+    fixture
+        .env
+        .set_account(
+            &legacy_pool,
+            &stake_tokens::ID,
+            legacy_pool_data(&other.pubkey(), bump, &mint, 10, funds, DAY),
+        )
+        .await;
+    assert_staking_error(
+        fixture.admin_send(pool.migrate_pool()).await,
+        StakingError::AdminOnly,
+    );
+
+    // the baseline pool, its funds held by the admin token account
+    fixture
+        .env
+        .set_account(
+            &legacy_pool,
+            &stake_tokens::ID,
+            legacy_pool_data(&admin, bump, &mint, 10, funds, DAY),
+        )
+        .await;
+    fixture.admin_send(pool.migrate_pool()).await.unwrap();
+    fixture.pool = pool.clone();
+    let migrated = fixture.staking_account().await;
+    assert_eq!(migrated.version, STAKING_ACCOUNT_VERSION);
+    assert_eq!(migrated.admin, admin);
+    assert_eq!(migrated.pool_id, 1);
+    assert_eq!(migrated.token_mint, mint);
+    assert_eq!(migrated.reward_rate, 10);
+    assert_eq!(migrated.min_staking_duration, DAY);
+    assert!(migrated.reward_mode == RewardMode::Apy);
+    assert_eq!(migrated.admin_reward_amount, funds);
+    assert_eq!(fixture.env.token_balance(&pool.vault()).await, funds);
+    assert_eq!(fixture.balance(&admin).await, ADMIN_TOKENS - funds);
+    assert!(fixture.env.account(&legacy_pool).await.is_none());
+
+    // baseline positions, started in milliseconds (client timestamp) or in seconds (clock)
+    let now = fixture.env.now().await;
+    let user = fixture.create_staker().await;
+    let owner = user.pubkey();
+    let amount = 500 * TOKEN;
+    fixture
+        .env
+        .set_account(
+            &pool.legacy_user_stake(&owner),
+            &stake_tokens::ID,
+            legacy_position_data(amount, (now - 30 * DAY) * 1_000 + 250),
+        )
+        .await;
+    let other_user = fixture.create_staker().await;
+    let other_owner = other_user.pubkey();
+    let other_amount = 200 * TOKEN;
+    fixture
+        .env
+        .set_account(
+            &pool.legacy_user_stake(&other_owner),
+            &stake_tokens::ID,
+            legacy_position_data(other_amount, now - 2 * DAY),
+        )
+        .await;
+
+    assert_staking_error(
+        fixture
+            .send(pool.migrate_user_stake(&owner, &owner, 0), &user)
+            .await,
+        StakingError::AdminOnly,
+    );
+    // the legacy position is not linked to a pool, the user picks the one it moves to
+    let mut instruction = pool.migrate_user_stake(&admin, &owner, 0);
+    instruction.accounts[2].is_signer = false;
+    assert_custom_error(
+        fixture.admin_send(instruction).await,
+        ErrorCode::AccountNotSigner.into(),
+    );
+    let admin_keypair = fixture.admin.insecure_clone();
+    fixture
+        .env
+        .send(
+            &[pool.migrate_user_stake(&admin, &owner, 0)],
+            &[&admin_keypair, &user],
+        )
+        .await
+        .unwrap();
+    fixture
+        .env
+        .send(
+            &[pool.migrate_user_stake(&admin, &other_owner, 0)],
+            &[&admin_keypair, &other_user],
+        )
+        .await
+        .unwrap();
+    // the legacy positions are closed, they can not be migrated twice
+    assert!(fixture
+        .env
+        .account(&pool.legacy_user_stake(&owner))
+        .await
+        .is_none());
+    assert_staking_error(
+        fixture
+            .env
+            .send(
+                &[pool.migrate_user_stake(&admin, &owner, 1)],
+                &[&admin_keypair, &user],
+            )
+            .await,
+        StakingError::InvalidArgument,
+    );
+
+    let reward = apy_reward(amount, 10, 10_000, 30 * DAY);
+    let position = fixture.user_stake(&owner, 0).await;
+    assert_eq!(position.version, USER_STAKE_VERSION);
+    assert_eq!(position.pool, pool.address);
+    assert_eq!(position.user, owner);
+    assert_eq!(position.amount, amount);
+    assert_eq!(position.start_time, now - 30 * DAY);
+    assert_eq!(position.last_reward_time, now);
+    assert_eq!(position.lock_duration, DAY);
+    assert_eq!(position.pending_reward, reward);
+    let other_position = fixture.user_stake(&other_owner, 0).await;
+    assert_eq!(other_position.start_time, now - 2 * DAY);
+    assert_eq!(
+        other_position.pending_reward,
+        apy_reward(other_amount, 10, 10_000, 2 * DAY)
+    );
+    let migrated = fixture.staking_account().await;
+    assert_eq!(migrated.total_staked, amount + other_amount);
+    assert_eq!(
+        fixture.env.token_balance(&pool.vault()).await,
+        funds + amount + other_amount
+    );
+    assert_eq!(
+        fixture.balance(&admin).await,
+        ADMIN_TOKENS - funds - amount - other_amount
+    );
+
+    // the position pays the reward accrued before the migration and after it
+    fixture.env.warp_forward(DAY).await;
+    fixture
+        .send(pool.redeem(&owner, 0, false), &user)
+        .await
+        .unwrap();
+    assert_eq!(
+        fixture.balance(&owner).await,
+        STAKER_TOKENS + amount + reward + apy_reward(amount, 10, 10_000, DAY)
+    );
+}
+
+#[tokio::test]
+async fn upgrade_accounts() {
+    let mut fixture = Fixture::new(0, 0, RewardMode::Apy).await;
+    let admin = fixture.admin.pubkey();
+    let pool = fixture.pool.clone();
+    let user = fixture.create_staker().await;
+    let owner = user.pubkey();
+    fixture.stake(&user, 0, 100 * TOKEN, 0).await;
+
+    // accounts of the current layout have nothing to upgrade
+    assert_staking_error(
+        fixture.admin_send(pool.upgrade_pool(&admin)).await,
+        StakingError::AlreadyMigrated,
+    );
+    assert_staking_error(
+        fixture
+            .send(pool.upgrade_user_stake(&owner, &owner, 0), &user)
+            .await,
+        StakingError::AlreadyMigrated,
+    );
+    // a position is not a pool
+    let mut instruction = pool.upgrade_pool(&admin);
+    instruction.accounts[0].pubkey = pool.user_stake(&owner, 0);
+    assert_staking_error(
+        fixture.admin_send(instruction).await,
+        StakingError::UnsupportedLayout,
+    );
+    // accounts of other programs are rejected
+    let mut instruction = pool.upgrade_pool(&admin);
+    instruction.accounts[0].pubkey = pool.vault();
+    assert_staking_error(
+        fixture.admin_send(instruction).await,
+        StakingError::InvalidArgument,
+    );
+
+    // baseline accounts are moved to new accounts by the migrate instructions, not upgraded in place
+    let legacy_pool = pool.legacy_address();
+    fixture
+        .env
+        .set_account(
+            &legacy_pool,
+            &stake_tokens::ID,
+            legacy_pool_data(&admin, 255, &pool.mint, 10, 0, DAY),
+        )
+        .await;
+    let mut instruction = pool.upgrade_pool(&admin);
+    instruction.accounts[0].pubkey = legacy_pool;
+    assert_staking_error(
+        fixture.admin_send(instruction.clone()).await,
+        StakingError::UnsupportedLayout,
+    );
+    let legacy_user_stake = pool.legacy_user_stake(&owner);
+    let now = fixture.env.now().await;
+    fixture
+        .env
+        .set_account(
+            &legacy_user_stake,
+            &stake_tokens::ID,
+            legacy_position_data(100 * TOKEN, now),
+        )
+        .await;
+    let mut position_instruction = pool.upgrade_user_stake(&owner, &owner, 0);
+    position_instruction.accounts[0].pubkey = legacy_user_stake;
+    assert_staking_error(
+        fixture.send(position_instruction, &user).await,
+        StakingError::UnsupportedLayout,
+    );
+    // so is an account of the current size without a version
+    let mut data = StakingAccount::DISCRIMINATOR.to_vec();
+    data.resize(8 + std::mem::size_of::<StakingAccount>(), 0);
+    fixture
+        .env
+        .set_account(&legacy_pool, &stake_tokens::ID, data.clone())
+        .await;
+    assert_staking_error(
+        fixture.admin_send(instruction.clone()).await,
+        StakingError::UnsupportedLayout,
+    );
+    // and an account larger than the current layout
+    data.push(0);
+    fixture
+        .env
+        .set_account(&legacy_pool, &stake_tokens::ID, data)
+        .await;
+    assert_staking_error(
+        fixture.admin_send(instruction).await,
+        StakingError::UnsupportedLayout,
+    );
+
+    assert_eq!(
+        fixture.staking_account().await.version,
+        STAKING_ACCOUNT_VERSION
+    );
+    assert_eq!(
+        fixture.user_stake(&owner, 0).await.version,
+        USER_STAKE_VERSION
+    );
+}
+
+////////////////////////////////////////////////////////////////////////////////
+//////////////////////////////////// ERRORS ////////////////////////////////////
+////////////////////////////////////////////////////////////////////////////////
+
+/// Error codes are part of the interface of the program, clients match on them
+/// `AlreadyStaked` is not returned anymore (users can hold several positions) but keeps its code
+#[test]
+fn error_codes_are_stable() {
+    let errors = [
+        StakingError::AlreadyStaked,
+        StakingError::NothingStaked,
+        StakingError::InvalidArgument,
+        StakingError::InsufficientRewardFunds,
+        StakingError::AdminOnly,
+        StakingError::StakingDurationNotMet,
+        StakingError::CalculationError,
+        StakingError::ZeroValueError,
+        StakingError::PoolMismatch,
+        StakingError::InvalidRewardMode,
+        StakingError::AutoCompoundDisabled,
+        StakingError::InvalidTier,
+        StakingError::UnbondingRequired,
+        StakingError::UnbondingPending,
+        StakingError::NothingUnbonding,
+        StakingError::CooldownNotMet,
+        StakingError::NoPendingConfigUpdate,
+        StakingError::TimelockNotElapsed,
+        StakingError::NotPendingAdmin,
+        StakingError::PoolNotActive,
+        StakingError::PoolNotInEmergency,
+        StakingError::RewardFundsAllocated,
+        StakingError::PoolShuttingDown,
+        StakingError::PoolNotShuttingDown,
+        StakingError::GracePeriodNotElapsed,
+        StakingError::OutstandingStake,
+        StakingError::SeparateRewardMint,
+        StakingError::TooManyRewardStreams,
+        StakingError::AccountNotMigrated,
+        StakingError::AlreadyMigrated,
+        StakingError::RewardStreamActive,
+        StakingError::UnsupportedLayout,
+    ];
+    for (index, error) in errors.into_iter().enumerate() {
+        assert_eq!(u32::from(error), 6_000 + index as u32, "{error:?}");
+    }
+}
@@ -6,7 +6,9 @@ use {
             create_metadata_accounts_v3, mpl_token_metadata::types::DataV2,
             CreateMetadataAccountsV3, Metadata,
         },
-        token::{mint_to, transfer, Mint, MintTo, Token, TokenAccount, Transfer},
+        token_interface::{
+            mint_to, transfer_checked, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked,
+        },
     },
 };
 
@@ -133,16 +135,18 @@ pub mod custom_spl_tokens {
             &ctx.accounts.recipient_token_account.key()
         );
 
-        transfer(
+        transfer_checked(
             CpiContext::new(
                 ctx.accounts.token_program.to_account_info(),
-                Transfer {
+                TransferChecked {
                     from: ctx.accounts.sender_token_account.to_account_info(),
+                    mint: ctx.accounts.mint_account.to_account_info(),
                     to: ctx.accounts.recipient_token_account.to_account_info(),
                     authority: ctx.accounts.sender.to_account_info(),
                 },
             ),
             amount * 10u64.pow(ctx.accounts.mint_account.decimals as u32),
+            ctx.accounts.mint_account.decimals,
         )?;
 
         msg!("Token transferred successfully");
@@ -163,7 +167,7 @@ pub mod custom_spl_tokens {
 /// * `metadata_account` - The metadata account to create
 /// * `mint_account` - The mint account to create
 /// * `token_metadata_program` - The token metadata program (used to create metadata account)
-/// * `token_program` - The token program
+/// * `token_program` - The token program (SPL Token or Token-2022)
 /// * `system_program` - The system program
 /// * `rent` - The rent sysvar
 #[derive(Accounts)]
@@ -186,11 +190,12 @@ pub struct CreateToken<'info> {
         payer = payer,
         mint::decimals = _token_decimals,
         mint::authority = payer.key(),
+        mint::token_program = token_program,
     )]
-    pub mint_account: Account<'info, Mint>,
+    pub mint_account: InterfaceAccount<'info, Mint>,
 
     pub token_metadata_program: Program<'info, Metadata>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
 }
@@ -203,7 +208,7 @@ pub struct CreateToken<'info> {
 /// * `recipient` - The system account to mint tokens to
 /// * `mint_account` - The mint account
 /// * `associated_token_account` - The associated token account to mint tokens to
-/// * `token_program` - The token program
+/// * `token_program` - The token program (SPL Token or Token-2022)
 /// * `associated_token_program` - The associated token program
 /// * `system_program` - The system program
 #[derive(Accounts)]
@@ -214,17 +219,18 @@ pub struct MintToken<'info> {
     pub recipient: SystemAccount<'info>,
 
     #[account(mut)]
-    pub mint_account: Account<'info, Mint>,
+    pub mint_account: InterfaceAccount<'info, Mint>,
 
     #[account(
         init_if_needed,
         payer = mint_authority,
         associated_token::mint = mint_account,
         associated_token::authority = recipient,
+        associated_token::token_program = token_program,
     )]
-    pub associated_token_account: Account<'info, TokenAccount>,
+    pub associated_token_account: InterfaceAccount<'info, TokenAccount>,
 
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
@@ -238,7 +244,7 @@ pub struct MintToken<'info> {
 /// * `mint_account` - The mint account
 /// * `sender_token_account` - The sender's associated token account
 /// * `recipient_token_account` - The recipient's associated token account
-/// * `token_program` - The token program
+/// * `token_program` - The token program (SPL Token or Token-2022)
 /// * `associated_token_program` - The associated token program
 /// * `system_program` - The system program
 #[derive(Accounts)]
@@ -248,14 +254,14 @@ pub struct TransferToken<'info> {
     pub recipient: SystemAccount<'info>,
 
     #[account(mut)]
-    pub mint_account: Account<'info, Mint>,
-    #[account(mut,associated_token::mint = mint_account, associated_token::authority = sender)]
-    pub sender_token_account: Account<'info, TokenAccount>,
+    pub mint_account: InterfaceAccount<'info, Mint>,
+    #[account(mut,associated_token::mint = mint_account, associated_token::authority = sender, associated_token::token_program = token_program)]
+    pub sender_token_account: InterfaceAccount<'info, TokenAccount>,
 
-    #[account(init_if_needed,payer = sender, associated_token::mint = mint_account, associated_token::authority = recipient)]
-    pub recipient_token_account: Account<'info, TokenAccount>,
+    #[account(init_if_needed,payer = sender, associated_token::mint = mint_account, associated_token::authority = recipient, associated_token::token_program = token_program)]
+    pub recipient_token_account: InterfaceAccount<'info, TokenAccount>,
 
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
@@ -1,9 +1,52 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_lang::system_program;
+use anchor_lang::Discriminator;
+use anchor_spl::token_interface::{
+    self, Burn, CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
 use std::mem::size_of;
 
 declare_id!("7a8fBQMwbtE1C61fcGUW6quAgdqdmzYojha5cQq9Ju4q");
 
+/// Seeds (with bump) of a staking account PDA, used to sign transfers out of the pool vault
+///
+/// # Example
+/// ```rs
+/// let staking_account = &ctx.accounts.staking_account;
+/// token_interface::transfer_checked(ctx.accounts.transfer_to_admin_ctx(&[staking_account_seeds!(staking_account)]), amount, decimals)?;
+/// ```
+macro_rules! staking_account_seeds {
+    ($staking_account:expr) => {
+        &[
+            $staking_account.creator.as_ref(),
+            b"staking_account",
+            &$staking_account.pool_id.to_le_bytes(),
+            &[$staking_account.bump],
+        ]
+    };
+}
+
+/// Accounts paying out a position (see `RewardPayout`), taken from the accounts of an instruction
+///
+/// # Example
+/// ```rs
+/// pay_reward(reward_payout!(ctx.accounts), principal, reward)?;
+/// ```
+macro_rules! reward_payout {
+    ($accounts:expr) => {
+        RewardPayout {
+            staking_account: &$accounts.staking_account,
+            token_program: &$accounts.token_program,
+            staking_token_account: &$accounts.staking_token_account,
+            mint: &$accounts.mint,
+            user_token_account: &$accounts.user_token_account,
+            reward_token_account: &$accounts.reward_token_account,
+            reward_mint: &$accounts.reward_mint,
+            user_reward_token_account: &$accounts.user_reward_token_account,
+        }
+    };
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 ///////////////////////// INSTRUCTIONS IMPLEMENTATIONS /////////////////////////
 ////////////////////////////////////////////////////////////////////////////////
@@ -16,28 +59,89 @@ pub mod stake_tokens {
     ////////////////////////////////////////////////////////////////////////////////
 
     /// Initialize instruction
-    /// This instruction is used to create a new staking pool
+    /// This instruction is used to create a new staking pool and its token vault
+    /// The vault is a PDA token account owned by the staking account, so tokens can only leave it through this program
     ///
     /// # Arguments
     /// * `ctx` - context of the program
-    /// * `bump` - unique bump for rach pool
+    /// * `pool_id` - unique id of the pool among the pools created by the same admin
     /// * `token_mint` - program_id (address) of specific token which is allowed stake
-    /// * `reward_rate` - APY return yearly in percentage (0-100)
+    /// * `reward_rate` - APY return yearly in percentage (0-100), unused in emission mode
     /// * `min_staking_duration` - minimum staking duration in seconds
+    /// * `reward_mode` - fixed APY per user, or a funded emission shared by all stakers
     pub fn initialize(
         ctx: Context<Initialize>,
-        bump: u8,                  // unique bump for rach pool
+        pool_id: u64,              // unique id of the pool for this admin
         token_mint: Pubkey,        // program_id (address) of specific token which is allowed stake
         reward_rate: u8,           // APY return yearly
         min_staking_duration: i64, // minimum staking duration in seconds
+        reward_mode: RewardMode,   // how rewards are distributed
     ) -> Result<()> {
         let staking_account = &mut ctx.accounts.staking_account;
-        staking_account.admin = *ctx.accounts.admin.key;
-        staking_account.reward_rate = reward_rate;
-        staking_account.token_mint = token_mint;
-        staking_account.bump = bump;
-        staking_account.admin_reward_amount = 0;
-        staking_account.min_staking_duration = min_staking_duration;
+        init_pool(
+            staking_account,
+            ctx.accounts.admin.key(),
+            pool_id,
+            token_mint,
+            reward_rate,
+            min_staking_duration,
+            reward_mode,
+        )?;
+        staking_account.bump = ctx.bumps.staking_account;
+
+        emit!(PoolInitialized {
+            pool: staking_account.key(),
+            admin: staking_account.admin,
+            pool_id,
+            token_mint,
+            reward_mode,
+            reward_rate,
+            min_staking_duration,
+            timestamp: staking_account.last_update_time,
+        });
+        Ok(())
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////
+    /////////////////////////////// SET REWARD MINT ///////////////////////////////
+    ////////////////////////////////////////////////////////////////////////////////
+
+    /// Set reward mint instruction
+    /// This instruction is used by the admin to pay the rewards of the pool in another token than the staked one
+    /// (e.g. for partner co-incentive campaigns), it creates the reward vault which holds the reward funds
+    /// It can only be called once, before the pool is funded or anything is staked
+    /// Rewards in another token can not be compounded, and principal penalties routed to the reward pool
+    /// stay in the staking vault until the pool is closed
+    ///
+    /// # Arguments
+    /// * `ctx` - context of the program
+    pub fn set_reward_mint(ctx: Context<SetRewardMint>) -> Result<()> {
+        let staking_account = &mut ctx.accounts.staking_account;
+        require!(
+            !has_reward_vault(staking_account),
+            StakingError::InvalidArgument
+        );
+        require!(
+            ctx.accounts.reward_mint.key() != staking_account.token_mint,
+            StakingError::InvalidArgument
+        );
+        require!(
+            staking_account.total_staked == 0 && staking_account.total_unbonding == 0,
+            StakingError::OutstandingStake
+        );
+        // reward funds already in the staking vault can not follow the rewards to the reward vault
+        require!(
+            staking_account.admin_reward_amount == 0,
+            StakingError::InvalidArgument
+        );
+
+        staking_account.reward_mint = ctx.accounts.reward_mint.key();
+
+        emit!(RewardMintSet {
+            pool: staking_account.key(),
+            reward_mint: staking_account.reward_mint,
+            reward_vault: ctx.accounts.reward_token_account.key(),
+        });
         Ok(())
     }
 
@@ -46,280 +150,2705 @@ pub mod stake_tokens {
     ////////////////////////////////////////////////////////////////////////////////
 
     /// Fund reward instruction
-    /// This instruction is used to fund the reward pool
+    /// This instruction is used to fund the reward pool of an APY mode pool
     ///
     /// # Arguments
     /// * `ctx` - context of the program
     /// * `amount` - amount to fund the reward pool
     pub fn fund_reward(ctx: Context<FundRewards>, amount: u64) -> Result<()> {
-        // only admin can fund the reward pool
         require!(
-            ctx.accounts.admin.key == &ctx.accounts.admin_token_account.owner,
-            StakingError::AdminOnly
+            ctx.accounts.staking_account.reward_mode == RewardMode::Apy,
+            StakingError::InvalidRewardMode
+        );
+        // rewards owed until the shutdown may still need funding
+        require!(
+            matches!(
+                ctx.accounts.staking_account.pool_state,
+                PoolState::Active | PoolState::ShuttingDown
+            ),
+            StakingError::PoolNotActive
         );
 
-        token::transfer(ctx.accounts.fund_reward_from_admin_ctx(), amount)?;
+        let received = ctx.accounts.transfer_from_admin(amount)?;
+
         let staking_account = &mut ctx.accounts.staking_account;
-        staking_account.admin_reward_amount += amount;
+        staking_account.admin_reward_amount += received;
+
+        emit!(RewardsFunded {
+            pool: staking_account.key(),
+            admin: ctx.accounts.admin.key(),
+            amount: received,
+            reward_funds: staking_account.admin_reward_amount,
+            emission_end_time: 0,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
         Ok(())
     }
 
     ////////////////////////////////////////////////////////////////////////////////
-    //////////////////////////////////// STAKE ////////////////////////////////////
+    //////////////////////////////// FUND EMISSION ////////////////////////////////
     ////////////////////////////////////////////////////////////////////////////////
 
-    /// Stake instruction
-    /// This instruction is used to stake tokens
+    /// Fund emission instruction
+    /// This instruction is used to fund an emission mode pool with `amount` tokens distributed over `duration` seconds
+    /// Rewards are shared between stakers pro rata to their stake, rewards not yet emitted
+    /// from a previous funding are rolled into the new emission
     ///
     /// # Arguments
     /// * `ctx` - context of the program
-    /// * `amount` - amount to stake
-    /// * `timestamp` - custom timestamp for testing
-    pub fn stake(ctx: Context<Stake>, amount: u64, timestamp: Option<i64>) -> Result<()> {
-        // let staking_account = &ctx.accounts.staking_account;
-
-        // require!(
-        //     ctx.accounts.user_token_account.mint.to_string() == staking_account.token_mint.to_string(),
-        //     StakingError::InvalidArgument
-        // );
-
+    /// * `amount` - amount of reward tokens to emit
+    /// * `duration` - emission period in seconds
+    pub fn fund_emission(ctx: Context<FundRewards>, amount: u64, duration: i64) -> Result<()> {
         require!(
-            ctx.accounts.user_stake.amount == 0,
-            StakingError::AlreadyStaked
+            ctx.accounts.staking_account.reward_mode == RewardMode::Emission,
+            StakingError::InvalidRewardMode
         );
+        require!(
+            ctx.accounts.staking_account.pool_state == PoolState::Active,
+            StakingError::PoolNotActive
+        );
+        require!(duration > 0, StakingError::ZeroValueError);
 
-        // Transfer tokens to the staking account
-        token::transfer(ctx.accounts.transfer_to_stake_ctx(), amount)?;
-        let user_stake = &mut ctx.accounts.user_stake;
+        let received = ctx.accounts.transfer_from_admin(amount)?;
 
-        // for mock testing
-        let current_time = match timestamp {
-            Some(custom_time) => custom_time,
-            None => Clock::get()?.unix_timestamp,
-        };
+        let current_time = Clock::get()?.unix_timestamp;
+        let staking_account = &mut ctx.accounts.staking_account;
+        update_reward_per_share(staking_account, current_time)?;
 
-        // Initialize user's stake data
-        user_stake.amount = amount;
-        user_stake.start_time = current_time;
+        // rewards of the running emission which are not emitted yet
+        let remaining_reward = remaining_emission(staking_account, current_time)?;
+        let emitted_reward = remaining_reward
+            .checked_add(received)
+            .ok_or(StakingError::CalculationError)?;
 
+        staking_account.reward_per_second = emitted_reward / duration as u64; // rounds down in favour of the pool
+        staking_account.emission_end_time = current_time
+            .checked_add(duration)
+            .ok_or(StakingError::CalculationError)?;
+        staking_account.admin_reward_amount = staking_account
+            .admin_reward_amount
+            .checked_add(received)
+            .ok_or(StakingError::CalculationError)?;
+
+        emit!(RewardsFunded {
+            pool: staking_account.key(),
+            admin: ctx.accounts.admin.key(),
+            amount: received,
+            reward_funds: staking_account.admin_reward_amount,
+            emission_end_time: staking_account.emission_end_time,
+            timestamp: current_time,
+        });
         Ok(())
     }
 
     ////////////////////////////////////////////////////////////////////////////////
-    //////////////////////////////////// REDEEM ////////////////////////////////////
+    /////////////////////////////// WITHDRAW REWARDS ///////////////////////////////
     ////////////////////////////////////////////////////////////////////////////////
 
-    /// Redeem instruction
-    /// This instruction is used to redeem staked tokens
+    /// Withdraw rewards instruction
+    /// This instruction is used by the admin to take back reward funds which are not owed to stakers,
+    /// e.g. the unspent budget of an ended campaign
+    /// Rewards accrued by the positions (paid or not) and the rest of a running emission can not be withdrawn
     ///
     /// # Arguments
     /// * `ctx` - context of the program
-    /// * `force_redeem` - force redeeming
-    pub fn redeem(ctx: Context<Redeem>, force_redeem: bool) -> Result<()> {
-        let staking_account = &mut ctx.accounts.staking_account;
+    /// * `amount` - amount of reward funds to withdraw
+    pub fn withdraw_rewards(ctx: Context<WithdrawRewards>, amount: u64) -> Result<()> {
+        require!(amount > 0, StakingError::ZeroValueError);
 
-        // Validate token mint matches staking account
+        let current_time = Clock::get()?.unix_timestamp;
+        let staking_account = &mut ctx.accounts.staking_account;
+        update_reward_per_share(staking_account, current_time)?;
         require!(
-            ctx.accounts.user_token_account.mint == staking_account.token_mint,
-            StakingError::InvalidArgument
+            amount <= unallocated_reward_funds(staking_account, current_time)?,
+            StakingError::RewardFundsAllocated
         );
 
-        let user_stake = &ctx.accounts.user_stake;
-        require!(user_stake.amount > 0, StakingError::NothingStaked);
-
-        let current_time = Clock::get()?.unix_timestamp * 1000; // Convert to milliseconds
-        let staking_duration = current_time - user_stake.start_time;
+        // the withdrawn funds are not owed to anyone, so the reward liability is unchanged
+        staking_account.admin_reward_amount = staking_account
+            .admin_reward_amount
+            .checked_sub(amount)
+            .ok_or(StakingError::CalculationError)?;
 
-        // Add minimum staking duration check if not force redeeming
-        if !force_redeem {
-            require!(
-                staking_duration >= staking_account.min_staking_duration * 1000, // Convert to milliseconds
-                StakingError::StakingDurationNotMet
-            );
+        // Transfer the rewards to the admin, signed by the staking account PDA
+        let staking_account = &ctx.accounts.staking_account;
+        let signer_seeds: &[&[&[u8]]] = &[staking_account_seeds!(staking_account)];
+        if has_reward_vault(staking_account) {
+            token_interface::transfer_checked(
+                ctx.accounts.transfer_reward_to_admin_ctx(signer_seeds)?,
+                amount,
+                reward_account(&ctx.accounts.reward_mint)?.decimals,
+            )?;
+        } else {
+            token_interface::transfer_checked(
+                ctx.accounts.transfer_to_admin_ctx(signer_seeds),
+                amount,
+                ctx.accounts.mint.decimals,
+            )?;
         }
 
+        emit!(RewardsWithdrawn {
+            pool: staking_account.key(),
+            admin: ctx.accounts.admin.key(),
+            amount,
+            reward_funds: staking_account.admin_reward_amount,
+            timestamp: current_time,
+        });
+        Ok(())
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////
+    //////////////////////////////// REWARD STREAMS ////////////////////////////////
+    ////////////////////////////////////////////////////////////////////////////////
+
+    /// Add reward stream instruction
+    /// This instruction is used by the admin to layer an extra reward token on top of the rewards of the pool
+    /// (e.g. a partner incentive), `amount` tokens being emitted between `start_time` and `end_time`
+    /// and shared pro rata to the stake weights (tier multipliers included)
+    /// Streams can be added to a running pool, existing positions earn from the start of the stream without any migration
+    /// Stream rewards are paid out together with the rewards of the pool, from the `remaining_accounts`
+    /// The stream takes the first slot freed by `retire_reward_stream`, or the next one
+    ///
+    /// # Arguments
+    /// * `ctx` - context of the program
+    /// * `index` - slot taken by the stream (the free one), part of the seeds of its vault
+    /// * `amount` - amount of reward tokens to emit
+    /// * `start_time` - time in seconds at which the emission starts (now if in the past)
+    /// * `end_time` - time in seconds at which the emission ends
+    pub fn add_reward_stream(
+        ctx: Context<AddRewardStream>,
+        index: u8,
+        amount: u64,
+        start_time: i64,
+        end_time: i64,
+    ) -> Result<()> {
         require!(
-            user_stake.amount >= u64::MIN
-                && staking_duration >= 0
-                && staking_account.reward_rate >= u8::MIN,
-            StakingError::ZeroValueError
+            ctx.accounts.staking_account.pool_state == PoolState::Active,
+            StakingError::PoolNotActive
         );
-
-        let calculated_reward = calculate_reward(
-            user_stake.amount,
-            staking_duration / 1000,
-            staking_account.reward_rate,
+        let free_slot = free_stream_slot(&ctx.accounts.staking_account);
+        require!(
+            (free_slot as usize) < MAX_REWARD_STREAMS,
+            StakingError::TooManyRewardStreams
         );
+        require!(index == free_slot, StakingError::InvalidArgument);
+        let index = index as usize;
 
-        // Validate and adjust reward based on available funds
-        let reward = if staking_account.admin_reward_amount < calculated_reward && force_redeem {
-            0 as u64
-        } else if force_redeem {
-            0 as u64
-        } else {
-            require!(
-                staking_account.admin_reward_amount >= calculated_reward,
-                StakingError::InsufficientRewardFunds
-            );
-            calculated_reward
-        };
+        let current_time = Clock::get()?.unix_timestamp;
+        let start_time = start_time.max(current_time);
+        require!(end_time > start_time, StakingError::InvalidArgument);
 
-        msg!("reward {}", reward);
+        // Transfer the stream rewards to its vault, only what the vault received is emitted
+        let vault_balance = ctx.accounts.reward_stream_vault.amount;
+        token_interface::transfer_checked(
+            ctx.accounts.fund_stream_from_admin_ctx(),
+            amount,
+            ctx.accounts.stream_mint.decimals,
+        )?;
+        let received = received_amount(&mut ctx.accounts.reward_stream_vault, vault_balance)?;
+        let reward_per_second = received / (end_time - start_time) as u64; // rounds down in favour of the pool
+        require!(reward_per_second > 0, StakingError::ZeroValueError);
 
-        // Update admin reward balance
-        staking_account.admin_reward_amount = staking_account
-            .admin_reward_amount
-            .checked_sub(reward)
+        let staking_account = &mut ctx.accounts.staking_account;
+        update_reward_per_share(staking_account, current_time)?;
+        staking_account.reward_streams[index] = RewardStream {
+            mint: ctx.accounts.stream_mint.key(),
+            vault: ctx.accounts.reward_stream_vault.key(),
+            reward_per_second,
+            start_time,
+            end_time,
+            acc_reward_per_share: 0,
+            last_update_time: start_time,
+            id: staking_account.next_stream_id,
+        };
+        staking_account.next_stream_id = staking_account
+            .next_stream_id
+            .checked_add(1)
             .ok_or(StakingError::CalculationError)?;
+        staking_account.stream_count = staking_account.stream_count.max(index as u8 + 1);
 
-        // Calculate total amount with overflow protection
-        let total_amount = user_stake
-            .amount
-            .checked_add(reward)
-            .ok_or(StakingError::CalculationError)?;
+        emit!(RewardStreamAdded {
+            pool: staking_account.key(),
+            index: index as u8,
+            stream_id: staking_account.reward_streams[index].id,
+            mint: ctx.accounts.stream_mint.key(),
+            vault: ctx.accounts.reward_stream_vault.key(),
+            amount: received,
+            reward_per_second,
+            start_time,
+            end_time,
+        });
+        Ok(())
+    }
 
-        // Transfer staked tokens and rewards back to the user
+    /// Retire reward stream instruction
+    /// This instruction is used by the admin to free the slot of a finished reward stream
+    /// Stakers have `STREAM_CLAIM_PERIOD` after the end of the stream to claim its rewards, the rewards left
+    /// in its vault (unclaimed or undistributed) are then sent to the admin, the vault is closed and its slot
+    /// can be taken by a new stream (rewards of the retired stream still pending in positions are forfeited)
+    ///
+    /// # Arguments
+    /// * `ctx` - context of the program
+    /// * `index` - slot of the reward stream to retire
+    pub fn retire_reward_stream(ctx: Context<RetireRewardStream>, index: u8) -> Result<()> {
+        let staking_account = &ctx.accounts.staking_account;
+        let stream = staking_account.reward_streams[index as usize];
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(
+            current_time
+                >= stream
+                    .end_time
+                    .checked_add(STREAM_CLAIM_PERIOD)
+                    .ok_or(StakingError::CalculationError)?,
+            StakingError::RewardStreamActive
+        );
 
-        token::transfer(ctx.accounts.transfer_to_user_ctx(), total_amount)?;
+        // Send the rewards left in the vault of the stream to the admin and close it
+        let signer_seeds: &[&[&[u8]]] = &[staking_account_seeds!(staking_account)];
+        let remaining_amount = ctx.accounts.reward_stream_vault.amount;
+        if remaining_amount > 0 {
+            token_interface::transfer_checked(
+                ctx.accounts.transfer_to_admin_ctx(signer_seeds),
+                remaining_amount,
+                ctx.accounts.stream_mint.decimals,
+            )?;
+        }
+        token_interface::close_account(ctx.accounts.close_vault_ctx(signer_seeds))?;
 
-        // Account closure will be handled automatically by the Solana runtime
-        // because of the `close = user` attribute on the user_stake account.
+        // Free the slot, trailing free slots are not iterated anymore
+        let staking_account = &mut ctx.accounts.staking_account;
+        staking_account.reward_streams[index as usize] = RewardStream::default();
+        while staking_account.stream_count > 0
+            && staking_account.reward_streams[staking_account.stream_count as usize - 1].id == 0
+        {
+            staking_account.stream_count -= 1;
+        }
 
+        emit!(RewardStreamRetired {
+            pool: staking_account.key(),
+            index,
+            stream_id: stream.id,
+            remaining_amount,
+            timestamp: current_time,
+        });
         Ok(())
     }
 
-    /// Close instruction
-    /// This instruction is used to close the account and reclaim rent
-    pub fn close(_ctx: Context<Close>) -> Result<()> {
-        // Close the account and reclaim rent
+    ////////////////////////////////////////////////////////////////////////////////
+    //////////////////////////////////// STAKE ////////////////////////////////////
+    ////////////////////////////////////////////////////////////////////////////////
+
+    /// Stake instruction
+    /// This instruction is used to stake tokens
+    /// Every call opens a new position numbered by the user's position counter,
+    /// so a user can hold several positions (each with its own start time) in the same pool
+    ///
+    /// # Arguments
+    /// * `ctx` - context of the program
+    /// * `amount` - amount to stake
+    /// * `tier` - index of the lockup tier of the position
+    pub fn stake(ctx: Context<Stake>, amount: u64, tier: u8) -> Result<()> {
+        require!(
+            tier < ctx.accounts.staking_account.tier_count,
+            StakingError::InvalidTier
+        );
+        require!(
+            ctx.accounts.staking_account.pool_state == PoolState::Active,
+            StakingError::PoolNotActive
+        );
+
+        // Transfer tokens to the staking account, only what the vault received is staked
+        let vault_balance = ctx.accounts.staking_token_account.amount;
+        token_interface::transfer_checked(
+            ctx.accounts.transfer_to_stake_ctx(),
+            amount,
+            ctx.accounts.mint.decimals,
+        )?;
+        let received = received_amount(&mut ctx.accounts.staking_token_account, vault_balance)?;
+        require!(received > 0, StakingError::ZeroValueError);
+
+        // Reserve the position id used in the user stake seeds
+        let user_stake_counter = &mut ctx.accounts.user_stake_counter;
+        let position_id = user_stake_counter.next_position_id;
+        user_stake_counter.pool = ctx.accounts.staking_account.key();
+        user_stake_counter.user = ctx.accounts.user.key();
+        user_stake_counter.next_position_id = position_id
+            .checked_add(1)
+            .ok_or(StakingError::CalculationError)?;
+
+        let staking_account = &mut ctx.accounts.staking_account;
+        let current_time = Clock::get()?.unix_timestamp;
+        update_reward_per_share(staking_account, current_time)?;
+        let lockup_tier = staking_account.tiers[tier as usize];
+
+        let user_stake = &mut ctx.accounts.user_stake;
+
+        // Initialize user's stake data
+        user_stake.pool = staking_account.key();
+        user_stake.user = ctx.accounts.user.key();
+        user_stake.position_id = position_id;
+        user_stake.tier = tier;
+        // the lock and multiplier are fixed when the position is opened
+        user_stake.lock_duration = lockup_tier
+            .lock_duration
+            .max(staking_account.min_staking_duration);
+        user_stake.reward_multiplier_bps = lockup_tier.reward_multiplier_bps;
+        user_stake.start_time = current_time;
+        user_stake.last_reward_time = current_time;
+        user_stake.apy_rate_index = apy_rate_index(staking_account, current_time)?;
+        user_stake.pending_reward = 0;
+        user_stake.last_claim_time = 0;
+        user_stake.rewards_claimed = 0;
+        user_stake.auto_compound = false;
+        user_stake.unbonding_amount = 0;
+        user_stake.unlock_at = 0;
+        user_stake.unbonding_cooldown = 0;
+        user_stake.stream_pending_rewards = [0; MAX_REWARD_STREAMS];
+        user_stake.stream_ids = staking_account.reward_streams.map(|stream| stream.id);
+        user_stake.version = USER_STAKE_VERSION;
+        set_stake_amount(staking_account, user_stake, received)?;
+
+        emit!(Staked {
+            pool: staking_account.key(),
+            user: user_stake.user,
+            user_stake: user_stake.key(),
+            position_id,
+            amount: received,
+            tier,
+            lock_duration: user_stake.lock_duration,
+            total_staked: staking_account.total_staked,
+            timestamp: current_time,
+        });
         Ok(())
     }
-}
 
-//////////////////////////////////////////////////////////////////////////////////////////
-///////////////////////////////// PROGRAM STATES STRUCTS /////////////////////////////////
-//////////////////////////////////////////////////////////////////////////////////////////
+    ////////////////////////////////////////////////////////////////////////////////
+    //////////////////////////////// INCREASE STAKE ////////////////////////////////
+    ////////////////////////////////////////////////////////////////////////////////
 
-/// Staking account struct
-/// This struct is used to define the state of the staking pool
-///
-/// # Fields
-/// * `id` - unique id for each pool (associated with user)
-/// * `admin` - admin account (signer)
-/// * `reward_rate` - annual percentage yield (APY) in percentage (0-100)
-/// * `bump` - bump for the PDA (multiple PDAs can be created with the same seeds)
-/// * `token_mint` - program_id (address) of specific token which is allowed stake
-/// * `admin_reward_amount` - admin reward amount
-/// * `min_staking_duration` - minimum staking duration in seconds
-#[account]
-pub struct StakingAccount {
-    pub admin: Pubkey,
-    pub reward_rate: u8,
-    pub bump: u8,
-    pub token_mint: Pubkey,
-    pub admin_reward_amount: u64,
-    pub min_staking_duration: i64, // Minimum staking duration in seconds
-}
+    /// Increase stake instruction
+    /// This instruction is used to add tokens to an existing position
+    /// Rewards accrued so far are settled at the old balance, the start time (lock) of the position is kept
+    ///
+    /// # Arguments
+    /// * `ctx` - context of the program
+    /// * `amount` - amount to add to the position
+    pub fn increase_stake(ctx: Context<IncreaseStake>, amount: u64) -> Result<()> {
+        require!(amount > 0, StakingError::ZeroValueError);
+        require!(
+            ctx.accounts.staking_account.pool_state == PoolState::Active,
+            StakingError::PoolNotActive
+        );
 
-/// User stake account struct
-/// This struct is used to define the state of the user stake account
-///
-/// # Fields
-/// * `amount` - Amount of tokens staked
-/// * `start_time` - Start time of staking in milliseconds
-#[account]
-pub struct UserStake {
-    pub amount: u64,
-    pub start_time: i64,
-}
+        // Transfer tokens to the staking account, only what the vault received is staked
+        let vault_balance = ctx.accounts.staking_token_account.amount;
+        token_interface::transfer_checked(
+            ctx.accounts.transfer_to_stake_ctx(),
+            amount,
+            ctx.accounts.mint.decimals,
+        )?;
+        let received = received_amount(&mut ctx.accounts.staking_token_account, vault_balance)?;
+        require!(received > 0, StakingError::ZeroValueError);
 
-//////////////////////////////////////////////////////////////////////////////////////////
-////////////////////////////////// INSTRUCTIONS STRUCTS //////////////////////////////////
-//////////////////////////////////////////////////////////////////////////////////////////
+        let current_time = Clock::get()?.unix_timestamp;
 
-/// Initialize instruction structs
-/// This struct is used to define the accounts and instructions required for the initialize instruction
-/// This instruction is used to create a new staking pool
-///
-/// # Fields
-/// * `staking_account` - staking pool account PDA (which is created in the initialize function)
-/// * `admin` - admin account (signer)
-/// * `system_program` - system program used to create the staking_account PDA
-#[derive(Accounts)]
-pub struct Initialize<'info> {
-    #[account(
-        init, // means initialize the account
-        payer = admin, // means the admin account will pay for the transaction and rent
-        seeds = [admin.key.as_ref(),b"staking_account"], // means it will create the PDA
-        bump, // multiple PDAs can be created with the same seeds
-        space = size_of::<StakingAccount>() + 8 // size of the account 8 for descriptor
-    )]
-    pub staking_account: Account<'info, StakingAccount>, // creates a new account (staking pool)
-    #[account(mut)]
-    pub admin: Signer<'info>, // admin account
-    pub system_program: Program<'info, System>, // system program used to create the staking_account PDA
-}
+        let staking_account = &mut ctx.accounts.staking_account;
+        update_reward_per_share(staking_account, current_time)?;
 
-/// Fund reward instruction structs
-/// This struct is used to define the accounts and instructions required for the fund reward instruction
-///
-/// # Fields
-/// * `staking_account` - staking pool account PDA (which is created in the initialize function)
-/// * `staking_token_account` - staking token account which holds the SPL tokens for staking (shared by all users within the pool)
-/// * `admin` - admin account (signer)
-/// * `admin_token_account` - admin token account which holds the required SPL tokens
-/// * `token_program` - token program used to transfer tokens
-#[derive(Accounts)]
-pub struct FundRewards<'info> {
-    #[account(mut)]
-    pub staking_account: Account<'info, StakingAccount>, // staking pool account (which is created in the initialize function)
-    #[account(mut)]
-    pub staking_token_account: Account<'info, TokenAccount>, // staking token account which holds the SPL tokens for staking (shared by all users within the pool)
-    #[account(mut)]
-    pub admin: Signer<'info>, // admin account (signer)
-    #[account(mut)]
-    pub admin_token_account: Account<'info, TokenAccount>, // admin token account which holds the required SPL tokens
-    pub token_program: Program<'info, Token>, // token program used to transfer tokens securly
-}
+        // Settle the reward earned by the old balance before it changes
+        let user_stake = &mut ctx.accounts.user_stake;
+        settle_reward(staking_account, user_stake, current_time)?;
+        let new_amount = user_stake
+            .amount
+            .checked_add(received)
+            .ok_or(StakingError::CalculationError)?;
+        set_stake_amount(staking_account, user_stake, new_amount)?;
 
-impl<'info> FundRewards<'info> {
-    /// This function creates CPI context for transferring tokens from admin to staking account
-    ///
-    /// # Example
-    /// ```rs
-    /// let ctx = FundRewards {...}
-    /// let cpi_ctx = ctx.fund_reward_from_admin_ctx();
-    /// token::transfer(cpi_ctx, amount)?;
-    /// ```
-    pub fn fund_reward_from_admin_ctx(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
-        CpiContext::new(
-            self.token_program.to_account_info(), // token program account info (used for token transferring)
-            Transfer {
-                from: self.admin_token_account.to_account_info(), // from admin's token account
-                to: self.staking_token_account.to_account_info(), // to staking pool's token account
-                authority: self.admin.to_account_info(), // authority to transfer tokens (signer)
-            },
-        )
+        emit!(StakeIncreased {
+            pool: staking_account.key(),
+            user: user_stake.user,
+            user_stake: user_stake.key(),
+            position_id: user_stake.position_id,
+            amount: received,
+            new_amount,
+            total_staked: staking_account.total_staked,
+            timestamp: current_time,
+        });
+        Ok(())
     }
-}
 
-/// Stake instruction structs
-/// This struct is used to define the accounts and instructions required for the stake instruction
-///
-/// # Fields
-/// * `staking_account` - staking pool account PDA (which is created in the initialize function)
-/// * `user_stake` - user stake account PDA (which is created in the stake function)
-/// * `user` - user account (signer)
-/// * `user_token_account` - user token account which holds the required SPL tokens
-/// * `staking_token_account` - staking token account which holds the SPL tokens for staking (shared by all users within the pool)
-/// * `token_program` - token program used to transfer tokens
-///
-/// # Example
-///
-/// ```rs
-/// let ctx = Stake {
-///    staking_account: staking_account.to_account_info(),
-///    user_stake: user_stake.to_account_info(),
+    ////////////////////////////////////////////////////////////////////////////////
+    /////////////////////////////// PARTIAL UNSTAKE ///////////////////////////////
+    ////////////////////////////////////////////////////////////////////////////////
+
+    /// Partial unstake instruction
+    /// This instruction is used to withdraw part of the principal of a position
+    /// All rewards accrued so far are paid out and the position stays open with the remaining amount
+    /// The rewards of the reward streams are paid out as well from the stream accounts in `remaining_accounts`,
+    /// a stream whose destination is frozen or closed does not block the exit and its rewards stay pending (see `pay_stream_rewards`)
+    ///
+    /// # Arguments
+    /// * `ctx` - context of the program
+    /// * `amount` - amount of principal to withdraw (must be lower than the staked amount, use `redeem` to withdraw everything)
+    pub fn partial_unstake<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PartialUnstake<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        let staking_account = &mut ctx.accounts.staking_account;
+        let user_stake = &mut ctx.accounts.user_stake;
+
+        require!(amount > 0, StakingError::ZeroValueError);
+        require!(amount < user_stake.amount, StakingError::InvalidArgument);
+        require!(
+            staking_account.unbonding_cooldown == 0 || staking_account.shutdown_time != 0,
+            StakingError::UnbondingRequired
+        );
+
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(
+            current_time - user_stake.start_time >= user_stake.lock_duration
+                || staking_account.shutdown_time != 0,
+            StakingError::StakingDurationNotMet
+        );
+
+        update_reward_per_share(staking_account, current_time)?;
+        let reward = settle_reward(staking_account, user_stake, current_time)?;
+
+        // Update admin reward balance and the position
+        take_reward_funds(staking_account, reward)?;
+        record_claim(user_stake, reward, current_time)?;
+        let remaining_amount = user_stake.amount - amount;
+        set_stake_amount(staking_account, user_stake, remaining_amount)?;
+
+        // Transfer the withdrawn principal and rewards to the user
+        pay_reward(reward_payout!(ctx.accounts), amount, reward)?;
+
+        // Pay out the rewards of the reward streams
+        let stream_rewards = pay_stream_rewards(
+            &ctx.accounts.staking_account,
+            &mut ctx.accounts.user_stake,
+            ctx.remaining_accounts,
+            &ctx.accounts.token_program,
+            false,
+        )?;
+
+        emit!(PartiallyUnstaked {
+            pool: ctx.accounts.staking_account.key(),
+            user: ctx.accounts.user.key(),
+            user_stake: ctx.accounts.user_stake.key(),
+            position_id: ctx.accounts.user_stake.position_id,
+            amount,
+            reward,
+            stream_rewards,
+            remaining_amount,
+            total_staked: ctx.accounts.staking_account.total_staked,
+            timestamp: current_time,
+        });
+        Ok(())
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////
+    ////////////////////////////////// UNBONDING ///////////////////////////////////
+    ////////////////////////////////////////////////////////////////////////////////
+
+    /// Request unstake instruction
+    /// This instruction is used to start unbonding part (or all) of the principal of a position
+    /// The rewards accrued so far are paid out and the unbonding amount stops earning rewards,
+    /// it can be withdrawn with `withdraw_unbonded` once the cooldown has elapsed
+    /// The cooldown of the pool is captured by the first pending request of the position, so a later config update
+    /// does not change it, a new request restarts the captured cooldown of the whole unbonding amount of the position
+    /// The rewards of the reward streams are paid out as well from the stream accounts in `remaining_accounts`,
+    /// a stream whose destination is frozen or closed does not block the exit and its rewards stay pending (see `pay_stream_rewards`)
+    ///
+    /// # Arguments
+    /// * `ctx` - context of the program
+    /// * `amount` - amount of principal to unbond
+    pub fn request_unstake<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RequestUnstake<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        let staking_account = &mut ctx.accounts.staking_account;
+        let user_stake = &mut ctx.accounts.user_stake;
+
+        require!(amount > 0, StakingError::ZeroValueError);
+        require!(amount <= user_stake.amount, StakingError::InvalidArgument);
+
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(
+            current_time - user_stake.start_time >= user_stake.lock_duration,
+            StakingError::StakingDurationNotMet
+        );
+
+        update_reward_per_share(staking_account, current_time)?;
+        let reward = settle_reward(staking_account, user_stake, current_time)?;
+
+        // Pay out the rewards and move the amount from the stake to the unbonding queue
+        take_reward_funds(staking_account, reward)?;
+        record_claim(user_stake, reward, current_time)?;
+        let remaining_amount = user_stake.amount - amount;
+        set_stake_amount(staking_account, user_stake, remaining_amount)?;
+
+        if user_stake.unbonding_amount == 0 {
+            user_stake.unbonding_cooldown = staking_account.unbonding_cooldown;
+        }
+        user_stake.unbonding_amount = user_stake
+            .unbonding_amount
+            .checked_add(amount)
+            .ok_or(StakingError::CalculationError)?;
+        user_stake.unlock_at = user_stake
+            .unbonding_cooldown
+            .checked_add(current_time)
+            .ok_or(StakingError::CalculationError)?;
+        staking_account.total_unbonding = staking_account
+            .total_unbonding
+            .checked_add(amount)
+            .ok_or(StakingError::CalculationError)?;
+
+        // Transfer the rewards to the user
+        pay_reward(reward_payout!(ctx.accounts), 0, reward)?;
+
+        // Pay out the rewards of the reward streams
+        let stream_rewards = pay_stream_rewards(
+            &ctx.accounts.staking_account,
+            &mut ctx.accounts.user_stake,
+            ctx.remaining_accounts,
+            &ctx.accounts.token_program,
+            false,
+        )?;
+
+        emit!(UnstakeRequested {
+            pool: ctx.accounts.staking_account.key(),
+            user: ctx.accounts.user.key(),
+            user_stake: ctx.accounts.user_stake.key(),
+            position_id: ctx.accounts.user_stake.position_id,
+            amount,
+            reward,
+            stream_rewards,
+            unbonding_amount: ctx.accounts.user_stake.unbonding_amount,
+            unlock_at: ctx.accounts.user_stake.unlock_at,
+            total_staked: ctx.accounts.staking_account.total_staked,
+            timestamp: current_time,
+        });
+        Ok(())
+    }
+
+    /// Withdraw unbonded instruction
+    /// This instruction is used to withdraw the unbonding amount of a position once its cooldown has elapsed
+    /// The position is closed (and its rent returned) when nothing is left staked in it
+    ///
+    /// # Arguments
+    /// * `ctx` - context of the program
+    pub fn withdraw_unbonded(ctx: Context<WithdrawUnbonded>) -> Result<()> {
+        let staking_account = &mut ctx.accounts.staking_account;
+        let user_stake = &mut ctx.accounts.user_stake;
+
+        let amount = user_stake.unbonding_amount;
+        require!(amount > 0, StakingError::NothingUnbonding);
+
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(
+            current_time >= user_stake.unlock_at,
+            StakingError::CooldownNotMet
+        );
+
+        user_stake.unbonding_amount = 0;
+        user_stake.unlock_at = 0;
+        user_stake.unbonding_cooldown = 0;
+        staking_account.total_unbonding = staking_account
+            .total_unbonding
+            .checked_sub(amount)
+            .ok_or(StakingError::CalculationError)?;
+
+        // Transfer the unbonded tokens to the user, signed by the staking account PDA
+        transfer_from_vault(
+            &ctx.accounts.staking_account,
+            &ctx.accounts.token_program,
+            ctx.accounts.staking_token_account.to_account_info(),
+            ctx.accounts.user_token_account.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.mint.decimals,
+            amount,
+        )?;
+
+        emit!(UnbondedWithdrawn {
+            pool: ctx.accounts.staking_account.key(),
+            user: ctx.accounts.user.key(),
+            user_stake: ctx.accounts.user_stake.key(),
+            position_id: ctx.accounts.user_stake.position_id,
+            amount,
+            timestamp: current_time,
+        });
+
+        // Close the emptied position and return its rent to the user, unless it still holds stream rewards to claim
+        if ctx.accounts.user_stake.amount == 0 && !has_stream_rewards(&ctx.accounts.user_stake) {
+            ctx.accounts
+                .user_stake
+                .close(ctx.accounts.user.to_account_info())?;
+        }
+
+        Ok(())
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////
+    //////////////////////////////// CLAIM REWARDS ////////////////////////////////
+    ////////////////////////////////////////////////////////////////////////////////
+
+    /// Claim rewards instruction
+    /// This instruction is used to pay out the rewards accrued by a position without unstaking
+    /// The principal and the lock of the position are left untouched
+    /// The rewards of the reward streams are paid out as well, `remaining_accounts` holding the accounts of every stream of the pool (see `pay_stream_rewards`)
+    /// A position kept open by an exit for its stream rewards is closed once they are claimed (or forfeited by the retirement of their stream)
+    ///
+    /// # Arguments
+    /// * `ctx` - context of the program
+    pub fn claim_rewards<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimRewards<'info>>,
+    ) -> Result<()> {
+        let staking_account = &mut ctx.accounts.staking_account;
+        let user_stake = &mut ctx.accounts.user_stake;
+        require!(
+            user_stake.amount > 0 || has_stream_rewards(user_stake),
+            StakingError::NothingStaked
+        );
+        let emptied = user_stake.amount == 0 && user_stake.unbonding_amount == 0;
+
+        let current_time = Clock::get()?.unix_timestamp;
+
+        update_reward_per_share(staking_account, current_time)?;
+        let reward = settle_reward(staking_account, user_stake, current_time)?;
+        require!(
+            reward > 0 || has_stream_rewards(user_stake) || emptied,
+            StakingError::ZeroValueError
+        );
+
+        // Update admin reward balance and the position
+        take_reward_funds(staking_account, reward)?;
+        record_claim(user_stake, reward, current_time)?;
+        reset_reward_debt(user_stake, staking_account)?;
+
+        // Transfer the rewards to the user
+        pay_reward(reward_payout!(ctx.accounts), 0, reward)?;
+
+        // Pay out the rewards of the reward streams
+        let stream_rewards = pay_stream_rewards(
+            &ctx.accounts.staking_account,
+            &mut ctx.accounts.user_stake,
+            ctx.remaining_accounts,
+            &ctx.accounts.token_program,
+            true,
+        )?;
+
+        emit!(RewardsClaimed {
+            pool: ctx.accounts.staking_account.key(),
+            user: ctx.accounts.user.key(),
+            user_stake: ctx.accounts.user_stake.key(),
+            position_id: ctx.accounts.user_stake.position_id,
+            reward,
+            stream_rewards,
+            timestamp: current_time,
+        });
+
+        // Close the position emptied by an exit and return its rent to the user
+        if emptied {
+            ctx.accounts
+                .user_stake
+                .close(ctx.accounts.user.to_account_info())?;
+        }
+        Ok(())
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////
+    //////////////////////////////// PREVIEW REWARD ////////////////////////////////
+    ////////////////////////////////////////////////////////////////////////////////
+
+    /// Preview reward instruction
+    /// This read-only instruction is used to compute what a position would receive at the current time,
+    /// settling copies of the accounts the same way `claim_rewards` and `redeem` do (no account is modified)
+    /// The preview is returned as return data, clients read it by simulating the transaction
+    ///
+    /// # Arguments
+    /// * `ctx` - context of the program
+    pub fn preview_reward(ctx: Context<PreviewReward>) -> Result<RewardPreview> {
+        let mut staking_account = (**ctx.accounts.staking_account).clone();
+        let mut user_stake = (*ctx.accounts.user_stake).clone();
+
+        let current_time = Clock::get()?.unix_timestamp;
+
+        update_reward_per_share(&mut staking_account, current_time)?;
+        let reward = settle_reward(&mut staking_account, &mut user_stake, current_time)?;
+
+        let unlock_time = user_stake
+            .start_time
+            .checked_add(user_stake.lock_duration)
+            .ok_or(StakingError::CalculationError)?;
+        // locks are lifted when the pool shuts down
+        let matured = current_time >= unlock_time || staking_account.shutdown_time != 0;
+
+        // Apply the penalty policy of the pool as a forced redeem would
+        let (forced_reward, penalty) = if matured {
+            (reward, 0)
+        } else {
+            let (forced_reward, penalty) = early_withdrawal_penalty(
+                &staking_account,
+                &user_stake,
+                reward,
+                current_time - user_stake.start_time,
+            )?;
+            (
+                forced_reward.min(staking_account.admin_reward_amount),
+                penalty,
+            )
+        };
+
+        Ok(RewardPreview {
+            timestamp: current_time,
+            reward,
+            funded: staking_account.admin_reward_amount >= reward,
+            stream_rewards: user_stake.stream_pending_rewards,
+            unlock_time,
+            matured,
+            forced_reward,
+            penalty,
+        })
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////
+    /////////////////////////////////// COMPOUND ///////////////////////////////////
+    ////////////////////////////////////////////////////////////////////////////////
+
+    /// Compound instruction
+    /// This instruction is used to restake the rewards accrued by a position
+    /// The rewards already sit in the vault, so they are moved from the reward funds to the principal without any transfer
+    /// The owner can always compound, anyone else (a cranker) only if the position opted in with `set_auto_compound`,
+    /// in which case the cranker receives `compound_tip_bps` of the reward
+    ///
+    /// # Arguments
+    /// * `ctx` - context of the program
+    pub fn compound(ctx: Context<Compound>) -> Result<()> {
+        let staking_account = &mut ctx.accounts.staking_account;
+        let user_stake = &mut ctx.accounts.user_stake;
+        require!(user_stake.amount > 0, StakingError::NothingStaked);
+
+        require!(
+            staking_account.pool_state == PoolState::Active,
+            StakingError::PoolNotActive
+        );
+        // rewards in another token can not be added to the principal
+        require!(
+            !has_reward_vault(staking_account),
+            StakingError::SeparateRewardMint
+        );
+
+        let is_cranker = ctx.accounts.authority.key() != user_stake.user;
+        require!(
+            !is_cranker || user_stake.auto_compound,
+            StakingError::AutoCompoundDisabled
+        );
+
+        let current_time = Clock::get()?.unix_timestamp;
+
+        update_reward_per_share(staking_account, current_time)?;
+        let reward = settle_reward(staking_account, user_stake, current_time)?;
+        require!(reward > 0, StakingError::ZeroValueError);
+
+        // tip paid to the cranker, rounded down in favour of the user
+        let tip = if is_cranker {
+            (reward as u128 * staking_account.compound_tip_bps as u128 / BPS_DENOMINATOR) as u64
+        } else {
+            0
+        };
+        let compounded = reward - tip;
+
+        // Move the reward into the principal of the position
+        take_reward_funds(staking_account, reward)?;
+        record_claim(user_stake, reward, current_time)?;
+        let new_amount = user_stake
+            .amount
+            .checked_add(compounded)
+            .ok_or(StakingError::CalculationError)?;
+        set_stake_amount(staking_account, user_stake, new_amount)?;
+
+        emit!(Compounded {
+            pool: staking_account.key(),
+            user: user_stake.user,
+            user_stake: user_stake.key(),
+            position_id: user_stake.position_id,
+            authority: ctx.accounts.authority.key(),
+            amount: compounded,
+            tip,
+            total_staked: staking_account.total_staked,
+            timestamp: current_time,
+        });
+
+        if tip > 0 {
+            let staking_account = &ctx.accounts.staking_account;
+            token_interface::transfer_checked(
+                ctx.accounts
+                    .transfer_tip_ctx(&[staking_account_seeds!(staking_account)])?,
+                tip,
+                ctx.accounts.mint.decimals,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Set auto compound instruction
+    /// This instruction is used by the owner of a position to allow (or forbid) anyone to compound it
+    ///
+    /// # Arguments
+    /// * `ctx` - context of the program
+    /// * `enabled` - whether crankers may compound the position
+    pub fn set_auto_compound(ctx: Context<SetAutoCompound>, enabled: bool) -> Result<()> {
+        ctx.accounts.user_stake.auto_compound = enabled;
+
+        emit!(AutoCompoundSet {
+            pool: ctx.accounts.staking_account.key(),
+            user: ctx.accounts.user.key(),
+            user_stake: ctx.accounts.user_stake.key(),
+            enabled,
+        });
+        Ok(())
+    }
+
+    /// Set compound tip instruction
+    /// This instruction is used by the admin to set the share of the reward paid to crankers
+    ///
+    /// # Arguments
+    /// * `ctx` - context of the program
+    /// * `compound_tip_bps` - tip in basis points of the compounded reward (at most `MAX_COMPOUND_TIP_BPS`)
+    pub fn set_compound_tip(ctx: Context<SetCompoundTip>, compound_tip_bps: u16) -> Result<()> {
+        require!(
+            compound_tip_bps <= MAX_COMPOUND_TIP_BPS,
+            StakingError::InvalidArgument
+        );
+        ctx.accounts.staking_account.compound_tip_bps = compound_tip_bps;
+
+        emit!(CompoundTipSet {
+            pool: ctx.accounts.staking_account.key(),
+            compound_tip_bps,
+        });
+        Ok(())
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////
+    ////////////////////////////// SET LOCKUP TIERS //////////////////////////////
+    ////////////////////////////////////////////////////////////////////////////////
+
+    /// Set lockup tiers instruction
+    /// This instruction is used by the admin to configure the lockup tiers users can pick when staking
+    /// Existing positions keep the lock and multiplier of the tier they were opened with
+    ///
+    /// # Arguments
+    /// * `ctx` - context of the program
+    /// * `tiers` - lockup tiers (1 to `MAX_LOCKUP_TIERS`), e.g. flexible / 30 / 90 / 180 / 365 days
+    pub fn set_lockup_tiers(ctx: Context<SetLockupTiers>, tiers: Vec<LockupTier>) -> Result<()> {
+        require!(
+            !tiers.is_empty() && tiers.len() <= MAX_LOCKUP_TIERS,
+            StakingError::InvalidTier
+        );
+        require!(
+            tiers
+                .iter()
+                .all(|tier| tier.lock_duration >= 0 && tier.reward_multiplier_bps > 0),
+            StakingError::InvalidTier
+        );
+
+        let staking_account = &mut ctx.accounts.staking_account;
+        staking_account.tiers = [LockupTier::default(); MAX_LOCKUP_TIERS];
+        staking_account.tiers[..tiers.len()].copy_from_slice(&tiers);
+        staking_account.tier_count = tiers.len() as u8;
+
+        emit!(LockupTiersUpdated {
+            pool: staking_account.key(),
+            tiers,
+        });
+        Ok(())
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////
+    ///////////////////////////// SET PENALTY POLICY /////////////////////////////
+    ////////////////////////////////////////////////////////////////////////////////
+
+    /// Set penalty policy instruction
+    /// This instruction is used by the admin to configure the penalty of force redeeming before the lock ends
+    ///
+    /// # Arguments
+    /// * `ctx` - context of the program
+    /// * `penalty_policy` - how the penalty is computed
+    /// * `penalty_bps` - penalty rate in basis points (of the principal, or of the reward for `ProRatedReward`)
+    /// * `penalty_destination` - where principal penalties go (the treasury being the `treasury_token_account` of the context)
+    pub fn set_penalty_policy(
+        ctx: Context<SetPenaltyPolicy>,
+        penalty_policy: PenaltyPolicy,
+        penalty_bps: u16,
+        penalty_destination: PenaltyDestination,
+    ) -> Result<()> {
+        require!(
+            penalty_bps as u128 <= BPS_DENOMINATOR,
+            StakingError::InvalidArgument
+        );
+
+        // the treasury must hold the staked token, or the penalties could not be transferred to it
+        let treasury = match penalty_destination {
+            PenaltyDestination::Treasury => ctx
+                .accounts
+                .treasury_token_account
+                .as_ref()
+                .ok_or(StakingError::InvalidArgument)?
+                .key(),
+            _ => Pubkey::default(),
+        };
+
+        let staking_account = &mut ctx.accounts.staking_account;
+        staking_account.penalty_policy = penalty_policy;
+        staking_account.penalty_bps = penalty_bps;
+        staking_account.penalty_destination = penalty_destination;
+        staking_account.treasury = treasury;
+
+        emit!(PenaltyPolicyUpdated {
+            pool: staking_account.key(),
+            penalty_policy,
+            penalty_bps,
+            penalty_destination,
+            treasury,
+        });
+        Ok(())
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////
+    //////////////////////////////// CONFIG UPDATE ////////////////////////////////
+    ////////////////////////////////////////////////////////////////////////////////
+
+    /// Propose config update instruction
+    /// This instruction is used by the admin to stage new pool parameters, applied by `apply_config_update`
+    /// once the current `config_timelock` has elapsed, so stakers get advance notice of the change
+    /// A new proposal replaces the staged one and restarts the timelock
+    ///
+    /// # Arguments
+    /// * `ctx` - context of the program
+    /// * `config` - new reward rate, minimum staking duration, timelock and unbonding cooldown of the pool
+    pub fn propose_config_update(ctx: Context<UpdateConfig>, config: PoolConfig) -> Result<()> {
+        require!(
+            config.reward_rate as u128 <= REWARD_RATE_DENOMINATOR
+                && config.min_staking_duration >= 0
+                && config.config_timelock >= 0
+                && (0..=MAX_UNBONDING_COOLDOWN).contains(&config.unbonding_cooldown),
+            StakingError::InvalidArgument
+        );
+
+        let staking_account = &mut ctx.accounts.staking_account;
+        let config_update_eta = Clock::get()?
+            .unix_timestamp
+            .checked_add(staking_account.config_timelock)
+            .ok_or(StakingError::CalculationError)?;
+        staking_account.pending_config = config;
+        staking_account.config_update_eta = config_update_eta;
+
+        emit!(ConfigUpdateProposed {
+            pool: staking_account.key(),
+            config,
+            eta: config_update_eta,
+        });
+        Ok(())
+    }
+
+    /// Apply config update instruction
+    /// This instruction is used by the admin to apply the staged pool parameters once the timelock has elapsed
+    /// The APY rewards accrued so far are checkpointed at the old reward rate, the new reward rate only applies
+    /// to the rewards accrued from now on, the new minimum staking duration only applies to the positions opened afterwards
+    /// and the new unbonding cooldown to the unbonding requests made afterwards
+    /// While a cooldown is set, principal can only leave the pool through the unbonding queue
+    /// (`partial_unstake` and `redeem` are disabled), a zero cooldown enables them again
+    ///
+    /// # Arguments
+    /// * `ctx` - context of the program
+    pub fn apply_config_update(ctx: Context<UpdateConfig>) -> Result<()> {
+        let staking_account = &mut ctx.accounts.staking_account;
+        require!(
+            staking_account.config_update_eta != 0,
+            StakingError::NoPendingConfigUpdate
+        );
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(
+            current_time >= staking_account.config_update_eta,
+            StakingError::TimelockNotElapsed
+        );
+
+        // the rewards accrued up to now keep the old rate
+        checkpoint_apy_rate_index(staking_account, current_time)?;
+
+        let config = staking_account.pending_config;
+        staking_account.reward_rate = config.reward_rate;
+        staking_account.min_staking_duration = config.min_staking_duration;
+        staking_account.config_timelock = config.config_timelock;
+        staking_account.unbonding_cooldown = config.unbonding_cooldown;
+        staking_account.pending_config = PoolConfig::default();
+        staking_account.config_update_eta = 0;
+
+        emit!(ConfigUpdateApplied {
+            pool: staking_account.key(),
+            config,
+        });
+        Ok(())
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////
+    //////////////////////////////// ADMIN TRANSFER ////////////////////////////////
+    ////////////////////////////////////////////////////////////////////////////////
+
+    /// Propose admin instruction
+    /// This instruction is used by the admin to start transferring the pool to a new admin
+    /// The transfer only happens once the new admin signs `accept_admin`, proposing again replaces
+    /// the pending admin and proposing the default pubkey cancels the transfer
+    ///
+    /// # Arguments
+    /// * `ctx` - context of the program
+    /// * `new_admin` - account allowed to accept the admin role
+    pub fn propose_admin(ctx: Context<ProposeAdmin>, new_admin: Pubkey) -> Result<()> {
+        ctx.accounts.staking_account.pending_admin = new_admin;
+
+        emit!(AdminProposed {
+            pool: ctx.accounts.staking_account.key(),
+            admin: ctx.accounts.admin.key(),
+            pending_admin: new_admin,
+        });
+        Ok(())
+    }
+
+    /// Accept admin instruction
+    /// This instruction is used by the pending admin to take over the admin role of the pool
+    ///
+    /// # Arguments
+    /// * `ctx` - context of the program
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        let staking_account = &mut ctx.accounts.staking_account;
+        let previous_admin = staking_account.admin;
+        staking_account.admin = staking_account.pending_admin;
+        staking_account.pending_admin = Pubkey::default();
+
+        emit!(AdminAccepted {
+            pool: staking_account.key(),
+            previous_admin,
+            admin: staking_account.admin,
+        });
+        Ok(())
+    }
+
+    /// Renounce admin instruction
+    /// This instruction is used by the admin to give up the admin role for good
+    /// No one can sign as the default pubkey, so the admin instructions of the pool are disabled afterwards
+    ///
+    /// # Arguments
+    /// * `ctx` - context of the program
+    pub fn renounce_admin(ctx: Context<ProposeAdmin>) -> Result<()> {
+        let staking_account = &mut ctx.accounts.staking_account;
+        staking_account.admin = Pubkey::default();
+        staking_account.pending_admin = Pubkey::default();
+
+        emit!(AdminRenounced {
+            pool: staking_account.key(),
+            admin: ctx.accounts.admin.key(),
+        });
+        Ok(())
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////
+    ////////////////////////////////// EMERGENCY ///////////////////////////////////
+    ////////////////////////////////////////////////////////////////////////////////
+
+    /// Set guardian instruction
+    /// This instruction is used by the admin to appoint the guardian allowed to pause the pool
+    ///
+    /// # Arguments
+    /// * `ctx` - context of the program
+    /// * `guardian` - guardian of the pool (default pubkey to remove it)
+    pub fn set_guardian(ctx: Context<SetGuardian>, guardian: Pubkey) -> Result<()> {
+        ctx.accounts.staking_account.guardian = guardian;
+
+        emit!(GuardianSet {
+            pool: ctx.accounts.staking_account.key(),
+            guardian,
+        });
+        Ok(())
+    }
+
+    /// Set pool state instruction
+    /// This instruction is used as a kill-switch for incident response
+    /// The admin can move the pool to any state but `ShuttingDown` (see `begin_shutdown`), the guardian can only pause it or trigger the emergency
+    ///
+    /// # Arguments
+    /// * `ctx` - context of the program
+    /// * `pool_state` - new state of the pool
+    pub fn set_pool_state(ctx: Context<SetPoolState>, pool_state: PoolState) -> Result<()> {
+        let staking_account = &mut ctx.accounts.staking_account;
+        let authority = ctx.accounts.authority.key();
+
+        let is_admin = authority == staking_account.admin;
+        let is_guardian =
+            authority == staking_account.guardian && staking_account.guardian != Pubkey::default();
+        require!(
+            is_admin || (is_guardian && pool_state != PoolState::Active),
+            StakingError::AdminOnly
+        );
+
+        // only `begin_shutdown` starts a shutdown, with its shutdown time and grace period
+        require!(
+            pool_state != PoolState::ShuttingDown,
+            StakingError::InvalidArgument
+        );
+        // a shutdown can not be undone, the emergency stays available
+        require!(
+            staking_account.shutdown_time == 0 || pool_state == PoolState::Emergency,
+            StakingError::PoolShuttingDown
+        );
+
+        staking_account.pool_state = pool_state;
+
+        emit!(PoolStateChanged {
+            pool: staking_account.key(),
+            authority,
+            pool_state,
+        });
+        Ok(())
+    }
+
+    /// Emergency withdraw instruction
+    /// This instruction is used by users to get the principal of a position back once the pool is in emergency,
+    /// without any reward, lock or cooldown check, and without any operator signature
+    /// The position (including its unbonding amount) is closed
+    ///
+    /// # Arguments
+    /// * `ctx` - context of the program
+    pub fn emergency_withdraw(ctx: Context<EmergencyWithdraw>) -> Result<()> {
+        let staking_account = &mut ctx.accounts.staking_account;
+        require!(
+            staking_account.pool_state == PoolState::Emergency,
+            StakingError::PoolNotInEmergency
+        );
+
+        let user_stake = &mut ctx.accounts.user_stake;
+        let unbonding_amount = user_stake.unbonding_amount;
+        let total_amount = user_stake
+            .amount
+            .checked_add(unbonding_amount)
+            .ok_or(StakingError::CalculationError)?;
+        require!(total_amount > 0, StakingError::NothingStaked);
+
+        // Keep the emission of the other stakers right before removing the position
+        let current_time = Clock::get()?.unix_timestamp;
+        update_reward_per_share(staking_account, current_time)?;
+        let forfeited_reward = settle_reward(staking_account, user_stake, current_time)?;
+        release_reward_liability(staking_account, forfeited_reward);
+        set_stake_amount(staking_account, user_stake, 0)?;
+        user_stake.unbonding_amount = 0;
+        staking_account.total_unbonding = staking_account
+            .total_unbonding
+            .checked_sub(unbonding_amount)
+            .ok_or(StakingError::CalculationError)?;
+
+        // Transfer the principal back to the user, signed by the staking account PDA
+        transfer_from_vault(
+            &ctx.accounts.staking_account,
+            &ctx.accounts.token_program,
+            ctx.accounts.staking_token_account.to_account_info(),
+            ctx.accounts.user_token_account.to_account_info(),
+            ctx.accounts.mint.to_account_info(),
+            ctx.accounts.mint.decimals,
+            total_amount,
+        )?;
+
+        emit!(EmergencyWithdrawn {
+            pool: ctx.accounts.staking_account.key(),
+            user: ctx.accounts.user.key(),
+            user_stake: ctx.accounts.user_stake.key(),
+            position_id: ctx.accounts.user_stake.position_id,
+            amount: total_amount,
+            forfeited_reward,
+            timestamp: current_time,
+        });
+
+        // Account closure is handled by the `close = user` attribute on the user_stake account
+
+        Ok(())
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////
+    //////////////////////////////////// REDEEM ////////////////////////////////////
+    ////////////////////////////////////////////////////////////////////////////////
+
+    /// Redeem instruction
+    /// This instruction is used to redeem the staked tokens of a single position
+    /// Force redeeming before the lock ends applies the penalty policy of the pool
+    /// The rewards of the reward streams are paid out as well from the stream accounts in `remaining_accounts`,
+    /// a stream whose destination is frozen or closed does not block the exit, its rewards stay pending in the position
+    /// which is kept open (with nothing staked) until they are claimed with `claim_rewards` (see `pay_stream_rewards`)
+    ///
+    /// # Arguments
+    /// * `ctx` - context of the program
+    /// * `force_redeem` - force redeeming (before the end of the lock)
+    pub fn redeem<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Redeem<'info>>,
+        force_redeem: bool,
+    ) -> Result<()> {
+        let staking_account = &mut ctx.accounts.staking_account;
+
+        // Validate token mint matches staking account
+        require!(
+            ctx.accounts.user_token_account.mint == staking_account.token_mint,
+            StakingError::InvalidArgument
+        );
+
+        require!(
+            staking_account.unbonding_cooldown == 0 || staking_account.shutdown_time != 0,
+            StakingError::UnbondingRequired
+        );
+
+        let user_stake = &mut ctx.accounts.user_stake;
+        require!(user_stake.amount > 0, StakingError::NothingStaked);
+        // the position is closed, so the unbonding amount must be withdrawn first
+        require!(
+            user_stake.unbonding_amount == 0,
+            StakingError::UnbondingPending
+        );
+
+        let current_time = Clock::get()?.unix_timestamp;
+        let staking_duration = current_time - user_stake.start_time;
+        // locks are lifted when the pool shuts down
+        let matured =
+            staking_duration >= user_stake.lock_duration || staking_account.shutdown_time != 0;
+
+        // Add lock duration (of the position tier) check if not force redeeming
+        if !force_redeem {
+            require!(matured, StakingError::StakingDurationNotMet);
+        }
+
+        require!(staking_duration >= 0, StakingError::ZeroValueError);
+
+        update_reward_per_share(staking_account, current_time)?;
+        let calculated_reward = settle_reward(staking_account, user_stake, current_time)?;
+
+        // Validate and adjust reward based on the penalty policy and available funds
+        let (reward, penalty) = if matured {
+            require!(
+                staking_account.admin_reward_amount >= calculated_reward,
+                StakingError::InsufficientRewardFunds
+            );
+            (calculated_reward, 0)
+        } else {
+            let (reward, penalty) = early_withdrawal_penalty(
+                staking_account,
+                user_stake,
+                calculated_reward,
+                staking_duration,
+            )?;
+            // a forced exit is never blocked by missing reward funds
+            (reward.min(staking_account.admin_reward_amount), penalty)
+        };
+
+        msg!("reward {} penalty {}", reward, penalty);
+
+        // penalties of a treasury which is not provided (e.g. closed) stay in the pool, so exits are never blocked
+        let penalty_destination = match staking_account.penalty_destination {
+            PenaltyDestination::Treasury if ctx.accounts.treasury_token_account.is_none() => {
+                PenaltyDestination::RewardPool
+            }
+            penalty_destination => penalty_destination,
+        };
+
+        // Update admin reward balance, forfeited rewards stay in the reward funds
+        take_reward_funds(staking_account, reward)?;
+        release_reward_liability(staking_account, calculated_reward - reward);
+        // principal penalties can only fund rewards in the staked token, otherwise they stay in the vault until the pool is closed
+        if penalty > 0
+            && penalty_destination == PenaltyDestination::RewardPool
+            && !has_reward_vault(staking_account)
+        {
+            staking_account.admin_reward_amount = staking_account
+                .admin_reward_amount
+                .checked_add(penalty)
+                .ok_or(StakingError::CalculationError)?;
+        }
+
+        // Calculate the returned principal with overflow protection
+        let principal = user_stake
+            .amount
+            .checked_sub(penalty)
+            .ok_or(StakingError::CalculationError)?;
+
+        // Remove the position from the pool totals
+        set_stake_amount(staking_account, user_stake, 0)?;
+
+        // Transfer staked tokens and rewards back to the user
+        pay_reward(reward_payout!(ctx.accounts), principal, reward)?;
+
+        // Route the principal penalty out of the vault, signed by the staking account PDA
+        let staking_account = &ctx.accounts.staking_account;
+        let signer_seeds: &[&[&[u8]]] = &[staking_account_seeds!(staking_account)];
+        if penalty > 0 {
+            match penalty_destination {
+                PenaltyDestination::RewardPool => {}
+                PenaltyDestination::Treasury => token_interface::transfer_checked(
+                    ctx.accounts.transfer_to_treasury_ctx(signer_seeds)?,
+                    penalty,
+                    ctx.accounts.mint.decimals,
+                )?,
+                PenaltyDestination::Burn => {
+                    token_interface::burn(ctx.accounts.burn_penalty_ctx(signer_seeds), penalty)?
+                }
+            }
+        }
+
+        // Pay out the rewards of the reward streams
+        let stream_rewards = pay_stream_rewards(
+            &ctx.accounts.staking_account,
+            &mut ctx.accounts.user_stake,
+            ctx.remaining_accounts,
+            &ctx.accounts.token_program,
+            false,
+        )?;
+
+        emit!(Redeemed {
+            pool: ctx.accounts.staking_account.key(),
+            user: ctx.accounts.user.key(),
+            user_stake: ctx.accounts.user_stake.key(),
+            position_id: ctx.accounts.user_stake.position_id,
+            principal,
+            reward,
+            stream_rewards,
+            penalty,
+            forced: !matured,
+            total_staked: ctx.accounts.staking_account.total_staked,
+            timestamp: current_time,
+        });
+
+        // Close the position and return its rent to the user, unless it still holds stream rewards to claim
+        if !has_stream_rewards(&ctx.accounts.user_stake) {
+            ctx.accounts
+                .user_stake
+                .close(ctx.accounts.user.to_account_info())?;
+        }
+
+        Ok(())
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////
+    /////////////////////////////////// SHUTDOWN ///////////////////////////////////
+    ////////////////////////////////////////////////////////////////////////////////
+
+    /// Begin shutdown instruction
+    /// This instruction is used by the admin to start winding down the pool
+    /// New stakes are blocked, rewards (reward streams included) stop accruing now, and users can redeem without lock or cooldown
+    /// until the end of the grace period, after which anyone can return the remaining positions with `return_position`
+    ///
+    /// # Arguments
+    /// * `ctx` - context of the program
+    /// * `grace_period` - time in seconds users have to redeem by themselves
+    pub fn begin_shutdown(ctx: Context<BeginShutdown>, grace_period: i64) -> Result<()> {
+        require!(grace_period >= 0, StakingError::InvalidArgument);
+
+        let current_time = Clock::get()?.unix_timestamp;
+        let staking_account = &mut ctx.accounts.staking_account;
+        require!(
+            staking_account.shutdown_time == 0,
+            StakingError::PoolShuttingDown
+        );
+
+        // stop the emission, its rest goes back to the admin with the unallocated funds
+        update_reward_per_share(staking_account, current_time)?;
+        staking_account.emission_end_time = staking_account.emission_end_time.min(current_time);
+        for stream in staking_account.reward_streams.iter_mut() {
+            stream.end_time = stream.end_time.min(current_time);
+        }
+
+        staking_account.pool_state = PoolState::ShuttingDown;
+        staking_account.shutdown_time = current_time;
+        staking_account.grace_period_end = current_time
+            .checked_add(grace_period)
+            .ok_or(StakingError::CalculationError)?;
+
+        emit!(ShutdownBegun {
+            pool: staking_account.key(),
+            admin: ctx.accounts.admin.key(),
+            grace_period_end: staking_account.grace_period_end,
+            timestamp: current_time,
+        });
+        Ok(())
+    }
+
+    /// Return position instruction
+    /// This instruction is used by anyone (a cranker) to return a position of a pool shutting down after the grace period
+    /// The principal, the unbonding amount and the rewards accrued until the shutdown are sent to the owner
+    /// (rewards are capped by the reward funds) and the position is closed, its rent going back to the owner
+    /// The rewards of the reward streams are paid out as well from the stream accounts in `remaining_accounts`,
+    /// a stream whose destination is frozen or closed does not block the return, its rewards stay pending in the position
+    /// which is kept open (with nothing staked) until they are claimed with `claim_rewards` (see `pay_stream_rewards`)
+    ///
+    /// # Arguments
+    /// * `ctx` - context of the program
+    pub fn return_position<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ReturnPosition<'info>>,
+    ) -> Result<()> {
+        let staking_account = &mut ctx.accounts.staking_account;
+        require!(
+            staking_account.shutdown_time != 0,
+            StakingError::PoolNotShuttingDown
+        );
+
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(
+            current_time >= staking_account.grace_period_end,
+            StakingError::GracePeriodNotElapsed
+        );
+
+        let user_stake = &mut ctx.accounts.user_stake;
+        update_reward_per_share(staking_account, current_time)?;
+        let calculated_reward = settle_reward(staking_account, user_stake, current_time)?;
+        let reward = calculated_reward.min(staking_account.admin_reward_amount);
+
+        // Update admin reward balance, rewards the funds can not cover are dropped
+        take_reward_funds(staking_account, reward)?;
+        release_reward_liability(staking_account, calculated_reward - reward);
+
+        let unbonding_amount = user_stake.unbonding_amount;
+        let principal = user_stake
+            .amount
+            .checked_add(unbonding_amount)
+            .ok_or(StakingError::CalculationError)?;
+
+        // Remove the position from the pool totals
+        set_stake_amount(staking_account, user_stake, 0)?;
+        user_stake.unbonding_amount = 0;
+        staking_account.total_unbonding = staking_account
+            .total_unbonding
+            .checked_sub(unbonding_amount)
+            .ok_or(StakingError::CalculationError)?;
+
+        // Transfer everything back to the owner
+        pay_reward(reward_payout!(ctx.accounts), principal, reward)?;
+
+        // Pay out the rewards of the reward streams
+        let stream_rewards = pay_stream_rewards(
+            &ctx.accounts.staking_account,
+            &mut ctx.accounts.user_stake,
+            ctx.remaining_accounts,
+            &ctx.accounts.token_program,
+            false,
+        )?;
+
+        // returned positions are matured, the shutdown lifted their lock
+        emit!(Redeemed {
+            pool: ctx.accounts.staking_account.key(),
+            user: ctx.accounts.user.key(),
+            user_stake: ctx.accounts.user_stake.key(),
+            position_id: ctx.accounts.user_stake.position_id,
+            principal,
+            reward,
+            stream_rewards,
+            penalty: 0,
+            forced: false,
+            total_staked: ctx.accounts.staking_account.total_staked,
+            timestamp: current_time,
+        });
+
+        // Close the position and return its rent to the owner, unless it still holds stream rewards to claim
+        if !has_stream_rewards(&ctx.accounts.user_stake) {
+            ctx.accounts
+                .user_stake
+                .close(ctx.accounts.user.to_account_info())?;
+        }
+
+        Ok(())
+    }
+
+    /// Close pool instruction
+    /// This instruction is used by the admin to close a pool shutting down once every position is gone
+    /// The tokens left in the vaults (unallocated rewards and rounding dust) are sent to the admin,
+    /// then the vaults and the staking account are closed and their rent returned to the admin
+    /// `remaining_accounts` holds the `(vault, mint, admin token account)` accounts of every reward stream, in order
+    /// A pool with reward streams can only be closed after their claim period, as emptied positions can still hold their rewards
+    ///
+    /// # Arguments
+    /// * `ctx` - context of the program
+    pub fn close_pool<'info>(ctx: Context<'_, '_, 'info, 'info, ClosePool<'info>>) -> Result<()> {
+        let staking_account = &ctx.accounts.staking_account;
+        require!(
+            staking_account.shutdown_time != 0,
+            StakingError::PoolNotShuttingDown
+        );
+        require!(
+            staking_account.total_staked == 0 && staking_account.total_unbonding == 0,
+            StakingError::OutstandingStake
+        );
+        let current_time = Clock::get()?.unix_timestamp;
+        for (_, stream) in active_streams(staking_account) {
+            require!(
+                current_time
+                    >= stream
+                        .end_time
+                        .checked_add(STREAM_CLAIM_PERIOD)
+                        .ok_or(StakingError::CalculationError)?,
+                StakingError::RewardStreamActive
+            );
+        }
+
+        let signer_seeds: &[&[&[u8]]] = &[staking_account_seeds!(staking_account)];
+        let remaining_amount = ctx.accounts.staking_token_account.amount;
+        let mut remaining_reward = 0;
+        if remaining_amount > 0 {
+            token_interface::transfer_checked(
+                ctx.accounts.transfer_to_admin_ctx(signer_seeds),
+                remaining_amount,
+                ctx.accounts.mint.decimals,
+            )?;
+        }
+        token_interface::close_account(ctx.accounts.close_vault_ctx(signer_seeds))?;
+
+        // The rewards left in the reward vault go to the admin as well
+        if has_reward_vault(staking_account) {
+            remaining_reward = reward_account(&ctx.accounts.reward_token_account)?.amount;
+            if remaining_reward > 0 {
+                token_interface::transfer_checked(
+                    ctx.accounts.transfer_reward_to_admin_ctx(signer_seeds)?,
+                    remaining_reward,
+                    reward_account(&ctx.accounts.reward_mint)?.decimals,
+                )?;
+            }
+            token_interface::close_account(ctx.accounts.close_reward_vault_ctx(signer_seeds)?)?;
+        }
+
+        // The rewards left in the vaults of the reward streams go to the admin as well
+        close_stream_vaults(
+            staking_account,
+            ctx.remaining_accounts,
+            &ctx.accounts.admin,
+            &ctx.accounts.token_program,
+        )?;
+
+        emit!(PoolClosed {
+            pool: staking_account.key(),
+            admin: ctx.accounts.admin.key(),
+            remaining_amount,
+            remaining_reward,
+            timestamp: current_time,
+        });
+
+        // The staking account is closed by the `close = admin` attribute
+
+        Ok(())
+    }
+
+    ////////////////////////////////////////////////////////////////////////////////
+    ////////////////////////////////// MIGRATION ///////////////////////////////////
+    ////////////////////////////////////////////////////////////////////////////////
+
+    /// Migrate pool instruction
+    /// This instruction is used by the admin to move a pool of the baseline layout (version 0, see `StakingAccountV0`)
+    /// to a new pool of the current layout, the legacy pool can not be upgraded in place as its PDA has no pool id
+    /// The new pool keeps the token mint, reward rate and minimum staking duration of the legacy pool and pays an APY,
+    /// the reward funds of the legacy pool (held by the admin token account in the baseline) are moved to its vault
+    /// The legacy pool is closed, its positions are then moved one by one with `migrate_user_stake`
+    ///
+    /// # Arguments
+    /// * `ctx` - context of the program
+    /// * `pool_id` - id of the new pool among the pools created by the admin
+    pub fn migrate_pool(ctx: Context<MigratePool>, pool_id: u64) -> Result<()> {
+        let legacy_pool = read_legacy_account::<StakingAccountV0>(
+            &ctx.accounts.legacy_staking_account,
+            &StakingAccount::DISCRIMINATOR,
+            StakingAccountV0::LEN,
+            size_of::<StakingAccount>() + 8,
+        )?;
+        require_keys_eq!(
+            legacy_pool.admin,
+            ctx.accounts.admin.key(),
+            StakingError::AdminOnly
+        );
+        require_keys_eq!(
+            legacy_pool.token_mint,
+            ctx.accounts.mint.key(),
+            StakingError::InvalidArgument
+        );
+
+        // Move the reward funds of the legacy pool to the vault, only what the vault received is credited
+        let reward_funds = if legacy_pool.admin_reward_amount > 0 {
+            ctx.accounts
+                .transfer_from_admin(legacy_pool.admin_reward_amount)?
+        } else {
+            0
+        };
+
+        let staking_account = &mut ctx.accounts.staking_account;
+        init_pool(
+            staking_account,
+            ctx.accounts.admin.key(),
+            pool_id,
+            legacy_pool.token_mint,
+            legacy_pool.reward_rate,
+            legacy_pool.min_staking_duration,
+            RewardMode::Apy,
+        )?;
+        staking_account.bump = ctx.bumps.staking_account;
+        staking_account.admin_reward_amount = reward_funds;
+
+        close_legacy_account(&ctx.accounts.legacy_staking_account, &ctx.accounts.admin)?;
+
+        emit!(PoolMigrated {
+            pool: staking_account.key(),
+            legacy_pool: ctx.accounts.legacy_staking_account.key(),
+            admin: staking_account.admin,
+            pool_id,
+            reward_rate: staking_account.reward_rate,
+            min_staking_duration: staking_account.min_staking_duration,
+            reward_funds,
+            timestamp: staking_account.last_update_time,
+        });
+        Ok(())
+    }
+
+    /// Migrate user stake instruction
+    /// This instruction is used by the user, with the admin co-signing, to move a position of the baseline layout
+    /// (version 0, see `UserStakeV0`) to a new position of the user in a migrated pool, the legacy position is not linked
+    /// to any pool and its PDA is derived from the user only, so it can not be upgraded in place
+    /// Nothing on chain tells which pool the legacy position belongs to, so the user picks it by signing
+    /// (the admin alone could move it to any pool it runs)
+    /// The principal (held by the admin token account in the baseline) is moved to the vault of the pool,
+    /// the start time of the position is kept (converted to seconds) so its lock runs from the original stake,
+    /// and the APY accrued since then at the rate of the pool is settled into the rewards owed to the position
+    /// The legacy position is closed, its rent goes back to the user
+    ///
+    /// # Arguments
+    /// * `ctx` - context of the program
+    pub fn migrate_user_stake(ctx: Context<MigrateUserStake>) -> Result<()> {
+        let legacy_stake = read_legacy_account::<UserStakeV0>(
+            &ctx.accounts.legacy_user_stake,
+            &UserStake::DISCRIMINATOR,
+            UserStakeV0::LEN,
+            size_of::<UserStake>() + 8,
+        )?;
+        require!(legacy_stake.amount > 0, StakingError::NothingStaked);
+        require!(
+            ctx.accounts.staking_account.reward_mode == RewardMode::Apy,
+            StakingError::InvalidArgument
+        );
+        require!(
+            ctx.accounts.staking_account.pool_state == PoolState::Active,
+            StakingError::PoolNotActive
+        );
+
+        // Move the principal to the vault, only what the vault received is staked
+        let received = ctx.accounts.transfer_from_admin(legacy_stake.amount)?;
+        require!(received > 0, StakingError::ZeroValueError);
+
+        // Reserve the position id used in the user stake seeds
+        let user_stake_counter = &mut ctx.accounts.user_stake_counter;
+        let position_id = user_stake_counter.next_position_id;
+        user_stake_counter.pool = ctx.accounts.staking_account.key();
+        user_stake_counter.user = ctx.accounts.user.key();
+        user_stake_counter.next_position_id = position_id
+            .checked_add(1)
+            .ok_or(StakingError::CalculationError)?;
+
+        let staking_account = &mut ctx.accounts.staking_account;
+        let current_time = Clock::get()?.unix_timestamp;
+        update_reward_per_share(staking_account, current_time)?;
+        // the lock may start a bit earlier, never after now
+        let start_time = legacy_time_to_seconds(legacy_stake.start_time).min(current_time);
+
+        // Settle the APY accrued since the legacy stake, it must be covered by the funds of the pool
+        let legacy_reward = calculate_reward(
+            received,
+            staking_account.reward_rate as u128 * (current_time - start_time) as u128,
+            BPS_DENOMINATOR as u16,
+        )?;
+        require!(
+            unallocated_reward_funds(staking_account, current_time)? >= legacy_reward,
+            StakingError::InsufficientRewardFunds
+        );
+        staking_account.reward_liability = staking_account
+            .reward_liability
+            .checked_add(legacy_reward)
+            .ok_or(StakingError::CalculationError)?;
+
+        // Initialize the position in the default tier, accruing from now on
+        let lockup_tier = staking_account.tiers[0];
+        let user_stake = &mut ctx.accounts.user_stake;
+        user_stake.pool = staking_account.key();
+        user_stake.user = ctx.accounts.user.key();
+        user_stake.position_id = position_id;
+        user_stake.tier = 0;
+        user_stake.lock_duration = lockup_tier
+            .lock_duration
+            .max(staking_account.min_staking_duration);
+        user_stake.reward_multiplier_bps = lockup_tier.reward_multiplier_bps;
+        user_stake.start_time = start_time;
+        user_stake.last_reward_time = current_time;
+        user_stake.apy_rate_index = apy_rate_index(staking_account, current_time)?;
+        user_stake.pending_reward = legacy_reward;
+        user_stake.last_claim_time = 0;
+        user_stake.rewards_claimed = 0;
+        user_stake.auto_compound = false;
+        user_stake.unbonding_amount = 0;
+        user_stake.unlock_at = 0;
+        user_stake.unbonding_cooldown = 0;
+        user_stake.stream_pending_rewards = [0; MAX_REWARD_STREAMS];
+        user_stake.stream_ids = staking_account.reward_streams.map(|stream| stream.id);
+        user_stake.version = USER_STAKE_VERSION;
+        set_stake_amount(staking_account, user_stake, received)?;
+
+        close_legacy_account(&ctx.accounts.legacy_user_stake, &ctx.accounts.user)?;
+
+        emit!(PositionMigrated {
+            pool: staking_account.key(),
+            user: user_stake.user,
+            user_stake: user_stake.key(),
+            legacy_user_stake: ctx.accounts.legacy_user_stake.key(),
+            position_id,
+            amount: received,
+            start_time,
+            reward: legacy_reward,
+            total_staked: staking_account.total_staked,
+            timestamp: current_time,
+        });
+        Ok(())
+    }
+
+    /// Upgrade pool instruction
+    /// This instruction is used by anyone to upgrade a pool of an older layout (from version 1) in place to the current one
+    /// Newer layouts only append fields after `version`, so the account is grown to the size of the current layout
+    /// with the new fields zeroed and its version is bumped, the payer tops up the rent of the larger account
+    /// Pools of the baseline layout (version 0) are moved with `migrate_pool` instead, their PDA has no pool id
+    ///
+    /// # Arguments
+    /// * `ctx` - context of the program
+    pub fn upgrade_pool(ctx: Context<UpgradePool>) -> Result<()> {
+        let account = ctx.accounts.staking_account.to_account_info();
+        grow_account(
+            &account,
+            &StakingAccount::DISCRIMINATOR,
+            StakingAccountV0::LEN,
+            size_of::<StakingAccount>() + 8,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+        )?;
+        let mut staking_account =
+            StakingAccount::try_deserialize(&mut &account.try_borrow_data()?[..])?;
+        let previous_version = staking_account.version;
+        require!(
+            previous_version != STAKING_ACCOUNT_VERSION,
+            StakingError::AlreadyMigrated
+        );
+        require!(
+            (1..STAKING_ACCOUNT_VERSION).contains(&previous_version),
+            StakingError::UnsupportedLayout
+        );
+
+        // Fields appended by a newer layout are zeroed, the ones which need another value are set here
+        // for every layout after the version of the account (`if previous_version < 2 { ... }`)
+        staking_account.version = STAKING_ACCOUNT_VERSION;
+        staking_account.try_serialize(&mut &mut account.try_borrow_mut_data()?[..])?;
+
+        emit!(AccountUpgraded {
+            account: account.key(),
+            previous_version,
+            version: STAKING_ACCOUNT_VERSION,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Upgrade user stake instruction
+    /// This instruction is used by anyone to upgrade a position of an older layout (from version 1) in place to the current one
+    /// Newer layouts only append fields after `version`, so the account is grown to the size of the current layout
+    /// with the new fields zeroed and its version is bumped, the payer tops up the rent of the larger account
+    /// Positions of the baseline layout (version 0) are moved with `migrate_user_stake` instead, their PDA has no pool
+    ///
+    /// # Arguments
+    /// * `ctx` - context of the program
+    pub fn upgrade_user_stake(ctx: Context<UpgradeUserStake>) -> Result<()> {
+        let account = ctx.accounts.user_stake.to_account_info();
+        grow_account(
+            &account,
+            &UserStake::DISCRIMINATOR,
+            UserStakeV0::LEN,
+            size_of::<UserStake>() + 8,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+        )?;
+        let mut user_stake = UserStake::try_deserialize(&mut &account.try_borrow_data()?[..])?;
+        let previous_version = user_stake.version;
+        require!(
+            previous_version != USER_STAKE_VERSION,
+            StakingError::AlreadyMigrated
+        );
+        require!(
+            (1..USER_STAKE_VERSION).contains(&previous_version),
+            StakingError::UnsupportedLayout
+        );
+
+        // Fields appended by a newer layout are zeroed, the ones which need another value are set here
+        // for every layout after the version of the account (`if previous_version < 2 { ... }`)
+        user_stake.version = USER_STAKE_VERSION;
+        user_stake.try_serialize(&mut &mut account.try_borrow_mut_data()?[..])?;
+
+        emit!(AccountUpgraded {
+            account: account.key(),
+            previous_version,
+            version: USER_STAKE_VERSION,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////////////
+///////////////////////////////// PROGRAM STATES STRUCTS /////////////////////////////////
+//////////////////////////////////////////////////////////////////////////////////////////
+
+/// Staking account struct
+/// This struct is used to define the state of the staking pool
+///
+/// # Fields
+/// * `admin` - admin account (signer)
+/// * `pool_id` - unique id for each pool (associated with creator), part of the PDA seeds
+/// * `reward_rate` - annual percentage yield (APY) in percentage (0-100)
+/// * `bump` - bump for the PDA (multiple PDAs can be created with the same seeds)
+/// * `token_mint` - program_id (address) of specific token which is allowed stake
+/// * `admin_reward_amount` - reward tokens held by the vault (the reward vault for a separate reward mint) which are not paid out yet
+/// * `min_staking_duration` - minimum staking duration in seconds
+/// * `reward_mode` - how rewards are distributed (fixed APY or funded emission)
+/// * `total_staked` - sum of the staked amounts of all positions in the pool
+/// * `acc_reward_per_share` - emission rewards per staked token since the pool creation, scaled by `ACC_REWARD_PRECISION`
+/// * `reward_per_second` - emission rate in reward tokens per second (shared by all stakers)
+/// * `last_update_time` - time in seconds up to which `acc_reward_per_share` is updated
+/// * `emission_end_time` - time in seconds at which the current emission ends
+/// * `compound_tip_bps` - share of the reward (in basis points) paid to crankers compounding opted in positions
+/// * `total_weight` - sum of the staked amounts weighted by the tier multipliers (shares of the emission)
+/// * `tier_count` - number of configured lockup tiers
+/// * `tiers` - lockup tiers users can pick when staking (only the first `tier_count` are used)
+/// * `penalty_policy` - how the penalty of force redeeming before the end of the lock is computed
+/// * `penalty_bps` - penalty rate in basis points (of the principal, or of the reward for `ProRatedReward`)
+/// * `penalty_destination` - where principal penalties go (reward pool, treasury or burned)
+/// * `treasury` - token account receiving the penalties routed to the treasury
+/// * `unbonding_cooldown` - time in seconds between `request_unstake` and `withdraw_unbonded` (0 allows instant exits)
+/// * `total_unbonding` - sum of the unbonding amounts of all positions, the vault must hold
+///   `total_staked + total_unbonding + admin_reward_amount` for the pool to be solvent
+///   (`admin_reward_amount` being held by the reward vault for a separate reward mint)
+/// * `config_timelock` - delay in seconds between proposing and applying a config update
+/// * `pending_config` - config update staged by `propose_config_update`
+/// * `config_update_eta` - time in seconds from which the staged config can be applied (0 if none is staged)
+/// * `creator` - admin which created the pool, part of the PDA seeds (never changes)
+/// * `pending_admin` - account allowed to accept the admin role (default pubkey if no transfer is pending)
+/// * `guardian` - account allowed to pause the pool besides the admin (default pubkey if none)
+/// * `pool_state` - whether the pool is active, paused or in emergency
+/// * `reward_liability` - rewards accrued by the positions and not paid out yet (emitted rewards in emission mode,
+///   settled rewards in APY mode), part of `admin_reward_amount`
+/// * `apy_weight_sum` - sum of `amount * reward_multiplier_bps` of all positions
+/// * `apy_weight_index_sum` - sum of `amount * reward_multiplier_bps * apy_rate_index` (at their last settlement) of all positions,
+///   gives the APY rewards accrued since the last settlements with `apy_weight_sum` and the current rate index
+/// * `apy_rate_index` - sum of `reward_rate * seconds` accrued by the pool up to `apy_index_time`, checkpointed when the reward rate changes
+/// * `apy_index_time` - time in seconds up to which `apy_rate_index` is accrued
+/// * `shutdown_time` - time in seconds at which the shutdown began and rewards stopped accruing (0 if not shutting down)
+/// * `grace_period_end` - time in seconds from which anyone can return the remaining positions
+/// * `reward_mint` - mint of the rewards, `token_mint` unless the admin set a separate reward mint
+///   (whose rewards are held by the reward vault of the pool)
+/// * `stream_count` - number of reward stream slots in use, up to the last stream (slots freed by `retire_reward_stream` below it are empty)
+/// * `reward_streams` - reward streams layered on top of the rewards of the pool (only the first `stream_count` are used)
+/// * `next_stream_id` - id given to the next reward stream added to the pool
+/// * `version` - layout version of the account (see `STAKING_ACCOUNT_VERSION`), upgraded in place by `upgrade_pool`
+///   (version 0 is moved by `migrate_pool`), fields of newer layouts go after it
+#[account]
+pub struct StakingAccount {
+    pub admin: Pubkey,
+    pub pool_id: u64,
+    pub reward_rate: u8,
+    pub bump: u8,
+    pub token_mint: Pubkey,
+    pub admin_reward_amount: u64,
+    pub min_staking_duration: i64, // Minimum staking duration in seconds
+    pub reward_mode: RewardMode,
+    pub total_staked: u64,
+    pub acc_reward_per_share: u128,
+    pub reward_per_second: u64,
+    pub last_update_time: i64,
+    pub emission_end_time: i64,
+    pub compound_tip_bps: u16,
+    pub total_weight: u128,
+    pub tier_count: u8,
+    pub tiers: [LockupTier; MAX_LOCKUP_TIERS],
+    pub penalty_policy: PenaltyPolicy,
+    pub penalty_bps: u16,
+    pub penalty_destination: PenaltyDestination,
+    pub treasury: Pubkey,
+    pub unbonding_cooldown: i64,
+    pub total_unbonding: u64,
+    pub config_timelock: i64,
+    pub pending_config: PoolConfig,
+    pub config_update_eta: i64,
+    pub creator: Pubkey,
+    pub pending_admin: Pubkey,
+    pub guardian: Pubkey,
+    pub pool_state: PoolState,
+    pub reward_liability: u64,
+    pub apy_weight_sum: u128,
+    pub apy_weight_index_sum: u128,
+    pub apy_rate_index: u128,
+    pub apy_index_time: i64,
+    pub shutdown_time: i64,
+    pub grace_period_end: i64,
+    pub reward_mint: Pubkey,
+    pub stream_count: u8,
+    pub reward_streams: [RewardStream; MAX_REWARD_STREAMS],
+    pub next_stream_id: u64,
+    pub version: u8,
+}
+
+/// Pool state enum
+/// This enum defines which instructions are available in a pool
+///
+/// # Variants
+/// * `Active` - all instructions are available
+/// * `Paused` - staking and funding are blocked, users can still exit
+/// * `Emergency` - like `Paused`, and users can withdraw their principal with `emergency_withdraw`
+/// * `ShuttingDown` - like `Paused` (the reward pool can still be funded), rewards are frozen and the pool is being closed
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum PoolState {
+    Active,
+    Paused,
+    Emergency,
+    ShuttingDown,
+}
+
+/// Pool config struct
+/// This struct holds the pool parameters the admin can update through the timelocked config update
+///
+/// # Fields
+/// * `reward_rate` - annual percentage yield (APY) in percentage (0-100)
+/// * `min_staking_duration` - minimum staking duration in seconds
+/// * `config_timelock` - delay in seconds between proposing and applying the next config updates
+/// * `unbonding_cooldown` - time in seconds between `request_unstake` and `withdraw_unbonded` (at most `MAX_UNBONDING_COOLDOWN`)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct PoolConfig {
+    pub reward_rate: u8,
+    pub min_staking_duration: i64,
+    pub config_timelock: i64,
+    pub unbonding_cooldown: i64,
+}
+
+/// Penalty policy enum
+/// This enum defines the penalty of force redeeming a position before the end of its lock
+///
+/// # Variants
+/// * `ForfeitRewards` - all the accrued rewards are forfeited, the principal is returned in full
+/// * `FlatPrincipal` - `penalty_bps` of the principal is charged, the accrued rewards are paid
+/// * `LinearDecay` - `penalty_bps` of the principal decaying linearly to zero at the end of the lock, the accrued rewards are paid
+/// * `ProRatedReward` - the rewards are paid pro rata to the elapsed part of the lock, minus `penalty_bps` of them
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum PenaltyPolicy {
+    ForfeitRewards,
+    FlatPrincipal,
+    LinearDecay,
+    ProRatedReward,
+}
+
+/// Penalty destination enum
+/// This enum defines where the principal penalties are routed
+/// Forfeited rewards are never paid out, so they always stay in the reward pool
+///
+/// # Variants
+/// * `RewardPool` - added to the reward funds of the pool
+/// * `Treasury` - transferred to the treasury token account of the pool (kept in the pool like `RewardPool` if it is not provided)
+/// * `Burn` - burned from the vault
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum PenaltyDestination {
+    RewardPool,
+    Treasury,
+    Burn,
+}
+
+/// Lockup tier struct
+/// This struct defines a lock duration users can commit to and the reward multiplier it earns
+///
+/// # Fields
+/// * `lock_duration` - lock duration in seconds (0 for a flexible tier)
+/// * `reward_multiplier_bps` - multiplier of the rewards in basis points (10000 = 1x)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct LockupTier {
+    pub lock_duration: i64,
+    pub reward_multiplier_bps: u16,
+}
+
+/// Reward stream struct
+/// This struct defines an extra reward token emitted over a period and shared pro rata to the stake weights,
+/// on top of the rewards of the pool (e.g. a partner incentive)
+///
+/// # Fields
+/// * `mint` - mint of the rewards of the stream
+/// * `vault` - token account PDA of the pool holding the rewards of the stream
+/// * `reward_per_second` - emission rate in reward tokens per second (shared by all stakers)
+/// * `start_time` - time in seconds at which the emission starts
+/// * `end_time` - time in seconds at which the emission ends
+/// * `acc_reward_per_share` - rewards per weight unit since the start of the stream, scaled by `ACC_REWARD_PRECISION`
+/// * `last_update_time` - time in seconds up to which `acc_reward_per_share` is updated
+/// * `id` - id of the stream in the pool, positions settled with another id hold the rewards of a retired stream (0 for a free slot)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct RewardStream {
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub reward_per_second: u64,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub acc_reward_per_share: u128,
+    pub last_update_time: i64,
+    pub id: u64,
+}
+
+/// Reward mode enum
+/// This enum defines how a staking pool distributes its rewards
+///
+/// # Variants
+/// * `Apy` - every position earns `reward_rate` percent a year, paid from the funded rewards while they last
+/// * `Emission` - the admin funds N tokens over a period and every second's emission is shared pro rata to the stake
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum RewardMode {
+    Apy,
+    Emission,
+}
+
+/// User stake account struct
+/// This struct is used to define the state of the user stake account
+///
+/// # Fields
+/// * `pool` - Staking pool (staking account) this stake belongs to
+/// * `user` - Owner of the stake
+/// * `position_id` - Index of the position among the user's positions in the pool
+/// * `tier` - Index of the lockup tier picked when staking
+/// * `lock_duration` - Lock duration of the position in seconds (from its tier, at least the pool minimum)
+/// * `reward_multiplier_bps` - Reward multiplier of the position in basis points (from its tier)
+/// * `amount` - Amount of tokens staked
+/// * `start_time` - Start time of staking in seconds (the lock is counted from it)
+/// * `last_reward_time` - Time in seconds up to which rewards have been settled
+/// * `apy_rate_index` - `apy_rate_index` of the pool at the last settlement (APY mode)
+/// * `pending_reward` - Rewards settled (e.g. on a top-up) but not paid out yet
+/// * `reward_debt` - `amount * acc_reward_per_share` at the last settlement (emission mode), scaled by `ACC_REWARD_PRECISION`
+/// * `last_claim_time` - Time in seconds of the last reward payout (0 if never claimed)
+/// * `rewards_claimed` - Total rewards paid out (or compounded) to the position so far
+/// * `auto_compound` - Whether anyone may compound the position (for a tip)
+/// * `unbonding_amount` - Principal requested for withdrawal, not earning rewards anymore
+/// * `unlock_at` - Time in seconds from which the unbonding amount can be withdrawn
+/// * `unbonding_cooldown` - Cooldown in seconds of the pool captured by the first pending unbonding request
+/// * `stream_reward_debts` - `weight * acc_reward_per_share` of every reward stream at the last settlement, scaled by `ACC_REWARD_PRECISION`
+/// * `stream_pending_rewards` - Rewards of every reward stream settled but not paid out yet
+/// * `stream_ids` - Id of the reward stream of every slot the debts and pending rewards refer to
+/// * `version` - layout version of the account (see `USER_STAKE_VERSION`), upgraded in place by `upgrade_user_stake`
+///   (version 0 is moved by `migrate_user_stake`), fields of newer layouts go after it
+#[account]
+pub struct UserStake {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub position_id: u64,
+    pub tier: u8,
+    pub lock_duration: i64,
+    pub reward_multiplier_bps: u16,
+    pub amount: u64,
+    pub start_time: i64,
+    pub last_reward_time: i64,
+    pub apy_rate_index: u128,
+    pub pending_reward: u64,
+    pub reward_debt: u128,
+    pub last_claim_time: i64,
+    pub rewards_claimed: u64,
+    pub auto_compound: bool,
+    pub unbonding_amount: u64,
+    pub unlock_at: i64,
+    pub unbonding_cooldown: i64,
+    pub stream_reward_debts: [u128; MAX_REWARD_STREAMS],
+    pub stream_pending_rewards: [u64; MAX_REWARD_STREAMS],
+    pub stream_ids: [u64; MAX_REWARD_STREAMS],
+    pub version: u8,
+}
+
+/// User stake counter account struct
+/// This struct keeps track of the positions opened by a user in a pool
+/// Open positions can be listed by deriving the user stake PDAs for ids `0..next_position_id`
+/// (redeemed positions are closed), or by filtering program accounts on `pool` and `user`
+///
+/// # Fields
+/// * `pool` - Staking pool (staking account) the counter belongs to
+/// * `user` - Owner of the positions
+/// * `next_position_id` - Id assigned to the next position opened by the user
+#[account]
+pub struct UserStakeCounter {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub next_position_id: u64,
+}
+
+/// Staking account struct of the baseline layout (version 0)
+/// Pools of this layout live at the PDA `[admin, "staking_account"]` (one pool per admin) and kept the reward funds
+/// and the staked tokens in the admin token account, `migrate_pool` moves them to a pool of the current layout
+///
+/// # Fields
+/// * `admin` - Admin of the pool
+/// * `reward_rate` - APY return yearly in percentage
+/// * `bump` - Bump of the PDA
+/// * `token_mint` - Mint of the staked token
+/// * `admin_reward_amount` - Reward funds of the pool
+/// * `min_staking_duration` - Minimum staking duration in seconds
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct StakingAccountV0 {
+    pub admin: Pubkey,
+    pub reward_rate: u8,
+    pub bump: u8,
+    pub token_mint: Pubkey,
+    pub admin_reward_amount: u64,
+    pub min_staking_duration: i64,
+}
+
+impl StakingAccountV0 {
+    /// Size of the accounts of this layout (8 for descriptor)
+    pub const LEN: usize = size_of::<Self>() + 8;
+}
+
+/// User stake account struct of the baseline layout (version 0)
+/// Positions of this layout live at the PDA `[user, "user_stake"]` (one position per user, not linked to a pool),
+/// `migrate_user_stake` moves them to a position of the current layout
+///
+/// # Fields
+/// * `amount` - Amount of tokens staked
+/// * `start_time` - Start time of the stake, in milliseconds (client timestamp) or seconds
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct UserStakeV0 {
+    pub amount: u64,
+    pub start_time: i64,
+}
+
+impl UserStakeV0 {
+    /// Size of the accounts of this layout (8 for descriptor)
+    pub const LEN: usize = size_of::<Self>() + 8;
+}
+
+/// Reward preview struct
+/// This struct is returned (as return data) by the preview reward instruction
+///
+/// # Fields
+/// * `timestamp` - time in seconds the preview is computed at
+/// * `reward` - reward owed to the position now, paid by `claim_rewards` (or by `redeem` once matured)
+/// * `funded` - whether the reward funds of the pool cover `reward` (claiming and redeeming a matured position fail otherwise)
+/// * `stream_rewards` - rewards of every reward stream owed to the position now (only the first `stream_count` are used)
+/// * `unlock_time` - time in seconds at which the lock of the position ends
+/// * `matured` - whether the position can be redeemed without penalty (lock ended or pool shutting down)
+/// * `forced_reward` - reward paid if the position is force redeemed now (`reward` once matured)
+/// * `penalty` - principal penalty if the position is force redeemed now (0 once matured)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct RewardPreview {
+    pub timestamp: i64,
+    pub reward: u64,
+    pub funded: bool,
+    pub stream_rewards: [u64; MAX_REWARD_STREAMS],
+    pub unlock_time: i64,
+    pub matured: bool,
+    pub forced_reward: u64,
+    pub penalty: u64,
+}
+
+//////////////////////////////////////////////////////////////////////////////////////////
+////////////////////////////////// INSTRUCTIONS STRUCTS //////////////////////////////////
+//////////////////////////////////////////////////////////////////////////////////////////
+
+/// Initialize instruction structs
+/// This struct is used to define the accounts and instructions required for the initialize instruction
+/// This instruction is used to create a new staking pool
+///
+/// # Fields
+/// * `staking_account` - staking pool account PDA (which is created in the initialize function)
+/// * `staking_token_account` - vault token account PDA owned by the staking account (shared by all users within the pool)
+/// * `mint` - mint of the token which is allowed to stake (must match `token_mint` argument)
+/// * `admin` - admin account (signer)
+/// * `token_program` - token program of the mint (SPL Token or Token-2022) used to create the vault token account
+/// * `system_program` - system program used to create the staking_account PDA
+#[derive(Accounts)]
+#[instruction(pool_id: u64, token_mint: Pubkey)]
+pub struct Initialize<'info> {
+    #[account(
+        init, // means initialize the account
+        payer = admin, // means the admin account will pay for the transaction and rent
+        seeds = [admin.key.as_ref(), b"staking_account", pool_id.to_le_bytes().as_ref()], // means it will create the PDA (one per admin and pool id)
+        bump, // canonical bump, stored on the account to sign vault transfers
+        space = size_of::<StakingAccount>() + 8 // size of the account 8 for descriptor
+    )]
+    pub staking_account: Box<Account<'info, StakingAccount>>, // creates a new account (staking pool)
+    #[account(
+        init, // means initialize the vault token account
+        payer = admin, // admin pays the rent for the vault
+        seeds = [staking_account.key().as_ref(), b"staking_token_account"], // vault PDA derived from the pool
+        bump,
+        token::mint = mint, // vault only holds the staked token
+        token::authority = staking_account, // only the pool PDA can move tokens out of the vault
+        token::token_program = token_program, // SPL Token or Token-2022, whichever owns the mint
+    )]
+    pub staking_token_account: InterfaceAccount<'info, TokenAccount>, // vault token account (shared by all users within the pool)
+    #[account(
+        constraint = mint.key() == token_mint @ StakingError::InvalidArgument, // an `address` constraint on an argument breaks the IDL resolution
+        mint::token_program = token_program, // mint must be owned by the given token program
+    )]
+    pub mint: InterfaceAccount<'info, Mint>, // mint of the token which is allowed to stake
+    #[account(mut)]
+    pub admin: Signer<'info>, // admin account
+    pub token_program: Interface<'info, TokenInterface>, // token program used to create the vault token account
+    pub system_program: Program<'info, System>, // system program used to create the staking_account PDA
+}
+
+/// Set reward mint instruction structs
+/// This struct is used to define the accounts and instructions required for the set reward mint instruction
+///
+/// # Fields
+/// * `staking_account` - staking pool account PDA (which is created in the initialize function)
+/// * `reward_token_account` - reward vault PDA owned by the staking account (which is created in the set reward mint function)
+/// * `reward_mint` - mint of the rewards
+/// * `mint` - mint of the staked token (the reward mint must be owned by the same token program)
+/// * `admin` - admin of the pool (signer)
+/// * `token_program` - token program of both mints used to create the reward vault
+/// * `system_program` - system program used to create the reward vault
+#[derive(Accounts)]
+pub struct SetRewardMint<'info> {
+    #[account(
+        mut,
+        seeds = [staking_account.creator.as_ref(), b"staking_account", staking_account.pool_id.to_le_bytes().as_ref()],
+        bump = staking_account.bump,
+        has_one = admin @ StakingError::AdminOnly,
+    )]
+    pub staking_account: Box<Account<'info, StakingAccount>>, // staking pool account (which is created in the initialize function)
+    #[account(
+        init, // means initialize the reward vault token account
+        payer = admin, // admin pays the rent for the reward vault
+        seeds = [staking_account.key().as_ref(), b"reward_token_account"], // reward vault PDA derived from the pool
+        bump,
+        token::mint = reward_mint, // reward vault only holds the reward token
+        token::authority = staking_account, // only the pool PDA can move tokens out of the reward vault
+        token::token_program = token_program,
+    )]
+    pub reward_token_account: InterfaceAccount<'info, TokenAccount>, // reward vault token account (shared by all users within the pool)
+    #[account(
+        mint::token_program = token_program, // every transfer of the pool goes through the same token program
+    )]
+    pub reward_mint: InterfaceAccount<'info, Mint>, // mint of the rewards
+    #[account(
+        address = staking_account.token_mint @ StakingError::InvalidArgument, // Verify it is the staked token
+        mint::token_program = token_program,
+    )]
+    pub mint: InterfaceAccount<'info, Mint>, // mint of the staked token
+    #[account(mut)]
+    pub admin: Signer<'info>, // admin account (signer)
+    pub token_program: Interface<'info, TokenInterface>, // token program used to create the reward vault token account
+    pub system_program: Program<'info, System>, // system program used to create the reward vault
+}
+
+/// Fund reward instruction structs
+/// This struct is used to define the accounts and instructions required for the fund reward instruction
+///
+/// # Fields
+/// * `staking_account` - staking pool account PDA (which is created in the initialize function)
+/// * `staking_token_account` - staking token account which holds the SPL tokens for staking (shared by all users within the pool)
+/// * `admin` - admin of the pool (signer), only the admin can fund the pool as funding an emission reschedules it
+/// * `admin_token_account` - admin token account which holds the required SPL tokens (of the reward mint)
+/// * `mint` - mint of the staked token (required by `transfer_checked`)
+/// * `reward_token_account` - reward vault PDA of the pool (required when the pool has a separate reward mint)
+/// * `reward_mint` - mint of the rewards (required when the pool has a separate reward mint)
+/// * `token_program` - token program used to transfer tokens
+#[derive(Accounts)]
+pub struct FundRewards<'info> {
+    #[account(
+        mut,
+        seeds = [staking_account.creator.as_ref(), b"staking_account", staking_account.pool_id.to_le_bytes().as_ref()],
+        bump = staking_account.bump,
+        has_one = admin @ StakingError::AdminOnly,
+    )]
+    pub staking_account: Box<Account<'info, StakingAccount>>, // staking pool account (which is created in the initialize function)
+    #[account(
+        mut,
+        seeds = [staking_account.key().as_ref(), b"staking_token_account"], // vault PDA of this pool
+        bump,
+    )]
+    pub staking_token_account: InterfaceAccount<'info, TokenAccount>, // staking token account which holds the SPL tokens for staking (shared by all users within the pool)
+    #[account(mut)]
+    pub admin: Signer<'info>, // admin account (signer)
+    #[account(mut)]
+    pub admin_token_account: InterfaceAccount<'info, TokenAccount>, // admin token account which holds the required SPL tokens
+    #[account(
+        address = staking_account.token_mint @ StakingError::InvalidArgument, // Verify it is the staked token
+    )]
+    pub mint: InterfaceAccount<'info, Mint>, // mint of the staked token (required by transfer_checked)
+    #[account(
+        mut,
+        seeds = [staking_account.key().as_ref(), b"reward_token_account"], // reward vault PDA of this pool
+        bump,
+    )]
+    pub reward_token_account: Option<InterfaceAccount<'info, TokenAccount>>, // reward vault of a pool with a separate reward mint
+    #[account(
+        address = staking_account.reward_mint @ StakingError::InvalidArgument, // Verify it is the reward token
+    )]
+    pub reward_mint: Option<InterfaceAccount<'info, Mint>>, // mint of the rewards of a pool with a separate reward mint
+    pub token_program: Interface<'info, TokenInterface>, // token program used to transfer tokens securly
+}
+
+impl<'info> FundRewards<'info> {
+    /// This function transfers reward funds from the admin to the vault holding the rewards of the pool
+    /// (the reward vault if the pool has a separate reward mint, the staking vault otherwise)
+    /// Returns the amount received by the vault
+    ///
+    /// # Example
+    /// ```rs
+    /// let received = ctx.accounts.transfer_from_admin(amount)?;
+    /// ```
+    pub fn transfer_from_admin(&mut self, amount: u64) -> Result<u64> {
+        if !has_reward_vault(&self.staking_account) {
+            let vault_balance = self.staking_token_account.amount;
+            token_interface::transfer_checked(
+                self.fund_reward_from_admin_ctx(),
+                amount,
+                self.mint.decimals,
+            )?;
+            return received_amount(&mut self.staking_token_account, vault_balance);
+        }
+
+        let reward_mint = reward_account(&self.reward_mint)?;
+        let reward_token_account = self
+            .reward_token_account
+            .as_mut()
+            .ok_or(StakingError::InvalidArgument)?;
+        let vault_balance = reward_token_account.amount;
+        token_interface::transfer_checked(
+            CpiContext::new(
+                self.token_program.to_account_info(), // token program account info (used for token transferring)
+                TransferChecked {
+                    from: self.admin_token_account.to_account_info(), // from admin's token account
+                    mint: reward_mint.to_account_info(),              // mint of the rewards
+                    to: reward_token_account.to_account_info(), // to the reward vault of the pool
+                    authority: self.admin.to_account_info(), // authority to transfer tokens (signer)
+                },
+            ),
+            amount,
+            reward_mint.decimals,
+        )?;
+        received_amount(reward_token_account, vault_balance)
+    }
+
+    /// This function creates CPI context for transferring tokens from admin to staking account
+    ///
+    /// # Example
+    /// ```rs
+    /// let ctx = FundRewards {...}
+    /// let cpi_ctx = ctx.fund_reward_from_admin_ctx();
+    /// token_interface::transfer_checked(cpi_ctx, amount, decimals)?;
+    /// ```
+    pub fn fund_reward_from_admin_ctx(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(), // token program account info (used for token transferring)
+            TransferChecked {
+                from: self.admin_token_account.to_account_info(), // from admin's token account
+                mint: self.mint.to_account_info(),                // mint of the transferred token
+                to: self.staking_token_account.to_account_info(), // to staking pool's token account
+                authority: self.admin.to_account_info(), // authority to transfer tokens (signer)
+            },
+        )
+    }
+}
+
+/// Withdraw rewards instruction structs
+/// This struct is used to define the accounts and instructions required for the withdraw rewards instruction
+///
+/// # Fields
+/// * `staking_account` - staking pool account PDA (which is created in the initialize function), signs the vault transfer
+/// * `staking_token_account` - staking token account which holds the SPL tokens for staking (shared by all users within the pool)
+/// * `admin` - admin of the pool (signer)
+/// * `admin_token_account` - token account which receives the withdrawn rewards (e.g. the treasury)
+/// * `mint` - mint of the staked token (required by `transfer_checked`)
+/// * `reward_token_account` - reward vault PDA of the pool (required when the pool has a separate reward mint)
+/// * `reward_mint` - mint of the rewards (required when the pool has a separate reward mint)
+/// * `token_program` - token program used to transfer tokens
+#[derive(Accounts)]
+pub struct WithdrawRewards<'info> {
+    #[account(
+        mut,
+        seeds = [staking_account.creator.as_ref(), b"staking_account", staking_account.pool_id.to_le_bytes().as_ref()],
+        bump = staking_account.bump,
+        has_one = admin @ StakingError::AdminOnly,
+    )]
+    pub staking_account: Box<Account<'info, StakingAccount>>, // staking pool account (which is created in the initialize function)
+    #[account(
+        mut,
+        seeds = [staking_account.key().as_ref(), b"staking_token_account"], // vault PDA of this pool
+        bump,
+    )]
+    pub staking_token_account: InterfaceAccount<'info, TokenAccount>, // staking token account which holds the SPL tokens for staking (shared by all users within the pool)
+    pub admin: Signer<'info>, // admin account (signer)
+    #[account(
+        mut,
+        constraint = admin_token_account.mint == staking_account.reward_mint @ StakingError::InvalidArgument, // Verify it holds the reward token
+    )]
+    pub admin_token_account: InterfaceAccount<'info, TokenAccount>, // token account which receives the withdrawn rewards
+    #[account(
+        address = staking_account.token_mint @ StakingError::InvalidArgument, // Verify it is the staked token
+    )]
+    pub mint: InterfaceAccount<'info, Mint>, // mint of the staked token (required by transfer_checked)
+    #[account(
+        mut,
+        seeds = [staking_account.key().as_ref(), b"reward_token_account"], // reward vault PDA of this pool
+        bump,
+    )]
+    pub reward_token_account: Option<InterfaceAccount<'info, TokenAccount>>, // reward vault of a pool with a separate reward mint
+    #[account(
+        address = staking_account.reward_mint @ StakingError::InvalidArgument, // Verify it is the reward token
+    )]
+    pub reward_mint: Option<InterfaceAccount<'info, Mint>>, // mint of the rewards of a pool with a separate reward mint
+    pub token_program: Interface<'info, TokenInterface>, // token program used to transfer tokens
+}
+
+impl<'info> WithdrawRewards<'info> {
+    /// This function creates CPI context for transferring tokens from staking account to admin
+    /// The vault is owned by the staking account PDA, so the transfer is signed with its seeds
+    ///
+    /// # Example
+    /// ```rs
+    /// let ctx = WithdrawRewards {...}
+    /// let cpi_ctx = ctx.transfer_to_admin_ctx(signer_seeds);
+    /// token_interface::transfer_checked(cpi_ctx, amount, decimals)?;
+    /// ```
+    pub fn transfer_to_admin_ctx<'a, 'b, 'c>(
+        &self,
+        signer_seeds: &'a [&'b [&'c [u8]]],
+    ) -> CpiContext<'a, 'b, 'c, 'info, TransferChecked<'info>> {
+        CpiContext::new_with_signer(
+            self.token_program.to_account_info(), // token program account info (used for token transferring)
+            TransferChecked {
+                from: self.staking_token_account.to_account_info(), // from staking pool's token account
+                mint: self.mint.to_account_info(),                  // mint of the transferred token
+                to: self.admin_token_account.to_account_info(),     // to admin's token account
+                authority: self.staking_account.to_account_info(), // authority to transfer tokens (staking pool PDA)
+            },
+            signer_seeds,
+        )
+    }
+
+    /// This function creates CPI context for transferring rewards from the reward vault to admin
+    /// Only used by pools with a separate reward mint, the reward vault is owned by the staking account PDA
+    ///
+    /// # Example
+    /// ```rs
+    /// let ctx = WithdrawRewards {...}
+    /// let cpi_ctx = ctx.transfer_reward_to_admin_ctx(signer_seeds)?;
+    /// token_interface::transfer_checked(cpi_ctx, amount, decimals)?;
+    /// ```
+    pub fn transfer_reward_to_admin_ctx<'a, 'b, 'c>(
+        &self,
+        signer_seeds: &'a [&'b [&'c [u8]]],
+    ) -> Result<CpiContext<'a, 'b, 'c, 'info, TransferChecked<'info>>> {
+        Ok(CpiContext::new_with_signer(
+            self.token_program.to_account_info(), // token program account info (used for token transferring)
+            TransferChecked {
+                from: reward_account(&self.reward_token_account)?.to_account_info(), // from the reward vault of the pool
+                mint: reward_account(&self.reward_mint)?.to_account_info(), // mint of the rewards
+                to: self.admin_token_account.to_account_info(), // to admin's token account
+                authority: self.staking_account.to_account_info(), // authority to transfer tokens (staking pool PDA)
+            },
+            signer_seeds,
+        ))
+    }
+}
+
+/// Add reward stream instruction structs
+/// This struct is used to define the accounts and instructions required for the add reward stream instruction
+///
+/// # Fields
+/// * `staking_account` - staking pool account PDA (which is created in the initialize function)
+/// * `reward_stream_vault` - token account PDA holding the rewards of the new stream (which is created in the add reward stream function)
+/// * `stream_mint` - mint of the rewards of the new stream
+/// * `mint` - mint of the staked token (the stream mint must be owned by the same token program)
+/// * `admin` - admin of the pool (signer)
+/// * `admin_token_account` - admin token account which holds the stream rewards
+/// * `token_program` - token program of both mints used to create the stream vault and transfer tokens
+/// * `system_program` - system program used to create the stream vault
+#[derive(Accounts)]
+#[instruction(index: u8)]
+pub struct AddRewardStream<'info> {
+    #[account(
+        mut,
+        seeds = [staking_account.creator.as_ref(), b"staking_account", staking_account.pool_id.to_le_bytes().as_ref()],
+        bump = staking_account.bump,
+        has_one = admin @ StakingError::AdminOnly,
+    )]
+    pub staking_account: Box<Account<'info, StakingAccount>>, // staking pool account (which is created in the initialize function)
+    #[account(
+        init, // means initialize the stream vault token account
+        payer = admin, // admin pays the rent for the stream vault
+        seeds = [
+            staking_account.key().as_ref(),
+            b"reward_stream",
+            index.to_le_bytes().as_ref(),
+        ], // stream vault PDA derived from the pool and the slot of the stream (checked to be the free one)
+        bump,
+        token::mint = stream_mint, // stream vault only holds the stream reward token
+        token::authority = staking_account, // only the pool PDA can move tokens out of the stream vault
+        token::token_program = token_program,
+    )]
+    pub reward_stream_vault: InterfaceAccount<'info, TokenAccount>, // token account holding the rewards of the stream
+    #[account(
+        mint::token_program = token_program, // every transfer of the pool goes through the same token program
+    )]
+    pub stream_mint: InterfaceAccount<'info, Mint>, // mint of the rewards of the stream
+    #[account(
+        address = staking_account.token_mint @ StakingError::InvalidArgument, // Verify it is the staked token
+        mint::token_program = token_program,
+    )]
+    pub mint: InterfaceAccount<'info, Mint>, // mint of the staked token
+    #[account(mut)]
+    pub admin: Signer<'info>, // admin account (signer)
+    #[account(
+        mut,
+        constraint = admin_token_account.mint == stream_mint.key() @ StakingError::InvalidArgument, // Verify it holds the stream reward token
+    )]
+    pub admin_token_account: InterfaceAccount<'info, TokenAccount>, // admin token account which holds the stream rewards
+    pub token_program: Interface<'info, TokenInterface>, // token program used to create the stream vault and transfer tokens
+    pub system_program: Program<'info, System>, // system program used to create the stream vault
+}
+
+impl<'info> AddRewardStream<'info> {
+    /// This function creates CPI context for transferring the stream rewards from admin to the stream vault
+    ///
+    /// # Example
+    /// ```rs
+    /// let ctx = AddRewardStream {...}
+    /// let cpi_ctx = ctx.fund_stream_from_admin_ctx();
+    /// token_interface::transfer_checked(cpi_ctx, amount, decimals)?;
+    /// ```
+    pub fn fund_stream_from_admin_ctx(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(), // token program account info (used for token transferring)
+            TransferChecked {
+                from: self.admin_token_account.to_account_info(), // from admin's token account
+                mint: self.stream_mint.to_account_info(),         // mint of the stream rewards
+                to: self.reward_stream_vault.to_account_info(),   // to the stream vault
+                authority: self.admin.to_account_info(), // authority to transfer tokens (signer)
+            },
+        )
+    }
+}
+
+/// Retire reward stream instruction structs
+/// This struct is used to define the accounts and instructions required for the retire reward stream instruction
+///
+/// # Fields
+/// * `staking_account` - staking pool account PDA (which is created in the initialize function)
+/// * `reward_stream_vault` - token account PDA holding the rewards of the retired stream (closed by the instruction)
+/// * `stream_mint` - mint of the rewards of the retired stream
+/// * `admin` - admin of the pool (signer), receives the rent of the closed vault
+/// * `admin_token_account` - admin token account receiving the rewards left in the vault
+/// * `token_program` - token program used to transfer tokens and close the vault
+#[derive(Accounts)]
+#[instruction(index: u8)]
+pub struct RetireRewardStream<'info> {
+    #[account(
+        mut,
+        seeds = [staking_account.creator.as_ref(), b"staking_account", staking_account.pool_id.to_le_bytes().as_ref()],
+        bump = staking_account.bump,
+        has_one = admin @ StakingError::AdminOnly,
+        constraint = (index as usize) < MAX_REWARD_STREAMS
+            && staking_account.reward_streams[index as usize].id != 0 @ StakingError::InvalidArgument, // Verify the slot holds a stream
+    )]
+    pub staking_account: Box<Account<'info, StakingAccount>>, // staking pool account (which is created in the initialize function)
+    #[account(
+        mut,
+        address = staking_account.reward_streams[index as usize].vault @ StakingError::InvalidArgument, // Verify it is the vault of the stream
+    )]
+    pub reward_stream_vault: InterfaceAccount<'info, TokenAccount>, // token account holding the rewards of the stream
+    #[account(
+        address = staking_account.reward_streams[index as usize].mint @ StakingError::InvalidArgument, // Verify it is the stream reward token
+    )]
+    pub stream_mint: InterfaceAccount<'info, Mint>, // mint of the rewards of the stream
+    #[account(mut)]
+    pub admin: Signer<'info>, // admin account (signer)
+    #[account(
+        mut,
+        constraint = admin_token_account.mint == stream_mint.key() @ StakingError::InvalidArgument, // Verify it holds the stream reward token
+    )]
+    pub admin_token_account: InterfaceAccount<'info, TokenAccount>, // admin token account receiving the rewards left in the vault
+    pub token_program: Interface<'info, TokenInterface>, // token program used to transfer tokens and close the vault
+}
+
+impl<'info> RetireRewardStream<'info> {
+    /// This function creates CPI context for transferring the rewards left in the stream vault to the admin
+    ///
+    /// # Example
+    /// ```rs
+    /// let ctx = RetireRewardStream {...}
+    /// let cpi_ctx = ctx.transfer_to_admin_ctx(signer_seeds);
+    /// token_interface::transfer_checked(cpi_ctx, amount, decimals)?;
+    /// ```
+    pub fn transfer_to_admin_ctx<'a, 'b, 'c>(
+        &self,
+        signer_seeds: &'a [&'b [&'c [u8]]],
+    ) -> CpiContext<'a, 'b, 'c, 'info, TransferChecked<'info>> {
+        CpiContext::new_with_signer(
+            self.token_program.to_account_info(), // token program account info (used for token transferring)
+            TransferChecked {
+                from: self.reward_stream_vault.to_account_info(), // from the vault of the stream
+                mint: self.stream_mint.to_account_info(),         // mint of the stream rewards
+                to: self.admin_token_account.to_account_info(),   // to admin's token account
+                authority: self.staking_account.to_account_info(), // authority to transfer tokens (staking pool PDA)
+            },
+            signer_seeds,
+        )
+    }
+
+    /// This function creates CPI context for closing the stream vault, its rent going to the admin
+    ///
+    /// # Example
+    /// ```rs
+    /// let ctx = RetireRewardStream {...}
+    /// let cpi_ctx = ctx.close_vault_ctx(signer_seeds);
+    /// token_interface::close_account(cpi_ctx)?;
+    /// ```
+    pub fn close_vault_ctx<'a, 'b, 'c>(
+        &self,
+        signer_seeds: &'a [&'b [&'c [u8]]],
+    ) -> CpiContext<'a, 'b, 'c, 'info, CloseAccount<'info>> {
+        CpiContext::new_with_signer(
+            self.token_program.to_account_info(), // token program account info (used for closing the vault)
+            CloseAccount {
+                account: self.reward_stream_vault.to_account_info(), // vault to close
+                destination: self.admin.to_account_info(),           // admin receives the rent
+                authority: self.staking_account.to_account_info(), // authority of the vault (staking pool PDA)
+            },
+            signer_seeds,
+        )
+    }
+}
+
+/// Stake instruction structs
+/// This struct is used to define the accounts and instructions required for the stake instruction
+///
+/// # Fields
+/// * `staking_account` - staking pool account PDA (which is created in the initialize function)
+/// * `user_stake_counter` - user stake counter PDA (created on the first stake of the user in the pool)
+/// * `user_stake` - user stake account PDA of the new position (which is created in the stake function)
+/// * `user` - user account (signer)
+/// * `user_token_account` - user token account which holds the required SPL tokens
+/// * `staking_token_account` - staking token account which holds the SPL tokens for staking (shared by all users within the pool)
+/// * `mint` - mint of the staked token (required by `transfer_checked`)
+/// * `token_program` - token program used to transfer tokens
+///
+/// # Example
+///
+/// ```rs
+/// let ctx = Stake {
+///    staking_account: staking_account.to_account_info(),
+///    user_stake_counter: user_stake_counter.to_account_info(),
+///    user_stake: user_stake.to_account_info(),
 ///    user: user.to_account_info(),
 ///    user_token_account: user_token_account.to_account_info(),
 ///    staking_token_account: staking_token_account.to_account_info(),
@@ -327,129 +2856,1915 @@ impl<'info> FundRewards<'info> {
 /// };
 /// ```
 #[derive(Accounts)]
-pub struct Stake<'info> {
+pub struct Stake<'info> {
+    #[account(
+        mut,
+        seeds = [staking_account.creator.as_ref(), b"staking_account", staking_account.pool_id.to_le_bytes().as_ref()],
+        bump = staking_account.bump,
+    )]
+    pub staking_account: Box<Account<'info, StakingAccount>>, // staking pool account (which is created in the initialize function)
+    #[account(
+        init_if_needed, // created on the first stake of the user in this pool
+        payer = user, // user will pay for the transaction and rent
+        seeds = [staking_account.key().as_ref(), user.key().as_ref(), b"user_stake_counter"], // seeds for the PDA (unique for each user within the pool)
+        bump,
+        space = size_of::<UserStakeCounter>() + 8 // size of the account 8 for descriptor
+    )]
+    pub user_stake_counter: Account<'info, UserStakeCounter>, // user stake counter account
+    #[account(
+        init, // initialize the new account with UserStake struct
+        payer = user, // user will pay for the transaction and rent
+        seeds = [
+            staking_account.key().as_ref(),
+            user.key().as_ref(),
+            b"user_stake",
+            user_stake_counter.next_position_id.to_le_bytes().as_ref(),
+        ], // seeds for the PDA (unique for each position of the user within the pool)
+        bump, // bump for the PDA (one user can have multiple positions per pool)
+        space = size_of::<UserStake>() + 8 // size of the account 8 for descriptor
+    )]
+    pub user_stake: Account<'info, UserStake>, // user stake account
+    #[account(mut)]
+    pub user: Signer<'info>, // user account (signer)
+    #[account(mut)]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>, // user token account which holds the required SPL tokens
+    #[account(
+        mut,
+        seeds = [staking_account.key().as_ref(), b"staking_token_account"], // vault PDA of this pool
+        bump,
+    )]
+    pub staking_token_account: InterfaceAccount<'info, TokenAccount>, // staking token account which holds the SPL tokens for staking (shared by all users within the pool)
+    #[account(
+        address = staking_account.token_mint @ StakingError::InvalidArgument, // Verify it is the staked token
+    )]
+    pub mint: InterfaceAccount<'info, Mint>, // mint of the staked token (required by transfer_checked)
+    pub token_program: Interface<'info, TokenInterface>, // token program used to transfer tokens securly
+    pub system_program: Program<'info, System>, // system program used to create the user_stake account
+}
+
+impl<'info> Stake<'info> {
+    /// Implementation of Stake instruction
+    /// This function is used to create CPI context for transferring tokens from user to staking account
+    ///
+    /// # Example
+    ///
+    /// ```rs
+    /// let ctx = Stake {...}
+    /// let cpi_ctx = ctx.transfer_to_stake_ctx();
+    /// token_interface::transfer_checked(cpi_ctx, amount, decimals)?;
+    /// ```
+    pub fn transfer_to_stake_ctx(&self) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+        // Creates the CPI context and return it
+        CpiContext::new(
+            self.token_program.to_account_info(), // token program account info (used for token transferring)
+            TransferChecked {
+                from: self.user_token_account.to_account_info(), // from user's token account
+                mint: self.mint.to_account_info(),               // mint of the transferred token
+                to: self.staking_token_account.to_account_info(), // to staking pool's token account
+                authority: self.user.to_account_info(), // authority to transfer tokens (signer)
+            },
+        )
+    }
+}
+
+/// Redeem instruction structs
+/// This struct is used to define the accounts and instructions required for the redeem instruction
+///
+/// # Fields
+/// * `staking_account` - staking pool account PDA (which is created in the initialize function), signs the vault transfer
+/// * `user_stake` - user stake account PDA (which is created in the stake function)
+/// * `user` - user account (signer)
+/// * `user_token_account` - user token account which holds the required SPL tokens
+/// * `staking_token_account` - staking token account which holds the SPL tokens for staking (shared by all users within the pool)
+/// * `treasury_token_account` - treasury of the pool (penalties routed to the treasury stay in the pool without it)
+/// * `mint` - mint of the staked token (required by `transfer_checked`, mutable to burn penalties)
+/// * `reward_token_account` - reward vault PDA of the pool (required when the pool has a separate reward mint)
+/// * `reward_mint` - mint of the rewards (required when the pool has a separate reward mint)
+/// * `user_reward_token_account` - user token account which receives the rewards (required when the pool has a separate reward mint)
+/// * `token_program` - token program used to transfer tokens
+#[derive(Accounts)]
+pub struct Redeem<'info> {
+    #[account(
+        mut,
+        seeds = [staking_account.creator.as_ref(), b"staking_account", staking_account.pool_id.to_le_bytes().as_ref()],
+        bump = staking_account.bump,
+    )]
+    pub staking_account: Box<Account<'info, StakingAccount>>, // staking pool account for managing state (which is created in the initialize function)
+
+    #[account(
+        mut, // mutable account (which is created in the stake function)
+        seeds = [
+            staking_account.key().as_ref(),
+            user.key.as_ref(),
+            b"user_stake",
+            user_stake.position_id.to_le_bytes().as_ref(),
+        ], // seeds (to access the PDA created in the stake function)
+        bump, // bump for the PDA (must be same as the one used in the stake function)
+        has_one = user @ StakingError::InvalidArgument, // stake must be owned by the signer
+        constraint = user_stake.pool == staking_account.key() @ StakingError::PoolMismatch, // stake must belong to this pool
+        constraint = user_stake.version == USER_STAKE_VERSION @ StakingError::AccountNotMigrated, // stake must be migrated to the current layout
+    )]
+    pub user_stake: Account<'info, UserStake>, // user stake account PDA
+    #[account(mut)]
+    pub user: Signer<'info>, // user account (signer) used to sign the transaction while redeeming it
+    #[account(
+        mut,
+        constraint = user_token_account.owner == user.key() // Verify user owns the token account
+    )]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>, // user token account which holds the required SPL tokens
+    #[account(
+        mut,
+        seeds = [staking_account.key().as_ref(), b"staking_token_account"], // vault PDA of this pool
+        bump,
+    )]
+    pub staking_token_account: InterfaceAccount<'info, TokenAccount>, // staking token account which holds the SPL tokens for staking (shared by all users within the pool)
+    #[account(
+        mut,
+        address = staking_account.treasury @ StakingError::InvalidArgument, // Verify it is the treasury of the pool
+    )]
+    pub treasury_token_account: Option<InterfaceAccount<'info, TokenAccount>>, // treasury receiving the penalties
+    #[account(
+        mut,
+        address = staking_account.token_mint @ StakingError::InvalidArgument, // Verify it is the staked token
+    )]
+    pub mint: InterfaceAccount<'info, Mint>, // mint of the staked token (required by transfer_checked, burns penalties)
+    #[account(
+        mut,
+        seeds = [staking_account.key().as_ref(), b"reward_token_account"], // reward vault PDA of this pool
+        bump,
+    )]
+    pub reward_token_account: Option<InterfaceAccount<'info, TokenAccount>>, // reward vault of a pool with a separate reward mint
+    #[account(
+        address = staking_account.reward_mint @ StakingError::InvalidArgument, // Verify it is the reward token
+    )]
+    pub reward_mint: Option<InterfaceAccount<'info, Mint>>, // mint of the rewards of a pool with a separate reward mint
+    #[account(
+        mut,
+        constraint = user_reward_token_account.owner == user.key(), // Verify user owns the token account
+        constraint = user_reward_token_account.mint == staking_account.reward_mint @ StakingError::InvalidArgument, // Verify it holds the reward token
+    )]
+    pub user_reward_token_account: Option<InterfaceAccount<'info, TokenAccount>>, // user token account which receives the rewards
+    pub token_program: Interface<'info, TokenInterface>, // token program used to transfer tokens
+}
+
+impl<'info> Redeem<'info> {
+    /// This function creates CPI context for transferring a penalty from staking account to the treasury
+    ///
+    /// # Example
+    /// ```rs
+    /// let ctx = Redeem {...}
+    /// let cpi_ctx = ctx.transfer_to_treasury_ctx(signer_seeds)?;
+    /// token_interface::transfer_checked(cpi_ctx, penalty, decimals)?;
+    /// ```
+    pub fn transfer_to_treasury_ctx<'a, 'b, 'c>(
+        &self,
+        signer_seeds: &'a [&'b [&'c [u8]]],
+    ) -> Result<CpiContext<'a, 'b, 'c, 'info, TransferChecked<'info>>> {
+        let treasury_token_account = self
+            .treasury_token_account
+            .as_ref()
+            .ok_or(StakingError::InvalidArgument)?;
+
+        Ok(CpiContext::new_with_signer(
+            self.token_program.to_account_info(), // token program account info (used for token transferring)
+            TransferChecked {
+                from: self.staking_token_account.to_account_info(), // from staking pool's token account
+                mint: self.mint.to_account_info(),                  // mint of the transferred token
+                to: treasury_token_account.to_account_info(),       // to the treasury
+                authority: self.staking_account.to_account_info(), // authority to transfer tokens (staking pool PDA)
+            },
+            signer_seeds,
+        ))
+    }
+
+    /// This function creates CPI context for burning a penalty from the staking account
+    ///
+    /// # Example
+    /// ```rs
+    /// let ctx = Redeem {...}
+    /// let cpi_ctx = ctx.burn_penalty_ctx(signer_seeds);
+    /// token_interface::burn(cpi_ctx, penalty)?;
+    /// ```
+    pub fn burn_penalty_ctx<'a, 'b, 'c>(
+        &self,
+        signer_seeds: &'a [&'b [&'c [u8]]],
+    ) -> CpiContext<'a, 'b, 'c, 'info, Burn<'info>> {
+        CpiContext::new_with_signer(
+            self.token_program.to_account_info(), // token program account info (used for burning)
+            Burn {
+                mint: self.mint.to_account_info(), // mint of the staked token
+                from: self.staking_token_account.to_account_info(), // from staking pool's token account
+                authority: self.staking_account.to_account_info(), // authority to burn tokens (staking pool PDA)
+            },
+            signer_seeds,
+        )
+    }
+}
+
+/// Increase stake instruction structs
+/// This struct is used to define the accounts and instructions required for the increase stake instruction
+///
+/// # Fields
+/// * `staking_account` - staking pool account PDA (which is created in the initialize function)
+/// * `user_stake` - user stake account PDA of the position to top up
+/// * `user` - user account (signer)
+/// * `user_token_account` - user token account which holds the required SPL tokens
+/// * `staking_token_account` - staking token account which holds the SPL tokens for staking (shared by all users within the pool)
+/// * `mint` - mint of the staked token (required by `transfer_checked`)
+/// * `token_program` - token program used to transfer tokens
+#[derive(Accounts)]
+pub struct IncreaseStake<'info> {
+    #[account(
+        mut,
+        seeds = [staking_account.creator.as_ref(), b"staking_account", staking_account.pool_id.to_le_bytes().as_ref()],
+        bump = staking_account.bump,
+    )]
+    pub staking_account: Box<Account<'info, StakingAccount>>, // staking pool account (which is created in the initialize function)
+    #[account(
+        mut,
+        seeds = [
+            staking_account.key().as_ref(),
+            user.key.as_ref(),
+            b"user_stake",
+            user_stake.position_id.to_le_bytes().as_ref(),
+        ], // seeds (to access the PDA created in the stake function)
+        bump,
+        has_one = user @ StakingError::InvalidArgument, // stake must be owned by the signer
+        constraint = user_stake.pool == staking_account.key() @ StakingError::PoolMismatch, // stake must belong to this pool
+        constraint = user_stake.version == USER_STAKE_VERSION @ StakingError::AccountNotMigrated, // stake must be migrated to the current layout
+    )]
+    pub user_stake: Account<'info, UserStake>, // user stake account PDA
+    #[account(mut)]
+    pub user: Signer<'info>, // user account (signer)
+    #[account(mut)]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>, // user token account which holds the required SPL tokens
+    #[account(
+        mut,
+        seeds = [staking_account.key().as_ref(), b"staking_token_account"], // vault PDA of this pool
+        bump,
+    )]
+    pub staking_token_account: InterfaceAccount<'info, TokenAccount>, // staking token account which holds the SPL tokens for staking (shared by all users within the pool)
+    #[account(
+        address = staking_account.token_mint @ StakingError::InvalidArgument, // Verify it is the staked token
+    )]
+    pub mint: InterfaceAccount<'info, Mint>, // mint of the staked token (required by transfer_checked)
+    pub token_program: Interface<'info, TokenInterface>, // token program used to transfer tokens securly
+}
+
+impl<'info> IncreaseStake<'info> {
+    /// This function creates CPI context for transferring tokens from user to staking account
+    ///
+    /// # Example
+    /// ```rs
+    /// let ctx = IncreaseStake {...}
+    /// let cpi_ctx = ctx.transfer_to_stake_ctx();
+    /// token_interface::transfer_checked(cpi_ctx, amount, decimals)?;
+    /// ```
+    pub fn transfer_to_stake_ctx(&self) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(), // token program account info (used for token transferring)
+            TransferChecked {
+                from: self.user_token_account.to_account_info(), // from user's token account
+                mint: self.mint.to_account_info(),               // mint of the transferred token
+                to: self.staking_token_account.to_account_info(), // to staking pool's token account
+                authority: self.user.to_account_info(), // authority to transfer tokens (signer)
+            },
+        )
+    }
+}
+
+/// Partial unstake instruction structs
+/// This struct is used to define the accounts and instructions required for the partial unstake instruction
+///
+/// # Fields
+/// * `staking_account` - staking pool account PDA (which is created in the initialize function), signs the vault transfer
+/// * `user_stake` - user stake account PDA of the position to withdraw from (stays open)
+/// * `user` - user account (signer)
+/// * `user_token_account` - user token account which receives the withdrawn tokens
+/// * `staking_token_account` - staking token account which holds the SPL tokens for staking (shared by all users within the pool)
+/// * `mint` - mint of the staked token (required by `transfer_checked`)
+/// * `reward_token_account` - reward vault PDA of the pool (required when the pool has a separate reward mint)
+/// * `reward_mint` - mint of the rewards (required when the pool has a separate reward mint)
+/// * `user_reward_token_account` - user token account which receives the rewards (required when the pool has a separate reward mint)
+/// * `token_program` - token program used to transfer tokens
+#[derive(Accounts)]
+pub struct PartialUnstake<'info> {
+    #[account(
+        mut,
+        seeds = [staking_account.creator.as_ref(), b"staking_account", staking_account.pool_id.to_le_bytes().as_ref()],
+        bump = staking_account.bump,
+    )]
+    pub staking_account: Box<Account<'info, StakingAccount>>, // staking pool account for managing state (which is created in the initialize function)
+    #[account(
+        mut,
+        seeds = [
+            staking_account.key().as_ref(),
+            user.key.as_ref(),
+            b"user_stake",
+            user_stake.position_id.to_le_bytes().as_ref(),
+        ], // seeds (to access the PDA created in the stake function)
+        bump,
+        has_one = user @ StakingError::InvalidArgument, // stake must be owned by the signer
+        constraint = user_stake.pool == staking_account.key() @ StakingError::PoolMismatch, // stake must belong to this pool
+        constraint = user_stake.version == USER_STAKE_VERSION @ StakingError::AccountNotMigrated, // stake must be migrated to the current layout
+    )]
+    pub user_stake: Account<'info, UserStake>, // user stake account PDA
+    #[account(mut)]
+    pub user: Signer<'info>, // user account (signer)
+    #[account(
+        mut,
+        constraint = user_token_account.owner == user.key(), // Verify user owns the token account
+        constraint = user_token_account.mint == staking_account.token_mint @ StakingError::InvalidArgument, // Verify token mint matches staking account
+    )]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>, // user token account which receives the withdrawn tokens
+    #[account(
+        mut,
+        seeds = [staking_account.key().as_ref(), b"staking_token_account"], // vault PDA of this pool
+        bump,
+    )]
+    pub staking_token_account: InterfaceAccount<'info, TokenAccount>, // staking token account which holds the SPL tokens for staking (shared by all users within the pool)
+    #[account(
+        address = staking_account.token_mint @ StakingError::InvalidArgument, // Verify it is the staked token
+    )]
+    pub mint: InterfaceAccount<'info, Mint>, // mint of the staked token (required by transfer_checked)
+    #[account(
+        mut,
+        seeds = [staking_account.key().as_ref(), b"reward_token_account"], // reward vault PDA of this pool
+        bump,
+    )]
+    pub reward_token_account: Option<InterfaceAccount<'info, TokenAccount>>, // reward vault of a pool with a separate reward mint
+    #[account(
+        address = staking_account.reward_mint @ StakingError::InvalidArgument, // Verify it is the reward token
+    )]
+    pub reward_mint: Option<InterfaceAccount<'info, Mint>>, // mint of the rewards of a pool with a separate reward mint
+    #[account(
+        mut,
+        constraint = user_reward_token_account.owner == user.key(), // Verify user owns the token account
+        constraint = user_reward_token_account.mint == staking_account.reward_mint @ StakingError::InvalidArgument, // Verify it holds the reward token
+    )]
+    pub user_reward_token_account: Option<InterfaceAccount<'info, TokenAccount>>, // user token account which receives the rewards
+    pub token_program: Interface<'info, TokenInterface>, // token program used to transfer tokens
+}
+
+/// Request unstake instruction structs
+/// This struct is used to define the accounts and instructions required for the request unstake instruction
+///
+/// # Fields
+/// * `staking_account` - staking pool account PDA (which is created in the initialize function), signs the vault transfer
+/// * `user_stake` - user stake account PDA of the position to unbond from (stays open)
+/// * `user` - user account (signer)
+/// * `user_token_account` - user token account which receives the rewards
+/// * `staking_token_account` - staking token account which holds the SPL tokens for staking (shared by all users within the pool)
+/// * `mint` - mint of the staked token (required by `transfer_checked`)
+/// * `reward_token_account` - reward vault PDA of the pool (required when the pool has a separate reward mint)
+/// * `reward_mint` - mint of the rewards (required when the pool has a separate reward mint)
+/// * `user_reward_token_account` - user token account which receives the rewards (required when the pool has a separate reward mint)
+/// * `token_program` - token program used to transfer tokens
+#[derive(Accounts)]
+pub struct RequestUnstake<'info> {
+    #[account(
+        mut,
+        seeds = [staking_account.creator.as_ref(), b"staking_account", staking_account.pool_id.to_le_bytes().as_ref()],
+        bump = staking_account.bump,
+    )]
+    pub staking_account: Box<Account<'info, StakingAccount>>, // staking pool account for managing state (which is created in the initialize function)
+    #[account(
+        mut,
+        seeds = [
+            staking_account.key().as_ref(),
+            user.key.as_ref(),
+            b"user_stake",
+            user_stake.position_id.to_le_bytes().as_ref(),
+        ], // seeds (to access the PDA created in the stake function)
+        bump,
+        has_one = user @ StakingError::InvalidArgument, // stake must be owned by the signer
+        constraint = user_stake.pool == staking_account.key() @ StakingError::PoolMismatch, // stake must belong to this pool
+        constraint = user_stake.version == USER_STAKE_VERSION @ StakingError::AccountNotMigrated, // stake must be migrated to the current layout
+    )]
+    pub user_stake: Account<'info, UserStake>, // user stake account PDA
+    #[account(mut)]
+    pub user: Signer<'info>, // user account (signer)
+    #[account(
+        mut,
+        constraint = user_token_account.owner == user.key(), // Verify user owns the token account
+        constraint = user_token_account.mint == staking_account.token_mint @ StakingError::InvalidArgument, // Verify token mint matches staking account
+    )]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>, // user token account which receives the rewards
+    #[account(
+        mut,
+        seeds = [staking_account.key().as_ref(), b"staking_token_account"], // vault PDA of this pool
+        bump,
+    )]
+    pub staking_token_account: InterfaceAccount<'info, TokenAccount>, // staking token account which holds the SPL tokens for staking (shared by all users within the pool)
+    #[account(
+        address = staking_account.token_mint @ StakingError::InvalidArgument, // Verify it is the staked token
+    )]
+    pub mint: InterfaceAccount<'info, Mint>, // mint of the staked token (required by transfer_checked)
+    #[account(
+        mut,
+        seeds = [staking_account.key().as_ref(), b"reward_token_account"], // reward vault PDA of this pool
+        bump,
+    )]
+    pub reward_token_account: Option<InterfaceAccount<'info, TokenAccount>>, // reward vault of a pool with a separate reward mint
+    #[account(
+        address = staking_account.reward_mint @ StakingError::InvalidArgument, // Verify it is the reward token
+    )]
+    pub reward_mint: Option<InterfaceAccount<'info, Mint>>, // mint of the rewards of a pool with a separate reward mint
+    #[account(
+        mut,
+        constraint = user_reward_token_account.owner == user.key(), // Verify user owns the token account
+        constraint = user_reward_token_account.mint == staking_account.reward_mint @ StakingError::InvalidArgument, // Verify it holds the reward token
+    )]
+    pub user_reward_token_account: Option<InterfaceAccount<'info, TokenAccount>>, // user token account which receives the rewards
+    pub token_program: Interface<'info, TokenInterface>, // token program used to transfer tokens
+}
+
+/// Withdraw unbonded instruction structs
+/// This struct is used to define the accounts and instructions required for the withdraw unbonded instruction
+///
+/// # Fields
+/// * `staking_account` - staking pool account PDA (which is created in the initialize function), signs the vault transfer
+/// * `user_stake` - user stake account PDA of the position to withdraw the unbonding amount of (closed when emptied)
+/// * `user` - user account (signer), receives the rent of the closed position
+/// * `user_token_account` - user token account which receives the unbonded tokens
+/// * `staking_token_account` - staking token account which holds the SPL tokens for staking (shared by all users within the pool)
+/// * `mint` - mint of the staked token (required by `transfer_checked`)
+/// * `token_program` - token program used to transfer tokens
+#[derive(Accounts)]
+pub struct WithdrawUnbonded<'info> {
+    #[account(
+        mut,
+        seeds = [staking_account.creator.as_ref(), b"staking_account", staking_account.pool_id.to_le_bytes().as_ref()],
+        bump = staking_account.bump,
+    )]
+    pub staking_account: Box<Account<'info, StakingAccount>>, // staking pool account for managing state (which is created in the initialize function)
+    #[account(
+        mut,
+        seeds = [
+            staking_account.key().as_ref(),
+            user.key.as_ref(),
+            b"user_stake",
+            user_stake.position_id.to_le_bytes().as_ref(),
+        ], // seeds (to access the PDA created in the stake function)
+        bump,
+        has_one = user @ StakingError::InvalidArgument, // stake must be owned by the signer
+        constraint = user_stake.pool == staking_account.key() @ StakingError::PoolMismatch, // stake must belong to this pool
+        constraint = user_stake.version == USER_STAKE_VERSION @ StakingError::AccountNotMigrated, // stake must be migrated to the current layout
+    )]
+    pub user_stake: Account<'info, UserStake>, // user stake account PDA
+    #[account(mut)]
+    pub user: Signer<'info>, // user account (signer), receives the rent of the closed position
+    #[account(
+        mut,
+        constraint = user_token_account.owner == user.key(), // Verify user owns the token account
+        constraint = user_token_account.mint == staking_account.token_mint @ StakingError::InvalidArgument, // Verify token mint matches staking account
+    )]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>, // user token account which receives the unbonded tokens
+    #[account(
+        mut,
+        seeds = [staking_account.key().as_ref(), b"staking_token_account"], // vault PDA of this pool
+        bump,
+    )]
+    pub staking_token_account: InterfaceAccount<'info, TokenAccount>, // staking token account which holds the SPL tokens for staking (shared by all users within the pool)
+    #[account(
+        address = staking_account.token_mint @ StakingError::InvalidArgument, // Verify it is the staked token
+    )]
+    pub mint: InterfaceAccount<'info, Mint>, // mint of the staked token (required by transfer_checked)
+    pub token_program: Interface<'info, TokenInterface>, // token program used to transfer tokens
+}
+
+/// Claim rewards instruction structs
+/// This struct is used to define the accounts and instructions required for the claim rewards instruction
+///
+/// # Fields
+/// * `staking_account` - staking pool account PDA (which is created in the initialize function), signs the vault transfer
+/// * `user_stake` - user stake account PDA of the position to claim the rewards of (stays open)
+/// * `user` - user account (signer)
+/// * `user_token_account` - user token account which receives the rewards
+/// * `staking_token_account` - staking token account which holds the SPL tokens for staking (shared by all users within the pool)
+/// * `mint` - mint of the staked token (required by `transfer_checked`)
+/// * `reward_token_account` - reward vault PDA of the pool (required when the pool has a separate reward mint)
+/// * `reward_mint` - mint of the rewards (required when the pool has a separate reward mint)
+/// * `user_reward_token_account` - user token account which receives the rewards (required when the pool has a separate reward mint)
+/// * `token_program` - token program used to transfer tokens
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    #[account(
+        mut,
+        seeds = [staking_account.creator.as_ref(), b"staking_account", staking_account.pool_id.to_le_bytes().as_ref()],
+        bump = staking_account.bump,
+    )]
+    pub staking_account: Box<Account<'info, StakingAccount>>, // staking pool account for managing state (which is created in the initialize function)
+    #[account(
+        mut,
+        seeds = [
+            staking_account.key().as_ref(),
+            user.key.as_ref(),
+            b"user_stake",
+            user_stake.position_id.to_le_bytes().as_ref(),
+        ], // seeds (to access the PDA created in the stake function)
+        bump,
+        has_one = user @ StakingError::InvalidArgument, // stake must be owned by the signer
+        constraint = user_stake.pool == staking_account.key() @ StakingError::PoolMismatch, // stake must belong to this pool
+        constraint = user_stake.version == USER_STAKE_VERSION @ StakingError::AccountNotMigrated, // stake must be migrated to the current layout
+    )]
+    pub user_stake: Account<'info, UserStake>, // user stake account PDA
+    #[account(mut)]
+    pub user: Signer<'info>, // user account (signer)
+    #[account(
+        mut,
+        constraint = user_token_account.owner == user.key(), // Verify user owns the token account
+        constraint = user_token_account.mint == staking_account.token_mint @ StakingError::InvalidArgument, // Verify token mint matches staking account
+    )]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>, // user token account which receives the rewards
+    #[account(
+        mut,
+        seeds = [staking_account.key().as_ref(), b"staking_token_account"], // vault PDA of this pool
+        bump,
+    )]
+    pub staking_token_account: InterfaceAccount<'info, TokenAccount>, // staking token account which holds the SPL tokens for staking (shared by all users within the pool)
+    #[account(
+        address = staking_account.token_mint @ StakingError::InvalidArgument, // Verify it is the staked token
+    )]
+    pub mint: InterfaceAccount<'info, Mint>, // mint of the staked token (required by transfer_checked)
+    #[account(
+        mut,
+        seeds = [staking_account.key().as_ref(), b"reward_token_account"], // reward vault PDA of this pool
+        bump,
+    )]
+    pub reward_token_account: Option<InterfaceAccount<'info, TokenAccount>>, // reward vault of a pool with a separate reward mint
+    #[account(
+        address = staking_account.reward_mint @ StakingError::InvalidArgument, // Verify it is the reward token
+    )]
+    pub reward_mint: Option<InterfaceAccount<'info, Mint>>, // mint of the rewards of a pool with a separate reward mint
+    #[account(
+        mut,
+        constraint = user_reward_token_account.owner == user.key(), // Verify user owns the token account
+        constraint = user_reward_token_account.mint == staking_account.reward_mint @ StakingError::InvalidArgument, // Verify it holds the reward token
+    )]
+    pub user_reward_token_account: Option<InterfaceAccount<'info, TokenAccount>>, // user token account which receives the rewards
+    pub token_program: Interface<'info, TokenInterface>, // token program used to transfer tokens
+}
+
+/// Preview reward instruction structs
+/// This struct is used to define the accounts and instructions required for the preview reward instruction
+///
+/// # Fields
+/// * `staking_account` - staking pool account PDA of the position (only read)
+/// * `user_stake` - user stake account PDA of the position to preview (only read)
+#[derive(Accounts)]
+pub struct PreviewReward<'info> {
+    pub staking_account: Box<Account<'info, StakingAccount>>, // staking pool account (which is created in the initialize function)
+    #[account(
+        constraint = user_stake.pool == staking_account.key() @ StakingError::PoolMismatch, // stake must belong to this pool
+        constraint = user_stake.version == USER_STAKE_VERSION @ StakingError::AccountNotMigrated, // stake must be migrated to the current layout
+    )]
+    pub user_stake: Account<'info, UserStake>, // user stake account PDA
+}
+
+/// Compound instruction structs
+/// This struct is used to define the accounts and instructions required for the compound instruction
+///
+/// # Fields
+/// * `staking_account` - staking pool account PDA (which is created in the initialize function), signs the tip transfer
+/// * `user_stake` - user stake account PDA of the position to compound
+/// * `authority` - owner of the position, or a cranker if the position opted in (signer)
+/// * `authority_token_account` - token account receiving the cranker tip (required when a cranker earns a tip)
+/// * `staking_token_account` - staking token account which holds the SPL tokens for staking (shared by all users within the pool)
+/// * `mint` - mint of the staked token (required by `transfer_checked`)
+/// * `token_program` - token program used to transfer tokens
+#[derive(Accounts)]
+pub struct Compound<'info> {
+    #[account(
+        mut,
+        seeds = [staking_account.creator.as_ref(), b"staking_account", staking_account.pool_id.to_le_bytes().as_ref()],
+        bump = staking_account.bump,
+    )]
+    pub staking_account: Box<Account<'info, StakingAccount>>, // staking pool account for managing state (which is created in the initialize function)
+    #[account(
+        mut,
+        seeds = [
+            staking_account.key().as_ref(),
+            user_stake.user.as_ref(),
+            b"user_stake",
+            user_stake.position_id.to_le_bytes().as_ref(),
+        ], // seeds (to access the PDA created in the stake function)
+        bump,
+        constraint = user_stake.pool == staking_account.key() @ StakingError::PoolMismatch, // stake must belong to this pool
+        constraint = user_stake.version == USER_STAKE_VERSION @ StakingError::AccountNotMigrated, // stake must be migrated to the current layout
+    )]
+    pub user_stake: Account<'info, UserStake>, // user stake account PDA
+    pub authority: Signer<'info>, // owner or cranker (signer)
+    #[account(
+        mut,
+        constraint = authority_token_account.owner == authority.key(), // Verify cranker owns the token account
+        constraint = authority_token_account.mint == staking_account.token_mint @ StakingError::InvalidArgument, // Verify token mint matches staking account
+    )]
+    pub authority_token_account: Option<InterfaceAccount<'info, TokenAccount>>, // token account receiving the cranker tip
+    #[account(
+        mut,
+        seeds = [staking_account.key().as_ref(), b"staking_token_account"], // vault PDA of this pool
+        bump,
+    )]
+    pub staking_token_account: InterfaceAccount<'info, TokenAccount>, // staking token account which holds the SPL tokens for staking (shared by all users within the pool)
+    #[account(
+        address = staking_account.token_mint @ StakingError::InvalidArgument, // Verify it is the staked token
+    )]
+    pub mint: InterfaceAccount<'info, Mint>, // mint of the staked token (required by transfer_checked)
+    pub token_program: Interface<'info, TokenInterface>, // token program used to transfer tokens
+}
+
+impl<'info> Compound<'info> {
+    /// This function creates CPI context for transferring the tip from staking account to the cranker
+    /// The vault is owned by the staking account PDA, so the transfer is signed with its seeds
+    ///
+    /// # Example
+    /// ```rs
+    /// let ctx = Compound {...}
+    /// let cpi_ctx = ctx.transfer_tip_ctx(signer_seeds)?;
+    /// token_interface::transfer_checked(cpi_ctx, tip, decimals)?;
+    /// ```
+    pub fn transfer_tip_ctx<'a, 'b, 'c>(
+        &self,
+        signer_seeds: &'a [&'b [&'c [u8]]],
+    ) -> Result<CpiContext<'a, 'b, 'c, 'info, TransferChecked<'info>>> {
+        let authority_token_account = self
+            .authority_token_account
+            .as_ref()
+            .ok_or(StakingError::InvalidArgument)?;
+
+        Ok(CpiContext::new_with_signer(
+            self.token_program.to_account_info(), // token program account info (used for token transferring)
+            TransferChecked {
+                from: self.staking_token_account.to_account_info(), // from staking pool's token account
+                mint: self.mint.to_account_info(),                  // mint of the transferred token
+                to: authority_token_account.to_account_info(),      // to cranker's token account
+                authority: self.staking_account.to_account_info(), // authority to transfer tokens (staking pool PDA)
+            },
+            signer_seeds,
+        ))
+    }
+}
+
+/// Set auto compound instruction structs
+/// This struct is used to define the accounts and instructions required for the set auto compound instruction
+///
+/// # Fields
+/// * `staking_account` - staking pool account PDA (which is created in the initialize function)
+/// * `user_stake` - user stake account PDA of the position
+/// * `user` - owner of the position (signer)
+#[derive(Accounts)]
+pub struct SetAutoCompound<'info> {
+    pub staking_account: Box<Account<'info, StakingAccount>>, // staking pool account (which is created in the initialize function)
+    #[account(
+        mut,
+        seeds = [
+            staking_account.key().as_ref(),
+            user.key.as_ref(),
+            b"user_stake",
+            user_stake.position_id.to_le_bytes().as_ref(),
+        ], // seeds (to access the PDA created in the stake function)
+        bump,
+        has_one = user @ StakingError::InvalidArgument, // stake must be owned by the signer
+    )]
+    pub user_stake: Account<'info, UserStake>, // user stake account PDA
+    pub user: Signer<'info>,                                  // user account (signer)
+}
+
+/// Set compound tip instruction structs
+/// This struct is used to define the accounts and instructions required for the set compound tip instruction
+///
+/// # Fields
+/// * `staking_account` - staking pool account PDA (which is created in the initialize function)
+/// * `admin` - admin of the pool (signer)
+#[derive(Accounts)]
+pub struct SetCompoundTip<'info> {
+    #[account(mut, has_one = admin @ StakingError::AdminOnly)]
+    pub staking_account: Box<Account<'info, StakingAccount>>, // staking pool account (which is created in the initialize function)
+    pub admin: Signer<'info>, // admin account (signer)
+}
+
+/// Set lockup tiers instruction structs
+/// This struct is used to define the accounts and instructions required for the set lockup tiers instruction
+///
+/// # Fields
+/// * `staking_account` - staking pool account PDA (which is created in the initialize function)
+/// * `admin` - admin of the pool (signer)
+#[derive(Accounts)]
+pub struct SetLockupTiers<'info> {
+    #[account(mut, has_one = admin @ StakingError::AdminOnly)]
+    pub staking_account: Box<Account<'info, StakingAccount>>, // staking pool account (which is created in the initialize function)
+    pub admin: Signer<'info>, // admin account (signer)
+}
+
+/// Set penalty policy instruction structs
+/// This struct is used to define the accounts and instructions required for the set penalty policy instruction
+///
+/// # Fields
+/// * `staking_account` - staking pool account PDA (which is created in the initialize function)
+/// * `admin` - admin of the pool (signer)
+/// * `treasury_token_account` - token account of the staked token receiving the penalties (required when they are routed to the treasury)
+#[derive(Accounts)]
+pub struct SetPenaltyPolicy<'info> {
+    #[account(mut, has_one = admin @ StakingError::AdminOnly)]
+    pub staking_account: Box<Account<'info, StakingAccount>>, // staking pool account (which is created in the initialize function)
+    pub admin: Signer<'info>, // admin account (signer)
+    #[account(
+        constraint = treasury_token_account.mint == staking_account.token_mint @ StakingError::InvalidArgument, // Verify it holds the staked token
+    )]
+    pub treasury_token_account: Option<InterfaceAccount<'info, TokenAccount>>, // treasury receiving the penalties
+}
+
+/// Update config instruction structs
+/// This struct is used to define the accounts and instructions required for the propose and apply config update instructions
+///
+/// # Fields
+/// * `staking_account` - staking pool account PDA (which is created in the initialize function)
+/// * `admin` - admin of the pool (signer)
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(mut, has_one = admin @ StakingError::AdminOnly)]
+    pub staking_account: Box<Account<'info, StakingAccount>>, // staking pool account (which is created in the initialize function)
+    pub admin: Signer<'info>, // admin account (signer)
+}
+
+/// Emergency withdraw instruction structs
+/// This struct is used to define the accounts and instructions required for the emergency withdraw instruction
+///
+/// # Fields
+/// * `staking_account` - staking pool account PDA (which is created in the initialize function), signs the vault transfer
+/// * `user_stake` - user stake account PDA of the position to withdraw (closed)
+/// * `user` - user account (signer), receives the rent of the closed position
+/// * `user_token_account` - user token account which receives the withdrawn tokens
+/// * `staking_token_account` - staking token account which holds the SPL tokens for staking (shared by all users within the pool)
+/// * `mint` - mint of the staked token (required by `transfer_checked`)
+/// * `token_program` - token program used to transfer tokens
+#[derive(Accounts)]
+pub struct EmergencyWithdraw<'info> {
+    #[account(
+        mut,
+        seeds = [staking_account.creator.as_ref(), b"staking_account", staking_account.pool_id.to_le_bytes().as_ref()],
+        bump = staking_account.bump,
+    )]
+    pub staking_account: Box<Account<'info, StakingAccount>>, // staking pool account for managing state (which is created in the initialize function)
+    #[account(
+        mut,
+        seeds = [
+            staking_account.key().as_ref(),
+            user.key.as_ref(),
+            b"user_stake",
+            user_stake.position_id.to_le_bytes().as_ref(),
+        ], // seeds (to access the PDA created in the stake function)
+        bump,
+        has_one = user @ StakingError::InvalidArgument, // stake must be owned by the signer
+        constraint = user_stake.pool == staking_account.key() @ StakingError::PoolMismatch, // stake must belong to this pool
+        constraint = user_stake.version == USER_STAKE_VERSION @ StakingError::AccountNotMigrated, // stake must be migrated to the current layout
+        close = user // close the account and transfer the remaining balance to the user account
+    )]
+    pub user_stake: Account<'info, UserStake>, // user stake account PDA
+    #[account(mut)]
+    pub user: Signer<'info>, // user account (signer), receives the rent of the closed position
+    #[account(
+        mut,
+        constraint = user_token_account.owner == user.key(), // Verify user owns the token account
+        constraint = user_token_account.mint == staking_account.token_mint @ StakingError::InvalidArgument, // Verify token mint matches staking account
+    )]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>, // user token account which receives the withdrawn tokens
+    #[account(
+        mut,
+        seeds = [staking_account.key().as_ref(), b"staking_token_account"], // vault PDA of this pool
+        bump,
+    )]
+    pub staking_token_account: InterfaceAccount<'info, TokenAccount>, // staking token account which holds the SPL tokens for staking (shared by all users within the pool)
+    #[account(
+        address = staking_account.token_mint @ StakingError::InvalidArgument, // Verify it is the staked token
+    )]
+    pub mint: InterfaceAccount<'info, Mint>, // mint of the staked token (required by transfer_checked)
+    pub token_program: Interface<'info, TokenInterface>, // token program used to transfer tokens
+}
+
+/// Set guardian instruction structs
+/// This struct is used to define the accounts and instructions required for the set guardian instruction
+///
+/// # Fields
+/// * `staking_account` - staking pool account PDA (which is created in the initialize function)
+/// * `admin` - admin of the pool (signer)
+#[derive(Accounts)]
+pub struct SetGuardian<'info> {
+    #[account(mut, has_one = admin @ StakingError::AdminOnly)]
+    pub staking_account: Box<Account<'info, StakingAccount>>, // staking pool account (which is created in the initialize function)
+    pub admin: Signer<'info>, // admin account (signer)
+}
+
+/// Migrate pool instruction structs
+/// This struct is used to define the accounts and instructions required for the migrate pool instruction
+///
+/// # Fields
+/// * `legacy_staking_account` - staking pool account of the baseline layout (PDA of the admin only, closed)
+/// * `staking_account` - staking pool account PDA replacing it (which is created in the migrate pool function)
+/// * `staking_token_account` - vault token account PDA owned by the new staking account
+/// * `mint` - mint of the staked token (must match the token mint of the legacy pool)
+/// * `admin` - admin of the legacy pool (signer), receives the rent of the legacy pool
+/// * `admin_token_account` - admin token account holding the reward funds of the legacy pool
+/// * `token_program` - token program of the mint used to create the vault and transfer the reward funds
+/// * `system_program` - system program used to create the staking_account PDA
+#[derive(Accounts)]
+#[instruction(pool_id: u64)]
+pub struct MigratePool<'info> {
+    /// CHECK: the baseline layout does not deserialize as `StakingAccount`, `migrate_pool` decodes it as `StakingAccountV0`
+    #[account(
+        mut,
+        seeds = [admin.key.as_ref(), b"staking_account"], // PDA of the baseline (one pool per admin)
+        bump,
+        owner = crate::ID @ StakingError::InvalidArgument,
+    )]
+    pub legacy_staking_account: UncheckedAccount<'info>, // staking pool account to migrate (closed)
+    #[account(
+        init, // means initialize the account
+        payer = admin, // means the admin account will pay for the transaction and rent
+        seeds = [admin.key.as_ref(), b"staking_account", pool_id.to_le_bytes().as_ref()], // means it will create the PDA (one per admin and pool id)
+        bump, // canonical bump, stored on the account to sign vault transfers
+        space = size_of::<StakingAccount>() + 8 // size of the account 8 for descriptor
+    )]
+    pub staking_account: Box<Account<'info, StakingAccount>>, // creates a new account (staking pool)
+    #[account(
+        init, // means initialize the vault token account
+        payer = admin, // admin pays the rent for the vault
+        seeds = [staking_account.key().as_ref(), b"staking_token_account"], // vault PDA derived from the pool
+        bump,
+        token::mint = mint, // vault only holds the staked token
+        token::authority = staking_account, // only the pool PDA can move tokens out of the vault
+        token::token_program = token_program, // SPL Token or Token-2022, whichever owns the mint
+    )]
+    pub staking_token_account: InterfaceAccount<'info, TokenAccount>, // vault token account (shared by all users within the pool)
+    #[account(
+        mint::token_program = token_program, // mint must be owned by the given token program
+    )]
+    pub mint: InterfaceAccount<'info, Mint>, // mint of the staked token (checked against the legacy pool)
+    #[account(mut)]
+    pub admin: Signer<'info>, // admin account
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = admin,
+    )]
+    pub admin_token_account: InterfaceAccount<'info, TokenAccount>, // admin token account which holds the reward funds of the legacy pool
+    pub token_program: Interface<'info, TokenInterface>, // token program used to create the vault and transfer tokens
+    pub system_program: Program<'info, System>, // system program used to create the staking_account PDA
+}
+
+impl<'info> MigratePool<'info> {
+    /// This function transfers the reward funds of the legacy pool from the admin to the vault
+    /// Returns the amount received by the vault
+    ///
+    /// # Example
+    /// ```rs
+    /// let received = ctx.accounts.transfer_from_admin(amount)?;
+    /// ```
+    pub fn transfer_from_admin(&mut self, amount: u64) -> Result<u64> {
+        let vault_balance = self.staking_token_account.amount;
+        token_interface::transfer_checked(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: self.admin_token_account.to_account_info(),
+                    mint: self.mint.to_account_info(),
+                    to: self.staking_token_account.to_account_info(),
+                    authority: self.admin.to_account_info(),
+                },
+            ),
+            amount,
+            self.mint.decimals,
+        )?;
+        received_amount(&mut self.staking_token_account, vault_balance)
+    }
+}
+
+/// Migrate user stake instruction structs
+/// This struct is used to define the accounts and instructions required for the migrate user stake instruction
+///
+/// # Fields
+/// * `staking_account` - staking pool account PDA the position moves to (migrated with `migrate_pool`)
+/// * `legacy_user_stake` - user stake account of the baseline layout (PDA of the user only, closed)
+/// * `user` - owner of the position (signer), picks the pool it moves to and receives the rent of the legacy position
+/// * `user_stake_counter` - counter of the positions of the user within the pool (created if needed)
+/// * `user_stake` - user stake account PDA replacing the legacy position (which is created in the migrate user stake function)
+/// * `staking_token_account` - vault of the pool which receives the principal
+/// * `mint` - mint of the staked token (required by `transfer_checked`)
+/// * `admin` - admin of the pool (signer), pays the rent of the new accounts
+/// * `admin_token_account` - admin token account holding the principal of the legacy position
+/// * `token_program` - token program used to transfer tokens
+/// * `system_program` - system program used to create the user_stake account
+#[derive(Accounts)]
+pub struct MigrateUserStake<'info> {
+    #[account(
+        mut,
+        seeds = [staking_account.creator.as_ref(), b"staking_account", staking_account.pool_id.to_le_bytes().as_ref()],
+        bump = staking_account.bump,
+        has_one = admin @ StakingError::AdminOnly,
+        constraint = staking_account.version == STAKING_ACCOUNT_VERSION @ StakingError::AccountNotMigrated, // pool must have the current layout
+    )]
+    pub staking_account: Box<Account<'info, StakingAccount>>, // staking pool account (which is created in the migrate pool function)
+    /// CHECK: the baseline layout does not deserialize as `UserStake`, `migrate_user_stake` decodes it as `UserStakeV0`
+    #[account(
+        mut,
+        seeds = [user.key().as_ref(), b"user_stake"], // PDA of the baseline (one position per user)
+        bump,
+        owner = crate::ID @ StakingError::InvalidArgument,
+    )]
+    pub legacy_user_stake: UncheckedAccount<'info>, // user stake account to migrate (closed)
+    #[account(mut)]
+    pub user: Signer<'info>, // owner of the position (signer)
+    #[account(
+        init_if_needed, // created on the first position of the user in this pool
+        payer = admin, // admin pays the rent of the migrated accounts
+        seeds = [staking_account.key().as_ref(), user.key().as_ref(), b"user_stake_counter"], // seeds for the PDA (unique for each user within the pool)
+        bump,
+        space = size_of::<UserStakeCounter>() + 8 // size of the account 8 for descriptor
+    )]
+    pub user_stake_counter: Account<'info, UserStakeCounter>, // user stake counter account
+    #[account(
+        init, // initialize the new account with UserStake struct
+        payer = admin, // admin pays the rent of the migrated accounts
+        seeds = [
+            staking_account.key().as_ref(),
+            user.key().as_ref(),
+            b"user_stake",
+            user_stake_counter.next_position_id.to_le_bytes().as_ref(),
+        ], // seeds for the PDA (unique for each position of the user within the pool)
+        bump,
+        space = size_of::<UserStake>() + 8 // size of the account 8 for descriptor
+    )]
+    pub user_stake: Box<Account<'info, UserStake>>, // user stake account
+    #[account(
+        mut,
+        seeds = [staking_account.key().as_ref(), b"staking_token_account"], // vault PDA of this pool
+        bump,
+    )]
+    pub staking_token_account: InterfaceAccount<'info, TokenAccount>, // staking token account which holds the SPL tokens for staking (shared by all users within the pool)
+    #[account(
+        address = staking_account.token_mint @ StakingError::InvalidArgument, // Verify it is the staked token
+    )]
+    pub mint: InterfaceAccount<'info, Mint>, // mint of the staked token (required by transfer_checked)
+    #[account(mut)]
+    pub admin: Signer<'info>, // admin account (signer)
+    #[account(mut)]
+    pub admin_token_account: InterfaceAccount<'info, TokenAccount>, // admin token account which holds the principal of the legacy position
+    pub token_program: Interface<'info, TokenInterface>, // token program used to transfer tokens securly
+    pub system_program: Program<'info, System>, // system program used to create the user_stake account
+}
+
+impl<'info> MigrateUserStake<'info> {
+    /// This function transfers the principal of the legacy position from the admin to the vault
+    /// Returns the amount received by the vault
+    ///
+    /// # Example
+    /// ```rs
+    /// let received = ctx.accounts.transfer_from_admin(amount)?;
+    /// ```
+    pub fn transfer_from_admin(&mut self, amount: u64) -> Result<u64> {
+        let vault_balance = self.staking_token_account.amount;
+        token_interface::transfer_checked(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: self.admin_token_account.to_account_info(),
+                    mint: self.mint.to_account_info(),
+                    to: self.staking_token_account.to_account_info(),
+                    authority: self.admin.to_account_info(),
+                },
+            ),
+            amount,
+            self.mint.decimals,
+        )?;
+        received_amount(&mut self.staking_token_account, vault_balance)
+    }
+}
+
+/// Upgrade pool instruction structs
+/// This struct is used to define the accounts and instructions required for the upgrade pool instruction
+///
+/// # Fields
+/// * `staking_account` - staking pool account of an older layout (grown to the size of the current layout)
+/// * `payer` - pays the rent of the grown account (signer)
+/// * `system_program` - system program used to top up the rent
+#[derive(Accounts)]
+pub struct UpgradePool<'info> {
+    /// CHECK: older layouts may not deserialize as `StakingAccount`, `upgrade_pool` grows the account before decoding it
+    #[account(
+        mut,
+        owner = crate::ID @ StakingError::InvalidArgument,
+    )]
+    pub staking_account: UncheckedAccount<'info>, // staking pool account to upgrade
     #[account(mut)]
-    pub staking_account: Account<'info, StakingAccount>, // staking pool account (which is created in the initialize function)
+    pub payer: Signer<'info>, // pays the rent of the grown account (signer)
+    pub system_program: Program<'info, System>, // system program used to top up the rent
+}
+
+/// Upgrade user stake instruction structs
+/// This struct is used to define the accounts and instructions required for the upgrade user stake instruction
+///
+/// # Fields
+/// * `user_stake` - user stake account of an older layout (grown to the size of the current layout)
+/// * `payer` - pays the rent of the grown account (signer)
+/// * `system_program` - system program used to top up the rent
+#[derive(Accounts)]
+pub struct UpgradeUserStake<'info> {
+    /// CHECK: older layouts may not deserialize as `UserStake`, `upgrade_user_stake` grows the account before decoding it
     #[account(
-        init, // initialize the new account with UserStake struct
-        payer = user, // user will pay for the transaction and rent
-        seeds = [user.key().as_ref(), b"user_stake"], // seeds for the PDA (unique for each user)
-        bump, // bump for the PDA (one user can have multiple PDAs)
-        space = size_of::<UserStake>() + 8 // size of the account 8 for descriptor
+        mut,
+        owner = crate::ID @ StakingError::InvalidArgument,
     )]
-    pub user_stake: Account<'info, UserStake>, // user stake account
+    pub user_stake: UncheckedAccount<'info>, // user stake account to upgrade
     #[account(mut)]
-    pub user: Signer<'info>, // user account (signer)
+    pub payer: Signer<'info>, // pays the rent of the grown account (signer)
+    pub system_program: Program<'info, System>, // system program used to top up the rent
+}
+
+/// Set pool state instruction structs
+/// This struct is used to define the accounts and instructions required for the set pool state instruction
+///
+/// # Fields
+/// * `staking_account` - staking pool account PDA (which is created in the initialize function)
+/// * `authority` - admin or guardian of the pool (signer)
+#[derive(Accounts)]
+pub struct SetPoolState<'info> {
     #[account(mut)]
-    pub user_token_account: Account<'info, TokenAccount>, // user token account which holds the required SPL tokens
+    pub staking_account: Box<Account<'info, StakingAccount>>, // staking pool account (which is created in the initialize function)
+    pub authority: Signer<'info>, // admin or guardian account (signer)
+}
+
+/// Propose admin instruction structs
+/// This struct is used to define the accounts and instructions required for the propose and renounce admin instructions
+///
+/// # Fields
+/// * `staking_account` - staking pool account PDA (which is created in the initialize function)
+/// * `admin` - admin of the pool (signer)
+#[derive(Accounts)]
+pub struct ProposeAdmin<'info> {
+    #[account(mut, has_one = admin @ StakingError::AdminOnly)]
+    pub staking_account: Box<Account<'info, StakingAccount>>, // staking pool account (which is created in the initialize function)
+    pub admin: Signer<'info>, // admin account (signer)
+}
+
+/// Accept admin instruction structs
+/// This struct is used to define the accounts and instructions required for the accept admin instruction
+///
+/// # Fields
+/// * `staking_account` - staking pool account PDA (which is created in the initialize function)
+/// * `pending_admin` - pending admin of the pool (signer)
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    #[account(mut, has_one = pending_admin @ StakingError::NotPendingAdmin)]
+    pub staking_account: Box<Account<'info, StakingAccount>>, // staking pool account (which is created in the initialize function)
+    pub pending_admin: Signer<'info>, // pending admin account (signer)
+}
+
+/// Begin shutdown instruction structs
+/// This struct is used to define the accounts and instructions required for the begin shutdown instruction
+///
+/// # Fields
+/// * `staking_account` - staking pool account PDA (which is created in the initialize function)
+/// * `admin` - admin of the pool (signer)
+#[derive(Accounts)]
+pub struct BeginShutdown<'info> {
+    #[account(mut, has_one = admin @ StakingError::AdminOnly)]
+    pub staking_account: Box<Account<'info, StakingAccount>>, // staking pool account (which is created in the initialize function)
+    pub admin: Signer<'info>, // admin account (signer)
+}
+
+/// Return position instruction structs
+/// This struct is used to define the accounts and instructions required for the return position instruction
+///
+/// # Fields
+/// * `staking_account` - staking pool account PDA (which is created in the initialize function), signs the vault transfer
+/// * `user_stake` - user stake account PDA of the position to return (closed)
+/// * `user` - owner of the position, receives the rent of the closed position
+/// * `user_token_account` - token account of the owner which receives the tokens
+/// * `staking_token_account` - staking token account which holds the SPL tokens for staking (shared by all users within the pool)
+/// * `authority` - cranker (signer)
+/// * `mint` - mint of the staked token (required by `transfer_checked`)
+/// * `reward_token_account` - reward vault PDA of the pool (required when the pool has a separate reward mint)
+/// * `reward_mint` - mint of the rewards (required when the pool has a separate reward mint)
+/// * `user_reward_token_account` - user token account which receives the rewards (required when the pool has a separate reward mint)
+/// * `token_program` - token program used to transfer tokens
+#[derive(Accounts)]
+pub struct ReturnPosition<'info> {
+    #[account(
+        mut,
+        seeds = [staking_account.creator.as_ref(), b"staking_account", staking_account.pool_id.to_le_bytes().as_ref()],
+        bump = staking_account.bump,
+    )]
+    pub staking_account: Box<Account<'info, StakingAccount>>, // staking pool account for managing state (which is created in the initialize function)
+    #[account(
+        mut,
+        seeds = [
+            staking_account.key().as_ref(),
+            user.key.as_ref(),
+            b"user_stake",
+            user_stake.position_id.to_le_bytes().as_ref(),
+        ], // seeds (to access the PDA created in the stake function)
+        bump,
+        has_one = user @ StakingError::InvalidArgument, // stake must be owned by the given user
+        constraint = user_stake.pool == staking_account.key() @ StakingError::PoolMismatch, // stake must belong to this pool
+        constraint = user_stake.version == USER_STAKE_VERSION @ StakingError::AccountNotMigrated, // stake must be migrated to the current layout
+    )]
+    pub user_stake: Account<'info, UserStake>, // user stake account PDA
     #[account(mut)]
-    pub staking_token_account: Account<'info, TokenAccount>, // staking token account which holds the SPL tokens for staking (shared by all users within the pool)
-    pub token_program: Program<'info, Token>, // token program used to transfer tokens securly
-    pub system_program: Program<'info, System>, // system program used to create the user_stake account
+    pub user: SystemAccount<'info>, // owner of the position (not a signer)
+    #[account(
+        mut,
+        constraint = user_token_account.owner == user.key() @ StakingError::InvalidArgument, // Verify the owner of the position owns the token account
+        constraint = user_token_account.mint == staking_account.token_mint @ StakingError::InvalidArgument, // Verify token mint matches staking account
+    )]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>, // token account of the owner which receives the tokens
+    #[account(
+        mut,
+        seeds = [staking_account.key().as_ref(), b"staking_token_account"], // vault PDA of this pool
+        bump,
+    )]
+    pub staking_token_account: InterfaceAccount<'info, TokenAccount>, // staking token account which holds the SPL tokens for staking (shared by all users within the pool)
+    pub authority: Signer<'info>, // cranker (signer)
+    #[account(
+        address = staking_account.token_mint @ StakingError::InvalidArgument, // Verify it is the staked token
+    )]
+    pub mint: InterfaceAccount<'info, Mint>, // mint of the staked token (required by transfer_checked)
+    #[account(
+        mut,
+        seeds = [staking_account.key().as_ref(), b"reward_token_account"], // reward vault PDA of this pool
+        bump,
+    )]
+    pub reward_token_account: Option<InterfaceAccount<'info, TokenAccount>>, // reward vault of a pool with a separate reward mint
+    #[account(
+        address = staking_account.reward_mint @ StakingError::InvalidArgument, // Verify it is the reward token
+    )]
+    pub reward_mint: Option<InterfaceAccount<'info, Mint>>, // mint of the rewards of a pool with a separate reward mint
+    #[account(
+        mut,
+        constraint = user_reward_token_account.owner == user.key() @ StakingError::InvalidArgument, // Verify user owns the token account
+        constraint = user_reward_token_account.mint == staking_account.reward_mint @ StakingError::InvalidArgument, // Verify it holds the reward token
+    )]
+    pub user_reward_token_account: Option<InterfaceAccount<'info, TokenAccount>>, // user token account which receives the rewards
+    pub token_program: Interface<'info, TokenInterface>, // token program used to transfer tokens
+}
+
+/// Close pool instruction structs
+/// This struct is used to define the accounts and instructions required for the close pool instruction
+///
+/// # Fields
+/// * `staking_account` - staking pool account PDA (which is created in the initialize function), closed
+/// * `staking_token_account` - staking token account of the pool, closed
+/// * `admin` - admin of the pool (signer), receives the rent of the closed accounts
+/// * `admin_token_account` - token account which receives the tokens left in the vault
+/// * `mint` - mint of the staked token (required by `transfer_checked`)
+/// * `reward_token_account` - reward vault PDA of the pool, closed (required when the pool has a separate reward mint)
+/// * `reward_mint` - mint of the rewards (required when the pool has a separate reward mint)
+/// * `admin_reward_token_account` - token account which receives the rewards left in the reward vault (required when the pool has a separate reward mint)
+/// * `token_program` - token program used to transfer tokens
+#[derive(Accounts)]
+pub struct ClosePool<'info> {
+    #[account(
+        mut,
+        seeds = [staking_account.creator.as_ref(), b"staking_account", staking_account.pool_id.to_le_bytes().as_ref()],
+        bump = staking_account.bump,
+        has_one = admin @ StakingError::AdminOnly,
+        close = admin // close the account and transfer the remaining balance to the admin account
+    )]
+    pub staking_account: Box<Account<'info, StakingAccount>>, // staking pool account (which is created in the initialize function)
+    #[account(
+        mut,
+        seeds = [staking_account.key().as_ref(), b"staking_token_account"], // vault PDA of this pool
+        bump,
+    )]
+    pub staking_token_account: InterfaceAccount<'info, TokenAccount>, // staking token account which holds the SPL tokens for staking (shared by all users within the pool)
+    #[account(mut)]
+    pub admin: Signer<'info>, // admin account (signer)
+    #[account(
+        mut,
+        constraint = admin_token_account.mint == staking_account.token_mint @ StakingError::InvalidArgument, // Verify token mint matches staking account
+    )]
+    pub admin_token_account: InterfaceAccount<'info, TokenAccount>, // token account which receives the tokens left in the vault
+    #[account(
+        address = staking_account.token_mint @ StakingError::InvalidArgument, // Verify it is the staked token
+    )]
+    pub mint: InterfaceAccount<'info, Mint>, // mint of the staked token (required by transfer_checked)
+    #[account(
+        mut,
+        seeds = [staking_account.key().as_ref(), b"reward_token_account"], // reward vault PDA of this pool
+        bump,
+    )]
+    pub reward_token_account: Option<InterfaceAccount<'info, TokenAccount>>, // reward vault of a pool with a separate reward mint
+    #[account(
+        address = staking_account.reward_mint @ StakingError::InvalidArgument, // Verify it is the reward token
+    )]
+    pub reward_mint: Option<InterfaceAccount<'info, Mint>>, // mint of the rewards of a pool with a separate reward mint
+    #[account(
+        mut,
+        constraint = admin_reward_token_account.mint == staking_account.reward_mint @ StakingError::InvalidArgument, // Verify it holds the reward token
+    )]
+    pub admin_reward_token_account: Option<InterfaceAccount<'info, TokenAccount>>, // token account which receives the rewards left in the reward vault
+    pub token_program: Interface<'info, TokenInterface>, // token program used to transfer tokens
+}
+
+impl<'info> ClosePool<'info> {
+    /// This function creates CPI context for transferring the tokens left in the vault to the admin
+    ///
+    /// # Example
+    /// ```rs
+    /// let ctx = ClosePool {...}
+    /// let cpi_ctx = ctx.transfer_to_admin_ctx(signer_seeds);
+    /// token_interface::transfer_checked(cpi_ctx, amount, decimals)?;
+    /// ```
+    pub fn transfer_to_admin_ctx<'a, 'b, 'c>(
+        &self,
+        signer_seeds: &'a [&'b [&'c [u8]]],
+    ) -> CpiContext<'a, 'b, 'c, 'info, TransferChecked<'info>> {
+        CpiContext::new_with_signer(
+            self.token_program.to_account_info(), // token program account info (used for token transferring)
+            TransferChecked {
+                from: self.staking_token_account.to_account_info(), // from staking pool's token account
+                mint: self.mint.to_account_info(),                  // mint of the transferred token
+                to: self.admin_token_account.to_account_info(),     // to admin's token account
+                authority: self.staking_account.to_account_info(), // authority to transfer tokens (staking pool PDA)
+            },
+            signer_seeds,
+        )
+    }
+
+    /// This function creates CPI context for closing the vault, its rent going to the admin
+    ///
+    /// # Example
+    /// ```rs
+    /// let ctx = ClosePool {...}
+    /// let cpi_ctx = ctx.close_vault_ctx(signer_seeds);
+    /// token_interface::close_account(cpi_ctx)?;
+    /// ```
+    pub fn close_vault_ctx<'a, 'b, 'c>(
+        &self,
+        signer_seeds: &'a [&'b [&'c [u8]]],
+    ) -> CpiContext<'a, 'b, 'c, 'info, CloseAccount<'info>> {
+        CpiContext::new_with_signer(
+            self.token_program.to_account_info(), // token program account info (used for closing the vault)
+            CloseAccount {
+                account: self.staking_token_account.to_account_info(), // vault to close
+                destination: self.admin.to_account_info(),             // admin receives the rent
+                authority: self.staking_account.to_account_info(), // authority of the vault (staking pool PDA)
+            },
+            signer_seeds,
+        )
+    }
+
+    /// This function creates CPI context for transferring rewards from the reward vault to admin
+    /// Only used by pools with a separate reward mint, the reward vault is owned by the staking account PDA
+    ///
+    /// # Example
+    /// ```rs
+    /// let ctx = ClosePool {...}
+    /// let cpi_ctx = ctx.transfer_reward_to_admin_ctx(signer_seeds)?;
+    /// token_interface::transfer_checked(cpi_ctx, amount, decimals)?;
+    /// ```
+    pub fn transfer_reward_to_admin_ctx<'a, 'b, 'c>(
+        &self,
+        signer_seeds: &'a [&'b [&'c [u8]]],
+    ) -> Result<CpiContext<'a, 'b, 'c, 'info, TransferChecked<'info>>> {
+        Ok(CpiContext::new_with_signer(
+            self.token_program.to_account_info(), // token program account info (used for token transferring)
+            TransferChecked {
+                from: reward_account(&self.reward_token_account)?.to_account_info(), // from the reward vault of the pool
+                mint: reward_account(&self.reward_mint)?.to_account_info(), // mint of the rewards
+                to: reward_account(&self.admin_reward_token_account)?.to_account_info(), // to admin's reward token account
+                authority: self.staking_account.to_account_info(), // authority to transfer tokens (staking pool PDA)
+            },
+            signer_seeds,
+        ))
+    }
+
+    /// This function creates CPI context for closing the reward vault, its rent going to the admin
+    ///
+    /// # Example
+    /// ```rs
+    /// let ctx = ClosePool {...}
+    /// let cpi_ctx = ctx.close_reward_vault_ctx(signer_seeds)?;
+    /// token_interface::close_account(cpi_ctx)?;
+    /// ```
+    pub fn close_reward_vault_ctx<'a, 'b, 'c>(
+        &self,
+        signer_seeds: &'a [&'b [&'c [u8]]],
+    ) -> Result<CpiContext<'a, 'b, 'c, 'info, CloseAccount<'info>>> {
+        Ok(CpiContext::new_with_signer(
+            self.token_program.to_account_info(), // token program account info (used for closing the reward vault)
+            CloseAccount {
+                account: reward_account(&self.reward_token_account)?.to_account_info(), // reward vault to close
+                destination: self.admin.to_account_info(), // admin receives the rent
+                authority: self.staking_account.to_account_info(), // authority of the reward vault (staking pool PDA)
+            },
+            signer_seeds,
+        ))
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////////////
+///////////////////////////////////////// EVENTS /////////////////////////////////////////
+//////////////////////////////////////////////////////////////////////////////////////////
+
+/// Config update proposed event
+/// Emitted when the admin stages new pool parameters
+///
+/// # Fields
+/// * `pool` - staking pool account
+/// * `config` - staged pool parameters
+/// * `eta` - time in seconds from which the config can be applied
+#[event]
+pub struct ConfigUpdateProposed {
+    pub pool: Pubkey,
+    pub config: PoolConfig,
+    pub eta: i64,
+}
+
+/// Config update applied event
+/// Emitted when the staged pool parameters take effect
+///
+/// # Fields
+/// * `pool` - staking pool account
+/// * `config` - applied pool parameters
+#[event]
+pub struct ConfigUpdateApplied {
+    pub pool: Pubkey,
+    pub config: PoolConfig,
+}
+
+/// Pool initialized event
+/// Emitted when a new staking pool is created
+///
+/// # Fields
+/// * `pool` - staking pool account
+/// * `admin` - admin (and creator) of the pool
+/// * `pool_id` - id of the pool among the pools of the creator
+/// * `token_mint` - mint of the staked token
+/// * `reward_mode` - how rewards are distributed
+/// * `reward_rate` - APY return yearly in percentage
+/// * `min_staking_duration` - minimum staking duration in seconds
+/// * `timestamp` - time in seconds of the creation
+#[event]
+pub struct PoolInitialized {
+    pub pool: Pubkey,
+    pub admin: Pubkey,
+    pub pool_id: u64,
+    pub token_mint: Pubkey,
+    pub reward_mode: RewardMode,
+    pub reward_rate: u8,
+    pub min_staking_duration: i64,
+    pub timestamp: i64,
+}
+
+/// Rewards funded event
+/// Emitted when the admin funds the rewards of an APY or emission mode pool
+///
+/// # Fields
+/// * `pool` - staking pool account
+/// * `admin` - admin funding the rewards
+/// * `amount` - amount received by the vault
+/// * `reward_funds` - reward funds of the pool after the funding
+/// * `emission_end_time` - time in seconds the emission ends at, 0 in APY mode
+/// * `timestamp` - time in seconds of the funding
+#[event]
+pub struct RewardsFunded {
+    pub pool: Pubkey,
+    pub admin: Pubkey,
+    pub amount: u64,
+    pub reward_funds: u64,
+    pub emission_end_time: i64,
+    pub timestamp: i64,
+}
+
+/// Staked event
+/// Emitted when a user opens a new position
+///
+/// # Fields
+/// * `pool` - staking pool account
+/// * `user` - owner of the position
+/// * `user_stake` - position account
+/// * `position_id` - id of the position among the positions of the user
+/// * `amount` - amount staked (received by the vault)
+/// * `tier` - index of the lockup tier of the position
+/// * `lock_duration` - lock of the position in seconds
+/// * `total_staked` - total staked in the pool after the stake
+/// * `timestamp` - time in seconds of the stake
+#[event]
+pub struct Staked {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub user_stake: Pubkey,
+    pub position_id: u64,
+    pub amount: u64,
+    pub tier: u8,
+    pub lock_duration: i64,
+    pub total_staked: u64,
+    pub timestamp: i64,
+}
+
+/// Redeemed event
+/// Emitted when a position is closed, by its owner or returned after a shutdown
+///
+/// # Fields
+/// * `pool` - staking pool account
+/// * `user` - owner of the position
+/// * `user_stake` - position account
+/// * `position_id` - id of the position among the positions of the user
+/// * `principal` - principal sent back to the owner, penalty deducted
+/// * `reward` - reward paid to the owner (reward streams excluded)
+/// * `stream_rewards` - rewards paid out of every reward stream slot
+/// * `penalty` - principal withheld by the penalty policy
+/// * `forced` - position redeemed before the end of its lock
+/// * `total_staked` - total staked in the pool after the redemption
+/// * `timestamp` - time in seconds of the redemption
+#[event]
+pub struct Redeemed {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub user_stake: Pubkey,
+    pub position_id: u64,
+    pub principal: u64,
+    pub reward: u64,
+    pub stream_rewards: [u64; MAX_REWARD_STREAMS],
+    pub penalty: u64,
+    pub forced: bool,
+    pub total_staked: u64,
+    pub timestamp: i64,
+}
+
+/// Stake increased event
+/// Emitted when a user adds tokens to an existing position
+///
+/// # Fields
+/// * `pool` - staking pool account
+/// * `user` - owner of the position
+/// * `user_stake` - position account
+/// * `position_id` - id of the position among the positions of the user
+/// * `amount` - amount added (received by the vault)
+/// * `new_amount` - amount staked in the position after the top up
+/// * `total_staked` - total staked in the pool after the top up
+/// * `timestamp` - time in seconds of the top up
+#[event]
+pub struct StakeIncreased {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub user_stake: Pubkey,
+    pub position_id: u64,
+    pub amount: u64,
+    pub new_amount: u64,
+    pub total_staked: u64,
+    pub timestamp: i64,
+}
+
+/// Partially unstaked event
+/// Emitted when a user withdraws part of the principal of a position
+///
+/// # Fields
+/// * `pool` - staking pool account
+/// * `user` - owner of the position
+/// * `user_stake` - position account
+/// * `position_id` - id of the position among the positions of the user
+/// * `amount` - principal withdrawn
+/// * `reward` - reward paid to the owner (reward streams excluded)
+/// * `stream_rewards` - rewards paid out of every reward stream slot
+/// * `remaining_amount` - amount left staked in the position
+/// * `total_staked` - total staked in the pool after the withdrawal
+/// * `timestamp` - time in seconds of the withdrawal
+#[event]
+pub struct PartiallyUnstaked {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub user_stake: Pubkey,
+    pub position_id: u64,
+    pub amount: u64,
+    pub reward: u64,
+    pub stream_rewards: [u64; MAX_REWARD_STREAMS],
+    pub remaining_amount: u64,
+    pub total_staked: u64,
+    pub timestamp: i64,
+}
+
+/// Unstake requested event
+/// Emitted when a user starts unbonding principal of a position
+///
+/// # Fields
+/// * `pool` - staking pool account
+/// * `user` - owner of the position
+/// * `user_stake` - position account
+/// * `position_id` - id of the position among the positions of the user
+/// * `amount` - principal moved to the unbonding queue
+/// * `reward` - reward paid to the owner (reward streams excluded)
+/// * `stream_rewards` - rewards paid out of every reward stream slot
+/// * `unbonding_amount` - amount unbonding in the position after the request
+/// * `unlock_at` - time in seconds from which the unbonding amount can be withdrawn
+/// * `total_staked` - total staked in the pool after the request
+/// * `timestamp` - time in seconds of the request
+#[event]
+pub struct UnstakeRequested {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub user_stake: Pubkey,
+    pub position_id: u64,
+    pub amount: u64,
+    pub reward: u64,
+    pub stream_rewards: [u64; MAX_REWARD_STREAMS],
+    pub unbonding_amount: u64,
+    pub unlock_at: i64,
+    pub total_staked: u64,
+    pub timestamp: i64,
+}
+
+/// Unbonded withdrawn event
+/// Emitted when a user withdraws the unbonding amount of a position after its cooldown
+///
+/// # Fields
+/// * `pool` - staking pool account
+/// * `user` - owner of the position
+/// * `user_stake` - position account
+/// * `position_id` - id of the position among the positions of the user
+/// * `amount` - unbonded amount withdrawn
+/// * `timestamp` - time in seconds of the withdrawal
+#[event]
+pub struct UnbondedWithdrawn {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub user_stake: Pubkey,
+    pub position_id: u64,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+/// Rewards claimed event
+/// Emitted when the rewards of a position are paid out without unstaking
+///
+/// # Fields
+/// * `pool` - staking pool account
+/// * `user` - owner of the position
+/// * `user_stake` - position account
+/// * `position_id` - id of the position among the positions of the user
+/// * `reward` - reward paid to the owner (reward streams excluded)
+/// * `stream_rewards` - rewards paid out of every reward stream slot
+/// * `timestamp` - time in seconds of the claim
+#[event]
+pub struct RewardsClaimed {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub user_stake: Pubkey,
+    pub position_id: u64,
+    pub reward: u64,
+    pub stream_rewards: [u64; MAX_REWARD_STREAMS],
+    pub timestamp: i64,
+}
+
+/// Compounded event
+/// Emitted when the reward of a position is added to its principal, by its owner or a cranker
+///
+/// # Fields
+/// * `pool` - staking pool account
+/// * `user` - owner of the position
+/// * `user_stake` - position account
+/// * `position_id` - id of the position among the positions of the user
+/// * `authority` - owner or cranker compounding the position
+/// * `amount` - reward added to the principal
+/// * `tip` - reward paid to the cranker
+/// * `total_staked` - total staked in the pool after the compounding
+/// * `timestamp` - time in seconds of the compounding
+#[event]
+pub struct Compounded {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub user_stake: Pubkey,
+    pub position_id: u64,
+    pub authority: Pubkey,
+    pub amount: u64,
+    pub tip: u64,
+    pub total_staked: u64,
+    pub timestamp: i64,
+}
+
+/// Emergency withdrawn event
+/// Emitted when a user takes the principal of a position back from a pool in emergency
+///
+/// # Fields
+/// * `pool` - staking pool account
+/// * `user` - owner of the position
+/// * `user_stake` - position account
+/// * `position_id` - id of the position among the positions of the user
+/// * `amount` - principal (unbonding amount included) sent back to the owner
+/// * `forfeited_reward` - reward of the position left in the pool
+/// * `timestamp` - time in seconds of the withdrawal
+#[event]
+pub struct EmergencyWithdrawn {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub user_stake: Pubkey,
+    pub position_id: u64,
+    pub amount: u64,
+    pub forfeited_reward: u64,
+    pub timestamp: i64,
+}
+
+/// Rewards withdrawn event
+/// Emitted when the admin takes back reward funds which are not owed to stakers
+///
+/// # Fields
+/// * `pool` - staking pool account
+/// * `admin` - admin withdrawing the rewards
+/// * `amount` - amount withdrawn
+/// * `reward_funds` - reward funds of the pool after the withdrawal
+/// * `timestamp` - time in seconds of the withdrawal
+#[event]
+pub struct RewardsWithdrawn {
+    pub pool: Pubkey,
+    pub admin: Pubkey,
+    pub amount: u64,
+    pub reward_funds: u64,
+    pub timestamp: i64,
+}
+
+/// Reward stream added event
+/// Emitted when the admin adds a reward stream to the pool
+///
+/// # Fields
+/// * `pool` - staking pool account
+/// * `index` - slot of the stream
+/// * `stream_id` - id of the stream
+/// * `mint` - mint of the stream rewards
+/// * `vault` - vault of the stream
+/// * `amount` - amount emitted by the stream (received by the vault)
+/// * `reward_per_second` - rewards emitted per second
+/// * `start_time` - time in seconds at which the emission starts
+/// * `end_time` - time in seconds at which the emission ends
+#[event]
+pub struct RewardStreamAdded {
+    pub pool: Pubkey,
+    pub index: u8,
+    pub stream_id: u64,
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub amount: u64,
+    pub reward_per_second: u64,
+    pub start_time: i64,
+    pub end_time: i64,
+}
+
+/// Reward stream retired event
+/// Emitted when the admin frees the slot of a finished reward stream
+///
+/// # Fields
+/// * `pool` - staking pool account
+/// * `index` - slot of the stream
+/// * `stream_id` - id of the stream
+/// * `remaining_amount` - rewards swept from the vault of the stream to the admin
+/// * `timestamp` - time in seconds of the retirement
+#[event]
+pub struct RewardStreamRetired {
+    pub pool: Pubkey,
+    pub index: u8,
+    pub stream_id: u64,
+    pub remaining_amount: u64,
+    pub timestamp: i64,
+}
+
+/// Shutdown begun event
+/// Emitted when the admin starts winding down the pool
+///
+/// # Fields
+/// * `pool` - staking pool account
+/// * `admin` - admin shutting the pool down
+/// * `grace_period_end` - time in seconds after which the positions can be returned
+/// * `timestamp` - time in seconds of the shutdown
+#[event]
+pub struct ShutdownBegun {
+    pub pool: Pubkey,
+    pub admin: Pubkey,
+    pub grace_period_end: i64,
+    pub timestamp: i64,
+}
+
+/// Guardian set event
+/// Emitted when the admin appoints (or removes, with the default pubkey) the guardian of the pool
+///
+/// # Fields
+/// * `pool` - staking pool account
+/// * `guardian` - new guardian of the pool
+#[event]
+pub struct GuardianSet {
+    pub pool: Pubkey,
+    pub guardian: Pubkey,
+}
+
+/// Pool state changed event
+/// Emitted when the admin or the guardian moves the pool to another state
+///
+/// # Fields
+/// * `pool` - staking pool account
+/// * `authority` - admin or guardian changing the state
+/// * `pool_state` - new state of the pool
+#[event]
+pub struct PoolStateChanged {
+    pub pool: Pubkey,
+    pub authority: Pubkey,
+    pub pool_state: PoolState,
+}
+
+/// Admin proposed event
+/// Emitted when the admin starts (or cancels, with the default pubkey) an admin transfer
+///
+/// # Fields
+/// * `pool` - staking pool account
+/// * `admin` - current admin of the pool
+/// * `pending_admin` - account allowed to accept the admin role
+#[event]
+pub struct AdminProposed {
+    pub pool: Pubkey,
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+}
+
+/// Admin accepted event
+/// Emitted when the pending admin takes over the admin role
+///
+/// # Fields
+/// * `pool` - staking pool account
+/// * `previous_admin` - admin before the transfer
+/// * `admin` - new admin of the pool
+#[event]
+pub struct AdminAccepted {
+    pub pool: Pubkey,
+    pub previous_admin: Pubkey,
+    pub admin: Pubkey,
+}
+
+/// Admin renounced event
+/// Emitted when the admin gives up the admin role for good
+///
+/// # Fields
+/// * `pool` - staking pool account
+/// * `admin` - admin giving up the role
+#[event]
+pub struct AdminRenounced {
+    pub pool: Pubkey,
+    pub admin: Pubkey,
+}
+
+/// Lockup tiers updated event
+/// Emitted when the admin configures the lockup tiers of the pool
+///
+/// # Fields
+/// * `pool` - staking pool account
+/// * `tiers` - new lockup tiers
+#[event]
+pub struct LockupTiersUpdated {
+    pub pool: Pubkey,
+    pub tiers: Vec<LockupTier>,
 }
 
-impl<'info> Stake<'info> {
-    /// Implementation of Stake instruction
-    /// This function is used to create CPI context for transferring tokens from user to staking account
-    ///
-    /// # Example
-    ///
-    /// ```rs
-    /// let ctx = Stake {...}
-    /// let cpi_ctx = ctx.transfer_to_stake_ctx();
-    /// token::transfer(cpi_ctx, amount)?;
-    /// ```
-    pub fn transfer_to_stake_ctx(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
-        // Creates the CPI context and return it
-        CpiContext::new(
-            self.token_program.to_account_info(), // token program account info (used for token transferring)
-            Transfer {
-                from: self.user_token_account.to_account_info(), // from user's token account
-                to: self.staking_token_account.to_account_info(), // to staking pool's token account
-                authority: self.user.to_account_info(), // authority to transfer tokens (signer)
-            },
-        )
-    }
+/// Penalty policy updated event
+/// Emitted when the admin configures the penalty of force redeeming
+///
+/// # Fields
+/// * `pool` - staking pool account
+/// * `penalty_policy` - how the penalty is computed
+/// * `penalty_bps` - penalty rate in basis points
+/// * `penalty_destination` - where principal penalties go
+/// * `treasury` - treasury receiving the penalties, default pubkey without a treasury
+#[event]
+pub struct PenaltyPolicyUpdated {
+    pub pool: Pubkey,
+    pub penalty_policy: PenaltyPolicy,
+    pub penalty_bps: u16,
+    pub penalty_destination: PenaltyDestination,
+    pub treasury: Pubkey,
 }
 
-/// Redeem instruction structs
-/// This struct is used to define the accounts and instructions required for the redeem instruction
+/// Reward mint set event
+/// Emitted when the admin pays the rewards of the pool in another token than the staked one
 ///
 /// # Fields
-/// * `staking_account` - staking pool account PDA (which is created in the initialize function)
-/// * `staking_token_account_owner` - staking pool account owner, who owns the pool  (signer)
-/// * `user_stake` - user stake account PDA (which is created in the stake function)
-/// * `user` - user account (signer)
-/// * `user_token_account` - user token account which holds the required SPL tokens
-/// * `staking_token_account` - staking token account which holds the SPL tokens for staking (shared by all users within the pool)
-/// * `token_program` - token program used to transfer tokens
-#[derive(Accounts)]
-pub struct Redeem<'info> {
-    #[account(mut)]
-    pub staking_account: Account<'info, StakingAccount>, // staking pool account for managing state (which is created in the initialize function)
+/// * `pool` - staking pool account
+/// * `reward_mint` - mint of the rewards
+/// * `reward_vault` - reward vault token account holding the reward funds
+#[event]
+pub struct RewardMintSet {
+    pub pool: Pubkey,
+    pub reward_mint: Pubkey,
+    pub reward_vault: Pubkey,
+}
 
-    #[account(
-        mut,
-        constraint = staking_token_account_owner.key() == staking_token_account.owner.key() // Verify staking pool account owner
-    )]
-    pub staking_token_account_owner: Signer<'info>, // staking pool account owner (signer)
+/// Auto compound set event
+/// Emitted when the owner of a position allows (or forbids) anyone to compound it
+///
+/// # Fields
+/// * `pool` - staking pool account
+/// * `user` - owner of the position
+/// * `user_stake` - position account
+/// * `enabled` - whether crankers may compound the position
+#[event]
+pub struct AutoCompoundSet {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub user_stake: Pubkey,
+    pub enabled: bool,
+}
 
-    #[account(
-        mut, // mutable account (which is created in the stake function)
-        seeds = [user.key.as_ref(), b"user_stake"], // seeds (to access the PDA created in the stake function)
-        bump, // bump for the PDA (must be same as the one used in the stake function)
-        close = user // close the account and transfer the remaining balance to the user account
-    )]
-    pub user_stake: Account<'info, UserStake>, // user stake account PDA
-    #[account(
-        mut,
-        // constraint = &user.key() == user_stake.to_account_info().owner // Verify user owns the stake account
-    )]
-    pub user: Signer<'info>, // user account (signer) used to sign the transaction while redeeming it
-    #[account(
-        mut,
-        constraint = user_token_account.owner == user.key() // Verify user owns the token account
-    )]
-    pub user_token_account: Account<'info, TokenAccount>, // user token account which holds the required SPL tokens
-    #[account(mut)]
-    pub staking_token_account: Account<'info, TokenAccount>, // staking token account which holds the SPL tokens for staking (shared by all users within the pool)
-    pub token_program: Program<'info, Token>, // token program used to transfer tokens
+/// Compound tip set event
+/// Emitted when the admin sets the share of the reward paid to crankers
+///
+/// # Fields
+/// * `pool` - staking pool account
+/// * `compound_tip_bps` - tip in basis points of the compounded reward
+#[event]
+pub struct CompoundTipSet {
+    pub pool: Pubkey,
+    pub compound_tip_bps: u16,
 }
 
-impl<'info> Redeem<'info> {
-    /// This function creates CPI context for transferring tokens from staking account to user
-    ///
-    /// # Example
-    /// ```rs
-    /// let ctx = Redeem {...}
-    /// let cpi_ctx = ctx.transfer_to_user_ctx();
-    /// token::transfer(cpi_ctx, amount)?;
-    /// ```
-    pub fn transfer_to_user_ctx(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
-        // Creates the CPI context and return it
-        CpiContext::new(
-            self.token_program.to_account_info(), // token program account info (used for token transferring)
-            Transfer {
-                from: self.staking_token_account.to_account_info(), // from staking pool's token account
-                to: self.user_token_account.to_account_info(),      // to user's token account
-                authority: self.staking_token_account_owner.to_account_info(), // authority to transfer tokens (staking pool)
-            },
-        )
-    }
+/// Pool closed event
+/// Emitted when the admin closes a pool after its shutdown
+///
+/// # Fields
+/// * `pool` - staking pool account
+/// * `admin` - admin receiving the remaining tokens and the rent
+/// * `remaining_amount` - tokens swept from the stake vault
+/// * `remaining_reward` - tokens swept from the reward vault, 0 without a separate reward mint
+/// * `timestamp` - time in seconds of the closure
+#[event]
+pub struct PoolClosed {
+    pub pool: Pubkey,
+    pub admin: Pubkey,
+    pub remaining_amount: u64,
+    pub remaining_reward: u64,
+    pub timestamp: i64,
 }
 
-/// Close instruction structs
-/// This struct is used to define the accounts and instructions required for the close instruction
+/// Pool migrated event
+/// Emitted when the admin moves a pool of the baseline layout to a new pool
 ///
 /// # Fields
-/// * `account_to_close` - account to close
-/// * `admin` - admin account (signer)
-#[derive(Accounts)]
-pub struct Close<'info> {
-    #[account(mut, close = admin)]
-    pub account_to_close: Account<'info, StakingAccount>, // account to close
-    #[account(mut)]
-    pub admin: Signer<'info>,
+/// * `pool` - new staking pool account
+/// * `legacy_pool` - staking pool account of the baseline layout (closed)
+/// * `admin` - admin (and creator) of the pool
+/// * `pool_id` - id of the new pool among the pools of the creator
+/// * `reward_rate` - APY return yearly in percentage
+/// * `min_staking_duration` - minimum staking duration in seconds
+/// * `reward_funds` - reward funds moved to the vault
+/// * `timestamp` - time in seconds of the migration
+#[event]
+pub struct PoolMigrated {
+    pub pool: Pubkey,
+    pub legacy_pool: Pubkey,
+    pub admin: Pubkey,
+    pub pool_id: u64,
+    pub reward_rate: u8,
+    pub min_staking_duration: i64,
+    pub reward_funds: u64,
+    pub timestamp: i64,
+}
+
+/// Position migrated event
+/// Emitted when the admin moves a position of the baseline layout to a migrated pool
+///
+/// # Fields
+/// * `pool` - staking pool account
+/// * `user` - owner of the position
+/// * `user_stake` - new position account
+/// * `legacy_user_stake` - position account of the baseline layout (closed)
+/// * `position_id` - id of the new position among the positions of the user
+/// * `amount` - principal moved to the vault
+/// * `start_time` - time in seconds the position was opened at
+/// * `reward` - reward accrued since then, owed to the position
+/// * `total_staked` - total amount staked in the pool after the migration
+/// * `timestamp` - time in seconds of the migration
+#[event]
+pub struct PositionMigrated {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub user_stake: Pubkey,
+    pub legacy_user_stake: Pubkey,
+    pub position_id: u64,
+    pub amount: u64,
+    pub start_time: i64,
+    pub reward: u64,
+    pub total_staked: u64,
+    pub timestamp: i64,
+}
+
+/// Account upgraded event
+/// Emitted when a pool or a position of an older layout is upgraded in place to the current layout
+///
+/// # Fields
+/// * `account` - staking pool or user stake account
+/// * `previous_version` - layout version of the account before the upgrade
+/// * `version` - layout version of the account after the upgrade
+/// * `timestamp` - time in seconds of the upgrade
+#[event]
+pub struct AccountUpgraded {
+    pub account: Pubkey,
+    pub previous_version: u8,
+    pub version: u8,
+    pub timestamp: i64,
 }
 
 //////////////////////////////////////////////////////////////////////////////////////////
@@ -461,15 +4776,41 @@ pub struct Close<'info> {
 /// Error codes are used to provide more information about the error
 ///
 /// # Available error codes:
-/// 1. `AlreadyStaked` - User has already staked
+/// 1. `AlreadyStaked` - Deprecated, not returned since users can hold several positions (kept so the other codes do not move)
 /// 2. `NothingStaked` - User has nothing staked
 /// 3. `InvalidArgument` - Invalid argument
 /// 4. `InsufficientRewardFunds` - Insufficient funds
 /// 5. `AdminOnly` - Only admin can fund the reward pool
 /// 6. `StakingDurationNotMet` - Staking duration not met
 /// 7. `CalculationError` - Calculation error
+/// 8. `ZeroValueError` - Provided parameters includes 0
+/// 9. `PoolMismatch` - Stake account does not belong to the given pool
+/// 10. `InvalidRewardMode` - Instruction not available in the reward mode of the pool
+/// 11. `AutoCompoundDisabled` - Position did not opt in to be compounded by others
+/// 12. `InvalidTier` - Lockup tier does not exist or is misconfigured
+/// 13. `UnbondingRequired` - Pool has an unbonding cooldown, principal must be unbonded first
+/// 14. `UnbondingPending` - Position has an unbonding amount to withdraw first
+/// 15. `NothingUnbonding` - Position has nothing unbonding
+/// 16. `CooldownNotMet` - Unbonding cooldown not elapsed
+/// 17. `NoPendingConfigUpdate` - No config update is staged
+/// 18. `TimelockNotElapsed` - Config update timelock not elapsed
+/// 19. `NotPendingAdmin` - Signer is not the pending admin of the pool
+/// 20. `PoolNotActive` - Pool is paused or in emergency
+/// 21. `PoolNotInEmergency` - Pool is not in emergency
+/// 22. `RewardFundsAllocated` - Amount exceeds the reward funds not owed to stakers
+/// 23. `PoolShuttingDown` - Pool is shutting down
+/// 24. `PoolNotShuttingDown` - Pool shutdown has not begun
+/// 25. `GracePeriodNotElapsed` - Shutdown grace period not elapsed
+/// 26. `OutstandingStake` - Pool still holds staked or unbonding tokens
+/// 27. `SeparateRewardMint` - Rewards of the pool are not paid in the staked token
+/// 28. `TooManyRewardStreams` - Pool already has the maximum number of reward streams
+/// 29. `AccountNotMigrated` - Account must be migrated to the current layout first
+/// 30. `AlreadyMigrated` - Account already has the current layout
+/// 31. `RewardStreamActive` - Reward stream is still running or in its claim period
+/// 32. `UnsupportedLayout` - Account does not have a supported layout
 #[error_code]
 pub enum StakingError {
+    // deprecated, never returned anymore, the variant keeps its place so the error codes stay stable
     #[msg("User has already staked.")]
     AlreadyStaked,
     #[msg("User has nothing staked.")]
@@ -486,42 +4827,997 @@ pub enum StakingError {
     CalculationError,
     #[msg("Provided parameters includes 0 which are not allowed")]
     ZeroValueError,
+    #[msg("Stake account does not belong to this pool.")]
+    PoolMismatch,
+    #[msg("Instruction is not available in the reward mode of this pool.")]
+    InvalidRewardMode,
+    #[msg("Position did not opt in to auto-compounding.")]
+    AutoCompoundDisabled,
+    #[msg("Invalid lockup tier.")]
+    InvalidTier,
+    #[msg("This pool has an unbonding cooldown, use request_unstake.")]
+    UnbondingRequired,
+    #[msg("Withdraw the unbonding amount of this position first.")]
+    UnbondingPending,
+    #[msg("Nothing is unbonding in this position.")]
+    NothingUnbonding,
+    #[msg("Unbonding cooldown not met.")]
+    CooldownNotMet,
+    #[msg("No config update is pending.")]
+    NoPendingConfigUpdate,
+    #[msg("Config update timelock not elapsed.")]
+    TimelockNotElapsed,
+    #[msg("Only the pending admin can accept the admin role.")]
+    NotPendingAdmin,
+    #[msg("Pool is paused.")]
+    PoolNotActive,
+    #[msg("Pool is not in emergency.")]
+    PoolNotInEmergency,
+    #[msg("Amount exceeds the unallocated reward funds.")]
+    RewardFundsAllocated,
+    #[msg("Pool is shutting down.")]
+    PoolShuttingDown,
+    #[msg("Pool shutdown has not begun.")]
+    PoolNotShuttingDown,
+    #[msg("Shutdown grace period not elapsed.")]
+    GracePeriodNotElapsed,
+    #[msg("Pool still holds staked or unbonding tokens.")]
+    OutstandingStake,
+    #[msg("Rewards of this pool are not paid in the staked token.")]
+    SeparateRewardMint,
+    #[msg("Pool already has the maximum number of reward streams.")]
+    TooManyRewardStreams,
+    #[msg("Account must be migrated to the current layout first.")]
+    AccountNotMigrated,
+    #[msg("Account already has the current layout.")]
+    AlreadyMigrated,
+    #[msg("Reward stream is still running or in its claim period.")]
+    RewardStreamActive,
+    #[msg("Account does not have a supported layout.")]
+    UnsupportedLayout,
+}
+
+/// Seconds in a (365 days) year, used to turn the APY into a per-second rate
+pub const SECONDS_PER_YEAR: u128 = 365 * 24 * 60 * 60;
+
+/// Denominator of the `reward_rate` percentage
+pub const REWARD_RATE_DENOMINATOR: u128 = 100;
+
+/// Longest duration (100 years in seconds) accepted by `calculate_reward` at a 100% reward rate
+pub const MAX_REWARD_DURATION: i64 = 100 * 365 * 24 * 60 * 60;
+
+/// Denominator of the basis points values (100% = 10000 bps)
+pub const BPS_DENOMINATOR: u128 = 10_000;
+
+/// Highest tip (10% of the reward) the admin can pay to crankers compounding positions
+pub const MAX_COMPOUND_TIP_BPS: u16 = 1_000;
+
+/// Highest number of lockup tiers of a pool
+pub const MAX_LOCKUP_TIERS: usize = 8;
+
+/// Highest number of reward streams of a pool
+pub const MAX_REWARD_STREAMS: usize = 4;
+
+/// Time (30 days in seconds) stakers have to claim the rewards of a finished reward stream before it can be retired
+pub const STREAM_CLAIM_PERIOD: i64 = 30 * 24 * 60 * 60;
+
+/// Longest unbonding cooldown (30 days in seconds) the admin can set
+pub const MAX_UNBONDING_COOLDOWN: i64 = 30 * 24 * 60 * 60;
+
+/// Delay (1 day in seconds) between proposing and applying a config update until the admin changes it
+pub const DEFAULT_CONFIG_TIMELOCK: i64 = 24 * 60 * 60;
+
+/// Scale of `acc_reward_per_share`, keeps precision when the emission is small compared to the stake
+pub const ACC_REWARD_PRECISION: u128 = 1_000_000_000_000;
+
+/// Layout version of the staking accounts, bumped when a layout appends fields after `version`
+/// Accounts of an older layout are upgraded in place by `upgrade_pool`, except version 0: the baseline layout
+/// (`StakingAccountV0`) has other seeds (no pool id), so `migrate_pool` moves it to a new account
+pub const STAKING_ACCOUNT_VERSION: u8 = 1;
+
+/// Layout version of the user stake accounts, bumped when a layout appends fields after `version`
+/// Accounts of an older layout are upgraded in place by `upgrade_user_stake`, except version 0: the baseline layout
+/// (`UserStakeV0`) has other seeds (no pool nor position id), so `migrate_user_stake` moves it to a new account
+/// Version 0 positions store their start time in milliseconds or seconds
+pub const USER_STAKE_VERSION: u8 = 1;
+
+/// Times of version 0 positions from this value on are in milliseconds (year 5138 in seconds),
+/// lower times are already in seconds (positions opened without a client timestamp stored seconds)
+pub const LEGACY_MILLISECONDS_THRESHOLD: i64 = 100_000_000_000;
+
+/// Initialize the fields of a new pool (all but its bump), shared by `initialize` and `migrate_pool`
+/// Fails with `InvalidArgument` if the reward rate or the minimum staking duration is out of bounds
+fn init_pool(
+    staking_account: &mut StakingAccount,
+    admin: Pubkey,
+    pool_id: u64,
+    token_mint: Pubkey,
+    reward_rate: u8,
+    min_staking_duration: i64,
+    reward_mode: RewardMode,
+) -> Result<()> {
+    require!(
+        reward_rate as u128 <= REWARD_RATE_DENOMINATOR && min_staking_duration >= 0,
+        StakingError::InvalidArgument
+    );
+
+    staking_account.admin = admin;
+    // the creator stays in the PDA seeds, so the pool address survives admin transfers
+    staking_account.creator = admin;
+    staking_account.pending_admin = Pubkey::default();
+    staking_account.guardian = Pubkey::default();
+    staking_account.pool_state = PoolState::Active;
+    staking_account.pool_id = pool_id;
+    staking_account.reward_rate = reward_rate;
+    staking_account.token_mint = token_mint;
+    staking_account.admin_reward_amount = 0;
+    staking_account.min_staking_duration = min_staking_duration;
+    staking_account.reward_mode = reward_mode;
+    staking_account.total_staked = 0;
+    staking_account.acc_reward_per_share = 0;
+    staking_account.reward_per_second = 0;
+    staking_account.last_update_time = Clock::get()?.unix_timestamp;
+    staking_account.emission_end_time = 0;
+    staking_account.compound_tip_bps = 0;
+    staking_account.total_weight = 0;
+    // single flexible tier until the admin configures the lockup tiers
+    staking_account.tier_count = 1;
+    staking_account.tiers = [LockupTier::default(); MAX_LOCKUP_TIERS];
+    staking_account.tiers[0] = LockupTier {
+        lock_duration: 0,
+        reward_multiplier_bps: BPS_DENOMINATOR as u16,
+    };
+    // early withdrawals forfeit the rewards until the admin sets a penalty policy
+    staking_account.penalty_policy = PenaltyPolicy::ForfeitRewards;
+    staking_account.penalty_bps = 0;
+    staking_account.penalty_destination = PenaltyDestination::RewardPool;
+    staking_account.treasury = Pubkey::default();
+    // principal can be withdrawn instantly until the admin sets an unbonding cooldown
+    staking_account.unbonding_cooldown = 0;
+    staking_account.total_unbonding = 0;
+    staking_account.config_timelock = DEFAULT_CONFIG_TIMELOCK;
+    staking_account.pending_config = PoolConfig::default();
+    staking_account.config_update_eta = 0;
+    staking_account.reward_liability = 0;
+    staking_account.apy_weight_sum = 0;
+    staking_account.apy_weight_index_sum = 0;
+    staking_account.apy_rate_index = 0;
+    staking_account.apy_index_time = Clock::get()?.unix_timestamp;
+    staking_account.shutdown_time = 0;
+    staking_account.grace_period_end = 0;
+    // rewards are paid in the staked token until the admin sets a separate reward mint
+    staking_account.reward_mint = token_mint;
+    staking_account.stream_count = 0;
+    staking_account.reward_streams = [RewardStream::default(); MAX_REWARD_STREAMS];
+    staking_account.next_stream_id = 1;
+    staking_account.version = STAKING_ACCOUNT_VERSION;
+    Ok(())
 }
 
-/// Calculate reward based on staked amount, duration and reward rate
-/// Priority is to minimize intermediate values to avoid overflow
+/// Calculate reward based on staked amount, rate duration and reward multiplier
+/// Rewards accrue per second at the reward rate of the pool: `amount * multiplier_bps * rate_duration / (100 * 10000 * SECONDS_PER_YEAR)`,
+/// `rate_duration` being the sum of `reward_rate * seconds` over the accrual period (see `apy_rate_index`)
+/// The product is computed in u128 and divided once at the end, rounding down (in favour of the pool),
+/// so the result is exact to the token base unit
 /// Returns reward amount
 ///
+/// # Envelope
+/// Any `amount` (up to `u64::MAX`) and `multiplier_bps` (up to `u16::MAX`) combined with a `rate_duration` up to
+/// `MAX_REWARD_DURATION` at a 100% reward rate fits in the u128 intermediate value. A reward which does not fit in
+/// a u64 or a rate duration above the envelope returns `CalculationError`
 ///
 /// # Arguments
 /// * `amount` - Amount of tokens staked
-/// * `duration` - Duration of staking in seconds
-/// * `reward_rate` - Annual percentage yield (APY) in percentage (0-100)
-/// * `min_staking_duration` - Minimum staking duration in seconds
+/// * `rate_duration` - Sum of the reward rate (APY in percentage, 0-100) times the seconds it applied
+/// * `multiplier_bps` - Reward multiplier of the lockup tier in basis points (10000 = 1x)
 ///
 /// # Example
 ///
 /// ```rs
-/// let reward = calculate_reward(100, 86400, 10); // 100 tokens staked for 1 day at 10% APY
-/// println!("Reward: {}", reward); // prints 10
+/// let reward = calculate_reward(1_000_000, 10 * 86400, 10_000)?; // 1_000_000 base units staked for 1 day at 10% APY
+/// println!("Reward: {}", reward); // prints 273 (273.97 rounded down)
 /// ```
+fn calculate_reward(amount: u64, rate_duration: u128, multiplier_bps: u16) -> Result<u64> {
+    require!(
+        rate_duration <= REWARD_RATE_DENOMINATOR * MAX_REWARD_DURATION as u128,
+        StakingError::CalculationError
+    );
+
+    let reward = (amount as u128)
+        .checked_mul(multiplier_bps as u128)
+        .and_then(|value| value.checked_mul(rate_duration))
+        .and_then(|value| {
+            value.checked_div(REWARD_RATE_DENOMINATOR * BPS_DENOMINATOR * SECONDS_PER_YEAR)
+        }) // rounds down
+        .ok_or(StakingError::CalculationError)?;
+
+    Ok(u64::try_from(reward).map_err(|_| StakingError::CalculationError)?)
+}
+
+/// Calculate the APY rate index of a pool at the given time (capped at the shutdown of the pool)
+/// Adds `reward_rate * seconds` since `apy_index_time` to the checkpointed `apy_rate_index`
+fn apy_rate_index(staking_account: &StakingAccount, current_time: i64) -> Result<u128> {
+    let elapsed = accrual_time(staking_account, current_time) - staking_account.apy_index_time;
+    Ok((staking_account.reward_rate as u128)
+        .checked_mul(elapsed.max(0) as u128)
+        .and_then(|value| staking_account.apy_rate_index.checked_add(value))
+        .ok_or(StakingError::CalculationError)?)
+}
+
+/// Checkpoint the APY rate index of a pool at the given time
+/// Must be called before the reward rate changes, so the rewards accrued so far keep the old rate
+fn checkpoint_apy_rate_index(
+    staking_account: &mut StakingAccount,
+    current_time: i64,
+) -> Result<()> {
+    staking_account.apy_rate_index = apy_rate_index(staking_account, current_time)?;
+    staking_account.apy_index_time = staking_account
+        .apy_index_time
+        .max(accrual_time(staking_account, current_time));
+    Ok(())
+}
+
+/// Update the emission accumulator of a pool up to the given time
+/// Adds the reward emitted since `last_update_time` (until the end of the emission) divided by the total weight
+/// Rewards emitted while nothing is staked are not distributed and stay in `admin_reward_amount`
+/// The reward streams of the pool are updated as well, the accumulator of the pool itself only in emission mode
+///
+/// # Arguments
+/// * `staking_account` - pool to update
+/// * `current_time` - Current time in seconds
+fn update_reward_per_share(staking_account: &mut StakingAccount, current_time: i64) -> Result<()> {
+    update_reward_streams(staking_account, current_time)?;
+    if staking_account.reward_mode != RewardMode::Emission {
+        return Ok(());
+    }
+
+    let emission_time = current_time.min(staking_account.emission_end_time);
+    if emission_time > staking_account.last_update_time && staking_account.total_weight > 0 {
+        let elapsed = (emission_time - staking_account.last_update_time) as u128;
+        let increment = (staking_account.reward_per_second as u128)
+            .checked_mul(elapsed)
+            .and_then(|value| value.checked_mul(ACC_REWARD_PRECISION))
+            .and_then(|value| value.checked_div(staking_account.total_weight)) // rounds down
+            .ok_or(StakingError::CalculationError)?;
+        staking_account.acc_reward_per_share = staking_account
+            .acc_reward_per_share
+            .checked_add(increment)
+            .ok_or(StakingError::CalculationError)?;
+        // the emitted rewards are owed to the stakers from now on
+        let emitted = (staking_account.reward_per_second as u128 * elapsed) as u64;
+        staking_account.reward_liability = staking_account
+            .reward_liability
+            .checked_add(emitted)
+            .ok_or(StakingError::CalculationError)?;
+    }
+
+    staking_account.last_update_time = staking_account.last_update_time.max(current_time);
+    Ok(())
+}
+
+/// Update the accumulators of the reward streams of a pool up to the given time
+/// Rewards of a stream emitted while nothing is staked are not distributed and stay in its vault
+///
+/// # Arguments
+/// * `staking_account` - pool to update
+/// * `current_time` - Current time in seconds
+fn update_reward_streams(staking_account: &mut StakingAccount, current_time: i64) -> Result<()> {
+    let total_weight = staking_account.total_weight;
+    let stream_count = staking_account.stream_count as usize;
+    for stream in staking_account.reward_streams.iter_mut().take(stream_count) {
+        let emission_time = current_time.min(stream.end_time);
+        if emission_time > stream.last_update_time && total_weight > 0 {
+            let elapsed = (emission_time - stream.last_update_time) as u128;
+            let increment = (stream.reward_per_second as u128)
+                .checked_mul(elapsed)
+                .and_then(|value| value.checked_mul(ACC_REWARD_PRECISION))
+                .and_then(|value| value.checked_div(total_weight)) // rounds down
+                .ok_or(StakingError::CalculationError)?;
+            stream.acc_reward_per_share = stream
+                .acc_reward_per_share
+                .checked_add(increment)
+                .ok_or(StakingError::CalculationError)?;
+        }
+        // streams which have not started yet keep their start time
+        stream.last_update_time = stream.last_update_time.max(emission_time);
+    }
+    Ok(())
+}
+
+/// Calculate the weight of a position in the emission (staked amount scaled by its tier multiplier)
+fn stake_weight(user_stake: &UserStake) -> u128 {
+    user_stake.amount as u128 * user_stake.reward_multiplier_bps as u128 / BPS_DENOMINATOR
+}
+
+/// Calculate `weight * acc_reward_per_share` of a position, scaled by `ACC_REWARD_PRECISION`
+fn reward_per_share_value(
+    user_stake: &UserStake,
+    staking_account: &StakingAccount,
+) -> Result<u128> {
+    Ok(stake_weight(user_stake)
+        .checked_mul(staking_account.acc_reward_per_share)
+        .ok_or(StakingError::CalculationError)?)
+}
+
+/// Change the staked amount of a position, keeping the pool totals and the reward debt in sync
+/// Rewards accrued at the old amount must be settled before calling it
+fn set_stake_amount(
+    staking_account: &mut StakingAccount,
+    user_stake: &mut UserStake,
+    amount: u64,
+) -> Result<()> {
+    let old_weight = stake_weight(user_stake);
+    untrack_apy_weight(staking_account, user_stake)?;
+    staking_account.total_staked = staking_account
+        .total_staked
+        .checked_sub(user_stake.amount)
+        .and_then(|total| total.checked_add(amount))
+        .ok_or(StakingError::CalculationError)?;
+
+    user_stake.amount = amount;
+    staking_account.total_weight = staking_account
+        .total_weight
+        .checked_sub(old_weight)
+        .and_then(|total| total.checked_add(stake_weight(user_stake)))
+        .ok_or(StakingError::CalculationError)?;
+    track_apy_weight(staking_account, user_stake)?;
+
+    reset_reward_debt(user_stake, staking_account)
+}
+
+/// Reset the reward debts of a position (emission mode and reward streams) after its staked amount changed
+fn reset_reward_debt(user_stake: &mut UserStake, staking_account: &StakingAccount) -> Result<()> {
+    user_stake.reward_debt = reward_per_share_value(user_stake, staking_account)?;
+    let weight = stake_weight(user_stake);
+    for index in 0..MAX_REWARD_STREAMS {
+        user_stake.stream_reward_debts[index] = weight
+            .checked_mul(staking_account.reward_streams[index].acc_reward_per_share)
+            .ok_or(StakingError::CalculationError)?;
+    }
+    Ok(())
+}
+
+/// Calculate the reward owed to a position at the given time
+/// Adds the reward accrued since the last settlement to the already settled `pending_reward`
+/// In emission mode `update_reward_per_share` must be called first
+///
+/// # Arguments
+/// * `user_stake` - position to calculate the reward for
+/// * `staking_account` - pool of the position
+/// * `current_time` - Current time in seconds
+fn accrued_reward(
+    user_stake: &UserStake,
+    staking_account: &StakingAccount,
+    current_time: i64,
+) -> Result<u64> {
+    let reward = match staking_account.reward_mode {
+        RewardMode::Apy => {
+            let rate_duration = apy_rate_index(staking_account, current_time)?
+                .checked_sub(user_stake.apy_rate_index)
+                .ok_or(StakingError::CalculationError)?;
+            calculate_reward(
+                user_stake.amount,
+                rate_duration,
+                user_stake.reward_multiplier_bps,
+            )?
+        }
+        RewardMode::Emission => {
+            let reward = reward_per_share_value(user_stake, staking_account)?
+                .checked_sub(user_stake.reward_debt)
+                .ok_or(StakingError::CalculationError)?
+                / ACC_REWARD_PRECISION; // rounds down
+            u64::try_from(reward).map_err(|_| StakingError::CalculationError)?
+        }
+    };
+
+    Ok(user_stake
+        .pending_reward
+        .checked_add(reward)
+        .ok_or(StakingError::CalculationError)?)
+}
+
+/// Calculate the amount a vault actually received from a transfer, given its balance before the transfer
+/// Token-2022 mints with a transfer fee withhold part of every transfer, only the rest is credited to the sender
+fn received_amount(vault: &mut InterfaceAccount<TokenAccount>, balance_before: u64) -> Result<u64> {
+    vault.reload()?;
+    Ok(vault
+        .amount
+        .checked_sub(balance_before)
+        .ok_or(StakingError::CalculationError)?)
+}
+
+/// Decode an account of the baseline layout (version 0), given its discriminator and size
+/// Fails with `AlreadyMigrated` if the account has the size of the current layout
+/// and with `UnsupportedLayout` if its discriminator or its size match neither
+fn read_legacy_account<T: AnchorDeserialize>(
+    account: &AccountInfo,
+    discriminator: &[u8],
+    legacy_len: usize,
+    current_len: usize,
+) -> Result<T> {
+    let data = account.try_borrow_data()?;
+    require!(
+        data.len() >= 8 && &data[..8] == discriminator,
+        StakingError::UnsupportedLayout
+    );
+    require!(data.len() != current_len, StakingError::AlreadyMigrated);
+    require!(data.len() == legacy_len, StakingError::UnsupportedLayout);
+    Ok(T::deserialize(&mut &data[8..])?)
+}
+
+/// Close an account of the baseline layout once migrated, its rent going to the destination
+/// The data is cleared and the account handed back to the system program so it can not be migrated twice
+fn close_legacy_account<'info>(
+    account: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+) -> Result<()> {
+    let lamports = account.lamports();
+    **destination.try_borrow_mut_lamports()? = destination
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(StakingError::CalculationError)?;
+    **account.try_borrow_mut_lamports()? = 0;
+    account.assign(&system_program::ID);
+    account.realloc(0, false)?;
+    Ok(())
+}
+
+/// Grow an account of an older layout to the size of the current layout, the payer tops up its rent
+/// The appended bytes are zeroed, so the fields a newer layout adds after `version` decode as 0
+/// Fails with `UnsupportedLayout` if its discriminator does not match, if it has the size of the baseline layout
+/// (moved to a new account by the migrate instructions) or if it is larger than the current layout
+fn grow_account<'info>(
+    account: &AccountInfo<'info>,
+    discriminator: &[u8],
+    legacy_len: usize,
+    current_len: usize,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    let len = account.data_len();
+    require!(
+        len >= 8 && &account.try_borrow_data()?[..8] == discriminator,
+        StakingError::UnsupportedLayout
+    );
+    require!(
+        len != legacy_len && len <= current_len,
+        StakingError::UnsupportedLayout
+    );
+    if len == current_len {
+        return Ok(());
+    }
+
+    let rent = Rent::get()?.minimum_balance(current_len);
+    if rent > account.lamports() {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                system_program::Transfer {
+                    from: payer.to_account_info(),
+                    to: account.clone(),
+                },
+            ),
+            rent - account.lamports(),
+        )?;
+    }
+    account.realloc(current_len, true)?;
+    Ok(())
+}
+
+/// Convert the start time of a version 0 position to seconds, rounding down
+/// Times below `LEGACY_MILLISECONDS_THRESHOLD` are already in seconds and kept as is
+fn legacy_time_to_seconds(time: i64) -> i64 {
+    if time < LEGACY_MILLISECONDS_THRESHOLD {
+        time
+    } else {
+        time / 1000
+    }
+}
+
+/// Whether a pool pays its rewards in another token than the staked one, out of its reward vault
+fn has_reward_vault(staking_account: &StakingAccount) -> bool {
+    staking_account.reward_mint != staking_account.token_mint
+}
+
+/// Get an optional account required by the pools with a separate reward mint
+/// Fails with `InvalidArgument` if it is missing
+fn reward_account<T>(account: &Option<T>) -> Result<&T> {
+    Ok(account.as_ref().ok_or(StakingError::InvalidArgument)?)
+}
+
+/// Accounts paying out the principal and the rewards of a position, built with `reward_payout!`
+/// The reward accounts are only required by the pools with a separate reward mint
+struct RewardPayout<'a, 'info> {
+    staking_account: &'a Account<'info, StakingAccount>, // staking pool account PDA, owner of the vaults
+    token_program: &'a Interface<'info, TokenInterface>, // token program used to transfer tokens
+    staking_token_account: &'a InterfaceAccount<'info, TokenAccount>, // vault of the pool
+    mint: &'a InterfaceAccount<'info, Mint>,             // mint of the staked token
+    user_token_account: &'a InterfaceAccount<'info, TokenAccount>, // user token account which receives the principal
+    reward_token_account: &'a Option<InterfaceAccount<'info, TokenAccount>>, // reward vault of the pool
+    reward_mint: &'a Option<InterfaceAccount<'info, Mint>>,                  // mint of the rewards
+    user_reward_token_account: &'a Option<InterfaceAccount<'info, TokenAccount>>, // user token account which receives the rewards
+}
+
+/// Pay out principal and rewards to the owner of a position, signed by the staking account PDA
+/// Rewards in the staked token are paid together with the principal out of the vault,
+/// the rewards of a pool with a separate reward mint out of its reward vault
+///
+/// # Arguments
+/// * `payout` - accounts paying out the position
+/// * `principal` - principal to pay out of the vault
+/// * `reward` - rewards to pay out
+fn pay_reward(payout: RewardPayout, principal: u64, reward: u64) -> Result<()> {
+    let separate_reward = has_reward_vault(payout.staking_account);
+    let vault_amount = if separate_reward {
+        principal
+    } else {
+        principal
+            .checked_add(reward)
+            .ok_or(StakingError::CalculationError)?
+    };
+
+    if vault_amount > 0 {
+        transfer_from_vault(
+            payout.staking_account,
+            payout.token_program,
+            payout.staking_token_account.to_account_info(),
+            payout.user_token_account.to_account_info(),
+            payout.mint.to_account_info(),
+            payout.mint.decimals,
+            vault_amount,
+        )?;
+    }
+    if separate_reward && reward > 0 {
+        let reward_mint = reward_account(payout.reward_mint)?;
+        transfer_from_vault(
+            payout.staking_account,
+            payout.token_program,
+            reward_account(payout.reward_token_account)?.to_account_info(),
+            reward_account(payout.user_reward_token_account)?.to_account_info(),
+            reward_mint.to_account_info(),
+            reward_mint.decimals,
+            reward,
+        )?;
+    }
+    Ok(())
+}
+
+/// Transfer tokens out of a vault owned by the staking account PDA, signed with its seeds
+///
+/// # Arguments
+/// * `staking_account` - staking pool account PDA owning the vault
+/// * `token_program` - token program used to transfer tokens
+/// * `vault` - vault to transfer from
+/// * `destination` - token account to transfer to
+/// * `mint` - mint of the transferred token
+/// * `decimals` - decimals of the mint
+/// * `amount` - amount to transfer
+fn transfer_from_vault<'info>(
+    staking_account: &Account<'info, StakingAccount>,
+    token_program: &Interface<'info, TokenInterface>,
+    vault: AccountInfo<'info>,
+    destination: AccountInfo<'info>,
+    mint: AccountInfo<'info>,
+    decimals: u8,
+    amount: u64,
+) -> Result<()> {
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(), // token program account info (used for token transferring)
+            TransferChecked {
+                from: vault,                                  // from the vault
+                mint,                                         // mint of the transferred token
+                to: destination,                              // to the destination token account
+                authority: staking_account.to_account_info(), // authority to transfer tokens (staking pool PDA)
+            },
+            &[staking_account_seeds!(staking_account)],
+        ),
+        amount,
+        decimals,
+    )
+}
+
+/// Settle the reward owed to a position up to the given time (seconds) into its `pending_reward`
+/// In APY mode the reward accrued since the last settlement is added to the reward liability of the pool
+/// (emission rewards are added when emitted), in emission mode `update_reward_per_share` must be called first
+/// The rewards of the reward streams are settled into `stream_pending_rewards` as well
+/// Returns the reward owed to the position
+fn settle_reward(
+    staking_account: &mut StakingAccount,
+    user_stake: &mut UserStake,
+    current_time: i64,
+) -> Result<u64> {
+    let current_time = accrual_time(staking_account, current_time);
+    let reward = accrued_reward(user_stake, staking_account, current_time)?;
+    if staking_account.reward_mode == RewardMode::Apy {
+        staking_account.reward_liability = staking_account
+            .reward_liability
+            .checked_add(reward - user_stake.pending_reward)
+            .ok_or(StakingError::CalculationError)?;
+    }
+
+    untrack_apy_weight(staking_account, user_stake)?;
+    user_stake.pending_reward = reward;
+    user_stake.last_reward_time = current_time;
+    user_stake.apy_rate_index = apy_rate_index(staking_account, current_time)?;
+    track_apy_weight(staking_account, user_stake)?;
+    settle_stream_rewards(staking_account, user_stake)?;
+    Ok(reward)
+}
+
+/// Settle the rewards of the reward streams owed to a position into its `stream_pending_rewards`
+/// The rewards of a retired stream still pending in the position are forfeited when its slot is freed or reused
+/// `update_reward_per_share` must be called first
+fn settle_stream_rewards(
+    staking_account: &StakingAccount,
+    user_stake: &mut UserStake,
+) -> Result<()> {
+    let weight = stake_weight(user_stake);
+    for index in 0..MAX_REWARD_STREAMS {
+        let stream_id = staking_account.reward_streams[index].id;
+        if user_stake.stream_ids[index] != stream_id {
+            // a new stream starts its accumulator at zero, so the position earns from its start
+            user_stake.stream_reward_debts[index] = 0;
+            user_stake.stream_pending_rewards[index] = 0;
+            user_stake.stream_ids[index] = stream_id;
+        }
+        let value = weight
+            .checked_mul(staking_account.reward_streams[index].acc_reward_per_share)
+            .ok_or(StakingError::CalculationError)?;
+        let reward = value
+            .checked_sub(user_stake.stream_reward_debts[index])
+            .ok_or(StakingError::CalculationError)?
+            / ACC_REWARD_PRECISION; // rounds down
+        user_stake.stream_pending_rewards[index] = u64::try_from(reward)
+            .ok()
+            .and_then(|reward| user_stake.stream_pending_rewards[index].checked_add(reward))
+            .ok_or(StakingError::CalculationError)?;
+        user_stake.stream_reward_debts[index] = value;
+    }
+    Ok(())
+}
+
+/// Whether a position has rewards of a reward stream settled and not paid out yet
+fn has_stream_rewards(user_stake: &UserStake) -> bool {
+    user_stake
+        .stream_pending_rewards
+        .iter()
+        .any(|reward| *reward > 0)
+}
+
+/// Pay out the rewards of the reward streams settled in a position, signed by the staking account PDA
+/// `remaining_accounts` holds `(vault, mint, destination token account)` triples of every reward stream (matched by vault),
+/// the destination token accounts being owned by the owner of the position
+/// Principal exits skip the streams whose destination is frozen or closed so a stream never blocks an exit,
+/// their rewards stay pending in the position (which the exit then keeps open), claims fail instead
+/// Returns the rewards paid out of every stream
+///
+/// # Arguments
+/// * `staking_account` - pool of the position
+/// * `user_stake` - position to pay the stream rewards of (settled with `settle_reward`)
+/// * `remaining_accounts` - accounts of the reward streams
+/// * `token_program` - token program used to transfer tokens
+/// * `required` - whether every stream must be paid (claims) or can be skipped when its destination is unusable (principal exits)
+fn pay_stream_rewards<'info>(
+    staking_account: &Account<'info, StakingAccount>,
+    user_stake: &mut UserStake,
+    remaining_accounts: &'info [AccountInfo<'info>],
+    token_program: &Interface<'info, TokenInterface>,
+    required: bool,
+) -> Result<[u64; MAX_REWARD_STREAMS]> {
+    let mut paid = [0; MAX_REWARD_STREAMS];
+
+    for (index, stream) in active_streams(staking_account) {
+        let reward = user_stake.stream_pending_rewards[index];
+        let Some((vault, mint, destination, decimals)) =
+            stream_payout_accounts(stream, user_stake, remaining_accounts, required)?
+        else {
+            continue;
+        };
+
+        if reward > 0 {
+            transfer_from_vault(
+                staking_account,
+                token_program,
+                vault.clone(),
+                destination.clone(),
+                mint.clone(),
+                decimals,
+                reward,
+            )?;
+        }
+        user_stake.stream_pending_rewards[index] = 0;
+        paid[index] = reward;
+    }
+    Ok(paid)
+}
+
+/// Vault, mint, destination token account and mint decimals paying the rewards of a reward stream
+type StreamPayoutAccounts<'info> = (
+    &'info AccountInfo<'info>,
+    &'info AccountInfo<'info>,
+    &'info AccountInfo<'info>,
+    u8,
+);
+
+/// Find the `(vault, mint, destination, decimals)` accounts paying the rewards of a reward stream to a position
+/// Fails with `InvalidArgument` if they are missing or invalid, so the rewards of a stream are never skipped by leaving its accounts out
+/// Returns `None` if the destination is frozen or closed, unless `required` (then fails with `InvalidArgument`)
+fn stream_payout_accounts<'info>(
+    stream: &RewardStream,
+    user_stake: &UserStake,
+    remaining_accounts: &'info [AccountInfo<'info>],
+    required: bool,
+) -> Result<Option<StreamPayoutAccounts<'info>>> {
+    let accounts = remaining_accounts
+        .chunks_exact(3)
+        .find(|accounts| accounts[0].key() == stream.vault)
+        .ok_or(StakingError::InvalidArgument)?;
+    let (vault, mint, destination) = (&accounts[0], &accounts[1], &accounts[2]);
+    require_keys_eq!(mint.key(), stream.mint, StakingError::InvalidArgument);
+    let mint_account = InterfaceAccount::<Mint>::try_from(mint)?;
+
+    // a closed (or never created) destination can not receive the payout
+    if destination.data_is_empty() {
+        require!(!required, StakingError::InvalidArgument);
+        return Ok(None);
+    }
+    let destination_account = InterfaceAccount::<TokenAccount>::try_from(destination)?;
+    require!(
+        destination_account.owner == user_stake.user && destination_account.mint == stream.mint,
+        StakingError::InvalidArgument
+    );
+    if destination_account.is_frozen() {
+        require!(!required, StakingError::InvalidArgument);
+        return Ok(None);
+    }
+    Ok(Some((vault, mint, destination, mint_account.decimals)))
+}
+
+/// Iterate over the reward streams of a pool with their slot, skipping the free slots
+fn active_streams(
+    staking_account: &StakingAccount,
+) -> impl Iterator<Item = (usize, &RewardStream)> {
+    staking_account
+        .reward_streams
+        .iter()
+        .take(staking_account.stream_count as usize)
+        .enumerate()
+        .filter(|(_, stream)| stream.id != 0)
+}
+
+/// Get the slot taken by the next reward stream of a pool, the first free one or the one after the last stream
+/// (`MAX_REWARD_STREAMS` when every slot is taken)
+fn free_stream_slot(staking_account: &StakingAccount) -> u8 {
+    staking_account
+        .reward_streams
+        .iter()
+        .take(staking_account.stream_count as usize)
+        .position(|stream| stream.id == 0)
+        .unwrap_or(staking_account.stream_count as usize) as u8
+}
+
+/// Cap a time (seconds) at the shutdown of the pool, after which rewards do not accrue anymore
+fn accrual_time(staking_account: &StakingAccount, current_time: i64) -> i64 {
+    if staking_account.shutdown_time != 0 {
+        current_time.min(staking_account.shutdown_time)
+    } else {
+        current_time
+    }
+}
+
+/// Calculate `amount * reward_multiplier_bps` and `apy_rate_index` of a position
+fn apy_weight(user_stake: &UserStake) -> (u128, u128) {
+    let weight = user_stake.amount as u128 * user_stake.reward_multiplier_bps as u128;
+    (weight, user_stake.apy_rate_index)
+}
+
+/// Remove a position from the APY weight sums of its pool
+fn untrack_apy_weight(staking_account: &mut StakingAccount, user_stake: &UserStake) -> Result<()> {
+    let (weight, index) = apy_weight(user_stake);
+    staking_account.apy_weight_sum = staking_account
+        .apy_weight_sum
+        .checked_sub(weight)
+        .ok_or(StakingError::CalculationError)?;
+    staking_account.apy_weight_index_sum = weight
+        .checked_mul(index)
+        .and_then(|value| staking_account.apy_weight_index_sum.checked_sub(value))
+        .ok_or(StakingError::CalculationError)?;
+    Ok(())
+}
+
+/// Add a position to the APY weight sums of its pool
+fn track_apy_weight(staking_account: &mut StakingAccount, user_stake: &UserStake) -> Result<()> {
+    let (weight, index) = apy_weight(user_stake);
+    staking_account.apy_weight_sum = staking_account
+        .apy_weight_sum
+        .checked_add(weight)
+        .ok_or(StakingError::CalculationError)?;
+    staking_account.apy_weight_index_sum = weight
+        .checked_mul(index)
+        .and_then(|value| staking_account.apy_weight_index_sum.checked_add(value))
+        .ok_or(StakingError::CalculationError)?;
+    Ok(())
+}
+
+/// Calculate the rewards of the running emission of a pool which are not emitted yet
+///
+/// # Arguments
+/// * `staking_account` - pool to calculate the remaining emission of
+/// * `current_time` - Current time in seconds
+fn remaining_emission(staking_account: &StakingAccount, current_time: i64) -> Result<u64> {
+    if staking_account.reward_mode != RewardMode::Emission
+        || current_time >= staking_account.emission_end_time
+    {
+        return Ok(0);
+    }
+
+    Ok(staking_account
+        .reward_per_second
+        .checked_mul((staking_account.emission_end_time - current_time) as u64)
+        .ok_or(StakingError::CalculationError)?)
+}
+
+/// Calculate the reward funds of a pool which are not owed to stakers and can be withdrawn by the admin
+/// Owed are the rewards settled or emitted and not paid out yet, the APY rewards accrued since the last
+/// settlements (rounded up) and the rest of a running emission
+/// In emission mode `update_reward_per_share` must be called first
+///
+/// # Arguments
+/// * `staking_account` - pool to calculate the unallocated reward funds of
+/// * `current_time` - Current time in seconds
+fn unallocated_reward_funds(staking_account: &StakingAccount, current_time: i64) -> Result<u64> {
+    let unsettled_reward = if staking_account.reward_mode == RewardMode::Apy {
+        let weight_rate_duration = staking_account
+            .apy_weight_sum
+            .checked_mul(apy_rate_index(staking_account, current_time)?)
+            .ok_or(StakingError::CalculationError)?
+            .saturating_sub(staking_account.apy_weight_index_sum);
+        let reward = weight_rate_duration
+            .div_ceil(REWARD_RATE_DENOMINATOR * BPS_DENOMINATOR * SECONDS_PER_YEAR);
+        u64::try_from(reward).map_err(|_| StakingError::CalculationError)?
+    } else {
+        0
+    };
+
+    let owed = (staking_account.reward_liability as u128)
+        + (unsettled_reward as u128)
+        + (remaining_emission(staking_account, current_time)? as u128);
+    Ok((staking_account.admin_reward_amount as u128).saturating_sub(owed) as u64)
+}
+
+/// Take a reward payout out of the funded rewards of a pool
+/// Fails with `InsufficientRewardFunds` if the pool can not cover it
+fn take_reward_funds(staking_account: &mut StakingAccount, reward: u64) -> Result<()> {
+    staking_account.admin_reward_amount = staking_account
+        .admin_reward_amount
+        .checked_sub(reward)
+        .ok_or(StakingError::InsufficientRewardFunds)?;
+    release_reward_liability(staking_account, reward);
+    Ok(())
+}
+
+/// Remove rewards paid out or forfeited from the reward liability of a pool
+/// Saturates at zero, as emission payouts are rounded down and never exceed the emitted rewards
+fn release_reward_liability(staking_account: &mut StakingAccount, reward: u64) {
+    staking_account.reward_liability = staking_account.reward_liability.saturating_sub(reward);
+}
+
+/// Mark the rewards settled by `settle_reward` as paid out at the given time (seconds)
+fn record_claim(user_stake: &mut UserStake, reward: u64, current_time: i64) -> Result<()> {
+    user_stake.pending_reward = 0;
+    user_stake.last_claim_time = current_time;
+    user_stake.rewards_claimed = user_stake
+        .rewards_claimed
+        .checked_add(reward)
+        .ok_or(StakingError::CalculationError)?;
+    Ok(())
+}
+
+/// Send the rewards left in the vaults of the reward streams of a pool to the admin and close the vaults, signed by the staking account PDA
+/// `remaining_accounts` must hold `(vault, mint, admin token account)` triples of every reward stream of the pool (matched by vault)
 ///
-/// # Panics
-/// Panics if amount, duration or reward_rate is less than or equal to 0
-fn calculate_reward(amount: u64, duration: i64, reward_rate: u8) -> u64 {
-    // Sanitize input (extra precaution)
-    if amount <= 0 || duration <= 0 || reward_rate <= 0 {
-        panic!("Invalid input");
+/// # Arguments
+/// * `staking_account` - pool being closed
+/// * `remaining_accounts` - accounts of the reward streams
+/// * `admin` - admin of the pool, receives the rent of the closed vaults
+/// * `token_program` - token program used to transfer tokens
+fn close_stream_vaults<'info>(
+    staking_account: &Account<'info, StakingAccount>,
+    remaining_accounts: &'info [AccountInfo<'info>],
+    admin: &Signer<'info>,
+    token_program: &Interface<'info, TokenInterface>,
+) -> Result<()> {
+    let signer_seeds: &[&[&[u8]]] = &[staking_account_seeds!(staking_account)];
+
+    for (_, stream) in active_streams(staking_account) {
+        let accounts = remaining_accounts
+            .chunks_exact(3)
+            .find(|accounts| accounts[0].key() == stream.vault)
+            .ok_or(StakingError::InvalidArgument)?;
+        let (vault, mint, destination) = (&accounts[0], &accounts[1], &accounts[2]);
+        require_keys_eq!(mint.key(), stream.mint, StakingError::InvalidArgument);
+        let vault_account = InterfaceAccount::<TokenAccount>::try_from(vault)?;
+        let mint_account = InterfaceAccount::<Mint>::try_from(mint)?;
+
+        if vault_account.amount > 0 {
+            transfer_from_vault(
+                staking_account,
+                token_program,
+                vault.clone(),
+                destination.clone(),
+                mint.clone(),
+                mint_account.decimals,
+                vault_account.amount,
+            )?;
+        }
+        token_interface::close_account(CpiContext::new_with_signer(
+            token_program.to_account_info(), // token program account info (used for closing the vault)
+            CloseAccount {
+                account: vault.clone(),                       // vault to close
+                destination: admin.to_account_info(),         // admin receives the rent
+                authority: staking_account.to_account_info(), // authority of the vault (staking pool PDA)
+            },
+            signer_seeds,
+        ))?;
     }
+    Ok(())
+}
+
+/// Calculate the reward paid and the principal penalty of force redeeming a position before the end of its lock
+/// Returns `(reward, penalty)`, both rounded down
+///
+/// # Arguments
+/// * `staking_account` - pool of the position (holds the penalty policy)
+/// * `user_stake` - position force redeemed
+/// * `reward` - reward accrued by the position
+/// * `staking_duration` - time elapsed since the start of the position in seconds
+fn early_withdrawal_penalty(
+    staking_account: &StakingAccount,
+    user_stake: &UserStake,
+    reward: u64,
+    staking_duration: i64,
+) -> Result<(u64, u64)> {
+    let penalty_bps = staking_account.penalty_bps as u64;
+    let lock_duration = user_stake.lock_duration.max(1) as u64;
+    let elapsed = staking_duration.clamp(0, user_stake.lock_duration) as u64;
+    let principal = user_stake.amount;
 
-    let days_staked = duration / 86400; // Seconds in a day
+    let (reward, penalty) = match staking_account.penalty_policy {
+        PenaltyPolicy::ForfeitRewards => (0, 0),
+        PenaltyPolicy::FlatPrincipal => (
+            reward,
+            mul_div(principal, penalty_bps, BPS_DENOMINATOR as u64)?,
+        ),
+        PenaltyPolicy::LinearDecay => {
+            let max_penalty = mul_div(principal, penalty_bps, BPS_DENOMINATOR as u64)?;
+            (
+                reward,
+                mul_div(max_penalty, lock_duration - elapsed, lock_duration)?,
+            )
+        }
+        PenaltyPolicy::ProRatedReward => {
+            let matured_reward = mul_div(reward, elapsed, lock_duration)?;
+            (
+                mul_div(
+                    matured_reward,
+                    BPS_DENOMINATOR as u64 - penalty_bps,
+                    BPS_DENOMINATOR as u64,
+                )?,
+                0,
+            )
+        }
+    };
 
-    // Calculate daily rate using floating-point division for precision
-    let annual_rate = reward_rate as f64 * 100.0; // Convert to basis points
-    let daily_rate = annual_rate / 365.0;
+    Ok((reward, penalty))
+}
 
-    // Calculate reward using floating-point multiplication
-    let reward = (amount as f64) * daily_rate * (days_staked as f64) / 10000.0;
+/// Calculate `value * numerator / denominator` in u128, rounding down
+fn mul_div(value: u64, numerator: u64, denominator: u64) -> Result<u64> {
+    let result = (value as u128)
+        .checked_mul(numerator as u128)
+        .and_then(|product| product.checked_div(denominator as u128))
+        .ok_or(StakingError::CalculationError)?;
 
-    reward.round() as u64 // Round to the nearest whole number and return as u64
+    Ok(u64::try_from(result).map_err(|_| StakingError::CalculationError)?)
 }
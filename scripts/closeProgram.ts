@@ -1,42 +1,82 @@
 import * as anchor from "@coral-xyz/anchor";
+import {
+  getAssociatedTokenAddressSync,
+  TOKEN_PROGRAM_ID,
+} from "@solana/spl-token";
 import { PublicKey } from "@solana/web3.js";
-import bs58 from "bs58";
 
 import keypair from "../utils/privateKey";
 import { StakeTokens } from "../target/types/stake_tokens";
 
 const provider = anchor.AnchorProvider.env();
 anchor.setProvider(provider);
-anchor.web3.PublicKey;
 
-const admin = keypair; // Admin keypair for initializing the staking pool
+const admin = keypair; // Admin keypair of the staking pool
 
 //////////////////////////////////////////////////////////////////////////////////////
 //////////////////////////////// IMPORTANT CONSTANTS ////////////////////////////////
 //////////////////////////////////////////////////////////////////////////////////////
 
 const program = anchor.workspace.StakeTokens as anchor.Program<StakeTokens>;
-const programId = new PublicKey("7a8fBQMwbtE1C61fcGUW6quAgdqdmzYojha5cQq9Ju4q");
+const mintAddr = new PublicKey("F7zbU4Lqs1cBNX35o6NmuXXjoE2ri7Z9nsLjk7UvmwuV");
+const poolId = new anchor.BN(0); // specify the pool id used while initializing
+const gracePeriod = new anchor.BN(7 * 24 * 60 * 60); // time users have to redeem by themselves (seconds)
 
+const [stakingAccountPDA] = PublicKey.findProgramAddressSync(
+  [
+    admin.publicKey.toBuffer(), // creator of the pool
+    Buffer.from("staking_account"),
+    poolId.toArrayLike(Buffer, "le", 8),
+  ],
+  program.programId
+);
+const [stakingAccountATA] = PublicKey.findProgramAddressSync(
+  [stakingAccountPDA.toBuffer(), Buffer.from("staking_token_account")],
+  program.programId
+);
+const adminTokenAccountATA = getAssociatedTokenAddressSync(
+  mintAddr,
+  admin.publicKey
+);
+
+// Run once to begin the shutdown, then again after the grace period
+// (once the remaining positions are returned) to close the pool
 (async function () {
   try {
+    const stakingAccount = await program.account.stakingAccount.fetch(
+      stakingAccountPDA
+    );
+    if (stakingAccount.shutdownTime.isZero()) {
+      await program.methods
+        .beginShutdown(gracePeriod)
+        .accounts({
+          stakingAccount: stakingAccountPDA,
+          admin: admin.publicKey,
+        })
+        .signers([admin])
+        .rpc();
+      console.log("Pool shutdown started!✅");
+      return;
+    }
+
     await program.methods
-      .close()
+      .closePool()
       .accounts({
+        // @ts-ignore
+        stakingAccount: stakingAccountPDA,
+        stakingTokenAccount: stakingAccountATA,
         admin: admin.publicKey,
-        accountToClose: programId,
+        adminTokenAccount: adminTokenAccountATA,
+        mint: mintAddr,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        rewardTokenAccount: null,
+        rewardMint: null,
+        adminRewardTokenAccount: null,
       })
       .signers([admin])
       .rpc();
-    console.log("Program closed successfully!✅");
+    console.log("Pool closed successfully!✅");
   } catch (error) {
     console.log(error.message);
   }
 })();
-
-const arrayToBase58PrivateKey = (numbers) => {
-  const bytes = new Uint8Array(numbers);
-  // Convert to Base58
-  const base58 = bs58.encode(bytes);
-  return base58;
-};
@@ -1,5 +1,8 @@
 import * as anchor from "@coral-xyz/anchor";
-import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+import {
+  getAssociatedTokenAddressSync,
+  TOKEN_PROGRAM_ID,
+} from "@solana/spl-token";
 import { Keypair } from "@solana/web3.js";
 
 import { CustomSplTokens } from "../target/types/custom_spl_tokens";
@@ -38,6 +41,7 @@ console.log({
     .accounts({
       payer: admin.publicKey,
       mintAccount: tokenMint.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
     })
     .signers([admin, tokenMint])
     .rpc();
@@ -47,6 +51,7 @@ console.log({
       mintAuthority: admin.publicKey,
       recipient: admin.publicKey,
       mintAccount: tokenMint.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
       // @ts-ignore
       associatedTokenAccount: tokenMintATA,
     })
@@ -1,5 +1,8 @@
 import * as anchor from "@coral-xyz/anchor";
-import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+import {
+  getAssociatedTokenAddressSync,
+  TOKEN_PROGRAM_ID,
+} from "@solana/spl-token";
 import { PublicKey } from "@solana/web3.js";
 import { provider, stakeProgram, transferTokens } from "../utils/helpers";
 import keypair from "../utils/privateKey";
@@ -9,9 +12,14 @@ const admin = keypair;
 
 const poolATA = getAssociatedTokenAddressSync(mintAddr, admin.publicKey);
 const fundAmount = new anchor.BN(1000); // specify the amount to fund
+const poolId = new anchor.BN(0); // specify the pool id used while initializing
 (async function () {
   const [stakingAccountPDA] = PublicKey.findProgramAddressSync(
-    [provider.wallet.publicKey.toBuffer(), Buffer.from("staking_account")],
+    [
+      provider.wallet.publicKey.toBuffer(),
+      Buffer.from("staking_account"),
+      poolId.toArrayLike(Buffer, "le", 8),
+    ],
     stakeProgram.programId
   );
   await stakeProgram.methods
@@ -21,6 +29,10 @@ const fundAmount = new anchor.BN(1000); // specify the amount to fund
       adminTokenAccount: poolATA,
       stakingAccount: stakingAccountPDA,
       stakingTokenAccount: poolATA,
+      mint: mintAddr,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      rewardTokenAccount: null,
+      rewardMint: null,
     })
     .signers([admin])
     .rpc();
@@ -1,10 +1,13 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
-import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+import {
+  getAssociatedTokenAddressSync,
+  TOKEN_PROGRAM_ID,
+} from "@solana/spl-token";
 
 import keypair from "../utils/privateKey";
 import { StakeTokens } from "../target/types/stake_tokens";
-import { createAndMintToken, transferTokens } from "../utils/helpers";
+import { createAndMintToken } from "../utils/helpers";
 
 const provider = anchor.AnchorProvider.env();
 anchor.setProvider(provider);
@@ -13,22 +16,29 @@ const program = anchor.workspace.StakeTokens as anchor.Program<StakeTokens>;
 
 const admin = keypair; // Admin keypair for initializing the staking pool
 const mintKeyPair = Keypair.generate(); // Token mint used for staking (address of the token mint ERC20 like)
-const stakingTokenAccountKP = Keypair.generate(); // account which is going to hold the funds
 const mintAmount = new anchor.BN(100000); // Amount to mint and stake
 
 const stakingAccountGlobalContextSeed = Buffer.from("staking_account");
 
-const [stakingAccountPDA, bump] = PublicKey.findProgramAddressSync(
-  [admin.publicKey.toBuffer(), stakingAccountGlobalContextSeed],
+// every admin can run several pools, each identified by its pool id
+const poolId = new anchor.BN(Math.floor(Math.random() * 1_000_000));
+
+const [stakingAccountPDA] = PublicKey.findProgramAddressSync(
+  [
+    admin.publicKey.toBuffer(),
+    stakingAccountGlobalContextSeed,
+    poolId.toArrayLike(Buffer, "le", 8),
+  ],
   program.programId
 );
 const adminTokenAccountATA = getAssociatedTokenAddressSync(
   mintKeyPair.publicKey,
   admin.publicKey
 );
-const stakingAccountATA = getAssociatedTokenAddressSync(
-  mintKeyPair.publicKey,
-  stakingTokenAccountKP.publicKey
+// vault token account owned by the staking account PDA
+const [stakingAccountATA] = PublicKey.findProgramAddressSync(
+  [stakingAccountPDA.toBuffer(), Buffer.from("staking_token_account")],
+  program.programId
 );
 
 const APY = 5;
@@ -48,18 +58,16 @@ const minStakingDuration = new anchor.BN(minStakingDurationForOneDayInSec);
   }
 
   await createAndMintToken(mintKeyPair, adminTokenAccountATA, mintAmount);
-  await transferTokens(
-    mintKeyPair.publicKey,
-    stakingTokenAccountKP.publicKey,
-    adminTokenAccountATA,
-    stakingAccountATA,
-    new anchor.BN(0)
-  );
   await program.methods
-    .initialize(bump, mintKeyPair.publicKey, APY, minStakingDuration)
+    .initialize(poolId, mintKeyPair.publicKey, APY, minStakingDuration, {
+      apy: {},
+    })
     .accounts({
       // @ts-ignore
       stakingAccount: stakingAccountPDA,
+      stakingTokenAccount: stakingAccountATA,
+      mint: mintKeyPair.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
       admin: admin.publicKey,
       systemProgram: SystemProgram.programId,
     })
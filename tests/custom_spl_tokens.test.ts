@@ -1,6 +1,9 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Keypair } from "@solana/web3.js";
-import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+import {
+  getAssociatedTokenAddressSync,
+  TOKEN_PROGRAM_ID,
+} from "@solana/spl-token";
 import { assert } from "chai";
 
 import { CustomSplTokens } from "../target/types/custom_spl_tokens";
@@ -40,6 +43,7 @@ describe("Create token", function () {
       .accounts({
         payer: payer.publicKey,
         mintAccount: mintKeyPair.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
       })
       .signers([mintKeyPair])
       .rpc();
@@ -55,6 +59,7 @@ describe("Create token", function () {
       .accounts({
         payer: payer.publicKey,
         mintAccount: mintKeyPair.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
       })
       .signers([mintKeyPair])
       .rpc();
@@ -80,6 +85,7 @@ describe("Create token", function () {
         mintAuthority: payer.publicKey,
         recipient: payer.publicKey,
         mintAccount: mintKeyPair.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
         // @ts-ignore
         associatedTokenAccount: associatedTokenAccount,
       })
@@ -121,6 +127,7 @@ describe("Create token", function () {
         sender: payer.publicKey,
         recipient: recipientKeyPair.publicKey,
         mintAccount: mintKeyPair.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
         // @ts-ignore
         senderTokenAccount: senderTokenAddress,
         recipientTokenAccount: recipientTokenAddress,
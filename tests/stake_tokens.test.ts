@@ -1,9 +1,21 @@
 import * as anchor from "@coral-xyz/anchor";
 import {
+  createAssociatedTokenAccount,
+  createInitializeMintInstruction,
+  createInitializeTransferFeeConfigInstruction,
+  ExtensionType,
   getAssociatedTokenAddressSync,
+  getMintLen,
+  mintTo,
+  TOKEN_2022_PROGRAM_ID,
   TOKEN_PROGRAM_ID,
 } from "@solana/spl-token";
-import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+import {
+  Keypair,
+  PublicKey,
+  SystemProgram,
+  Transaction,
+} from "@solana/web3.js";
 import { assert } from "chai";
 
 import type { StakeTokens } from "../target/types/stake_tokens";
@@ -12,7 +24,6 @@ import {
   airdrop,
   createAndMintToken,
   getStakeInfo,
-  oneYearInMilliseconds,
   simulateTransaction,
   transferTokens,
 } from "../utils/helpers";
@@ -29,23 +40,52 @@ const program = anchor.workspace.StakeTokens as anchor.Program<StakeTokens>;
 const admin = keypair; // Admin keypair for initializing the staking pool
 const mintKeyPair = Keypair.generate(); // Token mint used for staking (address of the token mint ERC20 like)
 const staker = Keypair.generate(); // User who will stake tokens
-const stakingTokenAccountKP = Keypair.generate(); // account which is going to hold the funds
-const mintAmount = new anchor.BN(100000); // Amount to mint and stake
+const mintAmount = new anchor.BN(1_000_000_000_000); // Amount to mint and stake
 
 const stakingAccountGlobalContextSeed = Buffer.from("staking_account");
 const userStakeAccountLocalContextSeed = Buffer.from("user_stake");
+const stakingTokenAccountSeed = Buffer.from("staking_token_account");
 
-// initialize id must be random number type between 0 and 255
-// const initialize_id = Math.floor(Math.random() * 255).toString();
+// every admin can run several pools, each identified by its pool id
+const poolId = new anchor.BN(Math.floor(Math.random() * 1_000_000));
 
-const [stakingAccountPDA, bump] = PublicKey.findProgramAddressSync(
-  [admin.publicKey.toBuffer(), stakingAccountGlobalContextSeed],
+const [stakingAccountPDA] = PublicKey.findProgramAddressSync(
+  [
+    admin.publicKey.toBuffer(),
+    stakingAccountGlobalContextSeed,
+    poolId.toArrayLike(Buffer, "le", 8),
+  ],
   program.programId
 );
-const [userStakeAccountPDA] = PublicKey.findProgramAddressSync(
-  [staker.publicKey.toBuffer(), userStakeAccountLocalContextSeed],
+const userStakeCounterSeed = Buffer.from("user_stake_counter");
+
+const [userStakeCounterPDA] = PublicKey.findProgramAddressSync(
+  [
+    stakingAccountPDA.toBuffer(),
+    staker.publicKey.toBuffer(),
+    userStakeCounterSeed,
+  ],
   program.programId
 );
+// every stake opens a new position, numbered from 0
+const getUserStakeAccountPDA = (positionId: number) =>
+  PublicKey.findProgramAddressSync(
+    [
+      stakingAccountPDA.toBuffer(),
+      staker.publicKey.toBuffer(),
+      userStakeAccountLocalContextSeed,
+      new anchor.BN(positionId).toArrayLike(Buffer, "le", 8),
+    ],
+    program.programId
+  )[0];
+const userStakeAccountPDA = getUserStakeAccountPDA(0);
+const forceRedeemUserStakeAccountPDA = getUserStakeAccountPDA(1);
+const topUpUserStakeAccountPDA = getUserStakeAccountPDA(2);
+const claimUserStakeAccountPDA = getUserStakeAccountPDA(3);
+const compoundUserStakeAccountPDA = getUserStakeAccountPDA(4);
+const tierUserStakeAccountPDA = getUserStakeAccountPDA(5);
+const unbondingUserStakeAccountPDA = getUserStakeAccountPDA(6);
+const emergencyUserStakeAccountPDA = getUserStakeAccountPDA(7);
 
 const adminTokenAccountATA = getAssociatedTokenAddressSync(
   mintKeyPair.publicKey,
@@ -55,19 +95,25 @@ const stakerTokenAccountATA = getAssociatedTokenAddressSync(
   mintKeyPair.publicKey,
   staker.publicKey
 );
-const stakingAccountATA = getAssociatedTokenAddressSync(
-  mintKeyPair.publicKey,
-  stakingTokenAccountKP.publicKey
+// vault token account owned by the staking account PDA
+const [stakingAccountATA] = PublicKey.findProgramAddressSync(
+  [stakingAccountPDA.toBuffer(), stakingTokenAccountSeed],
+  program.programId
 );
 
-const initialFundReward = new anchor.BN(500); // Initial reward amount
+const initialFundReward = new anchor.BN(1_000_000); // Initial reward amount
 const APY = 5;
-// 1 year in seconds
-const minStakingDuration = new anchor.BN(1 * 365 * 24 * 60 * 60);
+// the tests run on the cluster clock, so positions mature within seconds
+const minStakingDuration = new anchor.BN(2); // seconds
+// amount staked by the positions expecting rewards, a 5% APY only accrues whole tokens within seconds on large stakes
+const rewardStakingAmount = new anchor.BN(10_000_000_000);
 
-const oneYearBeforeTimeStamp = new anchor.BN(
-  new Date(Date.now() - 365 * 24 * 60 * 60 * 1000).getTime()
-);
+// waits until the positions opened so far matured on the cluster clock
+async function waitForMaturity() {
+  await new Promise((resolve) =>
+    setTimeout(resolve, (minStakingDuration.toNumber() + 2) * 1000)
+  );
+}
 
 //////////////////////////////////////////////////
 ///////////////////// TESTS /////////////////////
@@ -76,13 +122,6 @@ const oneYearBeforeTimeStamp = new anchor.BN(
 describe("Test for staking tokens", function () {
   it("It should initialize the pool (`initialize` instruction test)", async function () {
     await createAndMintToken(mintKeyPair, adminTokenAccountATA, mintAmount);
-    await transferTokens(
-      mintKeyPair.publicKey,
-      stakingTokenAccountKP.publicKey,
-      adminTokenAccountATA,
-      stakingAccountATA,
-      new anchor.BN(0)
-    );
 
     const adminBalance = await provider.connection.getTokenAccountBalance(
       adminTokenAccountATA
@@ -90,12 +129,17 @@ describe("Test for staking tokens", function () {
     assert(adminBalance.value.uiAmount === mintAmount.toNumber());
 
     await program.methods
-      .initialize(bump, mintKeyPair.publicKey, APY, minStakingDuration)
+      .initialize(poolId, mintKeyPair.publicKey, APY, minStakingDuration, {
+        apy: {},
+      })
       .accounts({
         // @ts-ignore
         stakingAccount: stakingAccountPDA,
+        stakingTokenAccount: stakingAccountATA,
+        mint: mintKeyPair.publicKey,
         admin: admin.publicKey,
         systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
       })
       .rpc();
 
@@ -104,8 +148,9 @@ describe("Test for staking tokens", function () {
       stakingAccountPDA
     );
     assert(stakingPool.adminRewardAmount.toNumber() === 0);
-    assert(stakingPool.bump == bump);
+    assert(stakingPool.poolId.eq(poolId));
     assert(stakingPool.rewardRate == APY);
+    assert("apy" in stakingPool.rewardMode);
     assert(
       stakingPool.tokenMint.toBase58() == mintKeyPair.publicKey.toBase58()
     );
@@ -123,6 +168,10 @@ describe("Test for staking tokens", function () {
         adminTokenAccount: adminTokenAccountATA,
         stakingAccount: stakingAccountPDA,
         stakingTokenAccount: stakingAccountATA,
+        mint: mintKeyPair.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        rewardTokenAccount: null,
+        rewardMint: null,
       })
       .rpc();
 
@@ -135,7 +184,7 @@ describe("Test for staking tokens", function () {
   });
 
   it("It should stake the user's token (`stake` instruction)", async function () {
-    const transferAmount = new anchor.BN(2000);
+    const transferAmount = rewardStakingAmount.muln(2);
     await transferTokens(
       mintKeyPair.publicKey,
       staker.publicKey,
@@ -155,22 +204,26 @@ describe("Test for staking tokens", function () {
       prevStakingTokenATAAccountBalance.value.uiAmount ===
         initialFundReward.toNumber()
     );
-    const stakingAmount = new anchor.BN(1000);
+    const stakingAmount = rewardStakingAmount;
     // requesting air drop for the staker's account
     await airdrop(staker.publicKey); // 1 SOL airdrop
 
     await program.methods
-      .stake(stakingAmount, oneYearBeforeTimeStamp)
+      .stake(stakingAmount, 0)
       .accounts({
         stakingAccount: stakingAccountPDA,
         stakingTokenAccount: stakingAccountATA,
         user: staker.publicKey,
         userTokenAccount: stakerTokenAccountATA,
         // @ts-ignore
+        userStakeCounter: userStakeCounterPDA,
         userStake: userStakeAccountPDA,
+        mint: mintKeyPair.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
       })
       .signers([staker])
       .rpc();
+    await waitForMaturity();
 
     const stakerBalance = await provider.connection.getTokenAccountBalance(
       stakerTokenAccountATA
@@ -183,9 +236,9 @@ describe("Test for staking tokens", function () {
     );
 
     assert(userStake.amount.toNumber() === stakingAmount.toNumber());
-    assert(
-      userStake.startTime.toNumber() === oneYearBeforeTimeStamp.toNumber()
-    );
+    assert(userStake.pool.toBase58() === stakingAccountPDA.toBase58());
+    assert(userStake.user.toBase58() === staker.publicKey.toBase58());
+    assert(userStake.positionId.toNumber() === 0);
     assert(
       stakerBalance.value.uiAmount ===
         transferAmount.toNumber() - stakingAmount.toNumber()
@@ -201,16 +254,16 @@ describe("Test for staking tokens", function () {
       userStakeAccountPDA
     );
 
-    // difference must be >= one year
-    const currentTimeStamp = Date.now();
+    // difference must be >= the minimum staking duration
+    const currentTimeStamp = Math.floor(Date.now() / 1000);
 
     assert(
       currentTimeStamp - userStake.startTime.toNumber() >=
-        oneYearInMilliseconds,
-      "The difference between timestamps must be at least 1 year"
+        minStakingDuration.toNumber(),
+      "The difference between timestamps must be at least the minimum staking duration"
     );
 
-    const { stakedAmount, reward } = await getStakeInfo(
+    const { stakedAmount } = await getStakeInfo(
       staker.publicKey,
       userStakeAccountPDA,
       stakingAccountPDA
@@ -230,12 +283,25 @@ describe("Test for staking tokens", function () {
         user: staker.publicKey,
         userTokenAccount: stakerTokenAccountATA,
         stakingTokenAccount: stakingAccountATA,
-        stakingTokenAccountOwner: stakingTokenAccountKP.publicKey,
+        treasuryTokenAccount: null,
+        mint: mintKeyPair.publicKey,
         tokenProgram: TOKEN_PROGRAM_ID,
+        rewardTokenAccount: null,
+        rewardMint: null,
+        userRewardTokenAccount: null,
       })
-      .signers([staker, stakingTokenAccountKP])
+      .signers([staker])
       .rpc();
 
+    // the reward accrued until the redeem is paid on top of the staked amount
+    const stakerBalanceAfterRedeem =
+      await provider.connection.getTokenAccountBalance(stakerTokenAccountATA);
+    const reward =
+      stakerBalanceAfterRedeem.value.uiAmount -
+      beforeRedeemStakerBalance.value.uiAmount -
+      stakedAmount;
+    assert(reward > 0);
+
     // balance must be deduced from stakingAccount (global pool)
     const afterStakingAccount =
       await provider.connection.getTokenAccountBalance(stakingAccountATA);
@@ -244,14 +310,6 @@ describe("Test for staking tokens", function () {
       afterStakingAccount.value.uiAmount ==
         beforeStakingAccount.adminRewardAmount.toNumber() - reward
     );
-
-    const stakerBalanceAfterRedeem =
-      await provider.connection.getTokenAccountBalance(stakerTokenAccountATA);
-
-    assert(
-      stakerBalanceAfterRedeem.value.uiAmount ==
-        beforeRedeemStakerBalance.value.uiAmount + reward + stakedAmount
-    );
   });
 
   it("It should allow force redeeming the staked amount (`redeem` instruction)", async function () {
@@ -261,14 +319,17 @@ describe("Test for staking tokens", function () {
       stakerTokenAccountATA
     );
     await program.methods
-      .stake(stakingAmount, null) // for force redeem
+      .stake(stakingAmount, 0) // for force redeem
       .accounts({
         stakingAccount: stakingAccountPDA,
         stakingTokenAccount: stakingAccountATA,
         user: staker.publicKey,
         userTokenAccount: stakerTokenAccountATA,
         // @ts-ignore
-        userStake: userStakeAccountPDA,
+        userStakeCounter: userStakeCounterPDA,
+        userStake: forceRedeemUserStakeAccountPDA,
+        mint: mintKeyPair.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
       })
       .signers([staker])
       .rpc();
@@ -286,16 +347,20 @@ describe("Test for staking tokens", function () {
       .accounts({
         stakingAccount: stakingAccountPDA,
         // @ts-ignore
-        userStake: userStakeAccountPDA,
+        userStake: forceRedeemUserStakeAccountPDA,
         user: staker.publicKey,
         userTokenAccount: stakerTokenAccountATA,
         stakingTokenAccount: stakingAccountATA,
-        stakingTokenAccountOwner: stakingTokenAccountKP.publicKey,
+        treasuryTokenAccount: null,
+        mint: mintKeyPair.publicKey,
         tokenProgram: TOKEN_PROGRAM_ID,
+        rewardTokenAccount: null,
+        rewardMint: null,
+        userRewardTokenAccount: null,
       })
-      .signers([staker, stakingTokenAccountKP])
+      .signers([staker])
       .instruction();
-    await simulateTransaction([tx], [staker, stakingTokenAccountKP]);
+    await simulateTransaction([tx], [staker]);
     // const stakerBalanceAfterRedeem =
     //   await provider.connection.getTokenAccountBalance(stakerTokenAccountATA);
     // console.log("Check");
@@ -307,4 +372,1411 @@ describe("Test for staking tokens", function () {
     //   stakerBalanceAfterRedeem.value.uiAmount === userBalance.value.uiAmount
     // );
   });
+
+  it("It should top up and partially unstake a position (`increase_stake` and `partial_unstake` instructions)", async function () {
+    const stakingAmount = rewardStakingAmount.divn(2);
+    const topUpAmount = rewardStakingAmount.divn(4);
+    const unstakeAmount = rewardStakingAmount.divn(2);
+
+    await program.methods
+      .stake(stakingAmount, 0)
+      .accounts({
+        stakingAccount: stakingAccountPDA,
+        stakingTokenAccount: stakingAccountATA,
+        user: staker.publicKey,
+        userTokenAccount: stakerTokenAccountATA,
+        // @ts-ignore
+        userStakeCounter: userStakeCounterPDA,
+        userStake: topUpUserStakeAccountPDA,
+        mint: mintKeyPair.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([staker])
+      .rpc();
+    const openedStake = await program.account.userStake.fetch(
+      topUpUserStakeAccountPDA
+    );
+    await waitForMaturity();
+
+    await program.methods
+      .increaseStake(topUpAmount)
+      .accounts({
+        stakingAccount: stakingAccountPDA,
+        // @ts-ignore
+        userStake: topUpUserStakeAccountPDA,
+        user: staker.publicKey,
+        userTokenAccount: stakerTokenAccountATA,
+        stakingTokenAccount: stakingAccountATA,
+        mint: mintKeyPair.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([staker])
+      .rpc();
+
+    // the top up keeps the start time and settles the reward of the old balance
+    const toppedUpStake = await program.account.userStake.fetch(
+      topUpUserStakeAccountPDA
+    );
+    assert(
+      toppedUpStake.amount.toNumber() ===
+        stakingAmount.toNumber() + topUpAmount.toNumber()
+    );
+    assert(toppedUpStake.startTime.eq(openedStake.startTime));
+    assert(toppedUpStake.pendingReward.toNumber() > 0);
+
+    const beforeUnstakeStakerBalance =
+      await provider.connection.getTokenAccountBalance(stakerTokenAccountATA);
+
+    await program.methods
+      .partialUnstake(unstakeAmount)
+      .accounts({
+        stakingAccount: stakingAccountPDA,
+        // @ts-ignore
+        userStake: topUpUserStakeAccountPDA,
+        user: staker.publicKey,
+        userTokenAccount: stakerTokenAccountATA,
+        stakingTokenAccount: stakingAccountATA,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        mint: mintKeyPair.publicKey,
+        rewardTokenAccount: null,
+        rewardMint: null,
+        userRewardTokenAccount: null,
+      })
+      .signers([staker])
+      .rpc();
+
+    // the position stays open with the remaining amount and the rewards are paid out
+    const remainingStake = await program.account.userStake.fetch(
+      topUpUserStakeAccountPDA
+    );
+    assert(
+      remainingStake.amount.toNumber() ===
+        toppedUpStake.amount.toNumber() - unstakeAmount.toNumber()
+    );
+    assert(remainingStake.pendingReward.toNumber() === 0);
+    // the settled reward is paid with what accrued since the top up
+    assert(
+      remainingStake.rewardsClaimed.toNumber() >=
+        toppedUpStake.pendingReward.toNumber()
+    );
+
+    const afterUnstakeStakerBalance =
+      await provider.connection.getTokenAccountBalance(stakerTokenAccountATA);
+    assert(
+      afterUnstakeStakerBalance.value.uiAmount ===
+        beforeUnstakeStakerBalance.value.uiAmount +
+          unstakeAmount.toNumber() +
+          remainingStake.rewardsClaimed.toNumber()
+    );
+  });
+
+  it("It should preview and claim the rewards without unstaking (`preview_reward` and `claim_rewards` instructions)", async function () {
+    const stakingAmount = rewardStakingAmount.divn(4);
+    await program.methods
+      .stake(stakingAmount, 0)
+      .accounts({
+        stakingAccount: stakingAccountPDA,
+        stakingTokenAccount: stakingAccountATA,
+        user: staker.publicKey,
+        userTokenAccount: stakerTokenAccountATA,
+        // @ts-ignore
+        userStakeCounter: userStakeCounterPDA,
+        userStake: claimUserStakeAccountPDA,
+        mint: mintKeyPair.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([staker])
+      .rpc();
+    await waitForMaturity();
+
+    const beforeClaimStakerBalance =
+      await provider.connection.getTokenAccountBalance(stakerTokenAccountATA);
+
+    // the preview is read from the return data of a simulated transaction
+    const preview = await program.methods
+      .previewReward()
+      .accounts({
+        stakingAccount: stakingAccountPDA,
+        userStake: claimUserStakeAccountPDA,
+      })
+      .view();
+    assert(preview.matured);
+    assert(preview.penalty.toNumber() === 0);
+    assert(preview.forcedReward.toNumber() === preview.reward.toNumber());
+
+    await program.methods
+      .claimRewards()
+      .accounts({
+        stakingAccount: stakingAccountPDA,
+        // @ts-ignore
+        userStake: claimUserStakeAccountPDA,
+        user: staker.publicKey,
+        userTokenAccount: stakerTokenAccountATA,
+        stakingTokenAccount: stakingAccountATA,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        mint: mintKeyPair.publicKey,
+        rewardTokenAccount: null,
+        rewardMint: null,
+        userRewardTokenAccount: null,
+      })
+      .signers([staker])
+      .rpc();
+
+    // the stake stays in place and only the rewards are paid out
+    const claimedStake = await program.account.userStake.fetch(
+      claimUserStakeAccountPDA
+    );
+    assert(claimedStake.amount.toNumber() === stakingAmount.toNumber());
+    assert(claimedStake.rewardsClaimed.toNumber() > 0);
+    assert(claimedStake.lastClaimTime.toNumber() > 0);
+    // the claim also pays what accrued since the preview
+    assert(
+      claimedStake.rewardsClaimed.toNumber() >= preview.reward.toNumber()
+    );
+
+    const afterClaimStakerBalance =
+      await provider.connection.getTokenAccountBalance(stakerTokenAccountATA);
+    assert(
+      afterClaimStakerBalance.value.uiAmount ===
+        beforeClaimStakerBalance.value.uiAmount +
+          claimedStake.rewardsClaimed.toNumber()
+    );
+  });
+
+  it("It should compound the rewards into the principal (`compound` instruction)", async function () {
+    const stakingAmount = rewardStakingAmount.divn(4);
+    await program.methods
+      .stake(stakingAmount, 0)
+      .accounts({
+        stakingAccount: stakingAccountPDA,
+        stakingTokenAccount: stakingAccountATA,
+        user: staker.publicKey,
+        userTokenAccount: stakerTokenAccountATA,
+        // @ts-ignore
+        userStakeCounter: userStakeCounterPDA,
+        userStake: compoundUserStakeAccountPDA,
+        mint: mintKeyPair.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([staker])
+      .rpc();
+    await waitForMaturity();
+
+    await program.methods
+      .setAutoCompound(true)
+      .accounts({
+        stakingAccount: stakingAccountPDA,
+        // @ts-ignore
+        userStake: compoundUserStakeAccountPDA,
+        user: staker.publicKey,
+      })
+      .signers([staker])
+      .rpc();
+
+    const beforeCompoundStakerBalance =
+      await provider.connection.getTokenAccountBalance(stakerTokenAccountATA);
+
+    await program.methods
+      .compound()
+      .accounts({
+        stakingAccount: stakingAccountPDA,
+        // @ts-ignore
+        userStake: compoundUserStakeAccountPDA,
+        authority: staker.publicKey,
+        authorityTokenAccount: null,
+        stakingTokenAccount: stakingAccountATA,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        mint: mintKeyPair.publicKey,
+      })
+      .signers([staker])
+      .rpc();
+
+    // the reward is added to the principal, nothing is transferred to the owner
+    const compoundedStake = await program.account.userStake.fetch(
+      compoundUserStakeAccountPDA
+    );
+    assert(compoundedStake.autoCompound);
+    assert(compoundedStake.rewardsClaimed.toNumber() > 0);
+    assert(
+      compoundedStake.amount.toNumber() ===
+        stakingAmount.toNumber() + compoundedStake.rewardsClaimed.toNumber()
+    );
+
+    const afterCompoundStakerBalance =
+      await provider.connection.getTokenAccountBalance(stakerTokenAccountATA);
+    assert(
+      afterCompoundStakerBalance.value.uiAmount ===
+        beforeCompoundStakerBalance.value.uiAmount
+    );
+  });
+
+  it("It should stake in a configured lockup tier (`set_lockup_tiers` instruction)", async function () {
+    const lockedTier = {
+      lockDuration: new anchor.BN(2 * 365 * 24 * 60 * 60), // 2 years
+      rewardMultiplierBps: 20000, // 2x
+    };
+    await program.methods
+      .setLockupTiers([
+        { lockDuration: new anchor.BN(0), rewardMultiplierBps: 10000 },
+        lockedTier,
+      ])
+      .accounts({
+        stakingAccount: stakingAccountPDA,
+        admin: admin.publicKey,
+      })
+      .rpc();
+
+    const stakingPool = await program.account.stakingAccount.fetch(
+      stakingAccountPDA
+    );
+    assert(stakingPool.tierCount === 2);
+
+    await program.methods
+      .stake(new anchor.BN(100), 1)
+      .accounts({
+        stakingAccount: stakingAccountPDA,
+        stakingTokenAccount: stakingAccountATA,
+        user: staker.publicKey,
+        userTokenAccount: stakerTokenAccountATA,
+        // @ts-ignore
+        userStakeCounter: userStakeCounterPDA,
+        userStake: tierUserStakeAccountPDA,
+        mint: mintKeyPair.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([staker])
+      .rpc();
+
+    // the position keeps the lock and multiplier of its tier
+    const tierStake = await program.account.userStake.fetch(
+      tierUserStakeAccountPDA
+    );
+    assert(tierStake.tier === 1);
+    assert(tierStake.lockDuration.eq(lockedTier.lockDuration));
+    assert(tierStake.rewardMultiplierBps === lockedTier.rewardMultiplierBps);
+  });
+
+  it("It should apply the penalty policy when force redeeming (`set_penalty_policy` instruction)", async function () {
+    // 10% of the principal goes back to the reward pool when leaving early
+    await program.methods
+      .setPenaltyPolicy({ flatPrincipal: {} }, 1000, { rewardPool: {} })
+      .accounts({
+        stakingAccount: stakingAccountPDA,
+        admin: admin.publicKey,
+        treasuryTokenAccount: null,
+      })
+      .rpc();
+
+    const beforeStakingPool = await program.account.stakingAccount.fetch(
+      stakingAccountPDA
+    );
+    const beforeRedeemStakerBalance =
+      await provider.connection.getTokenAccountBalance(stakerTokenAccountATA);
+
+    // the tier position is still locked for 2 years
+    await program.methods
+      .redeem(true)
+      .accounts({
+        stakingAccount: stakingAccountPDA,
+        // @ts-ignore
+        userStake: tierUserStakeAccountPDA,
+        user: staker.publicKey,
+        userTokenAccount: stakerTokenAccountATA,
+        stakingTokenAccount: stakingAccountATA,
+        treasuryTokenAccount: null,
+        mint: mintKeyPair.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        rewardTokenAccount: null,
+        rewardMint: null,
+        userRewardTokenAccount: null,
+      })
+      .signers([staker])
+      .rpc();
+
+    const afterRedeemStakerBalance =
+      await provider.connection.getTokenAccountBalance(stakerTokenAccountATA);
+    const afterStakingPool = await program.account.stakingAccount.fetch(
+      stakingAccountPDA
+    );
+    assert(
+      Number(afterRedeemStakerBalance.value.amount) -
+        Number(beforeRedeemStakerBalance.value.amount) ==
+        90
+    );
+    assert(
+      afterStakingPool.adminRewardAmount.eq(
+        beforeStakingPool.adminRewardAmount.addn(10)
+      )
+    );
+  });
+
+  it("It should unbond a position (`request_unstake` and `withdraw_unbonded` instructions)", async function () {
+    const stakingAmount = new anchor.BN(300);
+    await program.methods
+      .stake(stakingAmount, 0)
+      .accounts({
+        stakingAccount: stakingAccountPDA,
+        stakingTokenAccount: stakingAccountATA,
+        user: staker.publicKey,
+        userTokenAccount: stakerTokenAccountATA,
+        // @ts-ignore
+        userStakeCounter: userStakeCounterPDA,
+        userStake: unbondingUserStakeAccountPDA,
+        mint: mintKeyPair.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([staker])
+      .rpc();
+    await waitForMaturity();
+
+    const withdrawAccounts = {
+      stakingAccount: stakingAccountPDA,
+      userStake: unbondingUserStakeAccountPDA,
+      user: staker.publicKey,
+      userTokenAccount: stakerTokenAccountATA,
+      stakingTokenAccount: stakingAccountATA,
+      mint: mintKeyPair.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    };
+
+    await program.methods
+      .requestUnstake(stakingAmount)
+      .accounts({
+        ...withdrawAccounts,
+        rewardTokenAccount: null,
+        rewardMint: null,
+        userRewardTokenAccount: null,
+      })
+      .signers([staker])
+      .rpc();
+
+    // the unbonding amount left the stake but is still held by the vault
+    const unbondingStake = await program.account.userStake.fetch(
+      unbondingUserStakeAccountPDA
+    );
+    assert(unbondingStake.amount.toNumber() === 0);
+    assert(unbondingStake.unbondingAmount.eq(stakingAmount));
+    const unbondingPool = await program.account.stakingAccount.fetch(
+      stakingAccountPDA
+    );
+    assert(unbondingPool.totalUnbonding.eq(stakingAmount));
+
+    // the pool keeps the default cooldown of 0 (the cooldown is only updated behind the config timelock of a day),
+    // so the unbonding amount can be withdrawn right away
+    const beforeWithdrawStakerBalance =
+      await provider.connection.getTokenAccountBalance(stakerTokenAccountATA);
+    await program.methods
+      .withdrawUnbonded()
+      // @ts-ignore
+      .accounts(withdrawAccounts)
+      .signers([staker])
+      .rpc();
+    const afterWithdrawStakerBalance =
+      await provider.connection.getTokenAccountBalance(stakerTokenAccountATA);
+    assert(
+      Number(afterWithdrawStakerBalance.value.amount) -
+        Number(beforeWithdrawStakerBalance.value.amount) ===
+        stakingAmount.toNumber()
+    );
+
+    // the emptied position is closed
+    assert(
+      (await provider.connection.getAccountInfo(
+        unbondingUserStakeAccountPDA
+      )) === null
+    );
+  });
+
+  it("It should stage a config update behind the timelock (`propose_config_update` instruction)", async function () {
+    const config = {
+      rewardRate: APY + 1,
+      minStakingDuration: minStakingDuration,
+      configTimelock: new anchor.BN(24 * 60 * 60),
+      unbondingCooldown: new anchor.BN(0),
+    };
+    await program.methods
+      .proposeConfigUpdate(config)
+      .accounts({
+        stakingAccount: stakingAccountPDA,
+        admin: admin.publicKey,
+      })
+      .rpc();
+
+    const stakingPool = await program.account.stakingAccount.fetch(
+      stakingAccountPDA
+    );
+    assert(stakingPool.pendingConfig.rewardRate === config.rewardRate);
+    assert(stakingPool.configUpdateEta.toNumber() > Date.now() / 1000);
+
+    // the staged config can not be applied before the end of the timelock
+    try {
+      await program.methods
+        .applyConfigUpdate()
+        .accounts({
+          stakingAccount: stakingAccountPDA,
+          admin: admin.publicKey,
+        })
+        .rpc();
+      assert.fail("apply_config_update should fail during the timelock");
+    } catch (error) {
+      assert.include(String(error), "TimelockNotElapsed");
+    }
+    assert(
+      (await program.account.stakingAccount.fetch(stakingAccountPDA))
+        .rewardRate === APY
+    );
+  });
+
+  it("It should transfer the admin role in two steps (`propose_admin` and `accept_admin` instructions)", async function () {
+    const newAdmin = Keypair.generate();
+    const proposeAdmin = (
+      currentAdmin: Keypair,
+      pendingAdmin: PublicKey,
+      signers: Keypair[]
+    ) =>
+      program.methods
+        .proposeAdmin(pendingAdmin)
+        .accounts({
+          stakingAccount: stakingAccountPDA,
+          admin: currentAdmin.publicKey,
+        })
+        .signers(signers)
+        .rpc();
+    const acceptAdmin = (pendingAdmin: Keypair) =>
+      program.methods
+        .acceptAdmin()
+        .accounts({
+          stakingAccount: stakingAccountPDA,
+          pendingAdmin: pendingAdmin.publicKey,
+        })
+        .signers([pendingAdmin])
+        .rpc();
+
+    await proposeAdmin(admin, newAdmin.publicKey, []);
+    await acceptAdmin(newAdmin);
+
+    // the pool keeps its address, only the admin changes
+    const stakingPool = await program.account.stakingAccount.fetch(
+      stakingAccountPDA
+    );
+    assert(stakingPool.admin.equals(newAdmin.publicKey));
+    assert(stakingPool.creator.equals(admin.publicKey));
+    assert(stakingPool.pendingAdmin.equals(PublicKey.default));
+
+    // the previous admin lost its rights
+    try {
+      await proposeAdmin(admin, admin.publicKey, []);
+      assert.fail("the previous admin should not be able to propose an admin");
+    } catch (error) {
+      assert.include(String(error), "AdminOnly");
+    }
+
+    // hand the pool back for the following tests
+    await proposeAdmin(newAdmin, admin.publicKey, [newAdmin]);
+    await acceptAdmin(admin);
+  });
+
+  it("It should only migrate accounts of the baseline layout (`migrate_pool` and `migrate_user_stake` instructions)", async function () {
+    // accounts created by this build already have the current layout
+    const stakingPool = await program.account.stakingAccount.fetch(
+      stakingAccountPDA
+    );
+    const claimStake = await program.account.userStake.fetch(
+      claimUserStakeAccountPDA
+    );
+    assert(stakingPool.version === 1);
+    assert(claimStake.version === 1);
+
+    // baseline pools and positions live at PDAs without a pool id, none exist on this cluster
+    const [legacyStakingAccountPDA] = PublicKey.findProgramAddressSync(
+      [admin.publicKey.toBuffer(), stakingAccountGlobalContextSeed],
+      program.programId
+    );
+    const migratedPoolId = poolId.addn(1);
+    const [migratedStakingAccountPDA] = PublicKey.findProgramAddressSync(
+      [
+        admin.publicKey.toBuffer(),
+        stakingAccountGlobalContextSeed,
+        migratedPoolId.toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    );
+    const [migratedStakingAccountATA] = PublicKey.findProgramAddressSync(
+      [migratedStakingAccountPDA.toBuffer(), stakingTokenAccountSeed],
+      program.programId
+    );
+    try {
+      await program.methods
+        .migratePool(migratedPoolId)
+        .accounts({
+          // @ts-ignore
+          legacyStakingAccount: legacyStakingAccountPDA,
+          stakingAccount: migratedStakingAccountPDA,
+          stakingTokenAccount: migratedStakingAccountATA,
+          mint: mintKeyPair.publicKey,
+          admin: admin.publicKey,
+          adminTokenAccount: adminTokenAccountATA,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      assert.fail("migrate_pool should fail without a baseline pool");
+    } catch (error) {
+      assert.include(String(error), "InvalidArgument");
+    }
+
+    const [legacyUserStakeAccountPDA] = PublicKey.findProgramAddressSync(
+      [staker.publicKey.toBuffer(), userStakeAccountLocalContextSeed],
+      program.programId
+    );
+    const counter = await program.account.userStakeCounter.fetch(
+      userStakeCounterPDA
+    );
+    try {
+      await program.methods
+        .migrateUserStake()
+        .accounts({
+          // @ts-ignore
+          stakingAccount: stakingAccountPDA,
+          legacyUserStake: legacyUserStakeAccountPDA,
+          user: staker.publicKey,
+          userStakeCounter: userStakeCounterPDA,
+          userStake: getUserStakeAccountPDA(
+            counter.nextPositionId.toNumber()
+          ),
+          stakingTokenAccount: stakingAccountATA,
+          mint: mintKeyPair.publicKey,
+          admin: admin.publicKey,
+          adminTokenAccount: adminTokenAccountATA,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      assert.fail("migrate_user_stake should fail without a baseline position");
+    } catch (error) {
+      assert.include(String(error), "InvalidArgument");
+    }
+  });
+
+  it("It should let users exit a pool in emergency (`set_pool_state` and `emergency_withdraw` instructions)", async function () {
+    const stakingAmount = new anchor.BN(100);
+    const stake = () =>
+      program.methods
+        .stake(stakingAmount, 0)
+        .accounts({
+          stakingAccount: stakingAccountPDA,
+          stakingTokenAccount: stakingAccountATA,
+          user: staker.publicKey,
+          userTokenAccount: stakerTokenAccountATA,
+          // @ts-ignore
+          userStakeCounter: userStakeCounterPDA,
+          userStake: emergencyUserStakeAccountPDA,
+          mint: mintKeyPair.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([staker])
+        .rpc();
+    await stake();
+
+    const guardian = Keypair.generate();
+    await program.methods
+      .setGuardian(guardian.publicKey)
+      .accounts({
+        stakingAccount: stakingAccountPDA,
+        admin: admin.publicKey,
+      })
+      .rpc();
+    const setPoolState = (poolState: any, authority: Keypair) =>
+      program.methods
+        .setPoolState(poolState)
+        .accounts({
+          stakingAccount: stakingAccountPDA,
+          authority: authority.publicKey,
+        })
+        .signers(authority === admin ? [] : [authority])
+        .rpc();
+
+    // the guardian triggers the emergency, which blocks staking
+    await setPoolState({ emergency: {} }, guardian);
+    try {
+      await stake();
+      assert.fail("stake should fail while the pool is not active");
+    } catch (error) {
+      assert.include(String(error), "PoolNotActive");
+    }
+
+    // the locked position is withdrawn without reward and without any operator signature
+    const beforeWithdrawStakerBalance =
+      await provider.connection.getTokenAccountBalance(stakerTokenAccountATA);
+    await program.methods
+      .emergencyWithdraw()
+      .accounts({
+        stakingAccount: stakingAccountPDA,
+        // @ts-ignore
+        userStake: emergencyUserStakeAccountPDA,
+        user: staker.publicKey,
+        userTokenAccount: stakerTokenAccountATA,
+        stakingTokenAccount: stakingAccountATA,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        mint: mintKeyPair.publicKey,
+      })
+      .signers([staker])
+      .rpc();
+    const afterWithdrawStakerBalance =
+      await provider.connection.getTokenAccountBalance(stakerTokenAccountATA);
+    assert(
+      Number(afterWithdrawStakerBalance.value.amount) -
+        Number(beforeWithdrawStakerBalance.value.amount) ===
+        stakingAmount.toNumber()
+    );
+
+    // only the admin can resume the pool
+    try {
+      await setPoolState({ active: {} }, guardian);
+      assert.fail("the guardian should not be able to resume the pool");
+    } catch (error) {
+      assert.include(String(error), "AdminOnly");
+    }
+    await setPoolState({ active: {} }, admin);
+  });
+});
+
+describe("Test for emission staking pool", function () {
+  // second pool of the same admin, distributing a funded emission
+  const emissionPoolId = poolId.addn(1);
+  const [emissionPoolPDA] = PublicKey.findProgramAddressSync(
+    [
+      admin.publicKey.toBuffer(),
+      stakingAccountGlobalContextSeed,
+      emissionPoolId.toArrayLike(Buffer, "le", 8),
+    ],
+    program.programId
+  );
+  const [emissionPoolVault] = PublicKey.findProgramAddressSync(
+    [emissionPoolPDA.toBuffer(), stakingTokenAccountSeed],
+    program.programId
+  );
+  const [emissionUserStakeCounterPDA] = PublicKey.findProgramAddressSync(
+    [
+      emissionPoolPDA.toBuffer(),
+      staker.publicKey.toBuffer(),
+      userStakeCounterSeed,
+    ],
+    program.programId
+  );
+  const [emissionUserStakePDA] = PublicKey.findProgramAddressSync(
+    [
+      emissionPoolPDA.toBuffer(),
+      staker.publicKey.toBuffer(),
+      userStakeAccountLocalContextSeed,
+      new anchor.BN(0).toArrayLike(Buffer, "le", 8),
+    ],
+    program.programId
+  );
+  const [returnedUserStakePDA] = PublicKey.findProgramAddressSync(
+    [
+      emissionPoolPDA.toBuffer(),
+      staker.publicKey.toBuffer(),
+      userStakeAccountLocalContextSeed,
+      new anchor.BN(1).toArrayLike(Buffer, "le", 8),
+    ],
+    program.programId
+  );
+  const emissionAmount = new anchor.BN(1000);
+  const emissionDuration = new anchor.BN(10); // seconds
+
+  it("It should share the funded emission between stakers (`fund_emission` instruction)", async function () {
+    await program.methods
+      .initialize(emissionPoolId, mintKeyPair.publicKey, 0, new anchor.BN(0), {
+        emission: {},
+      })
+      .accounts({
+        // @ts-ignore
+        stakingAccount: emissionPoolPDA,
+        stakingTokenAccount: emissionPoolVault,
+        mint: mintKeyPair.publicKey,
+        admin: admin.publicKey,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    await program.methods
+      .stake(new anchor.BN(100), 0)
+      .accounts({
+        stakingAccount: emissionPoolPDA,
+        stakingTokenAccount: emissionPoolVault,
+        user: staker.publicKey,
+        userTokenAccount: stakerTokenAccountATA,
+        // @ts-ignore
+        userStakeCounter: emissionUserStakeCounterPDA,
+        userStake: emissionUserStakePDA,
+        mint: mintKeyPair.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([staker])
+      .rpc();
+
+    await program.methods
+      .fundEmission(emissionAmount, emissionDuration)
+      .accounts({
+        admin: admin.publicKey,
+        adminTokenAccount: adminTokenAccountATA,
+        stakingAccount: emissionPoolPDA,
+        stakingTokenAccount: emissionPoolVault,
+        mint: mintKeyPair.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        rewardTokenAccount: null,
+        rewardMint: null,
+      })
+      .rpc();
+
+    const emissionPool = await program.account.stakingAccount.fetch(
+      emissionPoolPDA
+    );
+    assert(
+      emissionPool.rewardPerSecond.toNumber() ===
+        emissionAmount.div(emissionDuration).toNumber()
+    );
+
+    // let a part of the emission accrue
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+
+    const beforeRedeemStakerBalance =
+      await provider.connection.getTokenAccountBalance(stakerTokenAccountATA);
+
+    await program.methods
+      .redeem(false)
+      .accounts({
+        stakingAccount: emissionPoolPDA,
+        // @ts-ignore
+        userStake: emissionUserStakePDA,
+        user: staker.publicKey,
+        userTokenAccount: stakerTokenAccountATA,
+        stakingTokenAccount: emissionPoolVault,
+        treasuryTokenAccount: null,
+        mint: mintKeyPair.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        rewardTokenAccount: null,
+        rewardMint: null,
+        userRewardTokenAccount: null,
+      })
+      .signers([staker])
+      .rpc();
+
+    // the only staker receives the whole emission since staking, never more than funded
+    const afterRedeemStakerBalance =
+      await provider.connection.getTokenAccountBalance(stakerTokenAccountATA);
+    const reward =
+      afterRedeemStakerBalance.value.uiAmount -
+      beforeRedeemStakerBalance.value.uiAmount -
+      100;
+    assert(reward > 0);
+    assert(reward <= emissionAmount.toNumber());
+  });
+
+  it("It should only withdraw the rewards not owed to stakers (`withdraw_rewards` instruction)", async function () {
+    const withdrawRewards = (amount: anchor.BN) =>
+      program.methods
+        .withdrawRewards(amount)
+        .accounts({
+          // @ts-ignore
+          stakingAccount: emissionPoolPDA,
+          stakingTokenAccount: emissionPoolVault,
+          admin: admin.publicKey,
+          adminTokenAccount: adminTokenAccountATA,
+          mint: mintKeyPair.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          rewardTokenAccount: null,
+          rewardMint: null,
+        })
+        .rpc();
+
+    // the rest of the running emission is still committed
+    const runningPool = await program.account.stakingAccount.fetch(
+      emissionPoolPDA
+    );
+    try {
+      await withdrawRewards(runningPool.adminRewardAmount);
+      assert.fail("withdraw_rewards should fail during the emission");
+    } catch (error) {
+      assert.include(String(error), "RewardFundsAllocated");
+    }
+
+    // once the emission ended, everything emitted after the only staker left is surplus
+    await new Promise((resolve) =>
+      setTimeout(resolve, emissionDuration.toNumber() * 1000)
+    );
+    const endedPool = await program.account.stakingAccount.fetch(
+      emissionPoolPDA
+    );
+    const surplus = endedPool.adminRewardAmount.sub(endedPool.rewardLiability);
+    assert(surplus.toNumber() > 0);
+
+    const beforeWithdrawAdminBalance =
+      await provider.connection.getTokenAccountBalance(adminTokenAccountATA);
+    await withdrawRewards(surplus);
+    const afterWithdrawAdminBalance =
+      await provider.connection.getTokenAccountBalance(adminTokenAccountATA);
+    assert(
+      Number(afterWithdrawAdminBalance.value.amount) -
+        Number(beforeWithdrawAdminBalance.value.amount) ===
+        surplus.toNumber()
+    );
+  });
+
+  it("It should shut down and close the pool (`begin_shutdown`, `return_position` and `close_pool` instructions)", async function () {
+    const stakingAmount = new anchor.BN(100);
+    await program.methods
+      .stake(stakingAmount, 0)
+      .accounts({
+        stakingAccount: emissionPoolPDA,
+        stakingTokenAccount: emissionPoolVault,
+        user: staker.publicKey,
+        userTokenAccount: stakerTokenAccountATA,
+        // @ts-ignore
+        userStakeCounter: emissionUserStakeCounterPDA,
+        userStake: returnedUserStakePDA,
+        mint: mintKeyPair.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([staker])
+      .rpc();
+
+    // no grace period, so the remaining position can be returned right away
+    await program.methods
+      .beginShutdown(new anchor.BN(0))
+      .accounts({
+        stakingAccount: emissionPoolPDA,
+        admin: admin.publicKey,
+      })
+      .rpc();
+
+    const closePool = () =>
+      program.methods
+        .closePool()
+        .accounts({
+          // @ts-ignore
+          stakingAccount: emissionPoolPDA,
+          stakingTokenAccount: emissionPoolVault,
+          admin: admin.publicKey,
+          adminTokenAccount: adminTokenAccountATA,
+          mint: mintKeyPair.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          rewardTokenAccount: null,
+          rewardMint: null,
+          adminRewardTokenAccount: null,
+        })
+        .rpc();
+    try {
+      await closePool();
+      assert.fail("close_pool should fail while positions are open");
+    } catch (error) {
+      assert.include(String(error), "OutstandingStake");
+    }
+
+    // anyone can return the position to its owner
+    const beforeReturnStakerBalance =
+      await provider.connection.getTokenAccountBalance(stakerTokenAccountATA);
+    await program.methods
+      .returnPosition()
+      .accounts({
+        stakingAccount: emissionPoolPDA,
+        // @ts-ignore
+        userStake: returnedUserStakePDA,
+        user: staker.publicKey,
+        userTokenAccount: stakerTokenAccountATA,
+        stakingTokenAccount: emissionPoolVault,
+        authority: admin.publicKey,
+        mint: mintKeyPair.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        rewardTokenAccount: null,
+        rewardMint: null,
+        userRewardTokenAccount: null,
+      })
+      .rpc();
+    const afterReturnStakerBalance =
+      await provider.connection.getTokenAccountBalance(stakerTokenAccountATA);
+    assert(
+      Number(afterReturnStakerBalance.value.amount) -
+        Number(beforeReturnStakerBalance.value.amount) ===
+        stakingAmount.toNumber()
+    );
+
+    await closePool();
+    assert(
+      (await provider.connection.getAccountInfo(emissionPoolPDA)) === null
+    );
+    assert(
+      (await provider.connection.getAccountInfo(emissionPoolVault)) === null
+    );
+  });
+});
+
+describe("Test for Token-2022 staking pool", function () {
+  // third pool of the same admin, staking a Token-2022 mint with a 1% transfer fee
+  const token2022MintKeyPair = Keypair.generate();
+  const transferFeeBps = 100;
+  const token2022PoolId = poolId.addn(2);
+  const [token2022PoolPDA] = PublicKey.findProgramAddressSync(
+    [
+      admin.publicKey.toBuffer(),
+      stakingAccountGlobalContextSeed,
+      token2022PoolId.toArrayLike(Buffer, "le", 8),
+    ],
+    program.programId
+  );
+  const [token2022PoolVault] = PublicKey.findProgramAddressSync(
+    [token2022PoolPDA.toBuffer(), stakingTokenAccountSeed],
+    program.programId
+  );
+  const [token2022UserStakeCounterPDA] = PublicKey.findProgramAddressSync(
+    [
+      token2022PoolPDA.toBuffer(),
+      staker.publicKey.toBuffer(),
+      userStakeCounterSeed,
+    ],
+    program.programId
+  );
+  const [token2022UserStakePDA] = PublicKey.findProgramAddressSync(
+    [
+      token2022PoolPDA.toBuffer(),
+      staker.publicKey.toBuffer(),
+      userStakeAccountLocalContextSeed,
+      new anchor.BN(0).toArrayLike(Buffer, "le", 8),
+    ],
+    program.programId
+  );
+  const stakerToken2022ATA = getAssociatedTokenAddressSync(
+    token2022MintKeyPair.publicKey,
+    staker.publicKey,
+    false,
+    TOKEN_2022_PROGRAM_ID
+  );
+
+  it("It should credit only the received amount of a transfer fee mint (`stake` instruction)", async function () {
+    const mintLen = getMintLen([ExtensionType.TransferFeeConfig]);
+    const lamports =
+      await provider.connection.getMinimumBalanceForRentExemption(mintLen);
+    await provider.sendAndConfirm(
+      new Transaction().add(
+        SystemProgram.createAccount({
+          fromPubkey: admin.publicKey,
+          newAccountPubkey: token2022MintKeyPair.publicKey,
+          space: mintLen,
+          lamports,
+          programId: TOKEN_2022_PROGRAM_ID,
+        }),
+        createInitializeTransferFeeConfigInstruction(
+          token2022MintKeyPair.publicKey,
+          admin.publicKey,
+          admin.publicKey,
+          transferFeeBps,
+          BigInt(1_000_000),
+          TOKEN_2022_PROGRAM_ID
+        ),
+        createInitializeMintInstruction(
+          token2022MintKeyPair.publicKey,
+          0,
+          admin.publicKey,
+          null,
+          TOKEN_2022_PROGRAM_ID
+        )
+      ),
+      [token2022MintKeyPair]
+    );
+    await createAssociatedTokenAccount(
+      provider.connection,
+      admin,
+      token2022MintKeyPair.publicKey,
+      staker.publicKey,
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+    await mintTo(
+      provider.connection,
+      admin,
+      token2022MintKeyPair.publicKey,
+      stakerToken2022ATA,
+      admin,
+      1000,
+      [],
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+
+    await program.methods
+      .initialize(
+        token2022PoolId,
+        token2022MintKeyPair.publicKey,
+        APY,
+        new anchor.BN(0),
+        { apy: {} }
+      )
+      .accounts({
+        // @ts-ignore
+        stakingAccount: token2022PoolPDA,
+        stakingTokenAccount: token2022PoolVault,
+        mint: token2022MintKeyPair.publicKey,
+        admin: admin.publicKey,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .rpc();
+
+    // indexers read the received amount from the `Staked` event
+    let stakedEvent;
+    const listener = program.addEventListener("staked", (event) => {
+      stakedEvent = event;
+    });
+
+    const stakingAmount = new anchor.BN(1000);
+    await program.methods
+      .stake(stakingAmount, 0)
+      .accounts({
+        stakingAccount: token2022PoolPDA,
+        stakingTokenAccount: token2022PoolVault,
+        user: staker.publicKey,
+        userTokenAccount: stakerToken2022ATA,
+        // @ts-ignore
+        userStakeCounter: token2022UserStakeCounterPDA,
+        userStake: token2022UserStakePDA,
+        mint: token2022MintKeyPair.publicKey,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      })
+      .signers([staker])
+      .rpc();
+
+    // the fee is withheld in the vault, the position only holds what arrived
+    const received =
+      stakingAmount.toNumber() -
+      (stakingAmount.toNumber() * transferFeeBps) / 10_000;
+    const userStake = await program.account.userStake.fetch(
+      token2022UserStakePDA
+    );
+    const token2022Pool = await program.account.stakingAccount.fetch(
+      token2022PoolPDA
+    );
+    assert(userStake.amount.toNumber() === received);
+    assert(token2022Pool.totalStaked.toNumber() === received);
+
+    await new Promise((resolve) => setTimeout(resolve, 1000));
+    await program.removeEventListener(listener);
+    assert(stakedEvent.pool.toBase58() === token2022PoolPDA.toBase58());
+    assert(
+      stakedEvent.userStake.toBase58() === token2022UserStakePDA.toBase58()
+    );
+    assert(stakedEvent.amount.toNumber() === received);
+  });
+});
+
+describe("Test for reward mint staking pool", function () {
+  // fourth pool of the same admin, staking the token of the other pools and paying rewards in a partner token
+  const rewardMintKeyPair = Keypair.generate();
+  const rewardPoolId = poolId.addn(3);
+  const [rewardPoolPDA] = PublicKey.findProgramAddressSync(
+    [
+      admin.publicKey.toBuffer(),
+      stakingAccountGlobalContextSeed,
+      rewardPoolId.toArrayLike(Buffer, "le", 8),
+    ],
+    program.programId
+  );
+  const [rewardPoolVault] = PublicKey.findProgramAddressSync(
+    [rewardPoolPDA.toBuffer(), stakingTokenAccountSeed],
+    program.programId
+  );
+  const [rewardPoolRewardVault] = PublicKey.findProgramAddressSync(
+    [rewardPoolPDA.toBuffer(), Buffer.from("reward_token_account")],
+    program.programId
+  );
+  const [rewardUserStakeCounterPDA] = PublicKey.findProgramAddressSync(
+    [
+      rewardPoolPDA.toBuffer(),
+      staker.publicKey.toBuffer(),
+      userStakeCounterSeed,
+    ],
+    program.programId
+  );
+  const [rewardUserStakePDA] = PublicKey.findProgramAddressSync(
+    [
+      rewardPoolPDA.toBuffer(),
+      staker.publicKey.toBuffer(),
+      userStakeAccountLocalContextSeed,
+      new anchor.BN(0).toArrayLike(Buffer, "le", 8),
+    ],
+    program.programId
+  );
+  const adminRewardTokenAccountATA = getAssociatedTokenAddressSync(
+    rewardMintKeyPair.publicKey,
+    admin.publicKey
+  );
+  const stakerRewardTokenAccountATA = getAssociatedTokenAddressSync(
+    rewardMintKeyPair.publicKey,
+    staker.publicKey
+  );
+
+  it("It should pay the rewards in the reward mint (`set_reward_mint` instruction)", async function () {
+    await createAndMintToken(
+      rewardMintKeyPair,
+      adminRewardTokenAccountATA,
+      mintAmount
+    );
+    // creates the reward token account of the staker
+    await transferTokens(
+      rewardMintKeyPair.publicKey,
+      staker.publicKey,
+      adminRewardTokenAccountATA,
+      stakerRewardTokenAccountATA,
+      new anchor.BN(1)
+    );
+    await transferTokens(
+      mintKeyPair.publicKey,
+      staker.publicKey,
+      adminTokenAccountATA,
+      stakerTokenAccountATA,
+      rewardStakingAmount
+    );
+
+    await program.methods
+      .initialize(rewardPoolId, mintKeyPair.publicKey, APY, new anchor.BN(0), {
+        apy: {},
+      })
+      .accounts({
+        // @ts-ignore
+        stakingAccount: rewardPoolPDA,
+        stakingTokenAccount: rewardPoolVault,
+        mint: mintKeyPair.publicKey,
+        admin: admin.publicKey,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    await program.methods
+      .setRewardMint()
+      .accounts({
+        stakingAccount: rewardPoolPDA,
+        // @ts-ignore
+        rewardTokenAccount: rewardPoolRewardVault,
+        rewardMint: rewardMintKeyPair.publicKey,
+        mint: mintKeyPair.publicKey,
+        admin: admin.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const fundAmount = new anchor.BN(1000);
+    await program.methods
+      .fundReward(fundAmount)
+      .accounts({
+        admin: admin.publicKey,
+        adminTokenAccount: adminRewardTokenAccountATA,
+        stakingAccount: rewardPoolPDA,
+        stakingTokenAccount: rewardPoolVault,
+        mint: mintKeyPair.publicKey,
+        rewardTokenAccount: rewardPoolRewardVault,
+        rewardMint: rewardMintKeyPair.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    const stakingAmount = rewardStakingAmount;
+    await program.methods
+      .stake(stakingAmount, 0)
+      .accounts({
+        stakingAccount: rewardPoolPDA,
+        stakingTokenAccount: rewardPoolVault,
+        user: staker.publicKey,
+        userTokenAccount: stakerTokenAccountATA,
+        // @ts-ignore
+        userStakeCounter: rewardUserStakeCounterPDA,
+        userStake: rewardUserStakePDA,
+        mint: mintKeyPair.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([staker])
+      .rpc();
+    // let some rewards accrue
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+
+    // rewards in another token can not be compounded into the principal
+    try {
+      await program.methods
+        .compound()
+        .accounts({
+          stakingAccount: rewardPoolPDA,
+          // @ts-ignore
+          userStake: rewardUserStakePDA,
+          authority: staker.publicKey,
+          authorityTokenAccount: null,
+          stakingTokenAccount: rewardPoolVault,
+          mint: mintKeyPair.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([staker])
+        .rpc();
+      assert.fail("compound should fail when rewards are in another token");
+    } catch (error) {
+      assert.include(String(error), "SeparateRewardMint");
+    }
+
+    const beforeClaimStakerRewardBalance =
+      await provider.connection.getTokenAccountBalance(
+        stakerRewardTokenAccountATA
+      );
+
+    await program.methods
+      .claimRewards()
+      .accounts({
+        stakingAccount: rewardPoolPDA,
+        // @ts-ignore
+        userStake: rewardUserStakePDA,
+        user: staker.publicKey,
+        userTokenAccount: stakerTokenAccountATA,
+        stakingTokenAccount: rewardPoolVault,
+        mint: mintKeyPair.publicKey,
+        rewardTokenAccount: rewardPoolRewardVault,
+        rewardMint: rewardMintKeyPair.publicKey,
+        userRewardTokenAccount: stakerRewardTokenAccountATA,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([staker])
+      .rpc();
+
+    // the rewards are paid out of the reward vault, the staked tokens stay in the staking vault
+    const afterClaimStakerRewardBalance =
+      await provider.connection.getTokenAccountBalance(
+        stakerRewardTokenAccountATA
+      );
+    const reward =
+      afterClaimStakerRewardBalance.value.uiAmount -
+      beforeClaimStakerRewardBalance.value.uiAmount;
+    assert(reward > 0);
+    const stakingPoolBalance = await provider.connection.getTokenAccountBalance(
+      rewardPoolVault
+    );
+    const rewardVaultBalance = await provider.connection.getTokenAccountBalance(
+      rewardPoolRewardVault
+    );
+    assert(stakingPoolBalance.value.uiAmount === stakingAmount.toNumber());
+    assert(
+      rewardVaultBalance.value.uiAmount === fundAmount.toNumber() - reward
+    );
+  });
+
+  it("It should pay the rewards of a stream added mid-life (`add_reward_stream` instruction)", async function () {
+    // partner incentive layered on top of the rewards of the pool
+    const streamMintKeyPair = Keypair.generate();
+    const adminStreamTokenAccountATA = getAssociatedTokenAddressSync(
+      streamMintKeyPair.publicKey,
+      admin.publicKey
+    );
+    const stakerStreamTokenAccountATA = getAssociatedTokenAddressSync(
+      streamMintKeyPair.publicKey,
+      staker.publicKey
+    );
+    const [streamVault] = PublicKey.findProgramAddressSync(
+      [
+        rewardPoolPDA.toBuffer(),
+        Buffer.from("reward_stream"),
+        Buffer.from([0]),
+      ],
+      program.programId
+    );
+    await createAndMintToken(
+      streamMintKeyPair,
+      adminStreamTokenAccountATA,
+      mintAmount
+    );
+    // creates the stream token account of the staker
+    await transferTokens(
+      streamMintKeyPair.publicKey,
+      staker.publicKey,
+      adminStreamTokenAccountATA,
+      stakerStreamTokenAccountATA,
+      new anchor.BN(1)
+    );
+
+    const streamAmount = new anchor.BN(1000);
+    const now = Math.floor(Date.now() / 1000);
+    await program.methods
+      .addRewardStream(
+        0, // first slot of the pool
+        streamAmount,
+        new anchor.BN(now),
+        new anchor.BN(now + 10)
+      )
+      .accounts({
+        stakingAccount: rewardPoolPDA,
+        // @ts-ignore
+        rewardStreamVault: streamVault,
+        streamMint: streamMintKeyPair.publicKey,
+        mint: mintKeyPair.publicKey,
+        admin: admin.publicKey,
+        adminTokenAccount: adminStreamTokenAccountATA,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const rewardPool = await program.account.stakingAccount.fetch(
+      rewardPoolPDA
+    );
+    assert(rewardPool.streamCount === 1);
+    assert(
+      rewardPool.rewardStreams[0].vault.toBase58() === streamVault.toBase58()
+    );
+
+    // let a part of the stream accrue
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+
+    const beforeClaimStakerStreamBalance =
+      await provider.connection.getTokenAccountBalance(
+        stakerStreamTokenAccountATA
+      );
+
+    await program.methods
+      .claimRewards()
+      .accounts({
+        stakingAccount: rewardPoolPDA,
+        // @ts-ignore
+        userStake: rewardUserStakePDA,
+        user: staker.publicKey,
+        userTokenAccount: stakerTokenAccountATA,
+        stakingTokenAccount: rewardPoolVault,
+        mint: mintKeyPair.publicKey,
+        rewardTokenAccount: rewardPoolRewardVault,
+        rewardMint: rewardMintKeyPair.publicKey,
+        userRewardTokenAccount: stakerRewardTokenAccountATA,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts([
+        { pubkey: streamVault, isWritable: true, isSigner: false },
+        {
+          pubkey: streamMintKeyPair.publicKey,
+          isWritable: false,
+          isSigner: false,
+        },
+        {
+          pubkey: stakerStreamTokenAccountATA,
+          isWritable: true,
+          isSigner: false,
+        },
+      ])
+      .signers([staker])
+      .rpc();
+
+    // the only staker receives the whole stream emission since its start, never more than funded
+    const afterClaimStakerStreamBalance =
+      await provider.connection.getTokenAccountBalance(
+        stakerStreamTokenAccountATA
+      );
+    const streamReward =
+      afterClaimStakerStreamBalance.value.uiAmount -
+      beforeClaimStakerStreamBalance.value.uiAmount;
+    assert(streamReward > 0);
+    assert(streamReward <= streamAmount.toNumber());
+  });
 });
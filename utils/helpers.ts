@@ -1,5 +1,8 @@
 import * as anchor from "@coral-xyz/anchor";
-import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+import {
+  getAssociatedTokenAddressSync,
+  TOKEN_PROGRAM_ID,
+} from "@solana/spl-token";
 import {
   Keypair,
   LAMPORTS_PER_SOL,
@@ -33,8 +36,6 @@ export const metadata = {
   uri: "https://bafkreic6kmxp2ndrkns3plteriluxpezhu53m736fskdjr5cisxn2yfxm4.ipfs.flk-ipfs.xyz",
 };
 
-export const oneYearInMilliseconds = 365 * 24 * 60 * 60 * 1000;
-
 export async function createMintToken(mintKeyPair: Keypair) {
   await program.methods
     .createTokenMint(
@@ -46,6 +47,7 @@ export async function createMintToken(mintKeyPair: Keypair) {
     .accounts({
       payer: payer.publicKey,
       mintAccount: mintKeyPair.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
     })
     .signers([mintKeyPair])
     .rpc();
@@ -63,6 +65,7 @@ export async function createAndMintToken(
       mintAuthority: payer.publicKey,
       recipient: payer.publicKey,
       mintAccount: mintKeyPair.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
       // @ts-ignore
       associatedTokenAccount: ata,
     })
@@ -79,6 +82,7 @@ export async function mintToken(
       mintAuthority: payer.publicKey,
       recipient: payer.publicKey,
       mintAccount: mintKeyPair,
+      tokenProgram: TOKEN_PROGRAM_ID,
       // @ts-ignore
       associatedTokenAccount: ata,
     })
@@ -97,6 +101,7 @@ export async function transferTokens(
       sender: payer.publicKey,
       recipient: recipientKeyPair,
       mintAccount: mintKeyPair,
+      tokenProgram: TOKEN_PROGRAM_ID,
       // @ts-ignore
       senderTokenAccount: senderTokenAddress,
       recipientTokenAccount: recipientTokenAddress,
@@ -122,8 +127,8 @@ export async function getStakeInfo(
     );
 
     const stakedAmount = userStake.amount.toNumber();
-    const startTime = userStake.startTime.toNumber(); // in milliseconds
-    const currentTime = Date.now(); // in milliseconds
+    const startTime = userStake.startTime.toNumber(); // in seconds
+    const currentTime = Math.floor(Date.now() / 1000); // in seconds
 
     // Validation
     if (startTime > currentTime) {
@@ -133,8 +138,8 @@ export async function getStakeInfo(
       return { stakedAmount, reward: 0 };
     }
 
-    const stakingDuration = currentTime - startTime; // milliseconds
-    const stakingDurationInDays = stakingDuration / (24 * 60 * 60 * 1000);
+    const stakingDuration = currentTime - startTime; // seconds
+    const stakingDurationInDays = stakingDuration / (24 * 60 * 60);
 
     const rewardRate = staking.rewardRate; // 5 for 5%
 